@@ -25,6 +25,7 @@ async function init(adminSigners: PublicKey[], minSignatures: number) {
     allowPnlWithdrawal: true,
     allowCollateralWithdrawal: true,
     allowSizeChange: true,
+    allowLiquidation: true,
   };
   client.init(adminSigners, perpetualsConfig);
 }
@@ -93,6 +94,7 @@ async function addCustody(
     allowPnlWithdrawal: true,
     allowCollateralWithdrawal: true,
     allowSizeChange: true,
+    allowLiquidation: true,
   };
   let fees = {
     mode: { linear: {} },
@@ -157,6 +159,14 @@ async function upgradeCustody(poolName: string, tokenMint: PublicKey) {
   client.upgradeCustody(poolName, tokenMint);
 }
 
+async function upgradeMultisig() {
+  client.upgradeMultisig();
+}
+
+async function refreshAum(poolName: string) {
+  client.prettyPrint(await client.refreshAum(poolName));
+}
+
 async function getUserPosition(
   wallet: PublicKey,
   poolName: string,
@@ -268,6 +278,17 @@ async function getLiquidationState(
   );
 }
 
+async function getPositionHealth(
+  wallet: PublicKey,
+  poolName: string,
+  tokenMint: PublicKey,
+  side: PositionSide
+) {
+  client.prettyPrint(
+    await client.getPositionHealth(wallet, poolName, tokenMint, side)
+  );
+}
+
 async function getPnl(
   wallet: PublicKey,
   poolName: string,
@@ -297,6 +318,10 @@ async function getAum(poolName: string) {
   client.prettyPrint(await client.getAum(poolName));
 }
 
+async function getPoolStats(poolName: string) {
+  client.prettyPrint(await client.getPoolStats(poolName));
+}
+
 (async function main() {
   const program = new Command();
   program
@@ -440,6 +465,21 @@ async function getAum(poolName: string) {
       await upgradeCustody(poolName, new PublicKey(tokenMint));
     });
 
+  program
+    .command("upgrade-multisig")
+    .description("Upgrade deprecated multisig to the new version")
+    .action(async () => {
+      await upgradeMultisig();
+    });
+
+  program
+    .command("refresh-aum")
+    .description("Refresh cached custody contributions to the pool AUM")
+    .argument("<string>", "Pool name")
+    .action(async (poolName) => {
+      await refreshAum(poolName);
+    });
+
   program
     .command("get-user-position")
     .description("Print user position metadata")
@@ -588,6 +628,22 @@ async function getAum(poolName: string) {
       );
     });
 
+  program
+    .command("get-position-health")
+    .description("Get health metrics of the position")
+    .argument("<pubkey>", "User wallet")
+    .argument("<string>", "Pool name")
+    .argument("<pubkey>", "Token mint")
+    .argument("<string>", "Position side (long / short)")
+    .action(async (wallet, poolName, tokenMint, side) => {
+      await getPositionHealth(
+        new PublicKey(wallet),
+        poolName,
+        new PublicKey(tokenMint),
+        side
+      );
+    });
+
   program
     .command("get-pnl")
     .description("Compute PnL of the position")
@@ -628,6 +684,14 @@ async function getAum(poolName: string) {
       await getAum(poolName);
     });
 
+  program
+    .command("get-pool-stats")
+    .description("Get pool and custody analytics")
+    .argument("<string>", "Pool name")
+    .action(async (poolName) => {
+      await getPoolStats(poolName);
+    });
+
   await program.parseAsync(process.argv);
 
   if (!process.argv.slice(2).length) {
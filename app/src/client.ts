@@ -119,6 +119,15 @@ export class PerpetualsClient {
     return this.program.account.pool.fetchMultiple(perpetuals.pools);
   };
 
+  // daily statistics of the pool for the current UTC day
+  getPoolDayStatsKey = (name: string) => {
+    let day = Math.floor(Date.now() / 1000 / 86400);
+    return this.findProgramAddress("pool_day_stats", [
+      this.getPoolKey(name),
+      new BN(day).toArray("le", 8),
+    ]).publicKey;
+  };
+
   getPoolLpTokenKey = (name: string) => {
     return this.findProgramAddress("lp_token_mint", [this.getPoolKey(name)])
       .publicKey;
@@ -145,6 +154,10 @@ export class PerpetualsClient {
     return (await this.getCustody(poolName, tokenMint)).oracle.oracleAccount;
   };
 
+  getTokenProgramKey = async (tokenMint: PublicKey) => {
+    return (await this.provider.connection.getAccountInfo(tokenMint)).owner;
+  };
+
   getCustodyTestOracleAccountKey = (poolName: string, tokenMint: PublicKey) => {
     return this.findProgramAddress("oracle_account", [
       this.getPoolKey(poolName),
@@ -333,6 +346,7 @@ export class PerpetualsClient {
         .accounts({
           admin: this.admin.publicKey,
           multisig: this.multisig.publicKey,
+          perpetuals: this.perpetuals.publicKey,
         })
         .remainingAccounts(adminMetas)
         .signers([this.admin])
@@ -405,6 +419,7 @@ export class PerpetualsClient {
         permissions,
         fees,
         borrowRate,
+        leverageTiers: [],
         ratios,
       })
       .accounts({
@@ -420,7 +435,7 @@ export class PerpetualsClient {
         ),
         custodyTokenMint: tokenMint,
         systemProgram: SystemProgram.programId,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: await this.getTokenProgramKey(tokenMint),
         rent: SYSVAR_RENT_PUBKEY,
       })
       .signers([this.admin])
@@ -446,7 +461,7 @@ export class PerpetualsClient {
           tokenMint
         ),
         systemProgram: SystemProgram.programId,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: await this.getTokenProgramKey(tokenMint),
       })
       .signers([this.admin])
       .rpc()
@@ -462,6 +477,7 @@ export class PerpetualsClient {
       .accounts({
         admin: this.admin.publicKey,
         multisig: this.multisig.publicKey,
+        perpetuals: this.perpetuals.publicKey,
         pool: this.getPoolKey(poolName),
         custody: this.getCustodyKey(poolName, tokenMint),
         systemProgram: SystemProgram.programId,
@@ -474,6 +490,22 @@ export class PerpetualsClient {
       });
   };
 
+  upgradeMultisig = async () => {
+    await this.program.methods
+      .upgradeMultisig({})
+      .accounts({
+        admin: this.admin.publicKey,
+        multisig: this.multisig.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([this.admin])
+      .rpc()
+      .catch((err) => {
+        console.error(err);
+        throw err;
+      });
+  };
+
   liquidate = async (
     wallet: PublicKey,
     poolName: string,
@@ -491,6 +523,7 @@ export class PerpetualsClient {
         transferAuthority: this.authority.publicKey,
         perpetuals: this.perpetuals.publicKey,
         pool: this.getPoolKey(poolName),
+        poolDayStats: this.getPoolDayStatsKey(poolName),
         position: this.getPositionKey(wallet, poolName, tokenMint, side),
         custody: this.getCustodyKey(poolName, tokenMint),
         custodyOracleAccount: await this.getCustodyOracleAccountKey(
@@ -501,7 +534,9 @@ export class PerpetualsClient {
           poolName,
           tokenMint
         ),
-        tokenProgram: TOKEN_PROGRAM_ID,
+        custodyTokenMint: tokenMint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: await this.getTokenProgramKey(tokenMint),
       })
       .rpc()
       .catch((err) => {
@@ -510,6 +545,21 @@ export class PerpetualsClient {
       });
   };
 
+  refreshAum = async (poolName: string) => {
+    return await this.program.methods
+      .refreshAum({})
+      .accounts({
+        perpetuals: this.perpetuals.publicKey,
+        pool: this.getPoolKey(poolName),
+      })
+      .remainingAccounts(await this.getCustodyMetas(poolName))
+      .rpc()
+      .catch((err) => {
+        console.error(err);
+        throw err;
+      });
+  };
+
   getOraclePrice = async (
     poolName: string,
     tokenMint: PublicKey,
@@ -698,6 +748,31 @@ export class PerpetualsClient {
       });
   };
 
+  getPositionHealth = async (
+    wallet: PublicKey,
+    poolName: string,
+    tokenMint: PublicKey,
+    side: PositionSide
+  ) => {
+    return await this.program.methods
+      .getPositionHealth({})
+      .accounts({
+        perpetuals: this.perpetuals.publicKey,
+        pool: this.getPoolKey(poolName),
+        position: this.getPositionKey(wallet, poolName, tokenMint, side),
+        custody: this.getCustodyKey(poolName, tokenMint),
+        custodyOracleAccount: await this.getCustodyOracleAccountKey(
+          poolName,
+          tokenMint
+        ),
+      })
+      .view()
+      .catch((err) => {
+        console.error(err);
+        throw err;
+      });
+  };
+
   getPnl = async (
     wallet: PublicKey,
     poolName: string,
@@ -768,4 +843,20 @@ export class PerpetualsClient {
         throw err;
       });
   };
+
+  getPoolStats = async (poolName: string) => {
+    return await this.program.methods
+      .getPoolStats({})
+      .accounts({
+        perpetuals: this.perpetuals.publicKey,
+        pool: this.getPoolKey(poolName),
+        lpTokenMint: this.getPoolLpTokenKey(poolName),
+      })
+      .remainingAccounts(await this.getCustodyMetas(poolName))
+      .view()
+      .catch((err) => {
+        console.error(err);
+        throw err;
+      });
+  };
 }
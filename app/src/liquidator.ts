@@ -93,7 +93,7 @@ async function run(poolName: string, tokenMint: PublicKey) {
       console.error(err);
     }
 
-    if (!perpetuals.permissions.allowClosePosition) {
+    if (!perpetuals.permissions.allowLiquidation) {
       client.error(
         `Liquidations are not allowed at this time. Retrying in ${errorDelay} sec...`
       );
@@ -54,4 +54,34 @@ pub enum PerpetualsError {
     InstructionNotAllowed,
     #[msg("Token utilization limit exceeded")]
     MaxUtilization,
+    #[msg("Invalid staking state")]
+    InvalidStakingState,
+    #[msg("Invalid withdrawal request state")]
+    InvalidWithdrawalState,
+    #[msg("Withdrawal queue is enabled for the pool")]
+    WithdrawalQueueEnabled,
+    #[msg("Invalid fee distribution config")]
+    InvalidFeeDistributionConfig,
+    #[msg("Approved multisig proposal is required")]
+    MultisigProposalRequired,
+    #[msg("Multisig proposal has expired")]
+    MultisigProposalExpired,
+    #[msg("Invalid multisig proposal state")]
+    InvalidProposalState,
+    #[msg("Instruction is timelocked and must be queued")]
+    TimelockRequired,
+    #[msg("Timelock delay has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Account is not a guardian")]
+    GuardianNotAuthorized,
+    #[msg("Pool amount limit exceeded")]
+    PoolAmountLimit,
+    #[msg("Invalid account version")]
+    InvalidAccountVersion,
+    #[msg("Token extension is not supported")]
+    UnsupportedTokenExtension,
+    #[msg("Cached assets under management are stale")]
+    StaleAumCache,
+    #[msg("Invalid custody aum index")]
+    InvalidCustodyAumIndex,
 }
@@ -1,13 +1,36 @@
 // admin instructions
 pub mod add_custody;
 pub mod add_pool;
+pub mod approve_proposal;
+pub mod cancel_proposal;
+pub mod cancel_queued;
+pub mod create_proposal;
 pub mod init;
+pub mod init_staking;
+pub mod queue_instruction;
 pub mod remove_custody;
 pub mod remove_pool;
+pub mod revoke_approval;
 pub mod set_admin_signers;
+pub mod set_custody_borrow_rate;
 pub mod set_custody_config;
+pub mod set_custody_fees;
+pub mod set_custody_oracle;
+pub mod set_custody_pricing;
+pub mod set_custody_ramp;
+pub mod set_custody_status;
+pub mod set_fee_distribution;
+pub mod set_guardians;
 pub mod set_permissions;
+pub mod set_pool_config;
+pub mod set_pool_ratios;
+pub mod set_pool_ratios_ramp;
+pub mod set_staking_config;
+pub mod set_timelock_delays;
+pub mod set_withdrawal_queue_config;
+pub mod sweep_custody;
 pub mod upgrade_custody;
+pub mod upgrade_multisig;
 pub mod withdraw_fees;
 pub mod withdraw_sol_fees;
 
@@ -19,7 +42,16 @@ pub mod test_init;
 // public instructions
 pub mod add_collateral;
 pub mod add_liquidity;
+pub mod add_liquidity_basket;
+pub mod add_margin_position;
+pub mod cancel_withdrawal;
+pub mod claim_stake_rewards;
+pub mod close_pool_day_stats;
 pub mod close_position;
+pub mod deposit_margin;
+pub mod distribute_fees;
+pub mod execute_queued;
+pub mod execute_withdrawal;
 pub mod get_add_liquidity_amount_and_fee;
 pub mod get_assets_under_management;
 pub mod get_entry_price_and_fee;
@@ -28,23 +60,48 @@ pub mod get_liquidation_price;
 pub mod get_liquidation_state;
 pub mod get_oracle_price;
 pub mod get_pnl;
+pub mod get_pool_stats;
+pub mod get_position_health;
 pub mod get_remove_liquidity_amount_and_fee;
 pub mod get_swap_amount_and_fees;
+pub mod guardian_pause;
+pub mod init_margin_account;
 pub mod liquidate;
+pub mod liquidate_margin_account;
+pub mod migrate_account;
 pub mod open_position;
+pub mod refresh_aum;
 pub mod remove_collateral;
 pub mod remove_liquidity;
+pub mod remove_liquidity_proportional;
+pub mod remove_margin_position;
+pub mod request_withdrawal;
+pub mod settle_position;
+pub mod stake;
 pub mod swap;
+pub mod unstake;
+pub mod withdraw_margin;
 
 // bring everything in scope
 pub use {
-    add_collateral::*, add_custody::*, add_liquidity::*, add_pool::*, close_position::*,
-    get_add_liquidity_amount_and_fee::*, get_assets_under_management::*,
+    add_collateral::*, add_custody::*, add_liquidity::*, add_liquidity_basket::*,
+    add_margin_position::*, add_pool::*, approve_proposal::*, cancel_proposal::*, cancel_queued::*,
+    cancel_withdrawal::*, claim_stake_rewards::*, close_pool_day_stats::*, close_position::*,
+    create_proposal::*, deposit_margin::*, distribute_fees::*, execute_queued::*,
+    execute_withdrawal::*, get_add_liquidity_amount_and_fee::*, get_assets_under_management::*,
     get_entry_price_and_fee::*, get_exit_price_and_fee::*, get_liquidation_price::*,
-    get_liquidation_state::*, get_oracle_price::*, get_pnl::*,
-    get_remove_liquidity_amount_and_fee::*, get_swap_amount_and_fees::*, init::*, liquidate::*,
-    open_position::*, remove_collateral::*, remove_custody::*, remove_liquidity::*, remove_pool::*,
-    set_admin_signers::*, set_custody_config::*, set_permissions::*, set_test_oracle_price::*,
-    set_test_time::*, swap::*, test_init::*, upgrade_custody::*, withdraw_fees::*,
-    withdraw_sol_fees::*,
+    get_liquidation_state::*, get_oracle_price::*, get_pnl::*, get_pool_stats::*,
+    get_position_health::*, get_remove_liquidity_amount_and_fee::*, get_swap_amount_and_fees::*,
+    guardian_pause::*, init::*, init_margin_account::*, init_staking::*, liquidate::*,
+    liquidate_margin_account::*, migrate_account::*, open_position::*, queue_instruction::*,
+    refresh_aum::*, remove_collateral::*, remove_custody::*, remove_liquidity::*,
+    remove_liquidity_proportional::*, remove_margin_position::*, remove_pool::*,
+    request_withdrawal::*, revoke_approval::*, set_admin_signers::*, set_custody_borrow_rate::*,
+    set_custody_config::*, set_custody_fees::*, set_custody_oracle::*, set_custody_pricing::*,
+    set_custody_ramp::*, set_custody_status::*, set_fee_distribution::*, set_guardians::*,
+    set_permissions::*, set_pool_config::*, set_pool_ratios::*, set_pool_ratios_ramp::*,
+    set_staking_config::*, set_test_oracle_price::*, set_test_time::*, set_timelock_delays::*,
+    set_withdrawal_queue_config::*, settle_position::*, stake::*, swap::*, sweep_custody::*,
+    test_init::*, unstake::*, upgrade_custody::*, upgrade_multisig::*, withdraw_fees::*,
+    withdraw_margin::*, withdraw_sol_fees::*,
 };
@@ -10,7 +10,6 @@ use {
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
     solana_program::program_error::ProgramError,
 };
 
@@ -20,12 +19,22 @@ pub struct AddCollateral<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    /// CHECK: token account of the custody mint or wallet of the owner for native SOL
     #[account(
         mut,
-        constraint = funding_account.mint == custody.mint,
-        has_one = owner
+        constraint = Perpetuals::is_token_account(&funding_account, &custody.mint, owner.key)
+            || Perpetuals::is_native_account(&funding_account, &custody.mint, owner.key)
     )]
-    pub funding_account: Box<Account<'info, TokenAccount>>,
+    pub funding_account: AccountInfo<'info>,
+
+    /// CHECK: temporary wrapped SOL account, only used for native SOL transfers
+    #[account(
+        mut,
+        seeds = [b"wsol_account",
+                 owner.key().as_ref()],
+        bump
+    )]
+    pub wsol_account: AccountInfo<'info>,
 
     /// CHECK: empty PDA, authority for token accounts
     #[account(
@@ -44,7 +53,8 @@ pub struct AddCollateral<'info> {
         mut,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -56,7 +66,8 @@ pub struct AddCollateral<'info> {
                  pool.key().as_ref(),
                  custody.key().as_ref(),
                  &[position.side as u8]],
-        bump = position.bump
+        bump = position.bump,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub position: Box<Account<'info, Position>>,
 
@@ -65,7 +76,8 @@ pub struct AddCollateral<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -75,6 +87,7 @@ pub struct AddCollateral<'info> {
     )]
     pub custody_oracle_account: AccountInfo<'info>,
 
+    /// CHECK: custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -82,9 +95,21 @@ pub struct AddCollateral<'info> {
                  custody.mint.as_ref()],
         bump = custody.token_account_bump
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
 
-    token_program: Program<'info, Token>,
+    system_program: Program<'info, System>,
+
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -93,6 +118,13 @@ pub struct AddCollateralParams {
 }
 
 pub fn add_collateral(ctx: Context<AddCollateral>, params: &AddCollateralParams) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    require!(
+        ctx.accounts.pool.permissions.allow_size_change,
+        PerpetualsError::InstructionNotAllowed
+    );
+
     // validate inputs
     msg!("Validate inputs");
     if params.collateral == 0 {
@@ -132,21 +164,61 @@ pub fn add_collateral(ctx: Context<AddCollateral>, params: &AddCollateralParams)
     };
 
     // compute fee
-    let fee_amount =
-        pool.get_add_liquidity_fee(token_id, params.collateral, custody, &token_ema_price)?;
+    let fee_amount = pool.get_add_liquidity_fee(
+        token_id,
+        params.collateral,
+        custody,
+        &token_ema_price,
+        curtime,
+    )?;
     msg!("Collected fee: {}", fee_amount);
 
     // compute amount to transfer
     let transfer_amount = math::checked_add(params.collateral, fee_amount)?;
-    let collateral_usd = min_price.get_asset_amount_usd(params.collateral, custody.decimals)?;
     msg!("Amount in: {}", transfer_amount);
+
+    // transfer tokens, token transfer fees are taken out of the collateral
+    msg!("Transfer tokens");
+    let received_amount = if Perpetuals::is_native_account(
+        &ctx.accounts.funding_account,
+        &custody.mint,
+        ctx.accounts.owner.key,
+    ) {
+        let owner_key = ctx.accounts.owner.key();
+        let wsol_account_bump = *ctx
+            .bumps
+            .get("wsol_account")
+            .ok_or(ProgramError::InvalidSeeds)?;
+        perpetuals.wrap_sol_from_user(
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.wsol_account.to_account_info(),
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[b"wsol_account", owner_key.as_ref(), &[wsol_account_bump]],
+            transfer_amount,
+        )?
+    } else {
+        perpetuals.transfer_tokens_from_user(
+            ctx.accounts.funding_account.clone(),
+            ctx.accounts.custody_token_account.clone(),
+            ctx.accounts.custody_token_mint.clone(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.token_program.clone(),
+            transfer_amount,
+        )?
+    };
+    let collateral = math::checked_sub(received_amount, fee_amount)?;
+    let collateral_usd = min_price.get_asset_amount_usd(collateral, custody.decimals)?;
     msg!("Collateral added in USD: {}", collateral_usd);
 
     // update existing position
     msg!("Update existing position");
     position.update_time = perpetuals.get_time()?;
     position.collateral_usd = math::checked_add(position.collateral_usd, collateral_usd)?;
-    position.collateral_amount = math::checked_add(position.collateral_amount, params.collateral)?;
+    position.collateral_amount = math::checked_add(position.collateral_amount, collateral)?;
 
     // check position risk
     msg!("Check position risks");
@@ -155,29 +227,27 @@ pub fn add_collateral(ctx: Context<AddCollateral>, params: &AddCollateralParams)
         PerpetualsError::MaxLeverage
     );
 
-    // transfer tokens
-    msg!("Transfer tokens");
-    perpetuals.transfer_tokens_from_user(
-        ctx.accounts.funding_account.to_account_info(),
-        ctx.accounts.custody_token_account.to_account_info(),
-        ctx.accounts.owner.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        transfer_amount,
-    )?;
-
     // update custody stats
     msg!("Update custody stats");
-    custody.collected_fees.open_position_usd = custody
-        .collected_fees
-        .open_position_usd
-        .wrapping_add(token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?);
+    custody.collected_fees.open_position_usd = math::checked_add(
+        custody.collected_fees.open_position_usd,
+        token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)? as u128,
+    )?;
 
-    custody.assets.collateral = math::checked_add(custody.assets.collateral, params.collateral)?;
+    custody.collected_token_fees.open_position = math::checked_add(
+        custody.collected_token_fees.open_position,
+        fee_amount as u128,
+    )?;
+
+    custody.assets.collateral = math::checked_add(custody.assets.collateral, collateral)?;
 
     let protocol_fee = Pool::get_fee_amount(custody.fees.protocol_share, fee_amount)?;
     custody.assets.protocol_fees = math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
 
     custody.add_collateral(position.side, collateral_usd)?;
 
+    // update cached aum contribution
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+
     Ok(())
 }
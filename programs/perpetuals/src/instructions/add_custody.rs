@@ -4,14 +4,18 @@ use {
     crate::{
         error::PerpetualsError,
         state::{
-            custody::{BorrowRateParams, Custody, Fees, OracleParams, PricingParams},
+            custody::{BorrowRateParams, Custody, Fees, LeverageTier, OracleParams, PricingParams},
             multisig::{AdminInstruction, Multisig},
             perpetuals::{Permissions, Perpetuals},
-            pool::{Pool, TokenRatios},
+            pool::{CustodyAum, Pool, RatiosRamp, TokenRatios},
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Mint, Token, TokenAccount},
+    solana_program::{program, system_instruction},
+    spl_token_2022::{
+        extension::{ExtensionType, StateWithExtensions},
+        state::{Account as TokenAccount, Mint},
+    },
 };
 
 #[derive(Accounts)]
@@ -41,13 +45,13 @@ pub struct AddCustody<'info> {
 
     #[account(
         mut,
-        realloc = Pool::LEN + (pool.custodies.len() + 1) * std::mem::size_of::<Pubkey>() +
-                              (pool.ratios.len() + 1) * std::mem::size_of::<TokenRatios>(),
+        realloc = Pool::get_len(pool.custodies.len() + 1),
         realloc::payer = admin,
         realloc::zero = false,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -62,23 +66,28 @@ pub struct AddCustody<'info> {
     )]
     pub custody: Box<Account<'info, Custody>>,
 
+    /// CHECK: created by the handler with the token program of the mint
     #[account(
-        init_if_needed,
-        payer = admin,
-        token::mint = custody_token_mint,
-        token::authority = transfer_authority,
+        mut,
         seeds = [b"custody_token_account",
                  pool.key().as_ref(),
                  custody_token_mint.key().as_ref()],
         bump
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: AccountInfo<'info>,
 
-    #[account()]
-    pub custody_token_mint: Box<Account<'info, Mint>>,
+    /// CHECK: SPL Token or Token-2022 mint, unpacked by the handler
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
 
     system_program: Program<'info, System>,
-    token_program: Program<'info, Token>,
+    /// CHECK: SPL Token or Token-2022 program
+    #[account(
+        constraint = Perpetuals::is_token_program(token_program.key)
+    )]
+    token_program: AccountInfo<'info>,
     rent: Sysvar<'info, Rent>,
 }
 
@@ -90,6 +99,7 @@ pub struct AddCustodyParams {
     pub permissions: Permissions,
     pub fees: Fees,
     pub borrow_rate: BorrowRateParams,
+    pub leverage_tiers: Vec<LeverageTier>,
     pub ratios: Vec<TokenRatios>,
 }
 
@@ -101,6 +111,12 @@ pub fn add_custody<'info>(
     if params.ratios.len() != ctx.accounts.pool.ratios.len() + 1 {
         return Err(ProgramError::InvalidArgument.into());
     }
+    // targets of existing tokens can only be changed with SetPoolRatios
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0
+            || params.ratios[..ctx.accounts.pool.ratios.len()] == ctx.accounts.pool.ratios[..],
+        PerpetualsError::TimelockRequired
+    );
 
     // validate signatures
     let mut multisig = ctx.accounts.multisig.load_mut()?;
@@ -109,6 +125,7 @@ pub fn add_custody<'info>(
         &ctx.accounts.admin,
         &Multisig::get_account_infos(&ctx)[1..],
         &Multisig::get_instruction_data(AdminInstruction::AddCustody, params)?,
+        ctx.accounts.perpetuals.get_time()?,
     )?;
     if signatures_left > 0 {
         msg!(
@@ -124,32 +141,118 @@ pub fn add_custody<'info>(
         return Err(ProgramError::AccountAlreadyInitialized.into());
     }
 
+    // validate mint extensions
+    let (decimals, account_extensions) = {
+        let mint_data = ctx.accounts.custody_token_mint.try_borrow_data()?;
+        let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+        let mint_extensions = mint.get_extension_types()?;
+        require!(
+            mint_extensions.iter().all(|extension| matches!(
+                extension,
+                ExtensionType::TransferFeeConfig | ExtensionType::MintCloseAuthority
+            )),
+            PerpetualsError::UnsupportedTokenExtension
+        );
+        (
+            mint.base.decimals,
+            ExtensionType::get_required_init_account_extensions(&mint_extensions),
+        )
+    };
+
+    // create custody token account
+    let token_account_bump = *ctx
+        .bumps
+        .get("custody_token_account")
+        .ok_or(ProgramError::InvalidSeeds)?;
+    if Perpetuals::is_empty_account(&ctx.accounts.custody_token_account)? {
+        msg!("Create custody token account");
+        let space = ExtensionType::get_account_len::<TokenAccount>(&account_extensions);
+        let pool_key = pool.key();
+        let mint_key = ctx.accounts.custody_token_mint.key();
+        let token_account_seeds: &[&[&[u8]]] = &[&[
+            b"custody_token_account",
+            pool_key.as_ref(),
+            mint_key.as_ref(),
+            &[token_account_bump],
+        ]];
+
+        program::invoke_signed(
+            &system_instruction::create_account(
+                ctx.accounts.admin.key,
+                ctx.accounts.custody_token_account.key,
+                ctx.accounts.rent.minimum_balance(space),
+                space as u64,
+                ctx.accounts.token_program.key,
+            ),
+            &[
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.custody_token_account.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            token_account_seeds,
+        )?;
+
+        program::invoke(
+            &spl_token_2022::instruction::initialize_account3(
+                ctx.accounts.token_program.key,
+                ctx.accounts.custody_token_account.key,
+                ctx.accounts.custody_token_mint.key,
+                ctx.accounts.transfer_authority.key,
+            )?,
+            &[
+                ctx.accounts.custody_token_account.clone(),
+                ctx.accounts.custody_token_mint.clone(),
+                ctx.accounts.token_program.clone(),
+            ],
+        )?;
+    } else {
+        let token_account = Perpetuals::get_token_account(&ctx.accounts.custody_token_account)?;
+        require_keys_eq!(token_account.mint, ctx.accounts.custody_token_mint.key());
+        require_keys_eq!(token_account.owner, ctx.accounts.transfer_authority.key());
+    }
+
     // update pool data
+    let keep_ramp = !pool.ratios.is_empty()
+        && pool.ratios_ramp.start_targets.len() == pool.ratios.len()
+        && params.ratios[..pool.ratios.len()] == pool.ratios[..];
     pool.custodies.push(ctx.accounts.custody.key());
     pool.ratios = params.ratios.clone();
+    // new custody holds no assets, its aum contribution is known
+    pool.custody_aum.push(CustodyAum {
+        price_time: ctx.accounts.perpetuals.get_time()?,
+        max_age_sec: params.oracle.max_price_age_sec,
+        ..CustodyAum::default()
+    });
+    if keep_ramp {
+        // existing targets are unchanged, new token starts at its target
+        pool.ratios_ramp
+            .start_targets
+            .push(params.ratios[params.ratios.len() - 1].target);
+    } else {
+        pool.ratios_ramp = RatiosRamp::default();
+    }
     if !pool.validate() {
         return err!(PerpetualsError::InvalidPoolConfig);
     }
 
     // record custody data
     let custody = ctx.accounts.custody.as_mut();
+    custody.version = Custody::VERSION;
     custody.pool = pool.key();
     custody.mint = ctx.accounts.custody_token_mint.key();
     custody.token_account = ctx.accounts.custody_token_account.key();
-    custody.decimals = ctx.accounts.custody_token_mint.decimals;
+    custody.decimals = decimals;
     custody.is_stable = params.is_stable;
     custody.oracle = params.oracle;
     custody.pricing = params.pricing;
     custody.permissions = params.permissions;
     custody.fees = params.fees;
     custody.borrow_rate = params.borrow_rate;
+    custody.set_leverage_tiers(&params.leverage_tiers)?;
     custody.borrow_rate_state.current_rate = params.borrow_rate.base_rate;
     custody.borrow_rate_state.last_update = ctx.accounts.perpetuals.get_time()?;
     custody.bump = *ctx.bumps.get("custody").ok_or(ProgramError::InvalidSeeds)?;
-    custody.token_account_bump = *ctx
-        .bumps
-        .get("custody_token_account")
-        .ok_or(ProgramError::InvalidSeeds)?;
+    custody.token_account_bump = token_account_bump;
 
     if !custody.validate() {
         err!(PerpetualsError::InvalidCustodyConfig)
@@ -9,6 +9,7 @@ use {
             oracle::OraclePrice,
             perpetuals::Perpetuals,
             pool::{AumCalcMode, Pool},
+            pool_day_stats::PoolDayStats,
         },
     },
     anchor_lang::prelude::*,
@@ -22,12 +23,22 @@ pub struct AddLiquidity<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    /// CHECK: token account of the custody mint or wallet of the owner for native SOL
     #[account(
         mut,
-        constraint = funding_account.mint == custody.mint,
-        has_one = owner
+        constraint = Perpetuals::is_token_account(&funding_account, &custody.mint, owner.key)
+            || Perpetuals::is_native_account(&funding_account, &custody.mint, owner.key)
+    )]
+    pub funding_account: AccountInfo<'info>,
+
+    /// CHECK: temporary wrapped SOL account, only used for native SOL transfers
+    #[account(
+        mut,
+        seeds = [b"wsol_account",
+                 owner.key().as_ref()],
+        bump
     )]
-    pub funding_account: Box<Account<'info, TokenAccount>>,
+    pub wsol_account: AccountInfo<'info>,
 
     #[account(
         mut,
@@ -53,16 +64,29 @@ pub struct AddLiquidity<'info> {
         mut,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
     #[account(
         mut,
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -72,6 +96,7 @@ pub struct AddLiquidity<'info> {
     )]
     pub custody_oracle_account: AccountInfo<'info>,
 
+    /// CHECK: custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -79,7 +104,13 @@ pub struct AddLiquidity<'info> {
                  custody.mint.as_ref()],
         bump = custody.token_account_bump
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
 
     #[account(
         mut,
@@ -89,8 +120,16 @@ pub struct AddLiquidity<'info> {
     )]
     pub lp_token_mint: Box<Account<'info, Mint>>,
 
+    system_program: Program<'info, System>,
+
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == custody_token_program.key
+    )]
+    custody_token_program: AccountInfo<'info>,
+
     token_program: Program<'info, Token>,
-    // remaining accounts:
+    // remaining accounts (optional, cached custody contributions are used if omitted):
     //   pool.tokens.len() custody accounts (read-only, unsigned)
     //   pool.tokens.len() custody oracles (read-only, unsigned)
 }
@@ -107,7 +146,10 @@ pub fn add_liquidity(ctx: Context<AddLiquidity>, params: &AddLiquidityParams) ->
     let perpetuals = ctx.accounts.perpetuals.as_mut();
     let custody = ctx.accounts.custody.as_mut();
     require!(
-        perpetuals.permissions.allow_add_liquidity && custody.permissions.allow_add_liquidity,
+        perpetuals.permissions.allow_add_liquidity
+            && ctx.accounts.pool.permissions.allow_add_liquidity
+            && custody.permissions.allow_add_liquidity
+            && !custody.is_reduce_only(),
         PerpetualsError::InstructionNotAllowed
     );
 
@@ -119,6 +161,41 @@ pub fn add_liquidity(ctx: Context<AddLiquidity>, params: &AddLiquidityParams) ->
     let pool = ctx.accounts.pool.as_mut();
     let token_id = pool.get_token_id(&custody.key())?;
 
+    // transfer tokens, token transfer fees are taken out of the deposit
+    msg!("Transfer tokens");
+    let amount_in = if Perpetuals::is_native_account(
+        &ctx.accounts.funding_account,
+        &custody.mint,
+        ctx.accounts.owner.key,
+    ) {
+        let owner_key = ctx.accounts.owner.key();
+        let wsol_account_bump = *ctx
+            .bumps
+            .get("wsol_account")
+            .ok_or(ProgramError::InvalidSeeds)?;
+        perpetuals.wrap_sol_from_user(
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.wsol_account.to_account_info(),
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.custody_token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[b"wsol_account", owner_key.as_ref(), &[wsol_account_bump]],
+            params.amount_in,
+        )?
+    } else {
+        perpetuals.transfer_tokens_from_user(
+            ctx.accounts.funding_account.to_account_info(),
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.custody_token_program.to_account_info(),
+            params.amount_in,
+        )?
+    };
+    msg!("Amount in: {}", amount_in);
+
     // calculate fee
     let curtime = perpetuals.get_time()?;
 
@@ -147,35 +224,25 @@ pub fn add_liquidity(ctx: Context<AddLiquidity>, params: &AddLiquidityParams) ->
     };
 
     let fee_amount =
-        pool.get_add_liquidity_fee(token_id, params.amount_in, custody, &token_ema_price)?;
+        pool.get_add_liquidity_fee(token_id, amount_in, custody, &token_ema_price, curtime)?;
     msg!("Collected fee: {}", fee_amount);
 
     // check pool constraints
     msg!("Check pool constraints");
     let protocol_fee = Pool::get_fee_amount(custody.fees.protocol_share, fee_amount)?;
-    let deposit_amount = math::checked_sub(params.amount_in, protocol_fee)?;
+    let deposit_amount = math::checked_sub(amount_in, protocol_fee)?;
     require!(
         pool.check_token_ratio(token_id, deposit_amount, 0, custody, &token_ema_price)?,
         PerpetualsError::TokenRatioOutOfRange
     );
 
-    // transfer tokens
-    msg!("Transfer tokens");
-    perpetuals.transfer_tokens_from_user(
-        ctx.accounts.funding_account.to_account_info(),
-        ctx.accounts.custody_token_account.to_account_info(),
-        ctx.accounts.owner.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        params.amount_in,
-    )?;
-
     // compute assets under management
     msg!("Compute assets under management");
-    let pool_amount_usd =
-        pool.get_assets_under_management_usd(AumCalcMode::Max, ctx.remaining_accounts, curtime)?;
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+    let pool_amount_usd = pool.get_aum_usd(AumCalcMode::Max, ctx.remaining_accounts, curtime)?;
 
     // compute amount of lp tokens to mint
-    let no_fee_amount = math::checked_sub(params.amount_in, fee_amount)?;
+    let no_fee_amount = math::checked_sub(amount_in, fee_amount)?;
     require_gte!(
         no_fee_amount,
         1u64,
@@ -213,15 +280,22 @@ pub fn add_liquidity(ctx: Context<AddLiquidity>, params: &AddLiquidityParams) ->
 
     // update custody stats
     msg!("Update custody stats");
-    custody.collected_fees.add_liquidity_usd = custody
-        .collected_fees
-        .add_liquidity_usd
-        .wrapping_add(token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?);
+    let amount_in_usd = token_ema_price.get_asset_amount_usd(amount_in, custody.decimals)?;
+    let fee_amount_usd = token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?;
+    custody.collected_fees.add_liquidity_usd = math::checked_add(
+        custody.collected_fees.add_liquidity_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    custody.collected_token_fees.add_liquidity = math::checked_add(
+        custody.collected_token_fees.add_liquidity,
+        fee_amount as u128,
+    )?;
 
-    custody.volume_stats.add_liquidity_usd = custody
-        .volume_stats
-        .add_liquidity_usd
-        .wrapping_add(token_ema_price.get_asset_amount_usd(params.amount_in, custody.decimals)?);
+    custody.volume_stats.add_liquidity_usd = math::checked_add(
+        custody.volume_stats.add_liquidity_usd,
+        amount_in_usd as u128,
+    )?;
 
     custody.assets.protocol_fees = math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
 
@@ -232,8 +306,34 @@ pub fn add_liquidity(ctx: Context<AddLiquidity>, params: &AddLiquidityParams) ->
     // update pool stats
     msg!("Update pool stats");
     custody.exit(&crate::ID)?;
-    pool.aum_usd =
-        pool.get_assets_under_management_usd(AumCalcMode::EMA, ctx.remaining_accounts, curtime)?;
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+    pool.update_aum_usd(ctx.remaining_accounts, curtime)?;
+    require!(
+        pool.check_aum_limit(pool.aum_usd),
+        PerpetualsError::PoolAmountLimit
+    );
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.add_liquidity_usd = math::checked_add(
+        pool_day_stats.volume.add_liquidity_usd,
+        amount_in_usd as u128,
+    )?;
+    pool_day_stats.fees.add_liquidity_usd = math::checked_add(
+        pool_day_stats.fees.add_liquidity_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.owner.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
 
     Ok(())
 }
@@ -0,0 +1,315 @@
+//! AddLiquidityBasket instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::Custody,
+            oracle::OraclePrice,
+            perpetuals::Perpetuals,
+            pool::{AumCalcMode, Pool},
+            pool_day_stats::PoolDayStats,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount},
+    solana_program::program_error::ProgramError,
+};
+
+#[derive(Accounts)]
+#[instruction(params: AddLiquidityBasketParams)]
+pub struct AddLiquidityBasket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = lp_token_account.mint == lp_token_mint.key(),
+        has_one = owner
+    )]
+    pub lp_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_token_mint",
+                 pool.key().as_ref()],
+        bump = pool.lp_token_bump
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    // remaining accounts:
+    //   pool.tokens.len() custody accounts (write, unsigned)
+    //   pool.tokens.len() custody oracles (read-only, unsigned)
+    //   pool.tokens.len() custody token accounts (write, unsigned)
+    //   pool.tokens.len() funding token accounts (write, unsigned)
+    //   pool.tokens.len() custody token mints (read-only, unsigned)
+    //   pool.tokens.len() token programs of the custody mints (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AddLiquidityBasketParams {
+    // in the same order as pool.custodies, zero for tokens not deposited
+    pub amounts_in: Vec<u64>,
+    pub min_lp_amount_out: u64,
+}
+
+pub fn add_liquidity_basket<'info>(
+    ctx: Context<'_, '_, '_, 'info, AddLiquidityBasket<'info>>,
+    params: &AddLiquidityBasketParams,
+) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    let perpetuals = ctx.accounts.perpetuals.as_mut();
+    require!(
+        perpetuals.permissions.allow_add_liquidity
+            && ctx.accounts.pool.permissions.allow_add_liquidity,
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    // validate inputs
+    msg!("Validate inputs");
+    let pool = ctx.accounts.pool.as_mut();
+    let num_custodies = pool.custodies.len();
+    if params.amounts_in.len() != num_custodies || params.amounts_in.iter().all(|&x| x == 0) {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    let accounts = ctx.remaining_accounts;
+    if accounts.len() < num_custodies * 6 {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+
+    // compute assets under management
+    msg!("Compute assets under management");
+    let curtime = perpetuals.get_time()?;
+    let pool_amount_usd =
+        pool.get_assets_under_management_usd(AumCalcMode::Max, accounts, curtime)?;
+
+    // deposit tokens
+    let mut deposits = Vec::with_capacity(num_custodies);
+    let mut deposit_amount_usd: u64 = 0;
+    let mut amount_in_usd: u64 = 0;
+    let mut fee_amount_usd: u64 = 0;
+    for (token_id, &custody_key) in pool.custodies.iter().enumerate() {
+        if params.amounts_in[token_id] == 0 {
+            continue;
+        }
+        let oracle_idx = token_id + num_custodies;
+        let token_account_idx = oracle_idx + num_custodies;
+        let funding_idx = token_account_idx + num_custodies;
+        let mint_idx = funding_idx + num_custodies;
+        let token_program_idx = mint_idx + num_custodies;
+
+        require_keys_eq!(accounts[token_id].key(), custody_key);
+        let mut custody = Account::<Custody>::try_from(&accounts[token_id])?;
+        require!(
+            custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        require!(
+            custody.permissions.allow_add_liquidity && !custody.is_reduce_only(),
+            PerpetualsError::InstructionNotAllowed
+        );
+        require_keys_eq!(accounts[oracle_idx].key(), custody.oracle.oracle_account);
+        require_keys_eq!(accounts[token_account_idx].key(), custody.token_account);
+        let funding_account = Perpetuals::get_token_account(&accounts[funding_idx])?;
+        require_keys_eq!(funding_account.mint, custody.mint);
+        require_keys_eq!(funding_account.owner, ctx.accounts.owner.key());
+        require_keys_eq!(accounts[mint_idx].key(), custody.mint);
+        require_keys_eq!(accounts[token_program_idx].key(), *accounts[mint_idx].owner);
+
+        // transfer tokens, token transfer fees are taken out of the deposit
+        msg!("Transfer tokens");
+        let amount_in = perpetuals.transfer_tokens_from_user(
+            accounts[funding_idx].clone(),
+            accounts[token_account_idx].clone(),
+            accounts[mint_idx].clone(),
+            ctx.accounts.owner.to_account_info(),
+            accounts[token_program_idx].clone(),
+            params.amounts_in[token_id],
+        )?;
+
+        let token_price = OraclePrice::new_from_oracle(
+            custody.oracle.oracle_type,
+            &accounts[oracle_idx],
+            custody.oracle.max_price_error,
+            custody.oracle.max_price_age_sec,
+            curtime,
+            false,
+        )?;
+
+        let token_ema_price = OraclePrice::new_from_oracle(
+            custody.oracle.oracle_type,
+            &accounts[oracle_idx],
+            custody.oracle.max_price_error,
+            custody.oracle.max_price_age_sec,
+            curtime,
+            custody.pricing.use_ema,
+        )?;
+
+        let min_price = if token_price < token_ema_price {
+            token_price
+        } else {
+            token_ema_price
+        };
+
+        let current_ratio = pool.get_current_ratio(&custody, &token_ema_price)?;
+
+        let fee_amount =
+            pool.get_add_liquidity_fee(token_id, amount_in, &custody, &token_ema_price, curtime)?;
+        msg!("Collected fee: {}", fee_amount);
+
+        let protocol_fee = Pool::get_fee_amount(custody.fees.protocol_share, fee_amount)?;
+        let deposit_amount = math::checked_sub(amount_in, protocol_fee)?;
+
+        let no_fee_amount = math::checked_sub(amount_in, fee_amount)?;
+        deposit_amount_usd = math::checked_add(
+            deposit_amount_usd,
+            min_price.get_asset_amount_usd(no_fee_amount, custody.decimals)?,
+        )?;
+
+        // update custody stats
+        msg!("Update custody stats");
+        let token_fee_usd = token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?;
+        let token_amount_in_usd =
+            token_ema_price.get_asset_amount_usd(amount_in, custody.decimals)?;
+        fee_amount_usd = math::checked_add(fee_amount_usd, token_fee_usd)?;
+        amount_in_usd = math::checked_add(amount_in_usd, token_amount_in_usd)?;
+
+        custody.collected_fees.add_liquidity_usd = math::checked_add(
+            custody.collected_fees.add_liquidity_usd,
+            token_fee_usd as u128,
+        )?;
+
+        custody.collected_token_fees.add_liquidity = math::checked_add(
+            custody.collected_token_fees.add_liquidity,
+            fee_amount as u128,
+        )?;
+
+        custody.volume_stats.add_liquidity_usd = math::checked_add(
+            custody.volume_stats.add_liquidity_usd,
+            token_amount_in_usd as u128,
+        )?;
+
+        custody.assets.protocol_fees =
+            math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
+
+        custody.assets.owned = math::checked_add(custody.assets.owned, deposit_amount)?;
+
+        custody.update_borrow_rate(curtime)?;
+
+        custody.exit(&crate::ID)?;
+
+        deposits.push((token_id, custody, token_ema_price, current_ratio));
+    }
+    require_gte!(
+        deposit_amount_usd,
+        1u64,
+        PerpetualsError::InsufficientAmountReturned
+    );
+
+    // compute amount of lp tokens to mint
+    let lp_amount = if pool_amount_usd == 0 {
+        deposit_amount_usd
+    } else {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(
+                deposit_amount_usd as u128,
+                ctx.accounts.lp_token_mint.supply as u128,
+            )?,
+            pool_amount_usd,
+        )?)?
+    };
+    msg!("LP tokens to mint: {}", lp_amount);
+
+    require!(
+        lp_amount >= params.min_lp_amount_out,
+        PerpetualsError::MaxPriceSlippage
+    );
+
+    // update pool stats
+    msg!("Update pool stats");
+    pool.update_aum_usd(accounts, curtime)?;
+    require!(
+        pool.check_aum_limit(pool.aum_usd),
+        PerpetualsError::PoolAmountLimit
+    );
+
+    // check pool constraints against the final state
+    msg!("Check pool constraints");
+    for (token_id, custody, token_ema_price, current_ratio) in deposits.iter() {
+        let new_ratio = pool.get_current_ratio(custody, token_ema_price)?;
+        require!(
+            pool.check_ratio_change(*token_id, *current_ratio, new_ratio),
+            PerpetualsError::TokenRatioOutOfRange
+        );
+    }
+
+    // mint lp tokens
+    perpetuals.mint_tokens(
+        ctx.accounts.lp_token_mint.to_account_info(),
+        ctx.accounts.lp_token_account.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        lp_amount,
+    )?;
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.add_liquidity_usd = math::checked_add(
+        pool_day_stats.volume.add_liquidity_usd,
+        amount_in_usd as u128,
+    )?;
+    pool_day_stats.fees.add_liquidity_usd = math::checked_add(
+        pool_day_stats.fees.add_liquidity_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.owner.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! AddMarginPosition instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            margin_account::MarginAccount, perpetuals::Perpetuals, pool::Pool, position::Position,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct AddMarginPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        realloc = MarginAccount::LEN + (margin_account.positions.len() + 1) * std::mem::size_of::<Pubkey>(),
+        realloc::payer = owner,
+        realloc::zero = false,
+        seeds = [b"margin_account",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump = margin_account.bump
+    )]
+    pub margin_account: Box<Account<'info, MarginAccount>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = pool,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AddMarginPositionParams {}
+
+pub fn add_margin_position(
+    ctx: Context<AddMarginPosition>,
+    _params: &AddMarginPositionParams,
+) -> Result<()> {
+    // validate inputs
+    msg!("Validate inputs");
+    let position_key = ctx.accounts.position.key();
+    let position = ctx.accounts.position.as_mut();
+    require!(
+        !position.is_cross_margin,
+        PerpetualsError::InvalidPositionState
+    );
+
+    // link position to the margin account
+    msg!("Link position");
+    ctx.accounts.margin_account.add_position(&position_key)?;
+    position.is_cross_margin = true;
+
+    Ok(())
+}
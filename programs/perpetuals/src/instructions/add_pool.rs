@@ -5,7 +5,7 @@ use {
         error::PerpetualsError,
         state::{
             multisig::{AdminInstruction, Multisig},
-            perpetuals::Perpetuals,
+            perpetuals::{Permissions, Perpetuals},
             pool::Pool,
         },
     },
@@ -96,6 +96,7 @@ pub fn add_pool<'info>(
         &ctx.accounts.admin,
         &Multisig::get_account_infos(&ctx)[1..],
         &Multisig::get_instruction_data(AdminInstruction::AddPool, params)?,
+        ctx.accounts.perpetuals.get_time()?,
     )?;
     if signatures_left > 0 {
         msg!(
@@ -116,6 +117,18 @@ pub fn add_pool<'info>(
     msg!("Record pool: {}", params.name);
     pool.inception_time = perpetuals.get_time()?;
     pool.name = params.name.clone();
+    pool.permissions = Permissions {
+        allow_swap: true,
+        allow_add_liquidity: true,
+        allow_remove_liquidity: true,
+        allow_open_position: true,
+        allow_close_position: true,
+        allow_pnl_withdrawal: true,
+        allow_collateral_withdrawal: true,
+        allow_size_change: true,
+        allow_liquidation: true,
+    };
+    pool.version = Pool::VERSION;
     pool.bump = *ctx.bumps.get("pool").ok_or(ProgramError::InvalidSeeds)?;
     pool.lp_token_bump = *ctx
         .bumps
@@ -0,0 +1,54 @@
+//! ApproveProposal instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::multisig::{Multisig, Proposal},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal",
+                 proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ApproveProposalParams {}
+
+pub fn approve_proposal(
+    ctx: Context<ApproveProposal>,
+    _params: &ApproveProposalParams,
+) -> Result<()> {
+    // validate inputs
+    msg!("Validate inputs");
+    let multisig = ctx.accounts.multisig.load()?;
+    multisig.get_signer_index(ctx.accounts.admin.key)?;
+
+    let proposal = ctx.accounts.proposal.as_mut();
+    if proposal.executed {
+        return err!(PerpetualsError::MultisigAlreadyExecuted);
+    }
+    if proposal.is_expired(Clock::get()?.unix_timestamp) {
+        return err!(PerpetualsError::MultisigProposalExpired);
+    }
+
+    // record approval
+    msg!("Approve proposal: {}", proposal.proposal_id);
+    proposal.approve(ctx.accounts.admin.key)
+}
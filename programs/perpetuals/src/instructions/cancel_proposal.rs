@@ -0,0 +1,60 @@
+//! CancelProposal instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::multisig::{Multisig, Proposal},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    /// CHECK: proposal creator, receives the rent back
+    #[account(
+        mut,
+        constraint = proposer.key() == proposal.proposer
+    )]
+    pub proposer: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal",
+                 proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        close = proposer
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CancelProposalParams {}
+
+pub fn cancel_proposal(ctx: Context<CancelProposal>, _params: &CancelProposalParams) -> Result<()> {
+    // pending proposals can only be cancelled by the proposer,
+    // expired or executed ones by any of multisig signers
+    msg!("Validate inputs");
+    let multisig = ctx.accounts.multisig.load()?;
+    multisig.get_signer_index(ctx.accounts.admin.key)?;
+
+    let proposal = ctx.accounts.proposal.as_ref();
+    if ctx.accounts.admin.key() != proposal.proposer
+        && !proposal.executed
+        && !proposal.is_expired(Clock::get()?.unix_timestamp)
+    {
+        return err!(PerpetualsError::MultisigAccountNotAuthorized);
+    }
+
+    msg!("Cancel proposal: {}", proposal.proposal_id);
+
+    Ok(())
+}
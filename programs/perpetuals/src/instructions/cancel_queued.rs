@@ -0,0 +1,81 @@
+//! CancelQueued instruction handler
+
+use {
+    crate::state::{
+        multisig::{AdminInstruction, Multisig},
+        perpetuals::Perpetuals,
+        timelock::QueuedInstruction,
+    },
+    anchor_lang::{prelude::*, AccountsClose},
+};
+
+#[derive(Accounts)]
+pub struct CancelQueued<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    /// CHECK: queued instruction payer, receives the rent back
+    #[account(
+        mut,
+        constraint = payer.key() == queued_instruction.payer
+    )]
+    pub payer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"queued_instruction",
+                 queued_instruction.queue_id.to_le_bytes().as_ref()],
+        bump = queued_instruction.bump
+    )]
+    pub queued_instruction: Box<Account<'info, QueuedInstruction>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CancelQueuedParams {}
+
+pub fn cancel_queued<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelQueued<'info>>,
+    params: &CancelQueuedParams,
+) -> Result<u8> {
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::CancelQueued, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // close the queued instruction, it is only closed once enough signatures are
+    // collected, hence no close constraint
+    msg!(
+        "Cancel queued instruction: {}",
+        ctx.accounts.queued_instruction.queue_id
+    );
+    ctx.accounts
+        .queued_instruction
+        .close(ctx.accounts.payer.to_account_info())?;
+
+    Ok(0)
+}
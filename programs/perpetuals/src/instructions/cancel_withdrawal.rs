@@ -0,0 +1,112 @@
+//! CancelWithdrawal instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            perpetuals::Perpetuals,
+            pool::Pool,
+            withdrawal_queue::{WithdrawalQueue, WithdrawalRequest},
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+#[instruction(params: CancelWithdrawalParams)]
+pub struct CancelWithdrawal<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = receiving_account.mint == lp_token_mint.key(),
+        has_one = owner
+    )]
+    pub receiving_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"withdrawal_queue",
+                 pool.key().as_ref()],
+        bump = withdrawal_queue.bump
+    )]
+    pub withdrawal_queue: Box<Account<'info, WithdrawalQueue>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"withdrawal_request",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump = withdrawal_request.bump,
+        close = owner
+    )]
+    pub withdrawal_request: Box<Account<'info, WithdrawalRequest>>,
+
+    #[account(
+        mut,
+        seeds = [b"withdrawal_escrow_account",
+                 pool.key().as_ref()],
+        bump = withdrawal_queue.escrow_token_account_bump
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"lp_token_mint",
+                 pool.key().as_ref()],
+        bump = pool.lp_token_bump
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    token_program: Program<'info, Token>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CancelWithdrawalParams {}
+
+pub fn cancel_withdrawal(
+    ctx: Context<CancelWithdrawal>,
+    _params: &CancelWithdrawalParams,
+) -> Result<()> {
+    // return escrowed tokens
+    msg!("Transfer tokens");
+    let lp_amount = ctx.accounts.withdrawal_request.lp_amount;
+    ctx.accounts.perpetuals.transfer_tokens(
+        ctx.accounts.escrow_token_account.to_account_info(),
+        ctx.accounts.receiving_account.to_account_info(),
+        ctx.accounts.lp_token_mint.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        lp_amount,
+    )?;
+
+    let withdrawal_queue = ctx.accounts.withdrawal_queue.as_mut();
+    withdrawal_queue.pending_lp_amount =
+        math::checked_sub(withdrawal_queue.pending_lp_amount, lp_amount)?;
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! ClaimStakeRewards instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::Custody,
+            perpetuals::Perpetuals,
+            pool::Pool,
+            staking::{StakeAccount, Staking},
+        },
+    },
+    anchor_lang::prelude::*,
+    solana_program::program_error::ProgramError,
+};
+
+#[derive(Accounts)]
+pub struct ClaimStakeRewards<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"staking",
+                 pool.key().as_ref()],
+        bump = staking.bump
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"stake_account",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump = stake_account.bump
+    )]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+    // remaining accounts:
+    //   pool.tokens.len() custody accounts (write, unsigned)
+    //   pool.tokens.len() custody token accounts (write, unsigned)
+    //   pool.tokens.len() receiving token accounts (write, unsigned)
+    //   pool.tokens.len() custody token mints (read-only, unsigned)
+    //   pool.tokens.len() token programs of the custody mints (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ClaimStakeRewardsParams {}
+
+pub fn claim_stake_rewards<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimStakeRewards<'info>>,
+    _params: &ClaimStakeRewardsParams,
+) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    require!(
+        ctx.accounts.pool.permissions.allow_remove_liquidity,
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    // validate inputs
+    msg!("Validate inputs");
+    let pool = &ctx.accounts.pool;
+    let num_custodies = pool.custodies.len();
+    let accounts = ctx.remaining_accounts;
+    if accounts.len() < num_custodies * 5 {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+
+    // update rewards
+    msg!("Update rewards");
+    let staking = ctx.accounts.staking.as_mut();
+    let stake_account = ctx.accounts.stake_account.as_mut();
+    staking.update_pool_rewards(pool, accounts)?;
+    stake_account.update_rewards(staking)?;
+
+    // transfer rewards
+    msg!("Transfer rewards");
+    for (idx, custody_key) in pool.custodies.iter().enumerate() {
+        let token_account_idx = idx + num_custodies;
+        let receiving_idx = token_account_idx + num_custodies;
+        let mint_idx = receiving_idx + num_custodies;
+        let token_program_idx = mint_idx + num_custodies;
+
+        let staking_idx = if let Some(staking_idx) = staking.get_reward_index(custody_key) {
+            staking_idx
+        } else {
+            continue;
+        };
+        let reward = if let Some(reward) = stake_account
+            .rewards
+            .iter_mut()
+            .find(|x| x.custody == *custody_key)
+        {
+            reward
+        } else {
+            continue;
+        };
+        if reward.pending_amount == 0 {
+            continue;
+        }
+
+        require_keys_eq!(accounts[idx].key(), *custody_key);
+        let custody = Account::<Custody>::try_from(&accounts[idx])?;
+        require!(
+            custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        require_keys_eq!(accounts[token_account_idx].key(), custody.token_account);
+        let receiving_account = Perpetuals::get_token_account(&accounts[receiving_idx])?;
+        require_keys_eq!(receiving_account.mint, custody.mint);
+        require_keys_eq!(receiving_account.owner, ctx.accounts.owner.key());
+        require_keys_eq!(accounts[mint_idx].key(), custody.mint);
+        require_keys_eq!(accounts[token_program_idx].key(), *accounts[mint_idx].owner);
+
+        let staking_reward = &mut staking.rewards[staking_idx];
+        require!(
+            staking_reward.reserved_amount >= reward.pending_amount,
+            PerpetualsError::InvalidStakingState
+        );
+
+        msg!("Claim amount: {}", reward.pending_amount);
+        ctx.accounts.perpetuals.transfer_tokens(
+            accounts[token_account_idx].clone(),
+            accounts[receiving_idx].clone(),
+            accounts[mint_idx].clone(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            accounts[token_program_idx].clone(),
+            reward.pending_amount,
+        )?;
+
+        staking_reward.reserved_amount =
+            math::checked_sub(staking_reward.reserved_amount, reward.pending_amount)?;
+        reward.pending_amount = 0;
+    }
+
+    Ok(())
+}
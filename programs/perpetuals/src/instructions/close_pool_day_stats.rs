@@ -0,0 +1,58 @@
+//! ClosePoolDayStats instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{perpetuals::Perpetuals, pool::Pool, pool_day_stats::PoolDayStats},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct ClosePoolDayStats<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = payer,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &pool_day_stats.day.to_le_bytes()],
+        bump = pool_day_stats.bump,
+        close = payer
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ClosePoolDayStatsParams {}
+
+pub fn close_pool_day_stats(
+    ctx: Context<ClosePoolDayStats>,
+    _params: &ClosePoolDayStatsParams,
+) -> Result<()> {
+    // rent is returned to the payer of the first update of the day
+    let curtime = ctx.accounts.perpetuals.get_time()?;
+    require!(
+        ctx.accounts.pool_day_stats.can_close(curtime),
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    Ok(())
+}
@@ -6,14 +6,16 @@ use {
         math,
         state::{
             custody::Custody,
+            margin_account::MarginAccount,
             oracle::OraclePrice,
             perpetuals::Perpetuals,
             pool::Pool,
+            pool_day_stats::PoolDayStats,
             position::{Position, Side},
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
+    solana_program::program_error::ProgramError,
 };
 
 #[derive(Accounts)]
@@ -21,12 +23,22 @@ pub struct ClosePosition<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    /// CHECK: token account of the custody mint or wallet of the owner for native SOL
     #[account(
         mut,
-        constraint = receiving_account.mint == custody.mint,
-        has_one = owner
+        constraint = Perpetuals::is_token_account(&receiving_account, &custody.mint, owner.key)
+            || Perpetuals::is_native_account(&receiving_account, &custody.mint, owner.key)
     )]
-    pub receiving_account: Box<Account<'info, TokenAccount>>,
+    pub receiving_account: AccountInfo<'info>,
+
+    /// CHECK: temporary wrapped SOL account, only used for native SOL transfers
+    #[account(
+        mut,
+        seeds = [b"wsol_account",
+                 owner.key().as_ref()],
+        bump
+    )]
+    pub wsol_account: AccountInfo<'info>,
 
     /// CHECK: empty PDA, authority for token accounts
     #[account(
@@ -45,10 +57,22 @@ pub struct ClosePosition<'info> {
         mut,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
     #[account(
         mut,
         has_one = owner,
@@ -58,7 +82,8 @@ pub struct ClosePosition<'info> {
                  custody.key().as_ref(),
                  &[position.side as u8]],
         bump = position.bump,
-        close = owner
+        close = owner,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub position: Box<Account<'info, Position>>,
 
@@ -67,7 +92,8 @@ pub struct ClosePosition<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -77,6 +103,7 @@ pub struct ClosePosition<'info> {
     )]
     pub custody_oracle_account: AccountInfo<'info>,
 
+    /// CHECK: custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -84,9 +111,28 @@ pub struct ClosePosition<'info> {
                  custody.mint.as_ref()],
         bump = custody.token_account_bump
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
 
-    token_program: Program<'info, Token>,
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
+    // remaining accounts, only for positions linked to a margin account:
+    //   1 margin account (writable, unsigned)
+    //   1 margin collateral custody (writable, unsigned)
+    //   1 margin collateral custody oracle (read-only, unsigned)
+    //   margin_account.positions.len() - 1 position accounts (read-only, unsigned)
+    //   margin_account.positions.len() - 1 custody accounts (read-only, unsigned)
+    //   margin_account.positions.len() - 1 custody oracles (read-only, unsigned)
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
@@ -100,7 +146,9 @@ pub fn close_position(ctx: Context<ClosePosition>, params: &ClosePositionParams)
     let perpetuals = ctx.accounts.perpetuals.as_mut();
     let custody = ctx.accounts.custody.as_mut();
     require!(
-        perpetuals.permissions.allow_close_position && custody.permissions.allow_close_position,
+        perpetuals.permissions.allow_close_position
+            && ctx.accounts.pool.permissions.allow_close_position
+            && custody.permissions.allow_close_position,
         PerpetualsError::InstructionNotAllowed
     );
 
@@ -109,8 +157,10 @@ pub fn close_position(ctx: Context<ClosePosition>, params: &ClosePositionParams)
     if params.price == 0 {
         return Err(ProgramError::InvalidArgument.into());
     }
+    let position_key = ctx.accounts.position.key();
     let position = ctx.accounts.position.as_mut();
     let pool = ctx.accounts.pool.as_mut();
+    let token_id = pool.get_token_id(&custody.key())?;
 
     // compute exit price
     let curtime = perpetuals.get_time()?;
@@ -133,7 +183,13 @@ pub fn close_position(ctx: Context<ClosePosition>, params: &ClosePositionParams)
         custody.pricing.use_ema,
     )?;
 
-    let exit_price = pool.get_exit_price(&token_price, &token_ema_price, position.side, custody)?;
+    let exit_price = pool.get_exit_price(
+        &token_price,
+        &token_ema_price,
+        position.side,
+        custody,
+        curtime,
+    )?;
     msg!("Exit price: {}", exit_price);
 
     if position.side == Side::Long {
@@ -158,6 +214,95 @@ pub fn close_position(ctx: Context<ClosePosition>, params: &ClosePositionParams)
     msg!("Collected fee: {}", fee_amount);
     msg!("Amount out: {}", transfer_amount);
 
+    // settle linked position against the margin account
+    if position.is_cross_margin {
+        msg!("Update margin account");
+        if ctx.remaining_accounts.len() < 3 {
+            return Err(ProgramError::NotEnoughAccountKeys.into());
+        }
+        let mut margin_account = Account::<MarginAccount>::try_from(&ctx.remaining_accounts[0])?;
+        require!(
+            margin_account.owner == position.owner && margin_account.pool == pool.key(),
+            PerpetualsError::InvalidPositionState
+        );
+        let mut collateral_custody = Account::<Custody>::try_from(&ctx.remaining_accounts[1])?;
+        require!(
+            collateral_custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        require_keys_eq!(collateral_custody.key(), margin_account.collateral_custody);
+        require_keys_neq!(collateral_custody.key(), custody.key());
+        require_keys_eq!(
+            ctx.remaining_accounts[2].key(),
+            collateral_custody.oracle.oracle_account
+        );
+
+        let collateral_price = OraclePrice::new_from_oracle(
+            collateral_custody.oracle.oracle_type,
+            &ctx.remaining_accounts[2],
+            collateral_custody.oracle.max_price_error,
+            collateral_custody.oracle.max_price_age_sec,
+            curtime,
+            false,
+        )?;
+
+        let collateral_ema_price = OraclePrice::new_from_oracle(
+            collateral_custody.oracle.oracle_type,
+            &ctx.remaining_accounts[2],
+            collateral_custody.oracle.max_price_error,
+            collateral_custody.oracle.max_price_age_sec,
+            curtime,
+            collateral_custody.pricing.use_ema,
+        )?;
+
+        let min_collateral_price = if collateral_price < collateral_ema_price {
+            collateral_price
+        } else {
+            collateral_ema_price
+        };
+
+        // cover position losses in excess of its own collateral with shared collateral
+        let shortfall_usd = loss_usd.saturating_sub(position.collateral_usd);
+        let shortfall_amount = std::cmp::min(
+            min_collateral_price.get_token_amount(shortfall_usd, collateral_custody.decimals)?,
+            margin_account.collateral_amount,
+        );
+        msg!("Collateral used: {}", shortfall_amount);
+
+        margin_account.remove_position(&position_key)?;
+        margin_account.collateral_amount =
+            math::checked_sub(margin_account.collateral_amount, shortfall_amount)?;
+
+        collateral_custody.assets.collateral =
+            math::checked_sub(collateral_custody.assets.collateral, shortfall_amount)?;
+        collateral_custody.assets.owned =
+            math::checked_add(collateral_custody.assets.owned, shortfall_amount)?;
+
+        // remaining linked positions must stay covered
+        require!(
+            margin_account.check_health(
+                pool,
+                &collateral_custody,
+                &min_collateral_price,
+                &ctx.remaining_accounts[3..],
+                curtime
+            )?,
+            PerpetualsError::MaxLeverage
+        );
+
+        let collateral_token_id = pool.get_token_id(&collateral_custody.key())?;
+        pool.update_custody_aum(
+            collateral_token_id,
+            &collateral_custody,
+            &collateral_price,
+            &collateral_ema_price,
+            curtime,
+        )?;
+
+        margin_account.exit(&crate::ID)?;
+        collateral_custody.exit(&crate::ID)?;
+    }
+
     // unlock pool funds
     custody.unlock_funds(position.locked_amount)?;
 
@@ -170,25 +315,55 @@ pub fn close_position(ctx: Context<ClosePosition>, params: &ClosePositionParams)
 
     // transfer tokens
     msg!("Transfer tokens");
-    perpetuals.transfer_tokens(
-        ctx.accounts.custody_token_account.to_account_info(),
-        ctx.accounts.receiving_account.to_account_info(),
-        ctx.accounts.transfer_authority.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        transfer_amount,
-    )?;
+    if Perpetuals::is_native_account(
+        &ctx.accounts.receiving_account,
+        &custody.mint,
+        ctx.accounts.owner.key,
+    ) {
+        let owner_key = ctx.accounts.owner.key();
+        let wsol_account_bump = *ctx
+            .bumps
+            .get("wsol_account")
+            .ok_or(ProgramError::InvalidSeeds)?;
+        perpetuals.unwrap_sol_to_user(
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.wsol_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[b"wsol_account", owner_key.as_ref(), &[wsol_account_bump]],
+            transfer_amount,
+        )?;
+    } else {
+        perpetuals.transfer_tokens(
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.receiving_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            transfer_amount,
+        )?;
+    }
 
     // update custody stats
     msg!("Update custody stats");
-    custody.collected_fees.close_position_usd = custody
-        .collected_fees
-        .close_position_usd
-        .wrapping_add(token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?);
+    let fee_amount_usd = token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?;
+    custody.collected_fees.close_position_usd = math::checked_add(
+        custody.collected_fees.close_position_usd,
+        fee_amount_usd as u128,
+    )?;
 
-    custody.volume_stats.close_position_usd = custody
-        .volume_stats
-        .close_position_usd
-        .wrapping_add(position.size_usd);
+    custody.collected_token_fees.close_position = math::checked_add(
+        custody.collected_token_fees.close_position,
+        fee_amount as u128,
+    )?;
+
+    custody.volume_stats.close_position_usd = math::checked_add(
+        custody.volume_stats.close_position_usd,
+        position.size_usd as u128,
+    )?;
 
     let amount_lost = transfer_amount.saturating_sub(position.collateral_amount);
     custody.assets.owned = math::checked_sub(custody.assets.owned, amount_lost)?;
@@ -197,22 +372,47 @@ pub fn close_position(ctx: Context<ClosePosition>, params: &ClosePositionParams)
     custody.assets.protocol_fees = math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
 
     if position.side == Side::Long {
-        custody.trade_stats.oi_long_usd = custody
-            .trade_stats
-            .oi_long_usd
-            .saturating_sub(position.size_usd);
+        custody.trade_stats.oi_long_usd =
+            math::checked_sub(custody.trade_stats.oi_long_usd, position.size_usd as u128)?;
     } else {
-        custody.trade_stats.oi_short_usd = custody
-            .trade_stats
-            .oi_short_usd
-            .saturating_sub(position.size_usd);
+        custody.trade_stats.oi_short_usd =
+            math::checked_sub(custody.trade_stats.oi_short_usd, position.size_usd as u128)?;
     }
+    pool.remove_open_interest(position.size_usd)?;
 
-    custody.trade_stats.profit_usd = custody.trade_stats.profit_usd.wrapping_add(profit_usd);
-    custody.trade_stats.loss_usd = custody.trade_stats.loss_usd.wrapping_add(loss_usd);
+    custody.trade_stats.profit_usd =
+        math::checked_add(custody.trade_stats.profit_usd, profit_usd as u128)?;
+    custody.trade_stats.loss_usd =
+        math::checked_add(custody.trade_stats.loss_usd, loss_usd as u128)?;
 
     custody.remove_position(position, curtime)?;
     custody.update_borrow_rate(curtime)?;
 
+    // update cached aum contribution
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.close_position_usd = math::checked_add(
+        pool_day_stats.volume.close_position_usd,
+        position.size_usd as u128,
+    )?;
+    pool_day_stats.fees.close_position_usd = math::checked_add(
+        pool_day_stats.fees.close_position_usd,
+        fee_amount_usd as u128,
+    )?;
+    pool_day_stats.add_pnl(profit_usd, loss_usd)?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.owner.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
+
     Ok(())
 }
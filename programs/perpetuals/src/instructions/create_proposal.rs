@@ -0,0 +1,86 @@
+//! CreateProposal instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::multisig::{AdminInstruction, Multisig, Proposal},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(params: CreateProposalParams)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::get_len(params.instruction_data.len(), params.instruction_accounts.len()),
+        seeds = [b"proposal",
+                 params.proposal_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateProposalParams {
+    pub proposal_id: u64,
+    pub instruction_data: Vec<u8>,
+    pub instruction_accounts: Vec<Pubkey>,
+    pub expiration_time: i64,
+}
+
+pub fn create_proposal(ctx: Context<CreateProposal>, params: &CreateProposalParams) -> Result<()> {
+    // validate inputs
+    msg!("Validate inputs");
+    let multisig = ctx.accounts.multisig.load()?;
+    multisig.get_signer_index(ctx.accounts.proposer.key)?;
+
+    if params.instruction_data.is_empty()
+        || params.instruction_data.len() > Proposal::MAX_INSTRUCTION_DATA_LEN
+        || params.instruction_accounts.len() > Proposal::MAX_INSTRUCTION_ACCOUNTS
+    {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    // instruction type follows the serialized params, see Multisig::get_instruction_data
+    let instruction_type = params.instruction_data[params.instruction_data.len() - 1];
+    if !AdminInstruction::is_valid(instruction_type) {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    require!(
+        params.expiration_time > Clock::get()?.unix_timestamp,
+        PerpetualsError::MultisigProposalExpired
+    );
+
+    // record proposal data, proposer approval is counted in
+    msg!("Record proposal: {}", params.proposal_id);
+    let proposal = ctx.accounts.proposal.as_mut();
+    proposal.version = Proposal::VERSION;
+    proposal.proposal_id = params.proposal_id;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.instruction_type = instruction_type;
+    proposal.instruction_data = params.instruction_data.clone();
+    proposal.instruction_accounts = params.instruction_accounts.clone();
+    proposal.instruction_hash =
+        Multisig::get_instruction_hash(&params.instruction_accounts, &params.instruction_data);
+    proposal.approvals = Vec::new();
+    proposal.expiration_time = params.expiration_time;
+    proposal.executed = false;
+    proposal.bump = *ctx
+        .bumps
+        .get("proposal")
+        .ok_or(ProgramError::InvalidSeeds)?;
+
+    proposal.approve(ctx.accounts.proposer.key)
+}
@@ -0,0 +1,130 @@
+//! DepositMargin instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::Custody, margin_account::MarginAccount, perpetuals::Perpetuals, pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+    solana_program::program_error::ProgramError,
+};
+
+#[derive(Accounts)]
+pub struct DepositMargin<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: token account of the collateral custody mint
+    #[account(
+        mut,
+        constraint = Perpetuals::is_token_account(&funding_account, &collateral_custody.mint, owner.key)
+    )]
+    pub funding_account: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = collateral_custody,
+        seeds = [b"margin_account",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump = margin_account.bump
+    )]
+    pub margin_account: Box<Account<'info, MarginAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 collateral_custody.mint.as_ref()],
+        bump = collateral_custody.bump,
+        constraint = collateral_custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub collateral_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: collateral custody token account, address is validated by the seeds
+    #[account(
+        mut,
+        seeds = [b"custody_token_account",
+                 pool.key().as_ref(),
+                 collateral_custody.mint.as_ref()],
+        bump = collateral_custody.token_account_bump
+    )]
+    pub collateral_custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the collateral custody token
+    #[account(
+        constraint = collateral_custody_token_mint.key() == collateral_custody.mint
+    )]
+    pub collateral_custody_token_mint: AccountInfo<'info>,
+
+    /// CHECK: token program of the collateral custody mint
+    #[account(
+        constraint = collateral_custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct DepositMarginParams {
+    pub amount: u64,
+}
+
+pub fn deposit_margin(ctx: Context<DepositMargin>, params: &DepositMarginParams) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    require!(
+        ctx.accounts.pool.permissions.allow_open_position
+            && !ctx.accounts.collateral_custody.is_reduce_only(),
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    // validate inputs
+    msg!("Validate inputs");
+    if params.amount == 0 {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+
+    // transfer tokens
+    msg!("Transfer tokens");
+    let amount = ctx.accounts.perpetuals.transfer_tokens_from_user(
+        ctx.accounts.funding_account.to_account_info(),
+        ctx.accounts
+            .collateral_custody_token_account
+            .to_account_info(),
+        ctx.accounts.collateral_custody_token_mint.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        params.amount,
+    )?;
+
+    // update margin account
+    msg!("Update margin account");
+    let margin_account = ctx.accounts.margin_account.as_mut();
+    margin_account.collateral_amount = math::checked_add(margin_account.collateral_amount, amount)?;
+
+    // update custody stats
+    msg!("Update custody stats");
+    let collateral_custody = ctx.accounts.collateral_custody.as_mut();
+    collateral_custody.assets.collateral =
+        math::checked_add(collateral_custody.assets.collateral, amount)?;
+
+    Ok(())
+}
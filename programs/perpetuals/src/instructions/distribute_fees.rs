@@ -0,0 +1,165 @@
+//! DistributeFees instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::Custody,
+            fee_distribution::{FeeDistribution, FeeRecipientType},
+            perpetuals::Perpetuals,
+            pool::Pool,
+            staking::Staking,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct DistributeFees<'info> {
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_distribution",
+                 pool.key().as_ref()],
+        bump = fee_distribution.bump
+    )]
+    pub fee_distribution: Box<Account<'info, FeeDistribution>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: custody token account, address is validated by the seeds
+    #[account(
+        mut,
+        seeds = [b"custody_token_account",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.token_account_bump
+    )]
+    pub custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
+
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
+    // remaining accounts:
+    //   fee_distribution.recipients.len() accounts in the recipients order (write, unsigned):
+    //     pool staking account for the stakers recipient,
+    //     recipient owned token account of the custody mint otherwise
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct DistributeFeesParams {}
+
+pub fn distribute_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, DistributeFees<'info>>,
+    _params: &DistributeFeesParams,
+) -> Result<()> {
+    // validate inputs
+    msg!("Validate inputs");
+    let fee_distribution = ctx.accounts.fee_distribution.as_mut();
+    let accounts = ctx.remaining_accounts;
+    if accounts.len() < fee_distribution.recipients.len() {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let custody_key = ctx.accounts.custody.key();
+    let custody = ctx.accounts.custody.as_mut();
+
+    // load staking state first, so its fee share is settled before the split
+    let mut staking_info = None;
+    for (idx, recipient) in fee_distribution.recipients.iter().enumerate() {
+        if recipient.recipient_type == FeeRecipientType::Stakers {
+            let mut staking = Account::<Staking>::try_from(&accounts[idx])?;
+            require_keys_eq!(
+                staking.pool,
+                ctx.accounts.pool.key(),
+                PerpetualsError::InvalidStakingState
+            );
+            staking.update_rewards(&custody_key, custody)?;
+            staking_info = Some((staking, recipient.weight));
+        }
+    }
+
+    let total_amount = custody.assets.protocol_fees;
+    msg!("Protocol fees to distribute: {}", total_amount);
+    if total_amount == 0 {
+        return Err(ProgramError::InsufficientFunds.into());
+    }
+
+    // transfer tokens to recipients
+    msg!("Transfer tokens");
+    let mut distributed_amount: u64 = 0;
+    for (idx, recipient) in fee_distribution.recipients.iter().enumerate() {
+        if recipient.recipient_type == FeeRecipientType::Stakers {
+            continue;
+        }
+        let amount = FeeDistribution::get_recipient_amount(total_amount, recipient.weight)?;
+        if amount == 0 {
+            continue;
+        }
+        let receiving_account = Perpetuals::get_token_account(&accounts[idx])?;
+        require_keys_eq!(receiving_account.mint, custody.mint);
+        require_keys_eq!(receiving_account.owner, recipient.owner);
+
+        ctx.accounts.perpetuals.transfer_tokens(
+            ctx.accounts.custody_token_account.to_account_info(),
+            accounts[idx].clone(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+        )?;
+
+        custody.assets.protocol_fees = math::checked_sub(custody.assets.protocol_fees, amount)?;
+        distributed_amount = math::checked_add(distributed_amount, amount)?;
+    }
+
+    // add stakers share to staking rewards, it stays in protocol fees if nothing is staked
+    if let Some((mut staking, weight)) = staking_info {
+        msg!("Add staking rewards");
+        let amount = FeeDistribution::get_recipient_amount(total_amount, weight)?;
+        let reward_amount = staking.add_rewards(&custody_key, custody, amount)?;
+        distributed_amount = math::checked_add(distributed_amount, reward_amount)?;
+        staking.exit(&crate::ID)?;
+    }
+
+    // record distribution
+    msg!("Distributed amount: {}", distributed_amount);
+    let curtime = ctx.accounts.perpetuals.get_time()?;
+    fee_distribution.record_distribution(&custody_key, distributed_amount, curtime)
+}
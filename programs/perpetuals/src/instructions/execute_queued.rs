@@ -0,0 +1,374 @@
+//! ExecuteQueued instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        instructions::{
+            SetAdminSignersParams, SetCustodyBorrowRateParams, SetCustodyConfigParams,
+            SetCustodyFeesParams, SetCustodyOracleParams, SetCustodyPricingParams,
+            SetCustodyRampParams, SetCustodyStatusParams, SetFeeDistributionParams,
+            SetPermissionsParams, SetPoolConfigParams, SetPoolRatiosParams,
+            SetPoolRatiosRampParams, SetStakingConfigParams, SetTimelockDelaysParams,
+            SetWithdrawalQueueConfigParams, SweepCustodyParams, WithdrawFeesParams,
+            WithdrawSolFeesParams,
+        },
+        state::{
+            custody::Custody,
+            fee_distribution::FeeDistribution,
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::Pool,
+            staking::Staking,
+            timelock::QueuedInstruction,
+            withdrawal_queue::WithdrawalQueue,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct ExecuteQueued<'info> {
+    /// CHECK: queued instruction payer, receives the rent back
+    #[account(
+        mut,
+        constraint = payer.key() == queued_instruction.payer
+    )]
+    pub payer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"queued_instruction",
+                 queued_instruction.queue_id.to_le_bytes().as_ref()],
+        bump = queued_instruction.bump,
+        close = payer
+    )]
+    pub queued_instruction: Box<Account<'info, QueuedInstruction>>,
+    // remaining accounts: queued_instruction.instruction_accounts in the same order:
+    //   SetCustodyConfig: pool and custody accounts (write, unsigned)
+    //   SetCustodyFees, SetCustodyPricing, SetCustodyOracle, SetCustodyBorrowRate,
+    //   SetCustodyRamp, SetCustodyStatus: custody account (write, unsigned)
+    //   SetPoolRatios, SetPoolRatiosRamp, SetPoolConfig: pool account (write, unsigned)
+    //   SetFeeDistribution: fee distribution account (write, unsigned)
+    //   SetStakingConfig: pool and staking accounts (write, unsigned),
+    //     followed by pool.custodies.len() custody accounts (write, unsigned)
+    //   SetWithdrawalQueueConfig: pool and withdrawal queue accounts (write, unsigned)
+    //   WithdrawFees, SweepCustody: pool, custody, custody token account (write, unsigned),
+    //     custody token mint, receiving token account (write, unsigned),
+    //     transfer authority and token program, SweepCustody is followed by
+    //     fee distribution and staking accounts (write, unsigned)
+    //   WithdrawSolFees: transfer authority and receiving account (write, unsigned)
+    //   SetAdminSigners: 1 to Multisig::MAX_SIGNERS admin signers (read-only, unsigned)
+    //   SetPermissions, SetTimelockDelays: none
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ExecuteQueuedParams {}
+
+pub fn execute_queued<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteQueued<'info>>,
+    _params: &ExecuteQueuedParams,
+) -> Result<()> {
+    // validate inputs
+    msg!("Validate inputs");
+    let queued_instruction = ctx.accounts.queued_instruction.as_ref();
+    let curtime = ctx.accounts.perpetuals.get_time()?;
+    require!(
+        queued_instruction.is_active(curtime),
+        PerpetualsError::TimelockNotElapsed
+    );
+
+    let num_accounts = queued_instruction.instruction_accounts.len();
+    if ctx.remaining_accounts.len() < num_accounts {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let accounts = &ctx.remaining_accounts[..num_accounts];
+    for (account, key) in accounts
+        .iter()
+        .zip(queued_instruction.instruction_accounts.iter())
+    {
+        require_keys_eq!(account.key(), *key);
+    }
+
+    // apply the change
+    msg!(
+        "Execute queued instruction: {}",
+        queued_instruction.queue_id
+    );
+    let instruction_type = queued_instruction.instruction_type;
+    if instruction_type == AdminInstruction::SetCustodyConfig as u8 {
+        let params: SetCustodyConfigParams = queued_instruction.get_params()?;
+        if accounts.len() != 2 {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let mut pool = Account::<Pool>::try_from(&accounts[0])?;
+        require!(
+            pool.version == Pool::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        let mut custody = Account::<Custody>::try_from(&accounts[1])?;
+        require!(
+            custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        require_keys_eq!(custody.pool, pool.key());
+
+        params.apply(&mut pool, &mut custody)?;
+
+        pool.exit(&crate::ID)?;
+        custody.exit(&crate::ID)?;
+    } else if instruction_type == AdminInstruction::SetCustodyFees as u8
+        || instruction_type == AdminInstruction::SetCustodyPricing as u8
+        || instruction_type == AdminInstruction::SetCustodyOracle as u8
+        || instruction_type == AdminInstruction::SetCustodyBorrowRate as u8
+        || instruction_type == AdminInstruction::SetCustodyRamp as u8
+        || instruction_type == AdminInstruction::SetCustodyStatus as u8
+    {
+        if accounts.len() != 1 {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let mut custody = Account::<Custody>::try_from(&accounts[0])?;
+        require!(
+            custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+
+        if instruction_type == AdminInstruction::SetCustodyFees as u8 {
+            let params: SetCustodyFeesParams = queued_instruction.get_params()?;
+            params.apply(&mut custody)?;
+        } else if instruction_type == AdminInstruction::SetCustodyPricing as u8 {
+            let params: SetCustodyPricingParams = queued_instruction.get_params()?;
+            params.apply(&mut custody)?;
+        } else if instruction_type == AdminInstruction::SetCustodyOracle as u8 {
+            let params: SetCustodyOracleParams = queued_instruction.get_params()?;
+            params.apply(&mut custody)?;
+        } else if instruction_type == AdminInstruction::SetCustodyBorrowRate as u8 {
+            let params: SetCustodyBorrowRateParams = queued_instruction.get_params()?;
+            params.apply(&mut custody, curtime)?;
+        } else if instruction_type == AdminInstruction::SetCustodyRamp as u8 {
+            let params: SetCustodyRampParams = queued_instruction.get_params()?;
+            params.apply(&mut custody, curtime)?;
+        } else {
+            let params: SetCustodyStatusParams = queued_instruction.get_params()?;
+            params.apply(&mut custody)?;
+        }
+
+        custody.exit(&crate::ID)?;
+    } else if instruction_type == AdminInstruction::SetPoolRatios as u8
+        || instruction_type == AdminInstruction::SetPoolRatiosRamp as u8
+        || instruction_type == AdminInstruction::SetPoolConfig as u8
+    {
+        if accounts.len() != 1 {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let mut pool = Account::<Pool>::try_from(&accounts[0])?;
+        require!(
+            pool.version == Pool::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+
+        if instruction_type == AdminInstruction::SetPoolRatios as u8 {
+            let params: SetPoolRatiosParams = queued_instruction.get_params()?;
+            params.apply(&mut pool)?;
+        } else if instruction_type == AdminInstruction::SetPoolRatiosRamp as u8 {
+            let params: SetPoolRatiosRampParams = queued_instruction.get_params()?;
+            params.apply(&mut pool, curtime)?;
+        } else {
+            let params: SetPoolConfigParams = queued_instruction.get_params()?;
+            params.apply(&mut pool)?;
+        }
+
+        pool.exit(&crate::ID)?;
+    } else if instruction_type == AdminInstruction::SetFeeDistribution as u8 {
+        let params: SetFeeDistributionParams = queued_instruction.get_params()?;
+        if accounts.len() != 1 {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let mut fee_distribution = Account::<FeeDistribution>::try_from(&accounts[0])?;
+
+        params.apply(&mut fee_distribution)?;
+
+        fee_distribution.exit(&crate::ID)?;
+    } else if instruction_type == AdminInstruction::SetStakingConfig as u8 {
+        let params: SetStakingConfigParams = queued_instruction.get_params()?;
+        if accounts.len() < 2 {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let pool = Account::<Pool>::try_from(&accounts[0])?;
+        require!(
+            pool.version == Pool::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        let mut staking = Account::<Staking>::try_from(&accounts[1])?;
+        require_keys_eq!(staking.pool, pool.key());
+
+        params.apply(&mut staking, &pool, &accounts[2..])?;
+
+        staking.exit(&crate::ID)?;
+    } else if instruction_type == AdminInstruction::SetWithdrawalQueueConfig as u8 {
+        let params: SetWithdrawalQueueConfigParams = queued_instruction.get_params()?;
+        if accounts.len() != 2 {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let mut pool = Account::<Pool>::try_from(&accounts[0])?;
+        require!(
+            pool.version == Pool::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        let mut withdrawal_queue = Account::<WithdrawalQueue>::try_from(&accounts[1])?;
+        require_keys_eq!(withdrawal_queue.pool, pool.key());
+
+        params.apply(&mut withdrawal_queue, &mut pool)?;
+
+        pool.exit(&crate::ID)?;
+        withdrawal_queue.exit(&crate::ID)?;
+    } else if instruction_type == AdminInstruction::WithdrawFees as u8
+        || instruction_type == AdminInstruction::SweepCustody as u8
+    {
+        let num_accounts = if instruction_type == AdminInstruction::WithdrawFees as u8 {
+            7
+        } else {
+            9
+        };
+        if accounts.len() != num_accounts {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let mut pool = Account::<Pool>::try_from(&accounts[0])?;
+        require!(
+            pool.version == Pool::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        let mut custody = Account::<Custody>::try_from(&accounts[1])?;
+        require!(
+            custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        require_keys_eq!(custody.pool, pool.key());
+        let perpetuals = ctx.accounts.perpetuals.as_ref();
+        validate_custody_transfer_accounts(perpetuals, &custody, accounts)?;
+
+        if instruction_type == AdminInstruction::WithdrawFees as u8 {
+            let params: WithdrawFeesParams = queued_instruction.get_params()?;
+            params.apply(
+                perpetuals,
+                &mut custody,
+                accounts[2].clone(),
+                accounts[4].clone(),
+                accounts[3].clone(),
+                accounts[5].clone(),
+                accounts[6].clone(),
+            )?;
+        } else {
+            let params: SweepCustodyParams = queued_instruction.get_params()?;
+            let fee_distribution = Account::<FeeDistribution>::try_from(&accounts[7])?;
+            let custody_key = custody.key();
+            params.apply(
+                perpetuals,
+                &mut pool,
+                &fee_distribution,
+                accounts[8].clone(),
+                &custody_key,
+                &mut custody,
+                accounts[2].clone(),
+                accounts[4].clone(),
+                accounts[3].clone(),
+                accounts[5].clone(),
+                accounts[6].clone(),
+            )?;
+            pool.exit(&crate::ID)?;
+        }
+
+        custody.exit(&crate::ID)?;
+    } else if instruction_type == AdminInstruction::WithdrawSolFees as u8 {
+        let params: WithdrawSolFeesParams = queued_instruction.get_params()?;
+        if accounts.len() != 2 {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let transfer_authority = Pubkey::create_program_address(
+            &[
+                b"transfer_authority",
+                &[ctx.accounts.perpetuals.transfer_authority_bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+        require_keys_eq!(accounts[0].key(), transfer_authority);
+        if !accounts[1].data_is_empty() {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+
+        params.apply(accounts[0].clone(), accounts[1].clone())?;
+    } else if instruction_type == AdminInstruction::SetPermissions as u8 {
+        let params: SetPermissionsParams = queued_instruction.get_params()?;
+        let perpetuals = ctx.accounts.perpetuals.as_mut();
+        perpetuals.permissions = perpetuals
+            .permissions
+            .apply_changes(&queued_instruction.permissions, &params.to_permissions());
+        if !perpetuals.validate() {
+            return err!(PerpetualsError::InvalidPerpetualsConfig);
+        }
+    } else if instruction_type == AdminInstruction::SetAdminSigners as u8 {
+        let params: SetAdminSignersParams = queued_instruction.get_params()?;
+        let mut multisig = ctx.accounts.multisig.load_mut()?;
+        multisig.set_signers(accounts, params.min_signatures)?;
+    } else if instruction_type == AdminInstruction::SetTimelockDelays as u8 {
+        let params: SetTimelockDelaysParams = queued_instruction.get_params()?;
+        let perpetuals = ctx.accounts.perpetuals.as_mut();
+        perpetuals.timelock_delays = params.to_delays();
+        if !perpetuals.validate() {
+            return err!(PerpetualsError::InvalidPerpetualsConfig);
+        }
+    } else {
+        return Err(ProgramError::InvalidInstructionData.into());
+    }
+
+    Ok(())
+}
+
+/// Checks token transfer accounts the same way WithdrawFees and SweepCustody
+/// account constraints do
+fn validate_custody_transfer_accounts(
+    perpetuals: &Perpetuals,
+    custody: &Custody,
+    accounts: &[AccountInfo],
+) -> Result<()> {
+    let custody_token_account = Pubkey::create_program_address(
+        &[
+            b"custody_token_account",
+            custody.pool.as_ref(),
+            custody.mint.as_ref(),
+            &[custody.token_account_bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| ProgramError::InvalidSeeds)?;
+    let transfer_authority = Pubkey::create_program_address(
+        &[b"transfer_authority", &[perpetuals.transfer_authority_bump]],
+        &crate::ID,
+    )
+    .map_err(|_| ProgramError::InvalidSeeds)?;
+
+    require_keys_eq!(accounts[2].key(), custody_token_account);
+    require_keys_eq!(accounts[3].key(), custody.mint);
+    require_keys_eq!(
+        Perpetuals::get_token_account(&accounts[4])?.mint,
+        custody.mint
+    );
+    require_keys_eq!(accounts[5].key(), transfer_authority);
+    require_keys_eq!(*accounts[3].owner, accounts[6].key());
+
+    Ok(())
+}
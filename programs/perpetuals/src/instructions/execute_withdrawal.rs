@@ -0,0 +1,335 @@
+//! ExecuteWithdrawal instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::Custody,
+            oracle::OraclePrice,
+            perpetuals::Perpetuals,
+            pool::{AumCalcMode, Pool},
+            pool_day_stats::PoolDayStats,
+            withdrawal_queue::{WithdrawalQueue, WithdrawalRequest},
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount},
+    solana_program::program_error::ProgramError,
+};
+
+#[derive(Accounts)]
+#[instruction(params: ExecuteWithdrawalParams)]
+pub struct ExecuteWithdrawal<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: token account of the custody mint
+    #[account(
+        mut,
+        constraint = Perpetuals::is_token_account(&receiving_account, &custody.mint, owner.key)
+    )]
+    pub receiving_account: AccountInfo<'info>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"withdrawal_queue",
+                 pool.key().as_ref()],
+        bump = withdrawal_queue.bump
+    )]
+    pub withdrawal_queue: Box<Account<'info, WithdrawalQueue>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"withdrawal_request",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump = withdrawal_request.bump,
+        close = owner
+    )]
+    pub withdrawal_request: Box<Account<'info, WithdrawalRequest>>,
+
+    #[account(
+        mut,
+        seeds = [b"withdrawal_escrow_account",
+                 pool.key().as_ref()],
+        bump = withdrawal_queue.escrow_token_account_bump
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the returned token
+    #[account(
+        constraint = custody_oracle_account.key() == custody.oracle.oracle_account
+    )]
+    pub custody_oracle_account: AccountInfo<'info>,
+
+    /// CHECK: custody token account, address is validated by the seeds
+    #[account(
+        mut,
+        seeds = [b"custody_token_account",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.token_account_bump
+    )]
+    pub custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_token_mint",
+                 pool.key().as_ref()],
+        bump = pool.lp_token_bump
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
+    system_program: Program<'info, System>,
+
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == custody_token_program.key
+    )]
+    custody_token_program: AccountInfo<'info>,
+
+    token_program: Program<'info, Token>,
+    // remaining accounts:
+    //   pool.tokens.len() custody accounts (read-only, unsigned)
+    //   pool.tokens.len() custody oracles (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ExecuteWithdrawalParams {
+    pub min_amount_out: u64,
+}
+
+pub fn execute_withdrawal(
+    ctx: Context<ExecuteWithdrawal>,
+    params: &ExecuteWithdrawalParams,
+) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    let perpetuals = ctx.accounts.perpetuals.as_mut();
+    let custody = ctx.accounts.custody.as_mut();
+    require!(
+        perpetuals.permissions.allow_remove_liquidity
+            && ctx.accounts.pool.permissions.allow_remove_liquidity
+            && custody.permissions.allow_remove_liquidity,
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    // validate inputs
+    msg!("Validate inputs");
+    let lp_amount_in = ctx.accounts.withdrawal_request.lp_amount;
+    if lp_amount_in == 0 {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    let pool = ctx.accounts.pool.as_mut();
+    let token_id = pool.get_token_id(&custody.key())?;
+
+    // compute assets under management
+    msg!("Compute assets under management");
+    let curtime = perpetuals.get_time()?;
+
+    let token_price = OraclePrice::new_from_oracle(
+        custody.oracle.oracle_type,
+        &ctx.accounts.custody_oracle_account.to_account_info(),
+        custody.oracle.max_price_error,
+        custody.oracle.max_price_age_sec,
+        curtime,
+        false,
+    )?;
+
+    let token_ema_price = OraclePrice::new_from_oracle(
+        custody.oracle.oracle_type,
+        &ctx.accounts.custody_oracle_account.to_account_info(),
+        custody.oracle.max_price_error,
+        custody.oracle.max_price_age_sec,
+        curtime,
+        custody.pricing.use_ema,
+    )?;
+
+    let max_price = if token_price > token_ema_price {
+        token_price
+    } else {
+        token_ema_price
+    };
+
+    let pool_amount_usd =
+        pool.get_assets_under_management_usd(AumCalcMode::Min, ctx.remaining_accounts, curtime)?;
+
+    // check withdrawal request
+    msg!("Check withdrawal request");
+    let lp_supply = ctx.accounts.lp_token_mint.supply;
+    let withdrawal_queue = ctx.accounts.withdrawal_queue.as_mut();
+    withdrawal_queue.update_epoch(pool_amount_usd, lp_supply, curtime)?;
+    require!(
+        withdrawal_queue.is_request_ready(&ctx.accounts.withdrawal_request, curtime)?,
+        PerpetualsError::InvalidWithdrawalState
+    );
+
+    // compute amount of tokens to return
+    let remove_amount_usd =
+        withdrawal_queue.get_withdrawal_amount_usd(lp_amount_in, pool_amount_usd, lp_supply)?;
+    require!(
+        withdrawal_queue.check_epoch_limit(remove_amount_usd)?,
+        PerpetualsError::InvalidWithdrawalState
+    );
+
+    let remove_amount = max_price.get_token_amount(remove_amount_usd, custody.decimals)?;
+
+    // calculate fee
+    let fee_amount =
+        pool.get_remove_liquidity_fee(token_id, remove_amount, custody, &token_ema_price, curtime)?;
+    msg!("Collected fee: {}", fee_amount);
+
+    let transfer_amount = math::checked_sub(remove_amount, fee_amount)?;
+    msg!("Amount out: {}", transfer_amount);
+
+    require!(
+        transfer_amount >= params.min_amount_out,
+        PerpetualsError::MaxPriceSlippage
+    );
+
+    // check pool constraints
+    msg!("Check pool constraints");
+    let protocol_fee = Pool::get_fee_amount(custody.fees.protocol_share, fee_amount)?;
+    let withdrawal_amount = math::checked_add(transfer_amount, protocol_fee)?;
+    require!(
+        pool.check_token_ratio(token_id, 0, withdrawal_amount, custody, &token_ema_price)?,
+        PerpetualsError::TokenRatioOutOfRange
+    );
+
+    require!(
+        math::checked_sub(custody.assets.owned, custody.assets.locked)? >= withdrawal_amount,
+        PerpetualsError::CustodyAmountLimit
+    );
+
+    // transfer tokens
+    msg!("Transfer tokens");
+    perpetuals.transfer_tokens(
+        ctx.accounts.custody_token_account.to_account_info(),
+        ctx.accounts.receiving_account.to_account_info(),
+        ctx.accounts.custody_token_mint.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.custody_token_program.to_account_info(),
+        transfer_amount,
+    )?;
+
+    // burn lp tokens
+    msg!("Burn LP tokens");
+    perpetuals.burn_tokens(
+        ctx.accounts.lp_token_mint.to_account_info(),
+        ctx.accounts.escrow_token_account.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        lp_amount_in,
+    )?;
+
+    // update withdrawal queue
+    withdrawal_queue.epoch_withdrawn_usd =
+        math::checked_add(withdrawal_queue.epoch_withdrawn_usd, remove_amount_usd)?;
+    withdrawal_queue.pending_lp_amount =
+        math::checked_sub(withdrawal_queue.pending_lp_amount, lp_amount_in)?;
+
+    // update custody stats
+    msg!("Update custody stats");
+    let fee_amount_usd = token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?;
+    custody.collected_fees.remove_liquidity_usd = math::checked_add(
+        custody.collected_fees.remove_liquidity_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    custody.collected_token_fees.remove_liquidity = math::checked_add(
+        custody.collected_token_fees.remove_liquidity,
+        fee_amount as u128,
+    )?;
+
+    custody.volume_stats.remove_liquidity_usd = math::checked_add(
+        custody.volume_stats.remove_liquidity_usd,
+        remove_amount_usd as u128,
+    )?;
+
+    custody.assets.protocol_fees = math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
+
+    custody.assets.owned = math::checked_sub(custody.assets.owned, withdrawal_amount)?;
+
+    custody.update_borrow_rate(curtime)?;
+
+    // update pool stats
+    msg!("Update pool stats");
+    custody.exit(&crate::ID)?;
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+    pool.update_aum_usd(ctx.remaining_accounts, curtime)?;
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.remove_liquidity_usd = math::checked_add(
+        pool_day_stats.volume.remove_liquidity_usd,
+        remove_amount_usd as u128,
+    )?;
+    pool_day_stats.fees.remove_liquidity_usd = math::checked_add(
+        pool_day_stats.fees.remove_liquidity_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.owner.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
+
+    Ok(())
+}
@@ -2,6 +2,7 @@
 
 use {
     crate::{
+        error::PerpetualsError,
         math,
         state::{
             custody::Custody,
@@ -26,7 +27,8 @@ pub struct GetAddLiquidityAmountAndFee<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -34,7 +36,8 @@ pub struct GetAddLiquidityAmountAndFee<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -91,7 +94,7 @@ pub fn get_add_liquidity_amount_and_fee(
     )?;
 
     let fee_amount =
-        pool.get_add_liquidity_fee(token_id, params.amount_in, custody, &token_price)?;
+        pool.get_add_liquidity_fee(token_id, params.amount_in, custody, &token_price, curtime)?;
     let no_fee_amount = math::checked_sub(params.amount_in, fee_amount)?;
 
     let pool_amount_usd =
@@ -1,9 +1,12 @@
 //! GetAssetsUnderManagement instruction handler
 
 use {
-    crate::state::{
-        perpetuals::Perpetuals,
-        pool::{AumCalcMode, Pool},
+    crate::{
+        error::PerpetualsError,
+        state::{
+            perpetuals::Perpetuals,
+            pool::{AumCalcMode, Pool},
+        },
     },
     anchor_lang::prelude::*,
 };
@@ -19,7 +22,8 @@ pub struct GetAssetsUnderManagement<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
     // remaining accounts:
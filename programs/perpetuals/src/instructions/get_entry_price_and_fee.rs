@@ -1,12 +1,15 @@
 //! GetEntryPriceAndFee instruction handler
 
 use {
-    crate::state::{
-        custody::Custody,
-        oracle::OraclePrice,
-        perpetuals::{NewPositionPricesAndFee, Perpetuals},
-        pool::Pool,
-        position::{Position, Side},
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody,
+            oracle::OraclePrice,
+            perpetuals::{NewPositionPricesAndFee, Perpetuals},
+            pool::Pool,
+            position::{Position, Side},
+        },
     },
     anchor_lang::prelude::*,
     solana_program::program_error::ProgramError,
@@ -23,7 +26,8 @@ pub struct GetEntryPriceAndFee<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -31,7 +35,8 @@ pub struct GetEntryPriceAndFee<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -87,7 +92,13 @@ pub fn get_entry_price_and_fee(
         token_ema_price
     };
 
-    let entry_price = pool.get_entry_price(&token_price, &token_ema_price, params.side, custody)?;
+    let entry_price = pool.get_entry_price(
+        &token_price,
+        &token_ema_price,
+        params.side,
+        custody,
+        curtime,
+    )?;
 
     let size_usd = min_price.get_asset_amount_usd(params.size, custody.decimals)?;
     let collateral_usd = min_price.get_asset_amount_usd(params.collateral, custody.decimals)?;
@@ -106,9 +117,15 @@ pub fn get_entry_price_and_fee(
 
     let fee = pool.get_entry_fee(params.size, custody)?;
 
+    let (leverage_tier, max_initial_leverage, max_leverage) =
+        custody.get_leverage_tier(size_usd, curtime);
+
     Ok(NewPositionPricesAndFee {
         entry_price,
         liquidation_price,
         fee,
+        leverage_tier,
+        max_initial_leverage,
+        max_leverage,
     })
 }
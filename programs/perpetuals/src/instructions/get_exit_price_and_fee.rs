@@ -1,12 +1,15 @@
 //! GetExitPriceAndFee instruction handler
 
 use {
-    crate::state::{
-        custody::Custody,
-        oracle::OraclePrice,
-        perpetuals::{Perpetuals, PriceAndFee},
-        pool::Pool,
-        position::Position,
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody,
+            oracle::OraclePrice,
+            perpetuals::{Perpetuals, PriceAndFee},
+            pool::Pool,
+            position::Position,
+        },
     },
     anchor_lang::prelude::*,
 };
@@ -22,7 +25,8 @@ pub struct GetExitPriceAndFee<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -32,7 +36,8 @@ pub struct GetExitPriceAndFee<'info> {
                  pool.key().as_ref(),
                  custody.key().as_ref(),
                  &[position.side as u8]],
-        bump = position.bump
+        bump = position.bump,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub position: Box<Account<'info, Position>>,
 
@@ -40,7 +45,8 @@ pub struct GetExitPriceAndFee<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -82,7 +88,13 @@ pub fn get_exit_price_and_fee(
         custody.pricing.use_ema,
     )?;
 
-    let price = pool.get_exit_price(&token_price, &token_ema_price, position.side, custody)?;
+    let price = pool.get_exit_price(
+        &token_price,
+        &token_ema_price,
+        position.side,
+        custody,
+        curtime,
+    )?;
 
     let size = token_ema_price.get_token_amount(position.size_usd, custody.decimals)?;
 
@@ -2,6 +2,7 @@
 
 use {
     crate::{
+        error::PerpetualsError,
         math,
         state::{
             custody::Custody, oracle::OraclePrice, perpetuals::Perpetuals, pool::Pool,
@@ -22,7 +23,8 @@ pub struct GetLiquidationPrice<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -32,7 +34,8 @@ pub struct GetLiquidationPrice<'info> {
                  pool.key().as_ref(),
                  custody.key().as_ref(),
                  &[position.side as u8]],
-        bump = position.bump
+        bump = position.bump,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub position: Box<Account<'info, Position>>,
 
@@ -40,7 +43,8 @@ pub struct GetLiquidationPrice<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
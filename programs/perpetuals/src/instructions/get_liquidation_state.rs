@@ -1,9 +1,12 @@
 //! GetLiquidationState instruction handler
 
 use {
-    crate::state::{
-        custody::Custody, oracle::OraclePrice, perpetuals::Perpetuals, pool::Pool,
-        position::Position,
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody, oracle::OraclePrice, perpetuals::Perpetuals, pool::Pool,
+            position::Position,
+        },
     },
     anchor_lang::prelude::*,
 };
@@ -19,7 +22,8 @@ pub struct GetLiquidationState<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -29,7 +33,8 @@ pub struct GetLiquidationState<'info> {
                  pool.key().as_ref(),
                  custody.key().as_ref(),
                  &[position.side as u8]],
-        bump = position.bump
+        bump = position.bump,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub position: Box<Account<'info, Position>>,
 
@@ -37,7 +42,8 @@ pub struct GetLiquidationState<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -1,7 +1,10 @@
 //! GetOraclePrice instruction handler
 
 use {
-    crate::state::{custody::Custody, oracle::OraclePrice, perpetuals::Perpetuals, pool::Pool},
+    crate::{
+        error::PerpetualsError,
+        state::{custody::Custody, oracle::OraclePrice, perpetuals::Perpetuals, pool::Pool},
+    },
     anchor_lang::prelude::*,
 };
 
@@ -16,7 +19,8 @@ pub struct GetOraclePrice<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -24,7 +28,8 @@ pub struct GetOraclePrice<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -1,12 +1,15 @@
 //! GetPnl instruction handler
 
 use {
-    crate::state::{
-        custody::Custody,
-        oracle::OraclePrice,
-        perpetuals::{Perpetuals, ProfitAndLoss},
-        pool::Pool,
-        position::Position,
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody,
+            oracle::OraclePrice,
+            perpetuals::{Perpetuals, ProfitAndLoss},
+            pool::Pool,
+            position::Position,
+        },
     },
     anchor_lang::prelude::*,
 };
@@ -22,7 +25,8 @@ pub struct GetPnl<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -32,7 +36,8 @@ pub struct GetPnl<'info> {
                  pool.key().as_ref(),
                  custody.key().as_ref(),
                  &[position.side as u8]],
-        bump = position.bump
+        bump = position.bump,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub position: Box<Account<'info, Position>>,
 
@@ -40,7 +45,8 @@ pub struct GetPnl<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
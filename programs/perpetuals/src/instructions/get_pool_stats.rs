@@ -0,0 +1,106 @@
+//! GetPoolStats instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::Custody,
+            oracle::OraclePrice,
+            perpetuals::{Perpetuals, PoolStats},
+            pool::{AumCalcMode, Pool},
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::Mint,
+};
+
+#[derive(Accounts)]
+pub struct GetPoolStats<'info> {
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [b"lp_token_mint",
+                 pool.key().as_ref()],
+        bump = pool.lp_token_bump
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+    // remaining accounts:
+    //   pool.tokens.len() custody accounts (read-only, unsigned)
+    //   pool.tokens.len() custody oracles (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct GetPoolStatsParams {}
+
+pub fn get_pool_stats(
+    ctx: Context<GetPoolStats>,
+    _params: &GetPoolStatsParams,
+) -> Result<PoolStats> {
+    let pool = &ctx.accounts.pool;
+    let accounts = ctx.remaining_accounts;
+    let curtime = ctx.accounts.perpetuals.get_time()?;
+
+    // compute pool stats
+    let aum_usd = pool.get_assets_under_management_usd(AumCalcMode::EMA, accounts, curtime)?;
+
+    let lp_supply = ctx.accounts.lp_token_mint.supply;
+    let lp_price_usd = if lp_supply > 0 {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(aum_usd, 10u128.pow(Perpetuals::LP_DECIMALS as u32))?,
+            lp_supply as u128,
+        )?)?
+    } else {
+        0
+    };
+
+    // compute custody stats
+    let num_custodies = pool.custodies.len();
+    let mut custodies = Vec::with_capacity(num_custodies);
+    for (idx, &custody) in pool.custodies.iter().enumerate() {
+        let oracle_idx = idx + num_custodies;
+        require_keys_eq!(accounts[idx].key(), custody);
+        let custody = Account::<Custody>::try_from(&accounts[idx])?;
+        require!(
+            custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        require_keys_eq!(accounts[oracle_idx].key(), custody.oracle.oracle_account);
+
+        let token_ema_price = OraclePrice::new_from_oracle(
+            custody.oracle.oracle_type,
+            &accounts[oracle_idx],
+            custody.oracle.max_price_error,
+            custody.oracle.max_price_age_sec,
+            curtime,
+            custody.pricing.use_ema,
+        )?;
+
+        custodies.push(pool.get_custody_stats(
+            idx,
+            &custody,
+            &token_ema_price,
+            aum_usd,
+            curtime,
+        )?);
+    }
+
+    Ok(PoolStats {
+        aum_usd,
+        lp_supply,
+        lp_price_usd,
+        custodies,
+    })
+}
@@ -0,0 +1,237 @@
+//! GetPositionHealth instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::Custody,
+            margin_account::MarginAccount,
+            oracle::OraclePrice,
+            perpetuals::{Perpetuals, PositionHealth},
+            pool::Pool,
+            position::{Position, Side},
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct GetPositionHealth<'info> {
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [b"position",
+                 position.owner.as_ref(),
+                 pool.key().as_ref(),
+                 custody.key().as_ref(),
+                 &[position.side as u8]],
+        bump = position.bump,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    #[account(
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the collateral token
+    #[account(
+        constraint = custody_oracle_account.key() == custody.oracle.oracle_account
+    )]
+    pub custody_oracle_account: AccountInfo<'info>,
+    // remaining accounts (linked positions only):
+    //   margin account (read-only, unsigned)
+    //   margin account collateral custody (read-only, unsigned)
+    //   margin account collateral custody oracle (read-only, unsigned)
+    //   margin_account.positions.len() position accounts (read-only, unsigned)
+    //   margin_account.positions.len() custody accounts (read-only, unsigned)
+    //   margin_account.positions.len() custody oracles (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct GetPositionHealthParams {}
+
+pub fn get_position_health(
+    ctx: Context<GetPositionHealth>,
+    _params: &GetPositionHealthParams,
+) -> Result<PositionHealth> {
+    let position = &ctx.accounts.position;
+    let pool = &ctx.accounts.pool;
+    let curtime = ctx.accounts.perpetuals.get_time()?;
+    let custody = &ctx.accounts.custody;
+
+    let token_price = OraclePrice::new_from_oracle(
+        custody.oracle.oracle_type,
+        &ctx.accounts.custody_oracle_account.to_account_info(),
+        custody.oracle.max_price_error,
+        custody.oracle.max_price_age_sec,
+        curtime,
+        false,
+    )?;
+
+    let token_ema_price = OraclePrice::new_from_oracle(
+        custody.oracle.oracle_type,
+        &ctx.accounts.custody_oracle_account.to_account_info(),
+        custody.oracle.max_price_error,
+        custody.oracle.max_price_age_sec,
+        curtime,
+        custody.pricing.use_ema,
+    )?;
+
+    // compute pnl and margin
+    let (profit_usd, loss_usd, _) = pool.get_pnl_usd(
+        position,
+        &token_price,
+        &token_ema_price,
+        custody,
+        curtime,
+        false,
+    )?;
+
+    let leverage = pool.get_leverage(position, &token_ema_price, custody, curtime)?;
+
+    let interest_usd = custody.get_interest_amount_usd(position, curtime)?;
+
+    // compute exit price and fee
+    let exit_price = pool.get_exit_price(
+        &token_price,
+        &token_ema_price,
+        position.side,
+        custody,
+        curtime,
+    )?;
+
+    let size = token_ema_price.get_token_amount(position.size_usd, custody.decimals)?;
+    let exit_fee = pool.get_exit_fee(size, custody)?;
+
+    // compute liquidation price and distance to it
+    let liquidation_price =
+        pool.get_liquidation_price(position, &token_ema_price, custody, curtime)?;
+
+    let current_price = token_ema_price
+        .scale_to_exponent(-(Perpetuals::PRICE_DECIMALS as i32))?
+        .price;
+
+    let price_diff = if position.side == Side::Long {
+        current_price.saturating_sub(liquidation_price)
+    } else {
+        liquidation_price.saturating_sub(current_price)
+    };
+
+    let liquidation_distance_bps = if current_price > 0 {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(price_diff as u128, Perpetuals::BPS_POWER)?,
+            current_price as u128,
+        )?)?
+    } else {
+        0
+    };
+
+    // linked positions can only be liquidated with the whole margin account,
+    // so margin and liquidation state are reported for the account
+    let (margin_usd, liquidation_state) = if position.is_cross_margin {
+        if ctx.remaining_accounts.len() < 3 {
+            return Err(ProgramError::NotEnoughAccountKeys.into());
+        }
+        let margin_account = Account::<MarginAccount>::try_from(&ctx.remaining_accounts[0])?;
+        require!(
+            margin_account.owner == position.owner
+                && margin_account.pool == pool.key()
+                && margin_account.positions.contains(&position.key()),
+            PerpetualsError::InvalidPositionState
+        );
+        let collateral_custody = Account::<Custody>::try_from(&ctx.remaining_accounts[1])?;
+        require!(
+            collateral_custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        require_keys_eq!(collateral_custody.key(), margin_account.collateral_custody);
+        require_keys_eq!(
+            ctx.remaining_accounts[2].key(),
+            collateral_custody.oracle.oracle_account
+        );
+
+        let collateral_price = OraclePrice::new_from_oracle(
+            collateral_custody.oracle.oracle_type,
+            &ctx.remaining_accounts[2],
+            collateral_custody.oracle.max_price_error,
+            collateral_custody.oracle.max_price_age_sec,
+            curtime,
+            false,
+        )?;
+
+        let collateral_ema_price = OraclePrice::new_from_oracle(
+            collateral_custody.oracle.oracle_type,
+            &ctx.remaining_accounts[2],
+            collateral_custody.oracle.max_price_error,
+            collateral_custody.oracle.max_price_age_sec,
+            curtime,
+            collateral_custody.pricing.use_ema,
+        )?;
+
+        let min_collateral_price = if collateral_price < collateral_ema_price {
+            collateral_price
+        } else {
+            collateral_ema_price
+        };
+
+        let (margin_usd, required_margin_usd) = margin_account.get_margin_usd(
+            pool,
+            &collateral_custody,
+            &min_collateral_price,
+            &ctx.remaining_accounts[3..],
+            curtime,
+        )?;
+
+        let liquidation_state = if margin_usd >= required_margin_usd {
+            0
+        } else {
+            1
+        };
+
+        (margin_usd, liquidation_state)
+    } else {
+        let margin_usd =
+            math::checked_add(position.collateral_usd, profit_usd)?.saturating_sub(loss_usd);
+
+        let liquidation_state =
+            if pool.check_leverage(position, &token_ema_price, custody, curtime, false)? {
+                0
+            } else {
+                1
+            };
+
+        (margin_usd, liquidation_state)
+    };
+
+    Ok(PositionHealth {
+        leverage,
+        margin_usd,
+        profit_usd,
+        loss_usd,
+        interest_usd,
+        exit_price,
+        exit_fee,
+        liquidation_price,
+        liquidation_distance_bps,
+        liquidation_state,
+    })
+}
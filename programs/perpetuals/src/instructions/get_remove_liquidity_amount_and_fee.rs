@@ -2,6 +2,7 @@
 
 use {
     crate::{
+        error::PerpetualsError,
         math,
         state::{
             custody::Custody,
@@ -26,7 +27,8 @@ pub struct GetRemoveLiquidityAmountAndFee<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -34,7 +36,8 @@ pub struct GetRemoveLiquidityAmountAndFee<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -106,7 +109,7 @@ pub fn get_remove_liquidity_amount_and_fee(
     let remove_amount = max_price.get_token_amount(remove_amount_usd, custody.decimals)?;
 
     let fee_amount =
-        pool.get_remove_liquidity_fee(token_id, remove_amount, custody, &token_price)?;
+        pool.get_remove_liquidity_fee(token_id, remove_amount, custody, &token_price, curtime)?;
 
     let transfer_amount = math::checked_sub(remove_amount, fee_amount)?;
 
@@ -1,11 +1,14 @@
 //! GetSwapAmountAndFees instruction handler
 
 use {
-    crate::state::{
-        custody::Custody,
-        oracle::OraclePrice,
-        perpetuals::{Perpetuals, SwapAmountAndFees},
-        pool::Pool,
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody,
+            oracle::OraclePrice,
+            perpetuals::{Perpetuals, SwapAmountAndFees},
+            pool::Pool,
+        },
     },
     anchor_lang::prelude::*,
     solana_program::program_error::ProgramError,
@@ -22,7 +25,8 @@ pub struct GetSwapAmountAndFees<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -30,7 +34,8 @@ pub struct GetSwapAmountAndFees<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  receiving_custody.mint.as_ref()],
-        bump = receiving_custody.bump
+        bump = receiving_custody.bump,
+        constraint = receiving_custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub receiving_custody: Box<Account<'info, Custody>>,
 
@@ -44,7 +49,8 @@ pub struct GetSwapAmountAndFees<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  dispensing_custody.mint.as_ref()],
-        bump = dispensing_custody.bump
+        bump = dispensing_custody.bump,
+        constraint = dispensing_custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub dispensing_custody: Box<Account<'info, Custody>>,
 
@@ -134,6 +140,7 @@ pub fn get_swap_amount_and_fees(
         receiving_custody,
         dispensing_custody,
         params.amount_in,
+        curtime,
     )?;
 
     // calculate fee
@@ -146,6 +153,7 @@ pub fn get_swap_amount_and_fees(
         &received_token_price,
         dispensing_custody,
         &dispensed_token_price,
+        curtime,
     )?;
 
     Ok(SwapAmountAndFees {
@@ -0,0 +1,100 @@
+//! GuardianPause instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody,
+            perpetuals::{Permissions, Perpetuals},
+            pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Debug)]
+pub enum PauseTarget {
+    Global,
+    Pool,
+    Custody,
+}
+
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    #[account()]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+    // remaining accounts:
+    //   Global: none
+    //   Pool: pool account (write, unsigned)
+    //   Custody: 1 or more custody accounts (write, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct GuardianPauseParams {
+    pub target: PauseTarget,
+    // flags set to false are disabled, the rest are left unchanged
+    pub permissions: Permissions,
+}
+
+pub fn guardian_pause<'info>(
+    ctx: Context<'_, '_, '_, 'info, GuardianPause<'info>>,
+    params: &GuardianPauseParams,
+) -> Result<()> {
+    // validate inputs
+    msg!("Validate inputs");
+    let perpetuals = ctx.accounts.perpetuals.as_mut();
+    require!(
+        perpetuals.is_guardian(ctx.accounts.guardian.key),
+        PerpetualsError::GuardianNotAuthorized
+    );
+    let accounts = ctx.remaining_accounts;
+
+    // disable permissions
+    match params.target {
+        PauseTarget::Global => {
+            msg!("Pause globally");
+            perpetuals.permissions = perpetuals.permissions.intersect(&params.permissions);
+        }
+        PauseTarget::Pool => {
+            if accounts.is_empty() {
+                return Err(ProgramError::NotEnoughAccountKeys.into());
+            }
+            let mut pool = Account::<Pool>::try_from(&accounts[0])?;
+            require!(
+                pool.version == Pool::VERSION,
+                PerpetualsError::InvalidAccountVersion
+            );
+            msg!("Pause pool: {}", pool.name);
+            pool.permissions = pool.permissions.intersect(&params.permissions);
+            pool.exit(&crate::ID)?;
+        }
+        PauseTarget::Custody => {
+            if accounts.is_empty() {
+                return Err(ProgramError::NotEnoughAccountKeys.into());
+            }
+            for account in accounts {
+                pause_custody(account, &params.permissions)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn pause_custody(account: &AccountInfo, permissions: &Permissions) -> Result<()> {
+    let mut custody = Account::<Custody>::try_from(account)?;
+    require!(
+        custody.version == Custody::VERSION,
+        PerpetualsError::InvalidAccountVersion
+    );
+    msg!("Pause custody: {}", custody.mint);
+    custody.permissions = custody.permissions.intersect(permissions);
+    custody.exit(&crate::ID)
+}
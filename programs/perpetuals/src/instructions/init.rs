@@ -69,6 +69,7 @@ pub struct InitParams {
     pub allow_pnl_withdrawal: bool,
     pub allow_collateral_withdrawal: bool,
     pub allow_size_change: bool,
+    pub allow_liquidation: bool,
 }
 
 pub fn init(ctx: Context<Init>, params: &InitParams) -> Result<()> {
@@ -85,6 +86,7 @@ pub fn init(ctx: Context<Init>, params: &InitParams) -> Result<()> {
 
     // record perpetuals
     let perpetuals = ctx.accounts.perpetuals.as_mut();
+    perpetuals.version = Perpetuals::VERSION;
     perpetuals.permissions.allow_swap = params.allow_swap;
     perpetuals.permissions.allow_add_liquidity = params.allow_add_liquidity;
     perpetuals.permissions.allow_remove_liquidity = params.allow_remove_liquidity;
@@ -93,6 +95,7 @@ pub fn init(ctx: Context<Init>, params: &InitParams) -> Result<()> {
     perpetuals.permissions.allow_pnl_withdrawal = params.allow_pnl_withdrawal;
     perpetuals.permissions.allow_collateral_withdrawal = params.allow_collateral_withdrawal;
     perpetuals.permissions.allow_size_change = params.allow_size_change;
+    perpetuals.permissions.allow_liquidation = params.allow_liquidation;
     perpetuals.transfer_authority_bump = *ctx
         .bumps
         .get("transfer_authority")
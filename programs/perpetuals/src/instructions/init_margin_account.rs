@@ -0,0 +1,84 @@
+//! InitMarginAccount instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody, margin_account::MarginAccount, perpetuals::Perpetuals, pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+    solana_program::program_error::ProgramError,
+};
+
+#[derive(Accounts)]
+pub struct InitMarginAccount<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MarginAccount::LEN,
+        seeds = [b"margin_account",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump
+    )]
+    pub margin_account: Box<Account<'info, MarginAccount>>,
+
+    #[account(
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 collateral_custody.mint.as_ref()],
+        bump = collateral_custody.bump,
+        constraint = collateral_custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub collateral_custody: Box<Account<'info, Custody>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitMarginAccountParams {}
+
+pub fn init_margin_account(
+    ctx: Context<InitMarginAccount>,
+    _params: &InitMarginAccountParams,
+) -> Result<()> {
+    // validate inputs
+    msg!("Validate inputs");
+    require!(
+        ctx.accounts.collateral_custody.is_stable,
+        PerpetualsError::UnsupportedToken
+    );
+
+    // record margin account data
+    let margin_account = ctx.accounts.margin_account.as_mut();
+    margin_account.version = MarginAccount::VERSION;
+    margin_account.owner = ctx.accounts.owner.key();
+    margin_account.pool = ctx.accounts.pool.key();
+    margin_account.collateral_custody = ctx.accounts.collateral_custody.key();
+    margin_account.collateral_amount = 0;
+    margin_account.positions = Vec::new();
+    margin_account.bump = *ctx
+        .bumps
+        .get("margin_account")
+        .ok_or(ProgramError::InvalidSeeds)?;
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+//! InitStaking instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::Pool,
+            staking::Staking,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct InitStaking<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    // instruction can be called multiple times due to multisig use, hence init_if_needed
+    // instead of init. On the first call account is zero initialized and filled out when
+    // all signatures are collected. When account is in zeroed state it can't be used in other
+    // instructions because seeds are computed with the pool key.
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = Staking::LEN,
+        seeds = [b"staking",
+                 pool.key().as_ref()],
+        bump
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        token::mint = lp_token_mint,
+        token::authority = transfer_authority,
+        seeds = [b"stake_token_account",
+                 pool.key().as_ref()],
+        bump
+    )]
+    pub stake_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"lp_token_mint",
+                 pool.key().as_ref()],
+        bump = pool.lp_token_bump
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    rent: Sysvar<'info, Rent>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitStakingParams {
+    pub fee_share: u64,
+}
+
+pub fn init_staking<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitStaking<'info>>,
+    params: &InitStakingParams,
+) -> Result<u8> {
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::InitStaking, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    let staking = ctx.accounts.staking.as_mut();
+    if staking.pool != Pubkey::default() {
+        // return error if staking is already initialized
+        return Err(ProgramError::AccountAlreadyInitialized.into());
+    }
+
+    // record staking data
+    staking.version = Staking::VERSION;
+    staking.pool = ctx.accounts.pool.key();
+    staking.stake_token_account = ctx.accounts.stake_token_account.key();
+    staking.total_staked = 0;
+    staking.fee_share = params.fee_share;
+    staking.rewards = Vec::new();
+    staking.bump = *ctx.bumps.get("staking").ok_or(ProgramError::InvalidSeeds)?;
+    staking.stake_token_account_bump = *ctx
+        .bumps
+        .get("stake_token_account")
+        .ok_or(ProgramError::InvalidSeeds)?;
+
+    if !staking.validate() {
+        err!(PerpetualsError::InvalidStakingState)
+    } else {
+        Ok(0)
+    }
+}
@@ -9,11 +9,12 @@ use {
             oracle::OraclePrice,
             perpetuals::Perpetuals,
             pool::Pool,
+            pool_day_stats::PoolDayStats,
             position::{Position, Side},
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
+    solana_program::program_error::ProgramError,
 };
 
 #[derive(Accounts)]
@@ -21,19 +22,19 @@ pub struct Liquidate<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
+    /// CHECK: token account of the custody mint
     #[account(
         mut,
-        constraint = receiving_account.mint == custody.mint,
-        constraint = receiving_account.owner == position.owner
+        constraint = Perpetuals::is_token_account(&receiving_account, &custody.mint, &position.owner)
     )]
-    pub receiving_account: Box<Account<'info, TokenAccount>>,
+    pub receiving_account: AccountInfo<'info>,
 
+    /// CHECK: token account of the custody mint
     #[account(
         mut,
-        constraint = rewards_receiving_account.mint == custody.mint,
-        constraint = rewards_receiving_account.owner == signer.key()
+        constraint = Perpetuals::is_token_account(&rewards_receiving_account, &custody.mint, signer.key)
     )]
-    pub rewards_receiving_account: Box<Account<'info, TokenAccount>>,
+    pub rewards_receiving_account: AccountInfo<'info>,
 
     /// CHECK: empty PDA, authority for token accounts
     #[account(
@@ -52,10 +53,22 @@ pub struct Liquidate<'info> {
         mut,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
     #[account(
         mut,
         seeds = [b"position",
@@ -64,7 +77,8 @@ pub struct Liquidate<'info> {
                  custody.key().as_ref(),
                  &[position.side as u8]],
         bump = position.bump,
-        close = signer
+        close = signer,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub position: Box<Account<'info, Position>>,
 
@@ -73,7 +87,8 @@ pub struct Liquidate<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -83,6 +98,7 @@ pub struct Liquidate<'info> {
     )]
     pub custody_oracle_account: AccountInfo<'info>,
 
+    /// CHECK: custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -90,9 +106,21 @@ pub struct Liquidate<'info> {
                  custody.mint.as_ref()],
         bump = custody.token_account_bump
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: AccountInfo<'info>,
 
-    token_program: Program<'info, Token>,
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
+
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -104,15 +132,22 @@ pub fn liquidate(ctx: Context<Liquidate>, _params: &LiquidateParams) -> Result<(
     let perpetuals = ctx.accounts.perpetuals.as_mut();
     let custody = ctx.accounts.custody.as_mut();
     require!(
-        perpetuals.permissions.allow_close_position && custody.permissions.allow_close_position,
+        perpetuals.permissions.allow_liquidation
+            && ctx.accounts.pool.permissions.allow_liquidation
+            && custody.permissions.allow_liquidation,
         PerpetualsError::InstructionNotAllowed
     );
 
     let position = ctx.accounts.position.as_mut();
     let pool = ctx.accounts.pool.as_mut();
+    let token_id = pool.get_token_id(&custody.key())?;
 
     // check if position can be liquidated
     msg!("Check position state");
+    require!(
+        !position.is_cross_margin,
+        PerpetualsError::InvalidPositionState
+    );
     let curtime = perpetuals.get_time()?;
 
     let token_price = OraclePrice::new_from_oracle(
@@ -174,6 +209,7 @@ pub fn liquidate(ctx: Context<Liquidate>, _params: &LiquidateParams) -> Result<(
     perpetuals.transfer_tokens(
         ctx.accounts.custody_token_account.to_account_info(),
         ctx.accounts.receiving_account.to_account_info(),
+        ctx.accounts.custody_token_mint.to_account_info(),
         ctx.accounts.transfer_authority.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
         user_amount,
@@ -182,6 +218,7 @@ pub fn liquidate(ctx: Context<Liquidate>, _params: &LiquidateParams) -> Result<(
     perpetuals.transfer_tokens(
         ctx.accounts.custody_token_account.to_account_info(),
         ctx.accounts.rewards_receiving_account.to_account_info(),
+        ctx.accounts.custody_token_mint.to_account_info(),
         ctx.accounts.transfer_authority.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
         reward,
@@ -189,13 +226,19 @@ pub fn liquidate(ctx: Context<Liquidate>, _params: &LiquidateParams) -> Result<(
 
     // update custody stats
     msg!("Update custody stats");
-    custody.collected_fees.liquidation_usd = custody
-        .collected_fees
-        .liquidation_usd
-        .wrapping_add(token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?);
+    let fee_amount_usd = token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?;
+    custody.collected_fees.liquidation_usd = math::checked_add(
+        custody.collected_fees.liquidation_usd,
+        fee_amount_usd as u128,
+    )?;
 
-    custody.volume_stats.liquidation_usd =
-        math::checked_add(custody.volume_stats.liquidation_usd, position.size_usd)?;
+    custody.collected_token_fees.liquidation =
+        math::checked_add(custody.collected_token_fees.liquidation, fee_amount as u128)?;
+
+    custody.volume_stats.liquidation_usd = math::checked_add(
+        custody.volume_stats.liquidation_usd,
+        position.size_usd as u128,
+    )?;
 
     let amount_lost = total_amount_out.saturating_sub(position.collateral_amount);
     custody.assets.owned = math::checked_sub(custody.assets.owned, amount_lost)?;
@@ -204,22 +247,45 @@ pub fn liquidate(ctx: Context<Liquidate>, _params: &LiquidateParams) -> Result<(
     custody.assets.protocol_fees = math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
 
     if position.side == Side::Long {
-        custody.trade_stats.oi_long_usd = custody
-            .trade_stats
-            .oi_long_usd
-            .saturating_sub(position.size_usd);
+        custody.trade_stats.oi_long_usd =
+            math::checked_sub(custody.trade_stats.oi_long_usd, position.size_usd as u128)?;
     } else {
-        custody.trade_stats.oi_short_usd = custody
-            .trade_stats
-            .oi_short_usd
-            .saturating_sub(position.size_usd);
+        custody.trade_stats.oi_short_usd =
+            math::checked_sub(custody.trade_stats.oi_short_usd, position.size_usd as u128)?;
     }
+    pool.remove_open_interest(position.size_usd)?;
 
-    custody.trade_stats.profit_usd = custody.trade_stats.profit_usd.wrapping_add(profit_usd);
-    custody.trade_stats.loss_usd = custody.trade_stats.loss_usd.wrapping_add(loss_usd);
+    custody.trade_stats.profit_usd =
+        math::checked_add(custody.trade_stats.profit_usd, profit_usd as u128)?;
+    custody.trade_stats.loss_usd =
+        math::checked_add(custody.trade_stats.loss_usd, loss_usd as u128)?;
 
     custody.remove_position(position, curtime)?;
     custody.update_borrow_rate(curtime)?;
 
+    // update cached aum contribution
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.liquidation_usd = math::checked_add(
+        pool_day_stats.volume.liquidation_usd,
+        position.size_usd as u128,
+    )?;
+    pool_day_stats.fees.liquidation_usd =
+        math::checked_add(pool_day_stats.fees.liquidation_usd, fee_amount_usd as u128)?;
+    pool_day_stats.add_pnl(profit_usd, loss_usd)?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.signer.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
+
     Ok(())
 }
@@ -0,0 +1,398 @@
+//! LiquidateMarginAccount instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::Custody,
+            margin_account::MarginAccount,
+            oracle::OraclePrice,
+            perpetuals::Perpetuals,
+            pool::Pool,
+            pool_day_stats::PoolDayStats,
+            position::{Position, Side},
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct LiquidateMarginAccount<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: token account of the custody mint
+    #[account(
+        mut,
+        constraint = Perpetuals::is_token_account(&receiving_account, &custody.mint, &position.owner)
+    )]
+    pub receiving_account: AccountInfo<'info>,
+
+    /// CHECK: token account of the custody mint
+    #[account(
+        mut,
+        constraint = Perpetuals::is_token_account(&rewards_receiving_account, &custody.mint, signer.key)
+    )]
+    pub rewards_receiving_account: AccountInfo<'info>,
+
+    /// CHECK: owner of the margin account, receives rent released by the shrink
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = collateral_custody,
+        realloc = MarginAccount::LEN + margin_account.positions.len().saturating_sub(1) * std::mem::size_of::<Pubkey>(),
+        realloc::payer = owner,
+        realloc::zero = false,
+        seeds = [b"margin_account",
+                 position.owner.as_ref(),
+                 pool.key().as_ref()],
+        bump = margin_account.bump
+    )]
+    pub margin_account: Box<Account<'info, MarginAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"position",
+                 position.owner.as_ref(),
+                 pool.key().as_ref(),
+                 custody.key().as_ref(),
+                 &[position.side as u8]],
+        bump = position.bump,
+        close = signer,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the position token
+    #[account(
+        constraint = custody_oracle_account.key() == custody.oracle.oracle_account
+    )]
+    pub custody_oracle_account: AccountInfo<'info>,
+
+    /// CHECK: custody token account, address is validated by the seeds
+    #[account(
+        mut,
+        seeds = [b"custody_token_account",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.token_account_bump
+    )]
+    pub custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = collateral_custody.key() != custody.key(),
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 collateral_custody.mint.as_ref()],
+        bump = collateral_custody.bump,
+        constraint = collateral_custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub collateral_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the collateral token
+    #[account(
+        constraint = collateral_custody_oracle_account.key() == collateral_custody.oracle.oracle_account
+    )]
+    pub collateral_custody_oracle_account: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
+    // remaining accounts:
+    //   margin_account.positions.len() position accounts (read-only, unsigned)
+    //   margin_account.positions.len() custody accounts (read-only, unsigned)
+    //   margin_account.positions.len() custody oracles (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LiquidateMarginAccountParams {}
+
+pub fn liquidate_margin_account(
+    ctx: Context<LiquidateMarginAccount>,
+    _params: &LiquidateMarginAccountParams,
+) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    let perpetuals = ctx.accounts.perpetuals.as_mut();
+    let custody = ctx.accounts.custody.as_mut();
+    require!(
+        perpetuals.permissions.allow_liquidation
+            && ctx.accounts.pool.permissions.allow_liquidation
+            && custody.permissions.allow_liquidation,
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    let position_key = ctx.accounts.position.key();
+    let position = ctx.accounts.position.as_mut();
+    let pool = ctx.accounts.pool.as_mut();
+    let margin_account = ctx.accounts.margin_account.as_mut();
+    let collateral_custody = ctx.accounts.collateral_custody.as_mut();
+
+    // check if margin account can be liquidated
+    msg!("Check margin account state");
+    require!(
+        position.is_cross_margin,
+        PerpetualsError::InvalidPositionState
+    );
+    let curtime = perpetuals.get_time()?;
+
+    let collateral_price = OraclePrice::new_from_oracle(
+        collateral_custody.oracle.oracle_type,
+        &ctx.accounts
+            .collateral_custody_oracle_account
+            .to_account_info(),
+        collateral_custody.oracle.max_price_error,
+        collateral_custody.oracle.max_price_age_sec,
+        curtime,
+        false,
+    )?;
+
+    let collateral_ema_price = OraclePrice::new_from_oracle(
+        collateral_custody.oracle.oracle_type,
+        &ctx.accounts
+            .collateral_custody_oracle_account
+            .to_account_info(),
+        collateral_custody.oracle.max_price_error,
+        collateral_custody.oracle.max_price_age_sec,
+        curtime,
+        collateral_custody.pricing.use_ema,
+    )?;
+
+    let min_collateral_price = if collateral_price < collateral_ema_price {
+        collateral_price
+    } else {
+        collateral_ema_price
+    };
+
+    require!(
+        !margin_account.check_health(
+            pool,
+            collateral_custody,
+            &min_collateral_price,
+            ctx.remaining_accounts,
+            curtime
+        )?,
+        PerpetualsError::InvalidPositionState
+    );
+
+    let token_price = OraclePrice::new_from_oracle(
+        custody.oracle.oracle_type,
+        &ctx.accounts.custody_oracle_account.to_account_info(),
+        custody.oracle.max_price_error,
+        custody.oracle.max_price_age_sec,
+        curtime,
+        false,
+    )?;
+
+    let token_ema_price = OraclePrice::new_from_oracle(
+        custody.oracle.oracle_type,
+        &ctx.accounts.custody_oracle_account.to_account_info(),
+        custody.oracle.max_price_error,
+        custody.oracle.max_price_age_sec,
+        curtime,
+        custody.pricing.use_ema,
+    )?;
+
+    msg!("Settle position");
+    let (total_amount_out, fee_amount, profit_usd, loss_usd) = pool.get_close_amount(
+        position,
+        &token_price,
+        &token_ema_price,
+        custody,
+        curtime,
+        true,
+    )?;
+
+    let protocol_fee = Pool::get_fee_amount(custody.fees.protocol_share, fee_amount)?;
+
+    msg!("Net profit: {}, loss: {}", profit_usd, loss_usd);
+    msg!("Collected fee: {}", fee_amount);
+
+    let reward = Pool::get_fee_amount(custody.fees.liquidation, total_amount_out)?;
+    let user_amount = math::checked_sub(total_amount_out, reward)?;
+
+    msg!("Amount out: {}", user_amount);
+    msg!("Reward: {}", reward);
+
+    // cover position losses in excess of its own collateral with shared collateral,
+    // the position collateral itself is kept by the custody as for isolated positions
+    // and the rest of the position loss (if shared collateral is short) is absorbed
+    // by the pool
+    let shortfall_usd = loss_usd.saturating_sub(position.collateral_usd);
+    let shortfall_amount = std::cmp::min(
+        min_collateral_price.get_token_amount(shortfall_usd, collateral_custody.decimals)?,
+        margin_account.collateral_amount,
+    );
+    msg!("Collateral seized: {}", shortfall_amount);
+
+    // unlink position from the margin account
+    margin_account.remove_position(&position_key)?;
+    margin_account.collateral_amount =
+        math::checked_sub(margin_account.collateral_amount, shortfall_amount)?;
+
+    // unlock pool funds
+    custody.unlock_funds(position.locked_amount)?;
+
+    // check pool constraints
+    msg!("Check pool constraints");
+    require!(
+        pool.check_available_amount(total_amount_out, custody)?,
+        PerpetualsError::CustodyAmountLimit
+    );
+
+    // transfer tokens
+    msg!("Transfer tokens");
+    perpetuals.transfer_tokens(
+        ctx.accounts.custody_token_account.to_account_info(),
+        ctx.accounts.receiving_account.to_account_info(),
+        ctx.accounts.custody_token_mint.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        user_amount,
+    )?;
+
+    perpetuals.transfer_tokens(
+        ctx.accounts.custody_token_account.to_account_info(),
+        ctx.accounts.rewards_receiving_account.to_account_info(),
+        ctx.accounts.custody_token_mint.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        reward,
+    )?;
+
+    // update custody stats
+    msg!("Update custody stats");
+    let fee_amount_usd = token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?;
+    custody.collected_fees.liquidation_usd = math::checked_add(
+        custody.collected_fees.liquidation_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    custody.collected_token_fees.liquidation =
+        math::checked_add(custody.collected_token_fees.liquidation, fee_amount as u128)?;
+
+    custody.volume_stats.liquidation_usd = math::checked_add(
+        custody.volume_stats.liquidation_usd,
+        position.size_usd as u128,
+    )?;
+
+    let amount_lost = total_amount_out.saturating_sub(position.collateral_amount);
+    custody.assets.owned = math::checked_sub(custody.assets.owned, amount_lost)?;
+    custody.assets.collateral =
+        math::checked_sub(custody.assets.collateral, position.collateral_amount)?;
+    custody.assets.protocol_fees = math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
+
+    if position.side == Side::Long {
+        custody.trade_stats.oi_long_usd =
+            math::checked_sub(custody.trade_stats.oi_long_usd, position.size_usd as u128)?;
+    } else {
+        custody.trade_stats.oi_short_usd =
+            math::checked_sub(custody.trade_stats.oi_short_usd, position.size_usd as u128)?;
+    }
+    pool.remove_open_interest(position.size_usd)?;
+
+    custody.trade_stats.profit_usd =
+        math::checked_add(custody.trade_stats.profit_usd, profit_usd as u128)?;
+    custody.trade_stats.loss_usd =
+        math::checked_add(custody.trade_stats.loss_usd, loss_usd as u128)?;
+
+    custody.remove_position(position, curtime)?;
+    custody.update_borrow_rate(curtime)?;
+
+    collateral_custody.assets.collateral =
+        math::checked_sub(collateral_custody.assets.collateral, shortfall_amount)?;
+    collateral_custody.assets.owned =
+        math::checked_add(collateral_custody.assets.owned, shortfall_amount)?;
+
+    // update cached aum contributions
+    let token_id = pool.get_token_id(&custody.key())?;
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+    let collateral_token_id = pool.get_token_id(&collateral_custody.key())?;
+    pool.update_custody_aum(
+        collateral_token_id,
+        collateral_custody,
+        &collateral_price,
+        &collateral_ema_price,
+        curtime,
+    )?;
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.liquidation_usd = math::checked_add(
+        pool_day_stats.volume.liquidation_usd,
+        position.size_usd as u128,
+    )?;
+    pool_day_stats.fees.liquidation_usd =
+        math::checked_add(pool_day_stats.fees.liquidation_usd, fee_amount_usd as u128)?;
+    pool_day_stats.add_pnl(profit_usd, loss_usd)?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.signer.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
+
+    Ok(())
+}
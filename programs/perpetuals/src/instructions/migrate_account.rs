@@ -0,0 +1,42 @@
+//! MigrateAccount instruction handler
+
+use {crate::state::migration, anchor_lang::prelude::*};
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: any program account, layout is validated by the migration steps
+    #[account(mut)]
+    pub account: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateAccountParams {}
+
+pub fn migrate_account<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigrateAccount<'info>>,
+    _params: &MigrateAccountParams,
+) -> Result<()> {
+    // migration steps only reformat existing data, so anyone can run them
+    msg!(
+        "Migrate account {} from version {}",
+        ctx.accounts.account.key(),
+        migration::get_version(
+            ctx.accounts.account.key,
+            &ctx.accounts.account.try_borrow_data()?
+        )?
+    );
+
+    // remaining accounts are read by steps that need data of other accounts,
+    // legacy pools expect their (migrated) custodies in pool order
+    migration::migrate_account(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.account.clone(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.remaining_accounts,
+    )
+}
@@ -9,11 +9,11 @@ use {
             oracle::OraclePrice,
             perpetuals::Perpetuals,
             pool::Pool,
+            pool_day_stats::PoolDayStats,
             position::{Position, Side},
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
     solana_program::program_error::ProgramError,
 };
 
@@ -23,12 +23,22 @@ pub struct OpenPosition<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    /// CHECK: token account of the custody mint or wallet of the owner for native SOL
     #[account(
         mut,
-        constraint = funding_account.mint == custody.mint,
-        has_one = owner
+        constraint = Perpetuals::is_token_account(&funding_account, &custody.mint, owner.key)
+            || Perpetuals::is_native_account(&funding_account, &custody.mint, owner.key)
     )]
-    pub funding_account: Box<Account<'info, TokenAccount>>,
+    pub funding_account: AccountInfo<'info>,
+
+    /// CHECK: temporary wrapped SOL account, only used for native SOL transfers
+    #[account(
+        mut,
+        seeds = [b"wsol_account",
+                 owner.key().as_ref()],
+        bump
+    )]
+    pub wsol_account: AccountInfo<'info>,
 
     /// CHECK: empty PDA, authority for token accounts
     #[account(
@@ -47,10 +57,22 @@ pub struct OpenPosition<'info> {
         mut,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
     #[account(
         init,
         payer = owner,
@@ -69,7 +91,8 @@ pub struct OpenPosition<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -79,6 +102,7 @@ pub struct OpenPosition<'info> {
     )]
     pub custody_oracle_account: AccountInfo<'info>,
 
+    /// CHECK: custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -86,10 +110,20 @@ pub struct OpenPosition<'info> {
                  custody.mint.as_ref()],
         bump = custody.token_account_bump
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
 
     system_program: Program<'info, System>,
-    token_program: Program<'info, Token>,
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
@@ -107,8 +141,10 @@ pub fn open_position(ctx: Context<OpenPosition>, params: &OpenPositionParams) ->
     let custody = ctx.accounts.custody.as_mut();
     require!(
         perpetuals.permissions.allow_open_position
+            && ctx.accounts.pool.permissions.allow_open_position
             && custody.permissions.allow_open_position
-            && !custody.is_stable,
+            && !custody.is_stable
+            && !custody.is_reduce_only(),
         PerpetualsError::InstructionNotAllowed
     );
 
@@ -120,6 +156,7 @@ pub fn open_position(ctx: Context<OpenPosition>, params: &OpenPositionParams) ->
     }
     let position = ctx.accounts.position.as_mut();
     let pool = ctx.accounts.pool.as_mut();
+    let token_id = pool.get_token_id(&custody.key())?;
 
     // compute position price
     let curtime = perpetuals.get_time()?;
@@ -148,8 +185,13 @@ pub fn open_position(ctx: Context<OpenPosition>, params: &OpenPositionParams) ->
         token_ema_price
     };
 
-    let position_price =
-        pool.get_entry_price(&token_price, &token_ema_price, params.side, custody)?;
+    let position_price = pool.get_entry_price(
+        &token_price,
+        &token_ema_price,
+        params.side,
+        custody,
+        curtime,
+    )?;
     msg!("Entry price: {}", position_price);
 
     if params.side == Side::Long {
@@ -174,11 +216,48 @@ pub fn open_position(ctx: Context<OpenPosition>, params: &OpenPositionParams) ->
     let transfer_amount = math::checked_add(params.collateral, fee_amount)?;
     msg!("Amount in: {}", transfer_amount);
 
+    // transfer tokens, token transfer fees are taken out of the collateral
+    msg!("Transfer tokens");
+    let received_amount = if Perpetuals::is_native_account(
+        &ctx.accounts.funding_account,
+        &custody.mint,
+        ctx.accounts.owner.key,
+    ) {
+        let owner_key = ctx.accounts.owner.key();
+        let wsol_account_bump = *ctx
+            .bumps
+            .get("wsol_account")
+            .ok_or(ProgramError::InvalidSeeds)?;
+        perpetuals.wrap_sol_from_user(
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.wsol_account.to_account_info(),
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[b"wsol_account", owner_key.as_ref(), &[wsol_account_bump]],
+            transfer_amount,
+        )?
+    } else {
+        perpetuals.transfer_tokens_from_user(
+            ctx.accounts.funding_account.clone(),
+            ctx.accounts.custody_token_account.clone(),
+            ctx.accounts.custody_token_mint.clone(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.token_program.clone(),
+            transfer_amount,
+        )?
+    };
+    let collateral = math::checked_sub(received_amount, fee_amount)?;
+    require!(collateral > 0, PerpetualsError::InsufficientAmountReturned);
+
     // init new position
     msg!("Initialize new position");
     let size_usd = min_price.get_asset_amount_usd(params.size, custody.decimals)?;
-    let collateral_usd = min_price.get_asset_amount_usd(params.collateral, custody.decimals)?;
+    let collateral_usd = min_price.get_asset_amount_usd(collateral, custody.decimals)?;
 
+    position.version = Position::VERSION;
     position.owner = ctx.accounts.owner.key();
     position.pool = pool.key();
     position.custody = custody.key();
@@ -196,11 +275,12 @@ pub fn open_position(ctx: Context<OpenPosition>, params: &OpenPositionParams) ->
         Perpetuals::BPS_POWER,
     )?)?;
 
-    position.collateral_amount = params.collateral;
+    position.collateral_amount = collateral;
     position.bump = *ctx
         .bumps
         .get("position")
         .ok_or(ProgramError::InvalidSeeds)?;
+    position.is_cross_margin = false;
 
     // check position risk
     msg!("Check position risks");
@@ -216,43 +296,61 @@ pub fn open_position(ctx: Context<OpenPosition>, params: &OpenPositionParams) ->
     // lock funds for potential profit payoff
     custody.lock_funds(position.locked_amount)?;
 
-    // transfer tokens
-    msg!("Transfer tokens");
-    perpetuals.transfer_tokens_from_user(
-        ctx.accounts.funding_account.to_account_info(),
-        ctx.accounts.custody_token_account.to_account_info(),
-        ctx.accounts.owner.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        transfer_amount,
-    )?;
-
     // update custody stats
     msg!("Update custody stats");
-    custody.collected_fees.open_position_usd = custody
-        .collected_fees
-        .open_position_usd
-        .wrapping_add(token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?);
+    let fee_amount_usd = token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?;
+    custody.collected_fees.open_position_usd = math::checked_add(
+        custody.collected_fees.open_position_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    custody.collected_token_fees.open_position = math::checked_add(
+        custody.collected_token_fees.open_position,
+        fee_amount as u128,
+    )?;
 
-    custody.volume_stats.open_position_usd = custody
-        .volume_stats
-        .open_position_usd
-        .wrapping_add(size_usd);
+    custody.volume_stats.open_position_usd =
+        math::checked_add(custody.volume_stats.open_position_usd, size_usd as u128)?;
 
-    custody.assets.collateral = math::checked_add(custody.assets.collateral, params.collateral)?;
+    custody.assets.collateral = math::checked_add(custody.assets.collateral, collateral)?;
 
     let protocol_fee = Pool::get_fee_amount(custody.fees.protocol_share, fee_amount)?;
     custody.assets.protocol_fees = math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
 
     if params.side == Side::Long {
         custody.trade_stats.oi_long_usd =
-            math::checked_add(custody.trade_stats.oi_long_usd, size_usd)?;
+            math::checked_add(custody.trade_stats.oi_long_usd, size_usd as u128)?;
     } else {
         custody.trade_stats.oi_short_usd =
-            math::checked_add(custody.trade_stats.oi_short_usd, size_usd)?;
+            math::checked_add(custody.trade_stats.oi_short_usd, size_usd as u128)?;
     }
+    pool.add_open_interest(size_usd)?;
 
     custody.add_position(position, &token_ema_price, curtime)?;
     custody.update_borrow_rate(curtime)?;
 
+    // update cached aum contribution
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.open_position_usd =
+        math::checked_add(pool_day_stats.volume.open_position_usd, size_usd as u128)?;
+    pool_day_stats.fees.open_position_usd = math::checked_add(
+        pool_day_stats.fees.open_position_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.owner.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
+
     Ok(())
 }
@@ -0,0 +1,115 @@
+//! QueueInstruction instruction handler
+
+use {
+    crate::state::{
+        multisig::{AdminInstruction, Multisig, Proposal},
+        perpetuals::Perpetuals,
+        timelock::QueuedInstruction,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(params: QueueInstructionParams)]
+pub struct QueueInstruction<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = QueuedInstruction::get_len(params.instruction_data.len(), params.instruction_accounts.len()),
+        seeds = [b"queued_instruction",
+                 params.queue_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub queued_instruction: Box<Account<'info, QueuedInstruction>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct QueueInstructionParams {
+    pub queue_id: u64,
+    // serialized params followed by the instruction type, see Multisig::get_instruction_data
+    pub instruction_data: Vec<u8>,
+    pub instruction_accounts: Vec<Pubkey>,
+}
+
+pub fn queue_instruction<'info>(
+    ctx: Context<'_, '_, '_, 'info, QueueInstruction<'info>>,
+    params: &QueueInstructionParams,
+) -> Result<u8> {
+    // validate inputs
+    if params.instruction_data.is_empty()
+        || params.instruction_data.len() > Proposal::MAX_INSTRUCTION_DATA_LEN
+        || params.instruction_accounts.len() > Proposal::MAX_INSTRUCTION_ACCOUNTS
+    {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    let instruction_type = params.instruction_data[params.instruction_data.len() - 1];
+    let delay = ctx
+        .accounts
+        .perpetuals
+        .timelock_delays
+        .get_delay(instruction_type)
+        .ok_or(ProgramError::InvalidArgument)?;
+    if !ctx.accounts.queued_instruction.instruction_data.is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized.into());
+    }
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::QueueInstruction, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // record queued instruction
+    msg!("Queue instruction: {}", params.queue_id);
+    let curtime = ctx.accounts.perpetuals.get_time()?;
+    let queued_instruction = ctx.accounts.queued_instruction.as_mut();
+    queued_instruction.version = QueuedInstruction::VERSION;
+    queued_instruction.queue_id = params.queue_id;
+    queued_instruction.payer = ctx.accounts.admin.key();
+    queued_instruction.instruction_type = instruction_type;
+    queued_instruction.instruction_data = params.instruction_data.clone();
+    queued_instruction.instruction_accounts = params.instruction_accounts.clone();
+    queued_instruction.queued_time = curtime;
+    queued_instruction.activation_time = curtime.saturating_add(delay);
+    queued_instruction.permissions = ctx.accounts.perpetuals.permissions;
+    queued_instruction.bump = *ctx
+        .bumps
+        .get("queued_instruction")
+        .ok_or(ProgramError::InvalidSeeds)?;
+
+    if !queued_instruction.validate() {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    msg!("Activation time: {}", queued_instruction.activation_time);
+
+    Ok(0)
+}
@@ -0,0 +1,91 @@
+//! RefreshAum instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody,
+            oracle::OraclePrice,
+            perpetuals::Perpetuals,
+            pool::{AumCalcMode, Pool},
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct RefreshAum<'info> {
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    // remaining accounts:
+    //   any number of pool custody accounts (read-only, unsigned)
+    //   the same number of custody oracles in the same order (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RefreshAumParams {}
+
+pub fn refresh_aum(ctx: Context<RefreshAum>, _params: &RefreshAumParams) -> Result<()> {
+    let accounts = ctx.remaining_accounts;
+    if accounts.is_empty() || accounts.len() % 2 != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+
+    let pool = ctx.accounts.pool.as_mut();
+    let curtime = ctx.accounts.perpetuals.get_time()?;
+
+    // refresh cached contributions of the given custodies
+    let num_custodies = accounts.len() / 2;
+    for (custody_info, oracle_info) in accounts[..num_custodies]
+        .iter()
+        .zip(&accounts[num_custodies..])
+    {
+        let token_id = pool.get_token_id(custody_info.key)?;
+        let custody = Account::<Custody>::try_from(custody_info)?;
+        require!(
+            custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        require_keys_eq!(oracle_info.key(), custody.oracle.oracle_account);
+
+        let token_price = OraclePrice::new_from_oracle(
+            custody.oracle.oracle_type,
+            oracle_info,
+            custody.oracle.max_price_error,
+            custody.oracle.max_price_age_sec,
+            curtime,
+            false,
+        )?;
+
+        let token_ema_price = OraclePrice::new_from_oracle(
+            custody.oracle.oracle_type,
+            oracle_info,
+            custody.oracle.max_price_error,
+            custody.oracle.max_price_age_sec,
+            curtime,
+            custody.pricing.use_ema,
+        )?;
+
+        pool.update_custody_aum(token_id, &custody, &token_price, &token_ema_price, curtime)?;
+    }
+
+    // pool aum is only updated once every cached contribution is fresh,
+    // so a large pool can be refreshed in several transactions
+    if let Ok(aum_usd) = pool.get_cached_assets_under_management_usd(AumCalcMode::EMA, curtime) {
+        pool.aum_usd = aum_usd;
+    }
+
+    Ok(())
+}
@@ -10,7 +10,6 @@ use {
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
     solana_program::program_error::ProgramError,
 };
 
@@ -20,12 +19,22 @@ pub struct RemoveCollateral<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    /// CHECK: token account of the custody mint or wallet of the owner for native SOL
     #[account(
         mut,
-        constraint = receiving_account.mint == custody.mint,
-        has_one = owner
+        constraint = Perpetuals::is_token_account(&receiving_account, &custody.mint, owner.key)
+            || Perpetuals::is_native_account(&receiving_account, &custody.mint, owner.key)
     )]
-    pub receiving_account: Box<Account<'info, TokenAccount>>,
+    pub receiving_account: AccountInfo<'info>,
+
+    /// CHECK: temporary wrapped SOL account, only used for native SOL transfers
+    #[account(
+        mut,
+        seeds = [b"wsol_account",
+                 owner.key().as_ref()],
+        bump
+    )]
+    pub wsol_account: AccountInfo<'info>,
 
     /// CHECK: empty PDA, authority for token accounts
     #[account(
@@ -44,7 +53,8 @@ pub struct RemoveCollateral<'info> {
         mut,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -56,7 +66,8 @@ pub struct RemoveCollateral<'info> {
                  pool.key().as_ref(),
                  custody.key().as_ref(),
                  &[position.side as u8]],
-        bump = position.bump
+        bump = position.bump,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub position: Box<Account<'info, Position>>,
 
@@ -65,7 +76,8 @@ pub struct RemoveCollateral<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -75,6 +87,7 @@ pub struct RemoveCollateral<'info> {
     )]
     pub custody_oracle_account: AccountInfo<'info>,
 
+    /// CHECK: custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -82,9 +95,21 @@ pub struct RemoveCollateral<'info> {
                  custody.mint.as_ref()],
         bump = custody.token_account_bump
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: AccountInfo<'info>,
 
-    token_program: Program<'info, Token>,
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
+
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -102,6 +127,7 @@ pub fn remove_collateral(
     let custody = ctx.accounts.custody.as_mut();
     require!(
         perpetuals.permissions.allow_collateral_withdrawal
+            && ctx.accounts.pool.permissions.allow_collateral_withdrawal
             && custody.permissions.allow_collateral_withdrawal,
         PerpetualsError::InstructionNotAllowed
     );
@@ -145,7 +171,7 @@ pub fn remove_collateral(
     // compute fee
     let collateral = max_price.get_token_amount(params.collateral_usd, custody.decimals)?;
     let fee_amount =
-        pool.get_remove_liquidity_fee(token_id, collateral, custody, &token_ema_price)?;
+        pool.get_remove_liquidity_fee(token_id, collateral, custody, &token_ema_price, curtime)?;
     msg!("Collected fee: {}", fee_amount);
 
     // compute amount to transfer
@@ -170,20 +196,49 @@ pub fn remove_collateral(
 
     // transfer tokens
     msg!("Transfer tokens");
-    perpetuals.transfer_tokens(
-        ctx.accounts.custody_token_account.to_account_info(),
-        ctx.accounts.receiving_account.to_account_info(),
-        ctx.accounts.transfer_authority.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        transfer_amount,
-    )?;
+    if Perpetuals::is_native_account(
+        &ctx.accounts.receiving_account,
+        &custody.mint,
+        ctx.accounts.owner.key,
+    ) {
+        let owner_key = ctx.accounts.owner.key();
+        let wsol_account_bump = *ctx
+            .bumps
+            .get("wsol_account")
+            .ok_or(ProgramError::InvalidSeeds)?;
+        perpetuals.unwrap_sol_to_user(
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.wsol_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[b"wsol_account", owner_key.as_ref(), &[wsol_account_bump]],
+            transfer_amount,
+        )?;
+    } else {
+        perpetuals.transfer_tokens(
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.receiving_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            transfer_amount,
+        )?;
+    }
 
     // update custody stats
     msg!("Update custody stats");
-    custody.collected_fees.open_position_usd = custody
-        .collected_fees
-        .open_position_usd
-        .wrapping_add(token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?);
+    custody.collected_fees.open_position_usd = math::checked_add(
+        custody.collected_fees.open_position_usd,
+        token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)? as u128,
+    )?;
+
+    custody.collected_token_fees.open_position = math::checked_add(
+        custody.collected_token_fees.open_position,
+        fee_amount as u128,
+    )?;
 
     custody.assets.collateral = math::checked_sub(custody.assets.collateral, collateral)?;
 
@@ -192,5 +247,8 @@ pub fn remove_collateral(
 
     custody.remove_collateral(position.side, params.collateral_usd)?;
 
+    // update cached aum contribution
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+
     Ok(())
 }
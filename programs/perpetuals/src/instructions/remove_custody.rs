@@ -7,11 +7,10 @@ use {
             custody::Custody,
             multisig::{AdminInstruction, Multisig},
             perpetuals::Perpetuals,
-            pool::{Pool, TokenRatios},
+            pool::{Pool, RatiosRamp, TokenRatios},
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
 };
 
 #[derive(Accounts)]
@@ -42,13 +41,13 @@ pub struct RemoveCustody<'info> {
 
     #[account(
         mut,
-        realloc = Pool::LEN + (pool.custodies.len() - 1) * std::mem::size_of::<Pubkey>() +
-                              (pool.ratios.len() - 1) * std::mem::size_of::<TokenRatios>(),
+        realloc = Pool::get_len(pool.custodies.len() - 1),
         realloc::payer = admin,
         realloc::zero = false,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -58,10 +57,12 @@ pub struct RemoveCustody<'info> {
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
         bump = custody.bump,
-        close = transfer_authority
+        close = transfer_authority,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
+    /// CHECK: custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -69,10 +70,14 @@ pub struct RemoveCustody<'info> {
                  custody.mint.as_ref()],
         bump = custody.token_account_bump,
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: AccountInfo<'info>,
 
     system_program: Program<'info, System>,
-    token_program: Program<'info, Token>,
+    /// CHECK: token program of the custody token account
+    #[account(
+        constraint = custody_token_account.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -90,6 +95,18 @@ pub fn remove_custody<'info>(
     {
         return Err(ProgramError::InvalidArgument.into());
     }
+    // targets of remaining tokens can only be changed with SetPoolRatios
+    let token_id = ctx
+        .accounts
+        .pool
+        .get_token_id(&ctx.accounts.custody.key())?;
+    let mut remaining_ratios = ctx.accounts.pool.ratios.clone();
+    remaining_ratios.remove(token_id);
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0
+            || params.ratios == remaining_ratios,
+        PerpetualsError::TimelockRequired
+    );
 
     // validate signatures
     let mut multisig = ctx.accounts.multisig.load_mut()?;
@@ -98,6 +115,7 @@ pub fn remove_custody<'info>(
         &ctx.accounts.admin,
         &Multisig::get_account_infos(&ctx)[1..],
         &Multisig::get_instruction_data(AdminInstruction::RemoveCustody, params)?,
+        ctx.accounts.perpetuals.get_time()?,
     )?;
     if signatures_left > 0 {
         msg!(
@@ -108,14 +126,22 @@ pub fn remove_custody<'info>(
     }
 
     require!(
-        ctx.accounts.custody_token_account.amount == 0,
+        Perpetuals::get_token_balance(&ctx.accounts.custody_token_account)? == 0,
         PerpetualsError::InvalidCustodyState
     );
 
     // remove token from the list
     let pool = ctx.accounts.pool.as_mut();
-    let token_id = pool.get_token_id(&ctx.accounts.custody.key())?;
     pool.custodies.remove(token_id);
+    pool.custody_aum.remove(token_id);
+    if pool.ratios_ramp.start_targets.len() == pool.ratios.len()
+        && params.ratios == remaining_ratios
+    {
+        // remaining targets are unchanged, keep the ramp in progress
+        pool.ratios_ramp.start_targets.remove(token_id);
+    } else {
+        pool.ratios_ramp = RatiosRamp::default();
+    }
     pool.ratios = params.ratios.clone();
     if !pool.validate() {
         return err!(PerpetualsError::InvalidPoolConfig);
@@ -9,6 +9,7 @@ use {
             oracle::OraclePrice,
             perpetuals::Perpetuals,
             pool::{AumCalcMode, Pool},
+            pool_day_stats::PoolDayStats,
         },
     },
     anchor_lang::prelude::*,
@@ -22,12 +23,22 @@ pub struct RemoveLiquidity<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    /// CHECK: token account of the custody mint or wallet of the owner for native SOL
     #[account(
         mut,
-        constraint = receiving_account.mint == custody.mint,
-        has_one = owner
+        constraint = Perpetuals::is_token_account(&receiving_account, &custody.mint, owner.key)
+            || Perpetuals::is_native_account(&receiving_account, &custody.mint, owner.key)
+    )]
+    pub receiving_account: AccountInfo<'info>,
+
+    /// CHECK: temporary wrapped SOL account, only used for native SOL transfers
+    #[account(
+        mut,
+        seeds = [b"wsol_account",
+                 owner.key().as_ref()],
+        bump
     )]
-    pub receiving_account: Box<Account<'info, TokenAccount>>,
+    pub wsol_account: AccountInfo<'info>,
 
     #[account(
         mut,
@@ -53,16 +64,29 @@ pub struct RemoveLiquidity<'info> {
         mut,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
     #[account(
         mut,
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -72,6 +96,7 @@ pub struct RemoveLiquidity<'info> {
     )]
     pub custody_oracle_account: AccountInfo<'info>,
 
+    /// CHECK: custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -79,7 +104,13 @@ pub struct RemoveLiquidity<'info> {
                  custody.mint.as_ref()],
         bump = custody.token_account_bump
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
 
     #[account(
         mut,
@@ -89,8 +120,16 @@ pub struct RemoveLiquidity<'info> {
     )]
     pub lp_token_mint: Box<Account<'info, Mint>>,
 
+    system_program: Program<'info, System>,
+
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == custody_token_program.key
+    )]
+    custody_token_program: AccountInfo<'info>,
+
     token_program: Program<'info, Token>,
-    // remaining accounts:
+    // remaining accounts (optional, cached custody contributions are used if omitted):
     //   pool.tokens.len() custody accounts (read-only, unsigned)
     //   pool.tokens.len() custody oracles (read-only, unsigned)
 }
@@ -110,7 +149,9 @@ pub fn remove_liquidity(
     let perpetuals = ctx.accounts.perpetuals.as_mut();
     let custody = ctx.accounts.custody.as_mut();
     require!(
-        perpetuals.permissions.allow_remove_liquidity && custody.permissions.allow_remove_liquidity,
+        perpetuals.permissions.allow_remove_liquidity
+            && ctx.accounts.pool.permissions.allow_remove_liquidity
+            && custody.permissions.allow_remove_liquidity,
         PerpetualsError::InstructionNotAllowed
     );
 
@@ -120,6 +161,10 @@ pub fn remove_liquidity(
         return Err(ProgramError::InvalidArgument.into());
     }
     let pool = ctx.accounts.pool.as_mut();
+    require!(
+        !pool.withdrawal_queue_enabled,
+        PerpetualsError::WithdrawalQueueEnabled
+    );
     let token_id = pool.get_token_id(&custody.key())?;
 
     // compute assets under management
@@ -150,8 +195,8 @@ pub fn remove_liquidity(
         token_ema_price
     };
 
-    let pool_amount_usd =
-        pool.get_assets_under_management_usd(AumCalcMode::Min, ctx.remaining_accounts, curtime)?;
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+    let pool_amount_usd = pool.get_aum_usd(AumCalcMode::Min, ctx.remaining_accounts, curtime)?;
 
     // compute amount of tokens to return
     let remove_amount_usd = math::checked_as_u64(math::checked_div(
@@ -163,7 +208,7 @@ pub fn remove_liquidity(
 
     // calculate fee
     let fee_amount =
-        pool.get_remove_liquidity_fee(token_id, remove_amount, custody, &token_ema_price)?;
+        pool.get_remove_liquidity_fee(token_id, remove_amount, custody, &token_ema_price, curtime)?;
     msg!("Collected fee: {}", fee_amount);
 
     let transfer_amount = math::checked_sub(remove_amount, fee_amount)?;
@@ -190,13 +235,37 @@ pub fn remove_liquidity(
 
     // transfer tokens
     msg!("Transfer tokens");
-    perpetuals.transfer_tokens(
-        ctx.accounts.custody_token_account.to_account_info(),
-        ctx.accounts.receiving_account.to_account_info(),
-        ctx.accounts.transfer_authority.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        transfer_amount,
-    )?;
+    if Perpetuals::is_native_account(
+        &ctx.accounts.receiving_account,
+        &custody.mint,
+        ctx.accounts.owner.key,
+    ) {
+        let owner_key = ctx.accounts.owner.key();
+        let wsol_account_bump = *ctx
+            .bumps
+            .get("wsol_account")
+            .ok_or(ProgramError::InvalidSeeds)?;
+        perpetuals.unwrap_sol_to_user(
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.wsol_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.custody_token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[b"wsol_account", owner_key.as_ref(), &[wsol_account_bump]],
+            transfer_amount,
+        )?;
+    } else {
+        perpetuals.transfer_tokens(
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.receiving_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.custody_token_program.to_account_info(),
+            transfer_amount,
+        )?;
+    }
 
     // burn lp tokens
     msg!("Burn LP tokens");
@@ -210,15 +279,21 @@ pub fn remove_liquidity(
 
     // update custody stats
     msg!("Update custody stats");
-    custody.collected_fees.remove_liquidity_usd = custody
-        .collected_fees
-        .remove_liquidity_usd
-        .wrapping_add(token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?);
+    let fee_amount_usd = token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?;
+    custody.collected_fees.remove_liquidity_usd = math::checked_add(
+        custody.collected_fees.remove_liquidity_usd,
+        fee_amount_usd as u128,
+    )?;
 
-    custody.volume_stats.remove_liquidity_usd = custody
-        .volume_stats
-        .remove_liquidity_usd
-        .wrapping_add(remove_amount_usd);
+    custody.collected_token_fees.remove_liquidity = math::checked_add(
+        custody.collected_token_fees.remove_liquidity,
+        fee_amount as u128,
+    )?;
+
+    custody.volume_stats.remove_liquidity_usd = math::checked_add(
+        custody.volume_stats.remove_liquidity_usd,
+        remove_amount_usd as u128,
+    )?;
 
     custody.assets.protocol_fees = math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
 
@@ -229,8 +304,30 @@ pub fn remove_liquidity(
     // update pool stats
     msg!("Update pool stats");
     custody.exit(&crate::ID)?;
-    pool.aum_usd =
-        pool.get_assets_under_management_usd(AumCalcMode::EMA, ctx.remaining_accounts, curtime)?;
+    pool.update_custody_aum(token_id, custody, &token_price, &token_ema_price, curtime)?;
+    pool.update_aum_usd(ctx.remaining_accounts, curtime)?;
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.remove_liquidity_usd = math::checked_add(
+        pool_day_stats.volume.remove_liquidity_usd,
+        remove_amount_usd as u128,
+    )?;
+    pool_day_stats.fees.remove_liquidity_usd = math::checked_add(
+        pool_day_stats.fees.remove_liquidity_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.owner.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
 
     Ok(())
 }
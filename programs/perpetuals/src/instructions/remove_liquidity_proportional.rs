@@ -0,0 +1,261 @@
+//! RemoveLiquidityProportional instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::Custody, oracle::OraclePrice, perpetuals::Perpetuals, pool::Pool,
+            pool_day_stats::PoolDayStats,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount},
+    solana_program::program_error::ProgramError,
+};
+
+#[derive(Accounts)]
+#[instruction(params: RemoveLiquidityProportionalParams)]
+pub struct RemoveLiquidityProportional<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = lp_token_account.mint == lp_token_mint.key(),
+        has_one = owner
+    )]
+    pub lp_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_token_mint",
+                 pool.key().as_ref()],
+        bump = pool.lp_token_bump
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    // remaining accounts:
+    //   pool.tokens.len() custody accounts (write, unsigned)
+    //   pool.tokens.len() custody oracles (read-only, unsigned)
+    //   pool.tokens.len() custody token accounts (write, unsigned)
+    //   pool.tokens.len() receiving token accounts (write, unsigned)
+    //   pool.tokens.len() custody token mints (read-only, unsigned)
+    //   pool.tokens.len() token programs of the custody mints (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RemoveLiquidityProportionalParams {
+    pub lp_amount_in: u64,
+    // in the same order as pool.custodies
+    pub min_amounts_out: Vec<u64>,
+}
+
+pub fn remove_liquidity_proportional<'info>(
+    ctx: Context<'_, '_, '_, 'info, RemoveLiquidityProportional<'info>>,
+    params: &RemoveLiquidityProportionalParams,
+) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    let perpetuals = ctx.accounts.perpetuals.as_mut();
+    require!(
+        perpetuals.permissions.allow_remove_liquidity
+            && ctx.accounts.pool.permissions.allow_remove_liquidity,
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    // validate inputs
+    msg!("Validate inputs");
+    let pool = ctx.accounts.pool.as_mut();
+    require!(
+        !pool.withdrawal_queue_enabled,
+        PerpetualsError::WithdrawalQueueEnabled
+    );
+    let num_custodies = pool.custodies.len();
+    if params.lp_amount_in == 0 || params.min_amounts_out.len() != num_custodies {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    let accounts = ctx.remaining_accounts;
+    if accounts.len() < num_custodies * 6 {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let lp_supply = ctx.accounts.lp_token_mint.supply;
+    let curtime = perpetuals.get_time()?;
+    let mut remove_amount_usd: u64 = 0;
+    let mut fee_amount_usd: u64 = 0;
+
+    // withdraw pro-rata share of every custody, pool composition stays the same
+    // so token ratio limits are not checked
+    for (idx, &custody_key) in pool.custodies.iter().enumerate() {
+        let oracle_idx = idx + num_custodies;
+        let token_account_idx = oracle_idx + num_custodies;
+        let receiving_idx = token_account_idx + num_custodies;
+        let mint_idx = receiving_idx + num_custodies;
+        let token_program_idx = mint_idx + num_custodies;
+
+        require_keys_eq!(accounts[idx].key(), custody_key);
+        let mut custody = Account::<Custody>::try_from(&accounts[idx])?;
+        require!(
+            custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        require!(
+            custody.permissions.allow_remove_liquidity,
+            PerpetualsError::InstructionNotAllowed
+        );
+        require_keys_eq!(accounts[oracle_idx].key(), custody.oracle.oracle_account);
+        require_keys_eq!(accounts[token_account_idx].key(), custody.token_account);
+        let receiving_account = Perpetuals::get_token_account(&accounts[receiving_idx])?;
+        require_keys_eq!(receiving_account.mint, custody.mint);
+        require_keys_eq!(receiving_account.owner, ctx.accounts.owner.key());
+        require_keys_eq!(accounts[mint_idx].key(), custody.mint);
+        require_keys_eq!(accounts[token_program_idx].key(), *accounts[mint_idx].owner);
+
+        let token_ema_price = OraclePrice::new_from_oracle(
+            custody.oracle.oracle_type,
+            &accounts[oracle_idx],
+            custody.oracle.max_price_error,
+            custody.oracle.max_price_age_sec,
+            curtime,
+            custody.pricing.use_ema,
+        )?;
+
+        // compute amount of tokens to return
+        let free_amount = math::checked_sub(custody.assets.owned, custody.assets.locked)?;
+        let remove_amount = math::checked_as_u64(math::checked_div(
+            math::checked_mul(free_amount as u128, params.lp_amount_in as u128)?,
+            lp_supply as u128,
+        )?)?;
+
+        // calculate fee
+        let fee_amount = Pool::get_fee_amount(custody.fees.remove_liquidity, remove_amount)?;
+        msg!("Collected fee: {}", fee_amount);
+
+        let transfer_amount = math::checked_sub(remove_amount, fee_amount)?;
+        msg!("Amount out: {}", transfer_amount);
+
+        require!(
+            transfer_amount >= params.min_amounts_out[idx],
+            PerpetualsError::MaxPriceSlippage
+        );
+
+        let protocol_fee = Pool::get_fee_amount(custody.fees.protocol_share, fee_amount)?;
+        let withdrawal_amount = math::checked_add(transfer_amount, protocol_fee)?;
+
+        // transfer tokens
+        msg!("Transfer tokens");
+        perpetuals.transfer_tokens(
+            accounts[token_account_idx].clone(),
+            accounts[receiving_idx].clone(),
+            accounts[mint_idx].clone(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            accounts[token_program_idx].clone(),
+            transfer_amount,
+        )?;
+
+        // update custody stats
+        msg!("Update custody stats");
+        let token_fee_usd = token_ema_price.get_asset_amount_usd(fee_amount, custody.decimals)?;
+        let token_remove_usd =
+            token_ema_price.get_asset_amount_usd(remove_amount, custody.decimals)?;
+        fee_amount_usd = math::checked_add(fee_amount_usd, token_fee_usd)?;
+        remove_amount_usd = math::checked_add(remove_amount_usd, token_remove_usd)?;
+
+        custody.collected_fees.remove_liquidity_usd = math::checked_add(
+            custody.collected_fees.remove_liquidity_usd,
+            token_fee_usd as u128,
+        )?;
+
+        custody.collected_token_fees.remove_liquidity = math::checked_add(
+            custody.collected_token_fees.remove_liquidity,
+            fee_amount as u128,
+        )?;
+
+        custody.volume_stats.remove_liquidity_usd = math::checked_add(
+            custody.volume_stats.remove_liquidity_usd,
+            token_remove_usd as u128,
+        )?;
+
+        custody.assets.protocol_fees =
+            math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
+
+        custody.assets.owned = math::checked_sub(custody.assets.owned, withdrawal_amount)?;
+
+        custody.update_borrow_rate(curtime)?;
+
+        custody.exit(&crate::ID)?;
+    }
+
+    // burn lp tokens
+    msg!("Burn LP tokens");
+    perpetuals.burn_tokens(
+        ctx.accounts.lp_token_mint.to_account_info(),
+        ctx.accounts.lp_token_account.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        params.lp_amount_in,
+    )?;
+
+    // update pool stats
+    msg!("Update pool stats");
+    pool.update_aum_usd(accounts, curtime)?;
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.remove_liquidity_usd = math::checked_add(
+        pool_day_stats.volume.remove_liquidity_usd,
+        remove_amount_usd as u128,
+    )?;
+    pool_day_stats.fees.remove_liquidity_usd = math::checked_add(
+        pool_day_stats.fees.remove_liquidity_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.owner.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,184 @@
+//! RemoveMarginPosition instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody, margin_account::MarginAccount, oracle::OraclePrice,
+            perpetuals::Perpetuals, pool::Pool, position::Position,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct RemoveMarginPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = collateral_custody,
+        realloc = MarginAccount::LEN + margin_account.positions.len().saturating_sub(1) * std::mem::size_of::<Pubkey>(),
+        realloc::payer = owner,
+        realloc::zero = false,
+        seeds = [b"margin_account",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump = margin_account.bump
+    )]
+    pub margin_account: Box<Account<'info, MarginAccount>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"position",
+                 owner.key().as_ref(),
+                 pool.key().as_ref(),
+                 custody.key().as_ref(),
+                 &[position.side as u8]],
+        bump = position.bump,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    #[account(
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the position token
+    #[account(
+        constraint = custody_oracle_account.key() == custody.oracle.oracle_account
+    )]
+    pub custody_oracle_account: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 collateral_custody.mint.as_ref()],
+        bump = collateral_custody.bump,
+        constraint = collateral_custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub collateral_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the collateral token
+    #[account(
+        constraint = collateral_custody_oracle_account.key() == collateral_custody.oracle.oracle_account
+    )]
+    pub collateral_custody_oracle_account: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
+    // remaining accounts:
+    //   margin_account.positions.len() - 1 position accounts (read-only, unsigned)
+    //   margin_account.positions.len() - 1 custody accounts (read-only, unsigned)
+    //   margin_account.positions.len() - 1 custody oracles (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RemoveMarginPositionParams {}
+
+pub fn remove_margin_position(
+    ctx: Context<RemoveMarginPosition>,
+    _params: &RemoveMarginPositionParams,
+) -> Result<()> {
+    // validate inputs
+    msg!("Validate inputs");
+    let position_key = ctx.accounts.position.key();
+    let position = ctx.accounts.position.as_mut();
+    require!(
+        position.is_cross_margin,
+        PerpetualsError::InvalidPositionState
+    );
+
+    // unlink position from the margin account
+    msg!("Unlink position");
+    let margin_account = ctx.accounts.margin_account.as_mut();
+    margin_account.remove_position(&position_key)?;
+    position.is_cross_margin = false;
+
+    // check position risk
+    msg!("Check position risks");
+    let perpetuals = ctx.accounts.perpetuals.as_ref();
+    let pool = ctx.accounts.pool.as_ref();
+    let custody = ctx.accounts.custody.as_ref();
+    let curtime = perpetuals.get_time()?;
+
+    let token_ema_price = OraclePrice::new_from_oracle(
+        custody.oracle.oracle_type,
+        &ctx.accounts.custody_oracle_account.to_account_info(),
+        custody.oracle.max_price_error,
+        custody.oracle.max_price_age_sec,
+        curtime,
+        custody.pricing.use_ema,
+    )?;
+
+    require!(
+        pool.check_leverage(position, &token_ema_price, custody, curtime, false)?,
+        PerpetualsError::MaxLeverage
+    );
+
+    // check margin account health
+    msg!("Check margin account health");
+    let collateral_custody = ctx.accounts.collateral_custody.as_ref();
+
+    let collateral_price = OraclePrice::new_from_oracle(
+        collateral_custody.oracle.oracle_type,
+        &ctx.accounts
+            .collateral_custody_oracle_account
+            .to_account_info(),
+        collateral_custody.oracle.max_price_error,
+        collateral_custody.oracle.max_price_age_sec,
+        curtime,
+        false,
+    )?;
+
+    let collateral_ema_price = OraclePrice::new_from_oracle(
+        collateral_custody.oracle.oracle_type,
+        &ctx.accounts
+            .collateral_custody_oracle_account
+            .to_account_info(),
+        collateral_custody.oracle.max_price_error,
+        collateral_custody.oracle.max_price_age_sec,
+        curtime,
+        collateral_custody.pricing.use_ema,
+    )?;
+
+    let min_collateral_price = if collateral_price < collateral_ema_price {
+        collateral_price
+    } else {
+        collateral_ema_price
+    };
+
+    require!(
+        margin_account.check_health(
+            pool,
+            collateral_custody,
+            &min_collateral_price,
+            ctx.remaining_accounts,
+            curtime
+        )?,
+        PerpetualsError::MaxLeverage
+    );
+
+    Ok(())
+}
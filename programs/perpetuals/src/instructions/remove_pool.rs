@@ -47,7 +47,8 @@ pub struct RemovePool<'info> {
         seeds = [b"pool",
                  pool.name.as_bytes()],
         bump = pool.bump,
-        close = transfer_authority
+        close = transfer_authority,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -68,6 +69,7 @@ pub fn remove_pool<'info>(
         &ctx.accounts.admin,
         &Multisig::get_account_infos(&ctx)[1..],
         &Multisig::get_instruction_data(AdminInstruction::RemovePool, params)?,
+        ctx.accounts.perpetuals.get_time()?,
     )?;
     if signatures_left > 0 {
         msg!(
@@ -0,0 +1,142 @@
+//! RequestWithdrawal instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            perpetuals::Perpetuals,
+            pool::Pool,
+            withdrawal_queue::{WithdrawalQueue, WithdrawalRequest},
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount},
+    solana_program::program_error::ProgramError,
+};
+
+#[derive(Accounts)]
+#[instruction(params: RequestWithdrawalParams)]
+pub struct RequestWithdrawal<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = lp_token_account.mint == lp_token_mint.key(),
+        has_one = owner
+    )]
+    pub lp_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"withdrawal_queue",
+                 pool.key().as_ref()],
+        bump = withdrawal_queue.bump
+    )]
+    pub withdrawal_queue: Box<Account<'info, WithdrawalQueue>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = WithdrawalRequest::LEN,
+        seeds = [b"withdrawal_request",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump
+    )]
+    pub withdrawal_request: Box<Account<'info, WithdrawalRequest>>,
+
+    #[account(
+        mut,
+        seeds = [b"withdrawal_escrow_account",
+                 pool.key().as_ref()],
+        bump = withdrawal_queue.escrow_token_account_bump
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"lp_token_mint",
+                 pool.key().as_ref()],
+        bump = pool.lp_token_bump
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RequestWithdrawalParams {
+    pub lp_amount: u64,
+}
+
+pub fn request_withdrawal(
+    ctx: Context<RequestWithdrawal>,
+    params: &RequestWithdrawalParams,
+) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    let perpetuals = ctx.accounts.perpetuals.as_mut();
+    require!(
+        perpetuals.permissions.allow_remove_liquidity
+            && ctx.accounts.pool.permissions.allow_remove_liquidity,
+        PerpetualsError::InstructionNotAllowed
+    );
+    require!(
+        ctx.accounts.pool.withdrawal_queue_enabled,
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    // validate inputs
+    msg!("Validate inputs");
+    if params.lp_amount == 0 {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+
+    // transfer tokens
+    msg!("Transfer tokens");
+    perpetuals.transfer_tokens_from_user(
+        ctx.accounts.lp_token_account.to_account_info(),
+        ctx.accounts.escrow_token_account.to_account_info(),
+        ctx.accounts.lp_token_mint.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        params.lp_amount,
+    )?;
+
+    // record withdrawal request, new tokens added to a pending request restart its cooldown
+    msg!("Record withdrawal request");
+    let request = ctx.accounts.withdrawal_request.as_mut();
+    if request.owner == Pubkey::default() {
+        request.version = WithdrawalRequest::VERSION;
+        request.owner = ctx.accounts.owner.key();
+        request.pool = ctx.accounts.pool.key();
+        request.bump = *ctx
+            .bumps
+            .get("withdrawal_request")
+            .ok_or(ProgramError::InvalidSeeds)?;
+    }
+    request.lp_amount = math::checked_add(request.lp_amount, params.lp_amount)?;
+    request.request_time = perpetuals.get_time()?;
+
+    let withdrawal_queue = ctx.accounts.withdrawal_queue.as_mut();
+    withdrawal_queue.pending_lp_amount =
+        math::checked_add(withdrawal_queue.pending_lp_amount, params.lp_amount)?;
+
+    Ok(())
+}
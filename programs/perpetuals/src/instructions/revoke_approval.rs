@@ -0,0 +1,48 @@
+//! RevokeApproval instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::multisig::{Multisig, Proposal},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct RevokeApproval<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal",
+                 proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RevokeApprovalParams {}
+
+pub fn revoke_approval(ctx: Context<RevokeApproval>, _params: &RevokeApprovalParams) -> Result<()> {
+    // validate inputs
+    msg!("Validate inputs");
+    let multisig = ctx.accounts.multisig.load()?;
+    multisig.get_signer_index(ctx.accounts.admin.key)?;
+
+    let proposal = ctx.accounts.proposal.as_mut();
+    if proposal.executed {
+        return err!(PerpetualsError::MultisigAlreadyExecuted);
+    }
+
+    // remove approval
+    msg!("Revoke approval: {}", proposal.proposal_id);
+    proposal.revoke(ctx.accounts.admin.key)
+}
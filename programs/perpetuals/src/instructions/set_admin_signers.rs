@@ -1,7 +1,13 @@
 //! SetAdminSigners instruction handler
 
 use {
-    crate::state::multisig::{AdminInstruction, Multisig},
+    crate::{
+        error::PerpetualsError,
+        state::{
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+        },
+    },
     anchor_lang::prelude::*,
 };
 
@@ -16,7 +22,14 @@ pub struct SetAdminSigners<'info> {
         bump = multisig.load()?.bump
     )]
     pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
     // remaining accounts: 1 to Multisig::MAX_SIGNERS admin signers (read-only, unsigned)
+    //                    proposal account if more than one signature is required (write, unsigned)
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -28,6 +41,12 @@ pub fn set_admin_signers<'info>(
     ctx: Context<'_, '_, '_, 'info, SetAdminSigners<'info>>,
     params: &SetAdminSignersParams,
 ) -> Result<u8> {
+    // validate inputs
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_admin_signers == 0,
+        PerpetualsError::TimelockRequired
+    );
+
     // validate signatures
     let mut multisig = ctx.accounts.multisig.load_mut()?;
 
@@ -35,6 +54,7 @@ pub fn set_admin_signers<'info>(
         &ctx.accounts.admin,
         &Multisig::get_account_infos(&ctx)[1..],
         &Multisig::get_instruction_data(AdminInstruction::SetAdminSigners, params)?,
+        ctx.accounts.perpetuals.get_time()?,
     )?;
     if signatures_left > 0 {
         msg!(
@@ -45,7 +65,10 @@ pub fn set_admin_signers<'info>(
     }
 
     // set new admin signers
-    multisig.set_signers(ctx.remaining_accounts, params.min_signatures)?;
+    multisig.set_signers(
+        Multisig::strip_proposal(ctx.remaining_accounts),
+        params.min_signatures,
+    )?;
 
     Ok(0)
 }
@@ -4,10 +4,13 @@ use {
     crate::{
         error::PerpetualsError,
         state::{
-            custody::{BorrowRateParams, Custody, Fees, OracleParams, PricingParams},
+            custody::{
+                BorrowRateParams, Custody, CustodyRamp, Fees, LeverageTier, OracleParams,
+                PricingParams,
+            },
             multisig::{AdminInstruction, Multisig},
-            perpetuals::Permissions,
-            pool::{Pool, TokenRatios},
+            perpetuals::{Permissions, Perpetuals},
+            pool::{Pool, RatiosRamp, TokenRatios},
         },
     },
     anchor_lang::prelude::*,
@@ -25,11 +28,18 @@ pub struct SetCustodyConfig<'info> {
     )]
     pub multisig: AccountLoader<'info, Multisig>,
 
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
     #[account(
         mut,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -38,7 +48,8 @@ pub struct SetCustodyConfig<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump
+        bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 }
@@ -51,6 +62,7 @@ pub struct SetCustodyConfigParams {
     pub permissions: Permissions,
     pub fees: Fees,
     pub borrow_rate: BorrowRateParams,
+    pub leverage_tiers: Vec<LeverageTier>,
     pub ratios: Vec<TokenRatios>,
 }
 
@@ -62,6 +74,10 @@ pub fn set_custody_config<'info>(
     if params.ratios.len() != ctx.accounts.pool.ratios.len() {
         return Err(ProgramError::InvalidArgument.into());
     }
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
 
     // validate signatures
     let mut multisig = ctx.accounts.multisig.load_mut()?;
@@ -70,6 +86,7 @@ pub fn set_custody_config<'info>(
         &ctx.accounts.admin,
         &Multisig::get_account_infos(&ctx)[1..],
         &Multisig::get_instruction_data(AdminInstruction::SetCustodyConfig, params)?,
+        ctx.accounts.perpetuals.get_time()?,
     )?;
     if signatures_left > 0 {
         msg!(
@@ -79,25 +96,40 @@ pub fn set_custody_config<'info>(
         return Ok(signatures_left);
     }
 
-    // update pool data
-    let pool = ctx.accounts.pool.as_mut();
-    pool.ratios = params.ratios.clone();
-    if !pool.validate() {
-        return err!(PerpetualsError::InvalidPoolConfig);
-    }
+    // update pool and custody config
+    params.apply(ctx.accounts.pool.as_mut(), ctx.accounts.custody.as_mut())?;
+
+    Ok(0)
+}
+
+impl SetCustodyConfigParams {
+    /// Updates pool ratios and custody config
+    pub fn apply(&self, pool: &mut Pool, custody: &mut Custody) -> Result<()> {
+        if self.ratios.len() != pool.ratios.len() {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+
+        // update pool data
+        pool.ratios = self.ratios.clone();
+        pool.ratios_ramp = RatiosRamp::default();
+        if !pool.validate() {
+            return err!(PerpetualsError::InvalidPoolConfig);
+        }
+
+        // update custody data
+        custody.is_stable = self.is_stable;
+        custody.oracle = self.oracle;
+        custody.pricing = self.pricing;
+        custody.permissions = self.permissions;
+        custody.fees = self.fees;
+        custody.borrow_rate = self.borrow_rate;
+        custody.set_leverage_tiers(&self.leverage_tiers)?;
+        custody.ramp = CustodyRamp::default();
 
-    // update custody data
-    let custody = ctx.accounts.custody.as_mut();
-    custody.is_stable = params.is_stable;
-    custody.oracle = params.oracle;
-    custody.pricing = params.pricing;
-    custody.permissions = params.permissions;
-    custody.fees = params.fees;
-    custody.borrow_rate = params.borrow_rate;
-
-    if !custody.validate() {
-        err!(PerpetualsError::InvalidCustodyConfig)
-    } else {
-        Ok(0)
+        if !custody.validate() {
+            err!(PerpetualsError::InvalidCustodyConfig)
+        } else {
+            Ok(())
+        }
     }
 }
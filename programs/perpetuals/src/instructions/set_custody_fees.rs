@@ -0,0 +1,101 @@
+//! SetCustodyFees instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::{Custody, Fees},
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetCustodyFees<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetCustodyFeesParams {
+    pub fees: Fees,
+}
+
+pub fn set_custody_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetCustodyFees<'info>>,
+    params: &SetCustodyFeesParams,
+) -> Result<u8> {
+    // validate inputs
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetCustodyFees, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // update custody fees
+    params.apply(ctx.accounts.custody.as_mut())?;
+
+    Ok(0)
+}
+
+impl SetCustodyFeesParams {
+    pub fn apply(&self, custody: &mut Custody) -> Result<()> {
+        custody.fees = self.fees;
+
+        if !custody.validate() {
+            err!(PerpetualsError::InvalidCustodyConfig)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! SetCustodyOracle instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::{Custody, OracleParams},
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetCustodyOracle<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetCustodyOracleParams {
+    pub oracle: OracleParams,
+}
+
+pub fn set_custody_oracle<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetCustodyOracle<'info>>,
+    params: &SetCustodyOracleParams,
+) -> Result<u8> {
+    // validate inputs
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetCustodyOracle, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // update custody oracle
+    params.apply(ctx.accounts.custody.as_mut())?;
+
+    Ok(0)
+}
+
+impl SetCustodyOracleParams {
+    pub fn apply(&self, custody: &mut Custody) -> Result<()> {
+        custody.oracle = self.oracle;
+
+        if !custody.validate() {
+            err!(PerpetualsError::InvalidCustodyConfig)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! SetCustodyRamp instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody,
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetCustodyRamp<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetCustodyRampParams {
+    // ramp targets, see PricingParams and BorrowRateParams for decimals
+    pub max_leverage: u64,
+    pub trade_spread_long: u64,
+    pub trade_spread_short: u64,
+    pub swap_spread: u64,
+    pub slope1: u64,
+    pub slope2: u64,
+    pub ramp_end_time: i64,
+}
+
+pub fn set_custody_ramp<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetCustodyRamp<'info>>,
+    params: &SetCustodyRampParams,
+) -> Result<u8> {
+    // validate inputs
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetCustodyRamp, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // start custody params ramp
+    let curtime = ctx.accounts.perpetuals.get_time()?;
+    params.apply(ctx.accounts.custody.as_mut(), curtime)?;
+
+    Ok(0)
+}
+
+impl SetCustodyRampParams {
+    /// Accrues interest at the current rate and starts ramping from the
+    /// current values
+    pub fn apply(&self, custody: &mut Custody, curtime: i64) -> Result<()> {
+        custody.update_borrow_rate(curtime)?;
+        custody.start_ramp(curtime, self.ramp_end_time)?;
+
+        custody.pricing.max_leverage = self.max_leverage;
+        custody.pricing.trade_spread_long = self.trade_spread_long;
+        custody.pricing.trade_spread_short = self.trade_spread_short;
+        custody.pricing.swap_spread = self.swap_spread;
+        custody.borrow_rate.slope1 = self.slope1;
+        custody.borrow_rate.slope2 = self.slope2;
+
+        if !custody.validate() {
+            return err!(PerpetualsError::InvalidCustodyConfig);
+        }
+
+        custody.update_borrow_rate(curtime)
+    }
+}
@@ -0,0 +1,125 @@
+//! SetCustodyStatus instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::{Custody, CustodyStatus},
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetCustodyStatus<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetCustodyStatusParams {
+    pub status: CustodyStatus,
+    // required for the settlement status, ignored otherwise
+    pub settlement_price: u64,
+}
+
+pub fn set_custody_status<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetCustodyStatus<'info>>,
+    params: &SetCustodyStatusParams,
+) -> Result<u8> {
+    // validate inputs
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetCustodyStatus, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // update custody status
+    params.apply(ctx.accounts.custody.as_mut())?;
+
+    Ok(0)
+}
+
+impl SetCustodyStatusParams {
+    pub fn apply(&self, custody: &mut Custody) -> Result<()> {
+        // reduce-only can be lifted, settlement is final and the delisted
+        // status is only reached by sweeping the custody
+        let allowed = matches!(
+            (custody.status, self.status),
+            (CustodyStatus::Active, CustodyStatus::ReduceOnly)
+                | (CustodyStatus::ReduceOnly, CustodyStatus::Active)
+                | (CustodyStatus::ReduceOnly, CustodyStatus::Settlement)
+        );
+        if !allowed {
+            msg!(
+                "Error: Custody status can't be changed from {:?} to {:?}",
+                custody.status,
+                self.status
+            );
+            return err!(PerpetualsError::InvalidCustodyState);
+        }
+
+        custody.status = self.status;
+        custody.settlement_price = if self.status == CustodyStatus::Settlement {
+            self.settlement_price
+        } else {
+            0
+        };
+
+        if !custody.validate() {
+            err!(PerpetualsError::InvalidCustodyConfig)
+        } else {
+            Ok(())
+        }
+    }
+}
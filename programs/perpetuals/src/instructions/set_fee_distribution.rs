@@ -0,0 +1,115 @@
+//! SetFeeDistribution instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            fee_distribution::{FeeDistribution, FeeRecipient},
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetFeeDistribution<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    // instruction can be called multiple times due to multisig use, hence init_if_needed
+    // instead of init. Account is filled out on the first call with all signatures collected.
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = FeeDistribution::LEN,
+        seeds = [b"fee_distribution",
+                 pool.key().as_ref()],
+        bump
+    )]
+    pub fee_distribution: Box<Account<'info, FeeDistribution>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetFeeDistributionParams {
+    pub recipients: Vec<FeeRecipient>,
+}
+
+pub fn set_fee_distribution<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetFeeDistribution<'info>>,
+    params: &SetFeeDistributionParams,
+) -> Result<u8> {
+    // redirecting fees is always subject to the timelock, the distribution is
+    // created while the delay is zero and updated with execute_queued afterwards
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetFeeDistribution, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // record fee distribution data, distribution records are kept
+    let fee_distribution = ctx.accounts.fee_distribution.as_mut();
+    if fee_distribution.pool == Pubkey::default() {
+        fee_distribution.version = FeeDistribution::VERSION;
+        fee_distribution.pool = ctx.accounts.pool.key();
+        fee_distribution.bump = *ctx
+            .bumps
+            .get("fee_distribution")
+            .ok_or(ProgramError::InvalidSeeds)?;
+    }
+    params.apply(fee_distribution)?;
+
+    Ok(0)
+}
+
+impl SetFeeDistributionParams {
+    pub fn apply(&self, fee_distribution: &mut FeeDistribution) -> Result<()> {
+        fee_distribution.recipients = self.recipients.clone();
+
+        if !fee_distribution.validate() {
+            err!(PerpetualsError::InvalidFeeDistributionConfig)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! SetGuardians instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetGuardians<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetGuardiansParams {
+    pub guardians: Vec<Pubkey>,
+}
+
+pub fn set_guardians<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetGuardians<'info>>,
+    params: &SetGuardiansParams,
+) -> Result<u8> {
+    // validate inputs
+    if params.guardians.len() > Perpetuals::MAX_GUARDIANS {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetGuardians, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // update guardians
+    let perpetuals = ctx.accounts.perpetuals.as_mut();
+    perpetuals.guardians = Default::default();
+    for (idx, guardian) in params.guardians.iter().enumerate() {
+        if perpetuals.is_guardian(guardian) {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        perpetuals.guardians[idx] = *guardian;
+    }
+
+    if !perpetuals.validate() {
+        err!(PerpetualsError::InvalidPerpetualsConfig)
+    } else {
+        Ok(0)
+    }
+}
@@ -5,7 +5,7 @@ use {
         error::PerpetualsError,
         state::{
             multisig::{AdminInstruction, Multisig},
-            perpetuals::Perpetuals,
+            perpetuals::{Permissions, Perpetuals},
         },
     },
     anchor_lang::prelude::*,
@@ -31,7 +31,7 @@ pub struct SetPermissions<'info> {
     pub perpetuals: Box<Account<'info, Perpetuals>>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize)]
 pub struct SetPermissionsParams {
     pub allow_swap: bool,
     pub allow_add_liquidity: bool,
@@ -41,12 +41,21 @@ pub struct SetPermissionsParams {
     pub allow_pnl_withdrawal: bool,
     pub allow_collateral_withdrawal: bool,
     pub allow_size_change: bool,
+    pub allow_liquidation: bool,
 }
 
 pub fn set_permissions<'info>(
     ctx: Context<'_, '_, '_, 'info, SetPermissions<'info>>,
     params: &SetPermissionsParams,
 ) -> Result<u8> {
+    // pausing is allowed instantly, anything else is subject to the timelock
+    let perpetuals = ctx.accounts.perpetuals.as_ref();
+    require!(
+        perpetuals.timelock_delays.set_permissions == 0
+            || params.to_permissions().is_pause_of(&perpetuals.permissions),
+        PerpetualsError::TimelockRequired
+    );
+
     // validate signatures
     let mut multisig = ctx.accounts.multisig.load_mut()?;
 
@@ -54,6 +63,7 @@ pub fn set_permissions<'info>(
         &ctx.accounts.admin,
         &Multisig::get_account_infos(&ctx)[1..],
         &Multisig::get_instruction_data(AdminInstruction::SetPermissions, params)?,
+        ctx.accounts.perpetuals.get_time()?,
     )?;
     if signatures_left > 0 {
         msg!(
@@ -65,14 +75,7 @@ pub fn set_permissions<'info>(
 
     // update permissions
     let perpetuals = ctx.accounts.perpetuals.as_mut();
-    perpetuals.permissions.allow_swap = params.allow_swap;
-    perpetuals.permissions.allow_add_liquidity = params.allow_add_liquidity;
-    perpetuals.permissions.allow_remove_liquidity = params.allow_remove_liquidity;
-    perpetuals.permissions.allow_open_position = params.allow_open_position;
-    perpetuals.permissions.allow_close_position = params.allow_close_position;
-    perpetuals.permissions.allow_pnl_withdrawal = params.allow_pnl_withdrawal;
-    perpetuals.permissions.allow_collateral_withdrawal = params.allow_collateral_withdrawal;
-    perpetuals.permissions.allow_size_change = params.allow_size_change;
+    perpetuals.permissions = params.to_permissions();
 
     if !perpetuals.validate() {
         err!(PerpetualsError::InvalidPerpetualsConfig)
@@ -80,3 +83,19 @@ pub fn set_permissions<'info>(
         Ok(0)
     }
 }
+
+impl SetPermissionsParams {
+    pub fn to_permissions(&self) -> Permissions {
+        Permissions {
+            allow_swap: self.allow_swap,
+            allow_add_liquidity: self.allow_add_liquidity,
+            allow_remove_liquidity: self.allow_remove_liquidity,
+            allow_open_position: self.allow_open_position,
+            allow_close_position: self.allow_close_position,
+            allow_pnl_withdrawal: self.allow_pnl_withdrawal,
+            allow_collateral_withdrawal: self.allow_collateral_withdrawal,
+            allow_size_change: self.allow_size_change,
+            allow_liquidation: self.allow_liquidation,
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! SetPoolConfig instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::{Permissions, Perpetuals},
+            pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetPoolConfig<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetPoolConfigParams {
+    pub permissions: Permissions,
+    pub max_aum_usd: u128,
+    pub max_open_interest_usd: u64,
+}
+
+pub fn set_pool_config<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetPoolConfig<'info>>,
+    params: &SetPoolConfigParams,
+) -> Result<u8> {
+    // pausing is allowed instantly, anything else is subject to the timelock
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0
+            || params.is_pause_of(&ctx.accounts.pool),
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetPoolConfig, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // update pool data
+    params.apply(ctx.accounts.pool.as_mut())?;
+
+    Ok(0)
+}
+
+impl SetPoolConfigParams {
+    /// Returns true if the new config only disables permissions and lowers limits
+    pub fn is_pause_of(&self, pool: &Pool) -> bool {
+        let is_limit_of = |new_limit: u128, current_limit: u128| {
+            current_limit == 0 || (new_limit != 0 && new_limit <= current_limit)
+        };
+        self.permissions.is_pause_of(&pool.permissions)
+            && is_limit_of(self.max_aum_usd, pool.max_aum_usd)
+            && is_limit_of(
+                self.max_open_interest_usd as u128,
+                pool.max_open_interest_usd as u128,
+            )
+    }
+
+    pub fn apply(&self, pool: &mut Pool) -> Result<()> {
+        pool.permissions = self.permissions;
+        pool.max_aum_usd = self.max_aum_usd;
+        pool.max_open_interest_usd = self.max_open_interest_usd;
+
+        if !pool.validate() {
+            err!(PerpetualsError::InvalidPoolConfig)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! SetPoolRatios instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::{Pool, RatiosRamp, TokenRatios},
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetPoolRatios<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetPoolRatiosParams {
+    pub ratios: Vec<TokenRatios>,
+}
+
+pub fn set_pool_ratios<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetPoolRatios<'info>>,
+    params: &SetPoolRatiosParams,
+) -> Result<u8> {
+    // validate inputs
+    if params.ratios.len() != ctx.accounts.pool.ratios.len() {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetPoolRatios, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // update pool ratios
+    params.apply(ctx.accounts.pool.as_mut())?;
+
+    Ok(0)
+}
+
+impl SetPoolRatiosParams {
+    pub fn apply(&self, pool: &mut Pool) -> Result<()> {
+        if self.ratios.len() != pool.ratios.len() {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        pool.ratios = self.ratios.clone();
+        pool.ratios_ramp = RatiosRamp::default();
+
+        if !pool.validate() {
+            err!(PerpetualsError::InvalidPoolConfig)
+        } else {
+            Ok(())
+        }
+    }
+}
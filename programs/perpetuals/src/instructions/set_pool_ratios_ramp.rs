@@ -0,0 +1,103 @@
+//! SetPoolRatiosRamp instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetPoolRatiosRamp<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetPoolRatiosRampParams {
+    // target ratios in the same order as pool.custodies
+    pub targets: Vec<u64>,
+    pub ramp_end_time: i64,
+}
+
+pub fn set_pool_ratios_ramp<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetPoolRatiosRamp<'info>>,
+    params: &SetPoolRatiosRampParams,
+) -> Result<u8> {
+    // validate inputs
+    if params.targets.len() != ctx.accounts.pool.ratios.len() {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetPoolRatiosRamp, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // start target ratios ramp
+    let curtime = ctx.accounts.perpetuals.get_time()?;
+    params.apply(ctx.accounts.pool.as_mut(), curtime)?;
+
+    Ok(0)
+}
+
+impl SetPoolRatiosRampParams {
+    pub fn apply(&self, pool: &mut Pool, curtime: i64) -> Result<()> {
+        if self.targets.len() != pool.ratios.len() {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        pool.start_ratios_ramp(curtime, self.ramp_end_time)?;
+        for (ratios, &target) in pool.ratios.iter_mut().zip(self.targets.iter()) {
+            ratios.target = target;
+        }
+
+        if !pool.validate() {
+            err!(PerpetualsError::InvalidPoolConfig)
+        } else {
+            Ok(())
+        }
+    }
+}
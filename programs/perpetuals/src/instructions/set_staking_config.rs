@@ -0,0 +1,113 @@
+//! SetStakingConfig instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::Pool,
+            staking::Staking,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetStakingConfig<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"staking",
+                 pool.key().as_ref()],
+        bump = staking.bump
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+    // remaining accounts:
+    //   pool.tokens.len() custody accounts (write, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetStakingConfigParams {
+    pub fee_share: u64,
+}
+
+pub fn set_staking_config<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetStakingConfig<'info>>,
+    params: &SetStakingConfigParams,
+) -> Result<u8> {
+    // validate inputs
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetStakingConfig, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // update staking data
+    params.apply(
+        ctx.accounts.staking.as_mut(),
+        &ctx.accounts.pool,
+        Multisig::strip_proposal(ctx.remaining_accounts),
+    )?;
+
+    Ok(0)
+}
+
+impl SetStakingConfigParams {
+    pub fn apply(
+        &self,
+        staking: &mut Staking,
+        pool: &Pool,
+        custodies: &[AccountInfo],
+    ) -> Result<()> {
+        // distribute fees collected under the previous config
+        staking.update_pool_rewards(pool, custodies)?;
+
+        staking.fee_share = self.fee_share;
+
+        if !staking.validate() {
+            err!(PerpetualsError::InvalidStakingState)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -1,12 +1,15 @@
 //! SetTestOraclePrice instruction handler
 
 use {
-    crate::state::{
-        custody::Custody,
-        multisig::{AdminInstruction, Multisig},
-        oracle::TestOracle,
-        perpetuals::Perpetuals,
-        pool::Pool,
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::Custody,
+            multisig::{AdminInstruction, Multisig},
+            oracle::TestOracle,
+            perpetuals::Perpetuals,
+            pool::Pool,
+        },
     },
     anchor_lang::prelude::*,
 };
@@ -32,7 +35,8 @@ pub struct SetTestOraclePrice<'info> {
     #[account(
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -40,7 +44,8 @@ pub struct SetTestOraclePrice<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
@@ -78,6 +83,7 @@ pub fn set_test_oracle_price<'info>(
         &ctx.accounts.admin,
         &Multisig::get_account_infos(&ctx)[1..],
         &Multisig::get_instruction_data(AdminInstruction::SetTestOraclePrice, params)?,
+        ctx.accounts.perpetuals.get_time()?,
     )?;
     if signatures_left > 0 {
         msg!(
@@ -89,6 +95,7 @@ pub fn set_test_oracle_price<'info>(
 
     // update oracle data
     let oracle_account = ctx.accounts.oracle_account.as_mut();
+    oracle_account.version = TestOracle::VERSION;
     oracle_account.price = params.price;
     oracle_account.expo = params.expo;
     oracle_account.conf = params.conf;
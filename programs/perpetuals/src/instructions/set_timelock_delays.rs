@@ -0,0 +1,90 @@
+//! SetTimelockDelays instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::{Perpetuals, TimelockDelays},
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetTimelockDelays<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+}
+
+#[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SetTimelockDelaysParams {
+    pub set_custody_config: i64,
+    pub set_permissions: i64,
+    pub set_admin_signers: i64,
+}
+
+pub fn set_timelock_delays<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetTimelockDelays<'info>>,
+    params: &SetTimelockDelaysParams,
+) -> Result<u8> {
+    // extending delays is allowed instantly, reducing any is subject to the timelock
+    let perpetuals = ctx.accounts.perpetuals.as_ref();
+    require!(
+        params
+            .to_delays()
+            .is_extension_of(&perpetuals.timelock_delays),
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetTimelockDelays, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // update timelock delays
+    let perpetuals = ctx.accounts.perpetuals.as_mut();
+    perpetuals.timelock_delays = params.to_delays();
+
+    if !perpetuals.validate() {
+        err!(PerpetualsError::InvalidPerpetualsConfig)
+    } else {
+        Ok(0)
+    }
+}
+
+impl SetTimelockDelaysParams {
+    pub fn to_delays(&self) -> TimelockDelays {
+        TimelockDelays {
+            set_custody_config: self.set_custody_config,
+            set_permissions: self.set_permissions,
+            set_admin_signers: self.set_admin_signers,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! SetWithdrawalQueueConfig instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::Pool,
+            withdrawal_queue::WithdrawalQueue,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct SetWithdrawalQueueConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    // instruction can be called multiple times due to multisig use, hence init_if_needed
+    // instead of init. Account is filled out on the first call with all signatures collected.
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = WithdrawalQueue::LEN,
+        seeds = [b"withdrawal_queue",
+                 pool.key().as_ref()],
+        bump
+    )]
+    pub withdrawal_queue: Box<Account<'info, WithdrawalQueue>>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        token::mint = lp_token_mint,
+        token::authority = transfer_authority,
+        seeds = [b"withdrawal_escrow_account",
+                 pool.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"lp_token_mint",
+                 pool.key().as_ref()],
+        bump = pool.lp_token_bump
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    rent: Sysvar<'info, Rent>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetWithdrawalQueueConfigParams {
+    pub enabled: bool,
+    pub cooldown_period: i64,
+    pub epoch_duration: i64,
+    pub max_epoch_withdrawal: u64,
+}
+
+pub fn set_withdrawal_queue_config<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetWithdrawalQueueConfig<'info>>,
+    params: &SetWithdrawalQueueConfigParams,
+) -> Result<u8> {
+    // enabling the queue is allowed instantly, anything else is subject to the timelock
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0
+            || params.is_pause_of(&ctx.accounts.withdrawal_queue),
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetWithdrawalQueueConfig, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // record withdrawal queue data
+    let withdrawal_queue = ctx.accounts.withdrawal_queue.as_mut();
+    if withdrawal_queue.pool == Pubkey::default() {
+        withdrawal_queue.version = WithdrawalQueue::VERSION;
+        withdrawal_queue.pool = ctx.accounts.pool.key();
+        withdrawal_queue.escrow_token_account = ctx.accounts.escrow_token_account.key();
+        withdrawal_queue.bump = *ctx
+            .bumps
+            .get("withdrawal_queue")
+            .ok_or(ProgramError::InvalidSeeds)?;
+        withdrawal_queue.escrow_token_account_bump = *ctx
+            .bumps
+            .get("escrow_token_account")
+            .ok_or(ProgramError::InvalidSeeds)?;
+    }
+
+    params.apply(withdrawal_queue, ctx.accounts.pool.as_mut())?;
+
+    Ok(0)
+}
+
+impl SetWithdrawalQueueConfigParams {
+    /// Returns true if the queue is enabled without changing its parameters,
+    /// a new queue can be set up with any parameters as it only gates withdrawals
+    pub fn is_pause_of(&self, withdrawal_queue: &WithdrawalQueue) -> bool {
+        self.enabled
+            && (withdrawal_queue.pool == Pubkey::default()
+                || (self.cooldown_period == withdrawal_queue.cooldown_period
+                    && self.epoch_duration == withdrawal_queue.epoch_duration
+                    && self.max_epoch_withdrawal == withdrawal_queue.max_epoch_withdrawal))
+    }
+
+    pub fn apply(&self, withdrawal_queue: &mut WithdrawalQueue, pool: &mut Pool) -> Result<()> {
+        if withdrawal_queue.epoch_duration != self.epoch_duration {
+            // restart epochs on the next request
+            withdrawal_queue.epoch_start_time = 0;
+            withdrawal_queue.epoch_aum_usd = 0;
+            withdrawal_queue.epoch_lp_supply = 0;
+            withdrawal_queue.epoch_withdrawn_usd = 0;
+        }
+        withdrawal_queue.cooldown_period = self.cooldown_period;
+        withdrawal_queue.epoch_duration = self.epoch_duration;
+        withdrawal_queue.max_epoch_withdrawal = self.max_epoch_withdrawal;
+
+        if !withdrawal_queue.validate() {
+            return err!(PerpetualsError::InvalidWithdrawalState);
+        }
+
+        pool.withdrawal_queue_enabled = self.enabled;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,343 @@
+//! SettlePosition instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::{Custody, CustodyStatus},
+            margin_account::MarginAccount,
+            oracle::OraclePrice,
+            perpetuals::Perpetuals,
+            pool::Pool,
+            pool_day_stats::PoolDayStats,
+            position::{Position, Side},
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SettlePosition<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: position owner, receives the rent back
+    #[account(
+        mut,
+        constraint = owner.key() == position.owner
+    )]
+    pub owner: AccountInfo<'info>,
+
+    /// CHECK: token account of the custody mint
+    #[account(
+        mut,
+        constraint = Perpetuals::is_token_account(&receiving_account, &custody.mint, &position.owner)
+    )]
+    pub receiving_account: AccountInfo<'info>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"position",
+                 position.owner.as_ref(),
+                 pool.key().as_ref(),
+                 custody.key().as_ref(),
+                 &[position.side as u8]],
+        bump = position.bump,
+        close = owner,
+        constraint = position.version == Position::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: custody token account, address is validated by the seeds
+    #[account(
+        mut,
+        seeds = [b"custody_token_account",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.token_account_bump
+    )]
+    pub custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
+
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
+    // remaining accounts, only for positions linked to a margin account:
+    //   1 margin account (writable, unsigned)
+    //   1 margin collateral custody (writable, unsigned)
+    //   1 margin collateral custody oracle (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SettlePositionParams {}
+
+pub fn settle_position(ctx: Context<SettlePosition>, _params: &SettlePositionParams) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    require!(
+        ctx.accounts.pool.permissions.allow_close_position,
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    // check custody status
+    msg!("Check custody status");
+    let perpetuals = ctx.accounts.perpetuals.as_mut();
+    let custody = ctx.accounts.custody.as_mut();
+    require!(
+        custody.status == CustodyStatus::Settlement,
+        PerpetualsError::InvalidCustodyState
+    );
+
+    let position_key = ctx.accounts.position.key();
+    let position = ctx.accounts.position.as_mut();
+    let pool = ctx.accounts.pool.as_mut();
+    let token_id = pool.get_token_id(&custody.key())?;
+
+    // position is closed as a regular close at the settlement price
+    let curtime = perpetuals.get_time()?;
+    let settlement_price = custody.get_settlement_price();
+
+    msg!("Settle position");
+    let (transfer_amount, fee_amount, profit_usd, loss_usd) = pool.get_close_amount(
+        position,
+        &settlement_price,
+        &settlement_price,
+        custody,
+        curtime,
+        false,
+    )?;
+
+    let protocol_fee = Pool::get_fee_amount(custody.fees.protocol_share, fee_amount)?;
+
+    msg!("Net profit: {}, loss: {}", profit_usd, loss_usd);
+    msg!("Collected fee: {}", fee_amount);
+    msg!("Amount out: {}", transfer_amount);
+
+    // settle linked position against the margin account, remaining linked
+    // positions are left to account-level liquidation
+    if position.is_cross_margin {
+        msg!("Update margin account");
+        if ctx.remaining_accounts.len() < 3 {
+            return Err(ProgramError::NotEnoughAccountKeys.into());
+        }
+        let mut margin_account = Account::<MarginAccount>::try_from(&ctx.remaining_accounts[0])?;
+        require!(
+            margin_account.owner == position.owner && margin_account.pool == pool.key(),
+            PerpetualsError::InvalidPositionState
+        );
+        let mut collateral_custody = Account::<Custody>::try_from(&ctx.remaining_accounts[1])?;
+        require!(
+            collateral_custody.version == Custody::VERSION,
+            PerpetualsError::InvalidAccountVersion
+        );
+        require_keys_eq!(collateral_custody.key(), margin_account.collateral_custody);
+        require_keys_neq!(collateral_custody.key(), custody.key());
+        require_keys_eq!(
+            ctx.remaining_accounts[2].key(),
+            collateral_custody.oracle.oracle_account
+        );
+
+        let collateral_price = OraclePrice::new_from_oracle(
+            collateral_custody.oracle.oracle_type,
+            &ctx.remaining_accounts[2],
+            collateral_custody.oracle.max_price_error,
+            collateral_custody.oracle.max_price_age_sec,
+            curtime,
+            false,
+        )?;
+
+        let collateral_ema_price = OraclePrice::new_from_oracle(
+            collateral_custody.oracle.oracle_type,
+            &ctx.remaining_accounts[2],
+            collateral_custody.oracle.max_price_error,
+            collateral_custody.oracle.max_price_age_sec,
+            curtime,
+            collateral_custody.pricing.use_ema,
+        )?;
+
+        let min_collateral_price = if collateral_price < collateral_ema_price {
+            collateral_price
+        } else {
+            collateral_ema_price
+        };
+
+        // cover position losses in excess of its own collateral with shared collateral
+        let shortfall_usd = loss_usd.saturating_sub(position.collateral_usd);
+        let shortfall_amount = std::cmp::min(
+            min_collateral_price.get_token_amount(shortfall_usd, collateral_custody.decimals)?,
+            margin_account.collateral_amount,
+        );
+        msg!("Collateral used: {}", shortfall_amount);
+
+        margin_account.remove_position(&position_key)?;
+        margin_account.collateral_amount =
+            math::checked_sub(margin_account.collateral_amount, shortfall_amount)?;
+
+        collateral_custody.assets.collateral =
+            math::checked_sub(collateral_custody.assets.collateral, shortfall_amount)?;
+        collateral_custody.assets.owned =
+            math::checked_add(collateral_custody.assets.owned, shortfall_amount)?;
+
+        let collateral_token_id = pool.get_token_id(&collateral_custody.key())?;
+        pool.update_custody_aum(
+            collateral_token_id,
+            &collateral_custody,
+            &collateral_price,
+            &collateral_ema_price,
+            curtime,
+        )?;
+
+        margin_account.exit(&crate::ID)?;
+        collateral_custody.exit(&crate::ID)?;
+    }
+
+    // unlock pool funds
+    custody.unlock_funds(position.locked_amount)?;
+
+    // check pool constraints
+    msg!("Check pool constraints");
+    require!(
+        pool.check_available_amount(transfer_amount, custody)?,
+        PerpetualsError::CustodyAmountLimit
+    );
+
+    // transfer tokens
+    msg!("Transfer tokens");
+    perpetuals.transfer_tokens(
+        ctx.accounts.custody_token_account.to_account_info(),
+        ctx.accounts.receiving_account.to_account_info(),
+        ctx.accounts.custody_token_mint.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        transfer_amount,
+    )?;
+
+    // update custody stats
+    msg!("Update custody stats");
+    let fee_amount_usd = settlement_price.get_asset_amount_usd(fee_amount, custody.decimals)?;
+    custody.collected_fees.close_position_usd = math::checked_add(
+        custody.collected_fees.close_position_usd,
+        fee_amount_usd as u128,
+    )?;
+
+    custody.collected_token_fees.close_position = math::checked_add(
+        custody.collected_token_fees.close_position,
+        fee_amount as u128,
+    )?;
+
+    custody.volume_stats.close_position_usd = math::checked_add(
+        custody.volume_stats.close_position_usd,
+        position.size_usd as u128,
+    )?;
+
+    let amount_lost = transfer_amount.saturating_sub(position.collateral_amount);
+    custody.assets.owned = math::checked_sub(custody.assets.owned, amount_lost)?;
+    custody.assets.collateral =
+        math::checked_sub(custody.assets.collateral, position.collateral_amount)?;
+    custody.assets.protocol_fees = math::checked_add(custody.assets.protocol_fees, protocol_fee)?;
+
+    if position.side == Side::Long {
+        custody.trade_stats.oi_long_usd =
+            math::checked_sub(custody.trade_stats.oi_long_usd, position.size_usd as u128)?;
+    } else {
+        custody.trade_stats.oi_short_usd =
+            math::checked_sub(custody.trade_stats.oi_short_usd, position.size_usd as u128)?;
+    }
+    pool.remove_open_interest(position.size_usd)?;
+
+    custody.trade_stats.profit_usd =
+        math::checked_add(custody.trade_stats.profit_usd, profit_usd as u128)?;
+    custody.trade_stats.loss_usd =
+        math::checked_add(custody.trade_stats.loss_usd, loss_usd as u128)?;
+
+    custody.remove_position(position, curtime)?;
+    custody.update_borrow_rate(curtime)?;
+
+    // update cached aum contribution, delisted tokens are valued at the
+    // settlement price
+    pool.update_custody_aum(
+        token_id,
+        custody,
+        &settlement_price,
+        &settlement_price,
+        curtime,
+    )?;
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.close_position_usd = math::checked_add(
+        pool_day_stats.volume.close_position_usd,
+        position.size_usd as u128,
+    )?;
+    pool_day_stats.fees.close_position_usd = math::checked_add(
+        pool_day_stats.fees.close_position_usd,
+        fee_amount_usd as u128,
+    )?;
+    pool_day_stats.add_pnl(profit_usd, loss_usd)?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.signer.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Stake instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            perpetuals::Perpetuals,
+            pool::Pool,
+            staking::{StakeAccount, Staking},
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount},
+    solana_program::program_error::ProgramError,
+};
+
+#[derive(Accounts)]
+#[instruction(params: StakeParams)]
+pub struct Stake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = funding_account.mint == lp_token_mint.key(),
+        has_one = owner
+    )]
+    pub funding_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"staking",
+                 pool.key().as_ref()],
+        bump = staking.bump
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = StakeAccount::LEN,
+        seeds = [b"stake_account",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump
+    )]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_token_account",
+                 pool.key().as_ref()],
+        bump = staking.stake_token_account_bump
+    )]
+    pub stake_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"lp_token_mint",
+                 pool.key().as_ref()],
+        bump = pool.lp_token_bump
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    // remaining accounts:
+    //   pool.tokens.len() custody accounts (write, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct StakeParams {
+    pub amount: u64,
+}
+
+pub fn stake<'info>(
+    ctx: Context<'_, '_, '_, 'info, Stake<'info>>,
+    params: &StakeParams,
+) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    require!(
+        ctx.accounts.pool.permissions.allow_add_liquidity,
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    // validate inputs
+    msg!("Validate inputs");
+    if params.amount == 0 {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+
+    let stake_account = ctx.accounts.stake_account.as_mut();
+    if stake_account.owner == Pubkey::default() {
+        stake_account.version = StakeAccount::VERSION;
+        stake_account.owner = ctx.accounts.owner.key();
+        stake_account.pool = ctx.accounts.pool.key();
+        stake_account.amount = 0;
+        stake_account.rewards = Vec::new();
+        stake_account.bump = *ctx
+            .bumps
+            .get("stake_account")
+            .ok_or(ProgramError::InvalidSeeds)?;
+    }
+
+    // update rewards
+    msg!("Update rewards");
+    let staking = ctx.accounts.staking.as_mut();
+    staking.update_pool_rewards(&ctx.accounts.pool, ctx.remaining_accounts)?;
+    stake_account.update_rewards(staking)?;
+
+    // transfer tokens
+    msg!("Transfer tokens");
+    ctx.accounts.perpetuals.transfer_tokens_from_user(
+        ctx.accounts.funding_account.to_account_info(),
+        ctx.accounts.stake_token_account.to_account_info(),
+        ctx.accounts.lp_token_mint.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        params.amount,
+    )?;
+
+    // update stake amounts
+    msg!("Update stake amounts");
+    stake_account.amount = math::checked_add(stake_account.amount, params.amount)?;
+    staking.total_staked = math::checked_add(staking.total_staked, params.amount)?;
+
+    Ok(())
+}
@@ -4,10 +4,12 @@ use {
     crate::{
         error::PerpetualsError,
         math,
-        state::{custody::Custody, oracle::OraclePrice, perpetuals::Perpetuals, pool::Pool},
+        state::{
+            custody::Custody, oracle::OraclePrice, perpetuals::Perpetuals, pool::Pool,
+            pool_day_stats::PoolDayStats,
+        },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
     solana_program::program_error::ProgramError,
 };
 
@@ -17,19 +19,30 @@ pub struct Swap<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    /// CHECK: token account of the receiving custody mint or wallet of the owner for native SOL
     #[account(
         mut,
-        constraint = funding_account.mint == receiving_custody.mint,
-        has_one = owner
+        constraint = Perpetuals::is_token_account(&funding_account, &receiving_custody.mint, owner.key)
+            || Perpetuals::is_native_account(&funding_account, &receiving_custody.mint, owner.key)
     )]
-    pub funding_account: Box<Account<'info, TokenAccount>>,
+    pub funding_account: AccountInfo<'info>,
 
+    /// CHECK: token account of the dispensing custody mint or wallet of the owner for native SOL
     #[account(
         mut,
-        constraint = receiving_account.mint == dispensing_custody.mint,
-        has_one = owner
+        constraint = Perpetuals::is_token_account(&receiving_account, &dispensing_custody.mint, owner.key)
+            || Perpetuals::is_native_account(&receiving_account, &dispensing_custody.mint, owner.key)
     )]
-    pub receiving_account: Box<Account<'info, TokenAccount>>,
+    pub receiving_account: AccountInfo<'info>,
+
+    /// CHECK: temporary wrapped SOL account, only used for native SOL transfers
+    #[account(
+        mut,
+        seeds = [b"wsol_account",
+                 owner.key().as_ref()],
+        bump
+    )]
+    pub wsol_account: AccountInfo<'info>,
 
     /// CHECK: empty PDA, authority for token accounts
     #[account(
@@ -48,16 +61,29 @@ pub struct Swap<'info> {
         mut,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = PoolDayStats::LEN,
+        seeds = [b"pool_day_stats",
+                 pool.key().as_ref(),
+                 &PoolDayStats::get_day(perpetuals.get_time()?).to_le_bytes()],
+        bump
+    )]
+    pub pool_day_stats: Box<Account<'info, PoolDayStats>>,
+
     #[account(
         mut,
         seeds = [b"custody",
                  pool.key().as_ref(),
                  receiving_custody.mint.as_ref()],
-        bump = receiving_custody.bump
+        bump = receiving_custody.bump,
+        constraint = receiving_custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub receiving_custody: Box<Account<'info, Custody>>,
 
@@ -67,6 +93,7 @@ pub struct Swap<'info> {
     )]
     pub receiving_custody_oracle_account: AccountInfo<'info>,
 
+    /// CHECK: receiving custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -74,14 +101,21 @@ pub struct Swap<'info> {
                  receiving_custody.mint.as_ref()],
         bump = receiving_custody.token_account_bump
     )]
-    pub receiving_custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub receiving_custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the receiving custody token
+    #[account(
+        constraint = receiving_custody_token_mint.key() == receiving_custody.mint
+    )]
+    pub receiving_custody_token_mint: AccountInfo<'info>,
 
     #[account(
         mut,
         seeds = [b"custody",
                  pool.key().as_ref(),
                  dispensing_custody.mint.as_ref()],
-        bump = dispensing_custody.bump
+        bump = dispensing_custody.bump,
+        constraint = dispensing_custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub dispensing_custody: Box<Account<'info, Custody>>,
 
@@ -91,6 +125,7 @@ pub struct Swap<'info> {
     )]
     pub dispensing_custody_oracle_account: AccountInfo<'info>,
 
+    /// CHECK: dispensing custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -98,9 +133,27 @@ pub struct Swap<'info> {
                  dispensing_custody.mint.as_ref()],
         bump = dispensing_custody.token_account_bump
     )]
-    pub dispensing_custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub dispensing_custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the dispensing custody token
+    #[account(
+        constraint = dispensing_custody_token_mint.key() == dispensing_custody.mint
+    )]
+    pub dispensing_custody_token_mint: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
 
-    token_program: Program<'info, Token>,
+    /// CHECK: token program of the receiving custody mint
+    #[account(
+        constraint = receiving_custody_token_mint.owner == receiving_token_program.key
+    )]
+    receiving_token_program: AccountInfo<'info>,
+
+    /// CHECK: token program of the dispensing custody mint
+    #[account(
+        constraint = dispensing_custody_token_mint.owner == dispensing_token_program.key
+    )]
+    dispensing_token_program: AccountInfo<'info>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
@@ -117,8 +170,10 @@ pub fn swap(ctx: Context<Swap>, params: &SwapParams) -> Result<()> {
     let dispensing_custody = ctx.accounts.dispensing_custody.as_mut();
     require!(
         perpetuals.permissions.allow_swap
+            && ctx.accounts.pool.permissions.allow_swap
             && receiving_custody.permissions.allow_swap
-            && dispensing_custody.permissions.allow_swap,
+            && dispensing_custody.permissions.allow_swap
+            && !receiving_custody.is_reduce_only(),
         PerpetualsError::InstructionNotAllowed
     );
 
@@ -129,6 +184,45 @@ pub fn swap(ctx: Context<Swap>, params: &SwapParams) -> Result<()> {
     }
     require_keys_neq!(receiving_custody.key(), dispensing_custody.key());
 
+    // transfer tokens in, token transfer fees are taken out of the swapped amount
+    msg!("Transfer tokens");
+    let amount_in = if Perpetuals::is_native_account(
+        &ctx.accounts.funding_account,
+        &receiving_custody.mint,
+        ctx.accounts.owner.key,
+    ) {
+        let owner_key = ctx.accounts.owner.key();
+        let wsol_account_bump = *ctx
+            .bumps
+            .get("wsol_account")
+            .ok_or(ProgramError::InvalidSeeds)?;
+        perpetuals.wrap_sol_from_user(
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.wsol_account.to_account_info(),
+            ctx.accounts
+                .receiving_custody_token_account
+                .to_account_info(),
+            ctx.accounts.receiving_custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.receiving_token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[b"wsol_account", owner_key.as_ref(), &[wsol_account_bump]],
+            params.amount_in,
+        )?
+    } else {
+        perpetuals.transfer_tokens_from_user(
+            ctx.accounts.funding_account.to_account_info(),
+            ctx.accounts
+                .receiving_custody_token_account
+                .to_account_info(),
+            ctx.accounts.receiving_custody_token_mint.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.receiving_token_program.to_account_info(),
+            params.amount_in,
+        )?
+    };
+    msg!("Amount in: {}", amount_in);
+
     // compute token amount returned to the user
     let pool = ctx.accounts.pool.as_mut();
     let curtime = perpetuals.get_time()?;
@@ -187,19 +281,21 @@ pub fn swap(ctx: Context<Swap>, params: &SwapParams) -> Result<()> {
         &dispensed_token_ema_price,
         receiving_custody,
         dispensing_custody,
-        params.amount_in,
+        amount_in,
+        curtime,
     )?;
 
     // calculate fee
     let fees = pool.get_swap_fees(
         token_id_in,
         token_id_out,
-        params.amount_in,
+        amount_in,
         amount_out,
         receiving_custody,
         &received_token_price,
         dispensing_custody,
         &dispensed_token_price,
+        curtime,
     )?;
     msg!("Collected fees: {} {}", fees.0, fees.1);
 
@@ -216,7 +312,7 @@ pub fn swap(ctx: Context<Swap>, params: &SwapParams) -> Result<()> {
     msg!("Check pool constraints");
     let protocol_fee_in = Pool::get_fee_amount(receiving_custody.fees.protocol_share, fees.0)?;
     let protocol_fee_out = Pool::get_fee_amount(dispensing_custody.fees.protocol_share, fees.1)?;
-    let deposit_amount = math::checked_sub(params.amount_in, protocol_fee_in)?;
+    let deposit_amount = math::checked_sub(amount_in, protocol_fee_in)?;
     let withdrawal_amount = math::checked_add(no_fee_amount, protocol_fee_out)?;
 
     require!(
@@ -243,38 +339,66 @@ pub fn swap(ctx: Context<Swap>, params: &SwapParams) -> Result<()> {
         PerpetualsError::CustodyAmountLimit
     );
 
-    // transfer tokens
-    msg!("Transfer tokens");
-    perpetuals.transfer_tokens_from_user(
-        ctx.accounts.funding_account.to_account_info(),
-        ctx.accounts
-            .receiving_custody_token_account
-            .to_account_info(),
-        ctx.accounts.owner.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        params.amount_in,
-    )?;
-
-    perpetuals.transfer_tokens(
-        ctx.accounts
-            .dispensing_custody_token_account
-            .to_account_info(),
-        ctx.accounts.receiving_account.to_account_info(),
-        ctx.accounts.transfer_authority.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        no_fee_amount,
-    )?;
+    // transfer tokens out
+    if Perpetuals::is_native_account(
+        &ctx.accounts.receiving_account,
+        &dispensing_custody.mint,
+        ctx.accounts.owner.key,
+    ) {
+        let owner_key = ctx.accounts.owner.key();
+        let wsol_account_bump = *ctx
+            .bumps
+            .get("wsol_account")
+            .ok_or(ProgramError::InvalidSeeds)?;
+        perpetuals.unwrap_sol_to_user(
+            ctx.accounts
+                .dispensing_custody_token_account
+                .to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.wsol_account.to_account_info(),
+            ctx.accounts.dispensing_custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.dispensing_token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[b"wsol_account", owner_key.as_ref(), &[wsol_account_bump]],
+            no_fee_amount,
+        )?;
+    } else {
+        perpetuals.transfer_tokens(
+            ctx.accounts
+                .dispensing_custody_token_account
+                .to_account_info(),
+            ctx.accounts.receiving_account.to_account_info(),
+            ctx.accounts.dispensing_custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.dispensing_token_program.to_account_info(),
+            no_fee_amount,
+        )?;
+    }
 
     // update custody stats
     msg!("Update custody stats");
-    receiving_custody.volume_stats.swap_usd = receiving_custody.volume_stats.swap_usd.wrapping_add(
-        received_token_price.get_asset_amount_usd(params.amount_in, receiving_custody.decimals)?,
-    );
+    let amount_in_usd =
+        received_token_price.get_asset_amount_usd(amount_in, receiving_custody.decimals)?;
+    let fee_in_usd =
+        dispensed_token_price.get_asset_amount_usd(fees.0, dispensing_custody.decimals)?;
+    let fee_out_usd =
+        dispensed_token_price.get_asset_amount_usd(fees.1, dispensing_custody.decimals)?;
+    let amount_out_usd =
+        dispensed_token_price.get_asset_amount_usd(amount_out, dispensing_custody.decimals)?;
+
+    receiving_custody.volume_stats.swap_usd = math::checked_add(
+        receiving_custody.volume_stats.swap_usd,
+        amount_in_usd as u128,
+    )?;
+
+    receiving_custody.collected_fees.swap_usd = math::checked_add(
+        receiving_custody.collected_fees.swap_usd,
+        fee_in_usd as u128,
+    )?;
 
-    receiving_custody.collected_fees.swap_usd =
-        receiving_custody.collected_fees.swap_usd.wrapping_add(
-            dispensed_token_price.get_asset_amount_usd(fees.0, dispensing_custody.decimals)?,
-        );
+    receiving_custody.collected_token_fees.swap =
+        math::checked_add(receiving_custody.collected_token_fees.swap, fees.0 as u128)?;
 
     receiving_custody.assets.owned =
         math::checked_add(receiving_custody.assets.owned, deposit_amount)?;
@@ -282,15 +406,18 @@ pub fn swap(ctx: Context<Swap>, params: &SwapParams) -> Result<()> {
     receiving_custody.assets.protocol_fees =
         math::checked_add(receiving_custody.assets.protocol_fees, protocol_fee_in)?;
 
-    dispensing_custody.collected_fees.swap_usd =
-        dispensing_custody.collected_fees.swap_usd.wrapping_add(
-            dispensed_token_price.get_asset_amount_usd(fees.1, dispensing_custody.decimals)?,
-        );
+    dispensing_custody.collected_fees.swap_usd = math::checked_add(
+        dispensing_custody.collected_fees.swap_usd,
+        fee_out_usd as u128,
+    )?;
 
-    dispensing_custody.volume_stats.swap_usd =
-        dispensing_custody.volume_stats.swap_usd.wrapping_add(
-            dispensed_token_price.get_asset_amount_usd(amount_out, dispensing_custody.decimals)?,
-        );
+    dispensing_custody.collected_token_fees.swap =
+        math::checked_add(dispensing_custody.collected_token_fees.swap, fees.1 as u128)?;
+
+    dispensing_custody.volume_stats.swap_usd = math::checked_add(
+        dispensing_custody.volume_stats.swap_usd,
+        amount_out_usd as u128,
+    )?;
 
     dispensing_custody.assets.protocol_fees =
         math::checked_add(dispensing_custody.assets.protocol_fees, protocol_fee_out)?;
@@ -301,5 +428,43 @@ pub fn swap(ctx: Context<Swap>, params: &SwapParams) -> Result<()> {
     receiving_custody.update_borrow_rate(curtime)?;
     dispensing_custody.update_borrow_rate(curtime)?;
 
+    // update cached aum contributions
+    pool.update_custody_aum(
+        token_id_in,
+        receiving_custody,
+        &received_token_price,
+        &received_token_ema_price,
+        curtime,
+    )?;
+    pool.update_custody_aum(
+        token_id_out,
+        dispensing_custody,
+        &dispensed_token_price,
+        &dispensed_token_ema_price,
+        curtime,
+    )?;
+
+    // update pool day stats
+    msg!("Update pool day stats");
+    let pool_day_stats = ctx.accounts.pool_day_stats.as_mut();
+    pool_day_stats.volume.swap_usd = math::checked_add(
+        math::checked_add(pool_day_stats.volume.swap_usd, amount_in_usd as u128)?,
+        amount_out_usd as u128,
+    )?;
+    pool_day_stats.fees.swap_usd = math::checked_add(
+        math::checked_add(pool_day_stats.fees.swap_usd, fee_in_usd as u128)?,
+        fee_out_usd as u128,
+    )?;
+
+    ctx.accounts.pool_day_stats.update_pool_state(
+        &ctx.accounts.pool,
+        ctx.accounts.pool.key(),
+        ctx.accounts.owner.key(),
+        curtime,
+        *ctx.bumps
+            .get("pool_day_stats")
+            .ok_or(ProgramError::InvalidSeeds)?,
+    );
+
     Ok(())
 }
@@ -0,0 +1,245 @@
+//! SweepCustody instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::{Assets, Custody, CustodyStatus},
+            fee_distribution::{FeeDistribution, FeeRecipientType},
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+            pool::{AumCalcMode, Pool},
+            staking::Staking,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SweepCustody<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [b"fee_distribution",
+                 pool.key().as_ref()],
+        bump = fee_distribution.bump
+    )]
+    pub fee_distribution: Box<Account<'info, FeeDistribution>>,
+
+    /// CHECK: pool staking account, may be uninitialized, validated in apply
+    #[account(mut)]
+    pub staking: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: custody token account, address is validated by the seeds
+    #[account(
+        mut,
+        seeds = [b"custody_token_account",
+                 pool.key().as_ref(),
+                 custody.mint.as_ref()],
+        bump = custody.token_account_bump
+    )]
+    pub custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
+
+    /// CHECK: token account of the custody mint owned by a pool treasury
+    #[account(
+        mut,
+        constraint = Perpetuals::get_token_account(&receiving_token_account)?.mint == custody.mint
+    )]
+    pub receiving_token_account: AccountInfo<'info>,
+
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SweepCustodyParams {}
+
+pub fn sweep_custody<'info>(
+    ctx: Context<'_, '_, '_, 'info, SweepCustody<'info>>,
+    params: &SweepCustodyParams,
+) -> Result<u8> {
+    // validate inputs
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
+
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SweepCustody, params)?,
+        ctx.accounts.perpetuals.get_time()?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // transfer remaining tokens to the treasury
+    let custody_key = ctx.accounts.custody.key();
+    params.apply(
+        &ctx.accounts.perpetuals,
+        ctx.accounts.pool.as_mut(),
+        &ctx.accounts.fee_distribution,
+        ctx.accounts.staking.to_account_info(),
+        &custody_key,
+        ctx.accounts.custody.as_mut(),
+        ctx.accounts.custody_token_account.to_account_info(),
+        ctx.accounts.receiving_token_account.to_account_info(),
+        ctx.accounts.custody_token_mint.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    Ok(0)
+}
+
+impl SweepCustodyParams {
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply<'info>(
+        &self,
+        perpetuals: &Perpetuals,
+        pool: &mut Pool,
+        fee_distribution: &FeeDistribution,
+        staking: AccountInfo<'info>,
+        custody_key: &Pubkey,
+        custody: &mut Custody,
+        custody_token_account: AccountInfo<'info>,
+        receiving_token_account: AccountInfo<'info>,
+        custody_token_mint: AccountInfo<'info>,
+        transfer_authority: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+    ) -> Result<()> {
+        // all positions must be settled before the sweep
+        require!(
+            custody.status == CustodyStatus::Settlement
+                && custody.long_positions.open_positions == 0
+                && custody.short_positions.open_positions == 0,
+            PerpetualsError::InvalidCustodyState
+        );
+
+        // tokens can only go to a treasury of the pool
+        require_keys_eq!(fee_distribution.pool, custody.pool);
+        let receiving_owner = Perpetuals::get_token_account(&receiving_token_account)?.owner;
+        if !fee_distribution.recipients.iter().any(|recipient| {
+            recipient.recipient_type == FeeRecipientType::Treasury
+                && recipient.owner == receiving_owner
+        }) {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+
+        // tokens set aside for stakers stay in the custody until claimed,
+        // their share of fees collected since the last update is settled first
+        let staking_key =
+            Pubkey::find_program_address(&[b"staking", custody.pool.as_ref()], &crate::ID).0;
+        require_keys_eq!(staking.key(), staking_key);
+        let reserved_amount = if staking.owner == &crate::ID {
+            let mut staking = Account::<Staking>::try_from(&staking)?;
+            staking.update_rewards(custody_key, custody)?;
+            let reserved_amount = staking
+                .get_reward_index(custody_key)
+                .map_or(0, |idx| staking.rewards[idx].reserved_amount);
+            staking.exit(&crate::ID)?;
+            reserved_amount
+        } else {
+            0
+        };
+
+        // the pool no longer accounts for swept tokens,
+        // so the custody can be removed afterwards
+        let amount = math::checked_sub(
+            Perpetuals::get_token_balance(&custody_token_account)?,
+            reserved_amount,
+        )?;
+        msg!("Sweep tokens: {}", amount);
+
+        if amount > 0 {
+            perpetuals.transfer_tokens(
+                custody_token_account,
+                receiving_token_account,
+                custody_token_mint,
+                transfer_authority,
+                token_program,
+                amount,
+            )?;
+        }
+
+        custody.assets = Assets::default();
+        custody.status = CustodyStatus::Delisted;
+
+        // drop the custody contribution from the cached aum, pool aum is only
+        // updated if all cached contributions are fresh, as in refresh_aum
+        let curtime = perpetuals.get_time()?;
+        let token_id = pool.get_token_id(custody_key)?;
+        let settlement_price = custody.get_settlement_price();
+        pool.update_custody_aum(
+            token_id,
+            custody,
+            &settlement_price,
+            &settlement_price,
+            curtime,
+        )?;
+        if let Ok(aum_usd) = pool.get_cached_assets_under_management_usd(AumCalcMode::EMA, curtime)
+        {
+            pool.aum_usd = aum_usd;
+        }
+
+        Ok(())
+    }
+}
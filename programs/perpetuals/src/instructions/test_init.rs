@@ -59,6 +59,7 @@ pub struct TestInitParams {
     pub allow_pnl_withdrawal: bool,
     pub allow_collateral_withdrawal: bool,
     pub allow_size_change: bool,
+    pub allow_liquidation: bool,
 }
 
 pub fn test_init(ctx: Context<TestInit>, params: &TestInitParams) -> Result<()> {
@@ -79,6 +80,7 @@ pub fn test_init(ctx: Context<TestInit>, params: &TestInitParams) -> Result<()>
 
     // record perpetuals
     let perpetuals = ctx.accounts.perpetuals.as_mut();
+    perpetuals.version = Perpetuals::VERSION;
     perpetuals.permissions.allow_swap = params.allow_swap;
     perpetuals.permissions.allow_add_liquidity = params.allow_add_liquidity;
     perpetuals.permissions.allow_remove_liquidity = params.allow_remove_liquidity;
@@ -87,6 +89,7 @@ pub fn test_init(ctx: Context<TestInit>, params: &TestInitParams) -> Result<()>
     perpetuals.permissions.allow_pnl_withdrawal = params.allow_pnl_withdrawal;
     perpetuals.permissions.allow_collateral_withdrawal = params.allow_collateral_withdrawal;
     perpetuals.permissions.allow_size_change = params.allow_size_change;
+    perpetuals.permissions.allow_liquidation = params.allow_liquidation;
     perpetuals.transfer_authority_bump = *ctx
         .bumps
         .get("transfer_authority")
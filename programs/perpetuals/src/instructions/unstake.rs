@@ -0,0 +1,136 @@
+//! Unstake instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            perpetuals::Perpetuals,
+            pool::Pool,
+            staking::{StakeAccount, Staking},
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount},
+    solana_program::program_error::ProgramError,
+};
+
+#[derive(Accounts)]
+#[instruction(params: UnstakeParams)]
+pub struct Unstake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = receiving_account.mint == lp_token_mint.key(),
+        has_one = owner
+    )]
+    pub receiving_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        seeds = [b"staking",
+                 pool.key().as_ref()],
+        bump = staking.bump
+    )]
+    pub staking: Box<Account<'info, Staking>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"stake_account",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump = stake_account.bump
+    )]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_token_account",
+                 pool.key().as_ref()],
+        bump = staking.stake_token_account_bump
+    )]
+    pub stake_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"lp_token_mint",
+                 pool.key().as_ref()],
+        bump = pool.lp_token_bump
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+
+    token_program: Program<'info, Token>,
+    // remaining accounts:
+    //   pool.tokens.len() custody accounts (write, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UnstakeParams {
+    pub amount: u64,
+}
+
+pub fn unstake<'info>(
+    ctx: Context<'_, '_, '_, 'info, Unstake<'info>>,
+    params: &UnstakeParams,
+) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    require!(
+        ctx.accounts.pool.permissions.allow_remove_liquidity,
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    // validate inputs
+    msg!("Validate inputs");
+    let stake_account = ctx.accounts.stake_account.as_mut();
+    if params.amount == 0 || params.amount > stake_account.amount {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+
+    // update rewards
+    msg!("Update rewards");
+    let staking = ctx.accounts.staking.as_mut();
+    staking.update_pool_rewards(&ctx.accounts.pool, ctx.remaining_accounts)?;
+    stake_account.update_rewards(staking)?;
+
+    // transfer tokens
+    msg!("Transfer tokens");
+    ctx.accounts.perpetuals.transfer_tokens(
+        ctx.accounts.stake_token_account.to_account_info(),
+        ctx.accounts.receiving_account.to_account_info(),
+        ctx.accounts.lp_token_mint.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        params.amount,
+    )?;
+
+    // update stake amounts
+    msg!("Update stake amounts");
+    stake_account.amount = math::checked_sub(stake_account.amount, params.amount)?;
+    staking.total_staked = math::checked_sub(staking.total_staked, params.amount)?;
+
+    Ok(())
+}
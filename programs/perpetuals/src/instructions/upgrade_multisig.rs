@@ -0,0 +1,78 @@
+//! UpgradeMultisig instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            migration,
+            multisig::{DeprecatedMultisig, Multisig},
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct UpgradeMultisig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump
+    )]
+    /// CHECK: Deprecated multisig account
+    pub multisig: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpgradeMultisigParams {}
+
+pub fn upgrade_multisig<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpgradeMultisig<'info>>,
+    _params: &UpgradeMultisigParams,
+) -> Result<u8> {
+    // load deprecated multisig data
+    msg!("Load deprecated multisig");
+    let multisig_account = &ctx.accounts.multisig;
+    if multisig_account.owner != &crate::ID {
+        return Err(ProgramError::IllegalOwner.into());
+    }
+    if multisig_account.try_data_len()? != DeprecatedMultisig::LEN {
+        return Err(ProgramError::InvalidAccountData.into());
+    }
+    let deprecated_multisig = Account::<DeprecatedMultisig>::try_from_unchecked(multisig_account)?;
+
+    // proposals can't be created against the deprecated layout, so a single
+    // admin signature is accepted. The upgrade can't change who controls the
+    // multisig: signers and threshold are carried over as is (checked below)
+    // and pending approvals are dropped, see migration::migrate_multisig_v0.
+    if !deprecated_multisig.signers[..deprecated_multisig.num_signers as usize]
+        .contains(ctx.accounts.admin.key)
+    {
+        return err!(PerpetualsError::MultisigAccountNotAuthorized);
+    }
+
+    msg!("Migrate multisig");
+    migration::migrate_account(
+        ctx.accounts.admin.to_account_info(),
+        ctx.accounts.multisig.clone(),
+        ctx.accounts.system_program.to_account_info(),
+        &[],
+    )?;
+
+    // check that the signer set is byte-for-byte identical
+    let multisig_loader = AccountLoader::<Multisig>::try_from(&ctx.accounts.multisig)?;
+    let multisig = multisig_loader.load()?;
+    if multisig.num_signers != deprecated_multisig.num_signers
+        || multisig.min_signatures != deprecated_multisig.min_signatures
+        || { multisig.signers } != deprecated_multisig.signers
+        || multisig.bump != deprecated_multisig.bump
+    {
+        return Err(ProgramError::InvalidAccountData.into());
+    }
+
+    Ok(0)
+}
@@ -2,6 +2,7 @@
 
 use {
     crate::{
+        error::PerpetualsError,
         math,
         state::{
             custody::Custody,
@@ -11,7 +12,6 @@ use {
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
 };
 
 #[derive(Accounts)]
@@ -43,7 +43,8 @@ pub struct WithdrawFees<'info> {
         mut,
         seeds = [b"pool",
                  pool.name.as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub pool: Box<Account<'info, Pool>>,
 
@@ -52,10 +53,12 @@ pub struct WithdrawFees<'info> {
         seeds = [b"custody",
                  pool.key().as_ref(),
                  custody.mint.key().as_ref()],
-        bump = custody.bump
+        bump = custody.bump,
+        constraint = custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
     )]
     pub custody: Box<Account<'info, Custody>>,
 
+    /// CHECK: custody token account, address is validated by the seeds
     #[account(
         mut,
         seeds = [b"custody_token_account",
@@ -63,18 +66,29 @@ pub struct WithdrawFees<'info> {
                  custody.mint.as_ref()],
         bump = custody.token_account_bump
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: AccountInfo<'info>,
 
+    /// CHECK: mint of the custody token
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: AccountInfo<'info>,
+
+    /// CHECK: token account of the custody mint
     #[account(
         mut,
-        constraint = receiving_token_account.mint == custody_token_account.mint
+        constraint = Perpetuals::get_token_account(&receiving_token_account)?.mint == custody.mint
     )]
-    pub receiving_token_account: Box<Account<'info, TokenAccount>>,
+    pub receiving_token_account: AccountInfo<'info>,
 
-    token_program: Program<'info, Token>,
+    /// CHECK: token program of the custody mint
+    #[account(
+        constraint = custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct WithdrawFeesParams {
     pub amount: u64,
 }
@@ -87,6 +101,10 @@ pub fn withdraw_fees<'info>(
     if params.amount == 0 {
         return Err(ProgramError::InvalidArgument.into());
     }
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
 
     // validate signatures
     let mut multisig = ctx.accounts.multisig.load_mut()?;
@@ -95,6 +113,7 @@ pub fn withdraw_fees<'info>(
         &ctx.accounts.admin,
         &Multisig::get_account_infos(&ctx)[1..],
         &Multisig::get_instruction_data(AdminInstruction::WithdrawFees, params)?,
+        ctx.accounts.perpetuals.get_time()?,
     )?;
     if signatures_left > 0 {
         msg!(
@@ -105,26 +124,50 @@ pub fn withdraw_fees<'info>(
     }
 
     // transfer token fees from the custody to the receiver
-    let custody = ctx.accounts.custody.as_mut();
-
-    msg!(
-        "Withdraw token fees: {} / {}",
-        params.amount,
-        custody.assets.protocol_fees
-    );
-
-    if custody.assets.protocol_fees < params.amount {
-        return Err(ProgramError::InsufficientFunds.into());
-    }
-    custody.assets.protocol_fees = math::checked_sub(custody.assets.protocol_fees, params.amount)?;
-
-    ctx.accounts.perpetuals.transfer_tokens(
+    params.apply(
+        &ctx.accounts.perpetuals,
+        ctx.accounts.custody.as_mut(),
         ctx.accounts.custody_token_account.to_account_info(),
         ctx.accounts.receiving_token_account.to_account_info(),
+        ctx.accounts.custody_token_mint.to_account_info(),
         ctx.accounts.transfer_authority.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
-        params.amount,
     )?;
 
     Ok(0)
 }
+
+impl WithdrawFeesParams {
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply<'info>(
+        &self,
+        perpetuals: &Perpetuals,
+        custody: &mut Custody,
+        custody_token_account: AccountInfo<'info>,
+        receiving_token_account: AccountInfo<'info>,
+        custody_token_mint: AccountInfo<'info>,
+        transfer_authority: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+    ) -> Result<()> {
+        msg!(
+            "Withdraw token fees: {} / {}",
+            self.amount,
+            custody.assets.protocol_fees
+        );
+
+        if custody.assets.protocol_fees < self.amount {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+        custody.assets.protocol_fees =
+            math::checked_sub(custody.assets.protocol_fees, self.amount)?;
+
+        perpetuals.transfer_tokens(
+            custody_token_account,
+            receiving_token_account,
+            custody_token_mint,
+            transfer_authority,
+            token_program,
+            self.amount,
+        )
+    }
+}
@@ -0,0 +1,197 @@
+//! WithdrawMargin instruction handler
+
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{
+            custody::Custody, margin_account::MarginAccount, oracle::OraclePrice,
+            perpetuals::Perpetuals, pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+    solana_program::program_error::ProgramError,
+};
+
+#[derive(Accounts)]
+pub struct WithdrawMargin<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: token account of the collateral custody mint
+    #[account(
+        mut,
+        constraint = Perpetuals::is_token_account(&receiving_account, &collateral_custody.mint, owner.key)
+    )]
+    pub receiving_account: AccountInfo<'info>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = perpetuals.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"perpetuals"],
+        bump = perpetuals.perpetuals_bump
+    )]
+    pub perpetuals: Box<Account<'info, Perpetuals>>,
+
+    #[account(
+        seeds = [b"pool",
+                 pool.name.as_bytes()],
+        bump = pool.bump,
+        constraint = pool.version == Pool::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = collateral_custody,
+        seeds = [b"margin_account",
+                 owner.key().as_ref(),
+                 pool.key().as_ref()],
+        bump = margin_account.bump
+    )]
+    pub margin_account: Box<Account<'info, MarginAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 pool.key().as_ref(),
+                 collateral_custody.mint.as_ref()],
+        bump = collateral_custody.bump,
+        constraint = collateral_custody.version == Custody::VERSION @ PerpetualsError::InvalidAccountVersion
+    )]
+    pub collateral_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the collateral token
+    #[account(
+        constraint = collateral_custody_oracle_account.key() == collateral_custody.oracle.oracle_account
+    )]
+    pub collateral_custody_oracle_account: AccountInfo<'info>,
+
+    /// CHECK: collateral custody token account, address is validated by the seeds
+    #[account(
+        mut,
+        seeds = [b"custody_token_account",
+                 pool.key().as_ref(),
+                 collateral_custody.mint.as_ref()],
+        bump = collateral_custody.token_account_bump
+    )]
+    pub collateral_custody_token_account: AccountInfo<'info>,
+
+    /// CHECK: mint of the collateral custody token
+    #[account(
+        constraint = collateral_custody_token_mint.key() == collateral_custody.mint
+    )]
+    pub collateral_custody_token_mint: AccountInfo<'info>,
+
+    /// CHECK: token program of the collateral custody mint
+    #[account(
+        constraint = collateral_custody_token_mint.owner == token_program.key
+    )]
+    token_program: AccountInfo<'info>,
+    // remaining accounts:
+    //   margin_account.positions.len() position accounts (read-only, unsigned)
+    //   margin_account.positions.len() custody accounts (read-only, unsigned)
+    //   margin_account.positions.len() custody oracles (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct WithdrawMarginParams {
+    pub amount: u64,
+}
+
+pub fn withdraw_margin(ctx: Context<WithdrawMargin>, params: &WithdrawMarginParams) -> Result<()> {
+    // check permissions
+    msg!("Check permissions");
+    let perpetuals = ctx.accounts.perpetuals.as_mut();
+    let collateral_custody = ctx.accounts.collateral_custody.as_mut();
+    require!(
+        perpetuals.permissions.allow_collateral_withdrawal
+            && ctx.accounts.pool.permissions.allow_collateral_withdrawal
+            && collateral_custody.permissions.allow_collateral_withdrawal,
+        PerpetualsError::InstructionNotAllowed
+    );
+
+    // validate inputs
+    msg!("Validate inputs");
+    let margin_account = ctx.accounts.margin_account.as_mut();
+    if params.amount == 0 {
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    if params.amount > margin_account.collateral_amount {
+        return Err(ProgramError::InsufficientFunds.into());
+    }
+
+    // update margin account
+    msg!("Update margin account");
+    margin_account.collateral_amount =
+        math::checked_sub(margin_account.collateral_amount, params.amount)?;
+
+    // check margin account health
+    msg!("Check margin account health");
+    let curtime = perpetuals.get_time()?;
+
+    let token_price = OraclePrice::new_from_oracle(
+        collateral_custody.oracle.oracle_type,
+        &ctx.accounts
+            .collateral_custody_oracle_account
+            .to_account_info(),
+        collateral_custody.oracle.max_price_error,
+        collateral_custody.oracle.max_price_age_sec,
+        curtime,
+        false,
+    )?;
+
+    let token_ema_price = OraclePrice::new_from_oracle(
+        collateral_custody.oracle.oracle_type,
+        &ctx.accounts
+            .collateral_custody_oracle_account
+            .to_account_info(),
+        collateral_custody.oracle.max_price_error,
+        collateral_custody.oracle.max_price_age_sec,
+        curtime,
+        collateral_custody.pricing.use_ema,
+    )?;
+
+    let min_price = if token_price < token_ema_price {
+        token_price
+    } else {
+        token_ema_price
+    };
+
+    require!(
+        margin_account.check_health(
+            &ctx.accounts.pool,
+            collateral_custody,
+            &min_price,
+            ctx.remaining_accounts,
+            curtime
+        )?,
+        PerpetualsError::MaxLeverage
+    );
+
+    // transfer tokens
+    msg!("Transfer tokens");
+    perpetuals.transfer_tokens(
+        ctx.accounts
+            .collateral_custody_token_account
+            .to_account_info(),
+        ctx.accounts.receiving_account.to_account_info(),
+        ctx.accounts.collateral_custody_token_mint.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        params.amount,
+    )?;
+
+    // update custody stats
+    msg!("Update custody stats");
+    collateral_custody.assets.collateral =
+        math::checked_sub(collateral_custody.assets.collateral, params.amount)?;
+
+    Ok(())
+}
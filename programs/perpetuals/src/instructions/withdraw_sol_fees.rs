@@ -2,6 +2,7 @@
 
 use {
     crate::{
+        error::PerpetualsError,
         math,
         state::{
             multisig::{AdminInstruction, Multisig},
@@ -45,7 +46,7 @@ pub struct WithdrawSolFees<'info> {
     pub receiving_account: AccountInfo<'info>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct WithdrawSolFeesParams {
     pub amount: u64,
 }
@@ -58,6 +59,10 @@ pub fn withdraw_sol_fees<'info>(
     if params.amount == 0 {
         return Err(ProgramError::InvalidArgument.into());
     }
+    require!(
+        ctx.accounts.perpetuals.timelock_delays.set_custody_config == 0,
+        PerpetualsError::TimelockRequired
+    );
 
     // validate signatures
     let mut multisig = ctx.accounts.multisig.load_mut()?;
@@ -66,6 +71,7 @@ pub fn withdraw_sol_fees<'info>(
         &ctx.accounts.admin,
         &Multisig::get_account_infos(&ctx)[1..],
         &Multisig::get_instruction_data(AdminInstruction::WithdrawSolFees, params)?,
+        ctx.accounts.perpetuals.get_time()?,
     )?;
     if signatures_left > 0 {
         msg!(
@@ -76,29 +82,34 @@ pub fn withdraw_sol_fees<'info>(
     }
 
     // transfer sol fees from the custody to the receiver
-    let balance = ctx.accounts.transfer_authority.try_lamports()?;
-    let min_balance = sysvar::rent::Rent::get().unwrap().minimum_balance(0);
-    let available_balance = if balance > min_balance {
-        math::checked_sub(balance, min_balance)?
-    } else {
-        0
-    };
-
-    msg!(
-        "Withdraw SOL fees: {} / {}",
-        params.amount,
-        available_balance
-    );
-
-    if available_balance < params.amount {
-        return Err(ProgramError::InsufficientFunds.into());
-    }
-
-    Perpetuals::transfer_sol_from_owned(
+    params.apply(
         ctx.accounts.transfer_authority.to_account_info(),
         ctx.accounts.receiving_account.to_account_info(),
-        params.amount,
     )?;
 
     Ok(0)
 }
+
+impl WithdrawSolFeesParams {
+    pub fn apply<'info>(
+        &self,
+        transfer_authority: AccountInfo<'info>,
+        receiving_account: AccountInfo<'info>,
+    ) -> Result<()> {
+        let balance = transfer_authority.try_lamports()?;
+        let min_balance = sysvar::rent::Rent::get().unwrap().minimum_balance(0);
+        let available_balance = if balance > min_balance {
+            math::checked_sub(balance, min_balance)?
+        } else {
+            0
+        };
+
+        msg!("Withdraw SOL fees: {} / {}", self.amount, available_balance);
+
+        if available_balance < self.amount {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        Perpetuals::transfer_sol_from_owned(transfer_authority, receiving_account, self.amount)
+    }
+}
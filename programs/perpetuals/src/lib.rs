@@ -11,7 +11,8 @@ use {
     anchor_lang::prelude::*,
     instructions::*,
     state::perpetuals::{
-        AmountAndFee, NewPositionPricesAndFee, PriceAndFee, ProfitAndLoss, SwapAmountAndFees,
+        AmountAndFee, NewPositionPricesAndFee, PoolStats, PositionHealth, PriceAndFee,
+        ProfitAndLoss, SwapAmountAndFees,
     },
 };
 
@@ -105,6 +106,160 @@ pub mod perpetuals {
         instructions::upgrade_custody(ctx, &params)
     }
 
+    pub fn upgrade_multisig<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpgradeMultisig<'info>>,
+        params: UpgradeMultisigParams,
+    ) -> Result<u8> {
+        instructions::upgrade_multisig(ctx, &params)
+    }
+
+    pub fn init_staking<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitStaking<'info>>,
+        params: InitStakingParams,
+    ) -> Result<u8> {
+        instructions::init_staking(ctx, &params)
+    }
+
+    pub fn set_staking_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetStakingConfig<'info>>,
+        params: SetStakingConfigParams,
+    ) -> Result<u8> {
+        instructions::set_staking_config(ctx, &params)
+    }
+
+    pub fn set_withdrawal_queue_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetWithdrawalQueueConfig<'info>>,
+        params: SetWithdrawalQueueConfigParams,
+    ) -> Result<u8> {
+        instructions::set_withdrawal_queue_config(ctx, &params)
+    }
+
+    pub fn set_fee_distribution<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetFeeDistribution<'info>>,
+        params: SetFeeDistributionParams,
+    ) -> Result<u8> {
+        instructions::set_fee_distribution(ctx, &params)
+    }
+
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        params: CreateProposalParams,
+    ) -> Result<()> {
+        instructions::create_proposal(ctx, &params)
+    }
+
+    pub fn approve_proposal(
+        ctx: Context<ApproveProposal>,
+        params: ApproveProposalParams,
+    ) -> Result<()> {
+        instructions::approve_proposal(ctx, &params)
+    }
+
+    pub fn cancel_proposal(
+        ctx: Context<CancelProposal>,
+        params: CancelProposalParams,
+    ) -> Result<()> {
+        instructions::cancel_proposal(ctx, &params)
+    }
+
+    pub fn revoke_approval(
+        ctx: Context<RevokeApproval>,
+        params: RevokeApprovalParams,
+    ) -> Result<()> {
+        instructions::revoke_approval(ctx, &params)
+    }
+
+    pub fn set_timelock_delays<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetTimelockDelays<'info>>,
+        params: SetTimelockDelaysParams,
+    ) -> Result<u8> {
+        instructions::set_timelock_delays(ctx, &params)
+    }
+
+    pub fn queue_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, QueueInstruction<'info>>,
+        params: QueueInstructionParams,
+    ) -> Result<u8> {
+        instructions::queue_instruction(ctx, &params)
+    }
+
+    pub fn set_guardians<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetGuardians<'info>>,
+        params: SetGuardiansParams,
+    ) -> Result<u8> {
+        instructions::set_guardians(ctx, &params)
+    }
+
+    pub fn set_pool_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetPoolConfig<'info>>,
+        params: SetPoolConfigParams,
+    ) -> Result<u8> {
+        instructions::set_pool_config(ctx, &params)
+    }
+
+    pub fn set_custody_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetCustodyFees<'info>>,
+        params: SetCustodyFeesParams,
+    ) -> Result<u8> {
+        instructions::set_custody_fees(ctx, &params)
+    }
+
+    pub fn set_custody_pricing<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetCustodyPricing<'info>>,
+        params: SetCustodyPricingParams,
+    ) -> Result<u8> {
+        instructions::set_custody_pricing(ctx, &params)
+    }
+
+    pub fn set_custody_oracle<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetCustodyOracle<'info>>,
+        params: SetCustodyOracleParams,
+    ) -> Result<u8> {
+        instructions::set_custody_oracle(ctx, &params)
+    }
+
+    pub fn set_custody_borrow_rate<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetCustodyBorrowRate<'info>>,
+        params: SetCustodyBorrowRateParams,
+    ) -> Result<u8> {
+        instructions::set_custody_borrow_rate(ctx, &params)
+    }
+
+    pub fn set_pool_ratios<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetPoolRatios<'info>>,
+        params: SetPoolRatiosParams,
+    ) -> Result<u8> {
+        instructions::set_pool_ratios(ctx, &params)
+    }
+
+    pub fn set_custody_ramp<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetCustodyRamp<'info>>,
+        params: SetCustodyRampParams,
+    ) -> Result<u8> {
+        instructions::set_custody_ramp(ctx, &params)
+    }
+
+    pub fn set_pool_ratios_ramp<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetPoolRatiosRamp<'info>>,
+        params: SetPoolRatiosRampParams,
+    ) -> Result<u8> {
+        instructions::set_pool_ratios_ramp(ctx, &params)
+    }
+
+    pub fn set_custody_status<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetCustodyStatus<'info>>,
+        params: SetCustodyStatusParams,
+    ) -> Result<u8> {
+        instructions::set_custody_status(ctx, &params)
+    }
+
+    pub fn sweep_custody<'info>(
+        ctx: Context<'_, '_, '_, 'info, SweepCustody<'info>>,
+        params: SweepCustodyParams,
+    ) -> Result<u8> {
+        instructions::sweep_custody(ctx, &params)
+    }
+
     // test instructions
 
     pub fn test_init(ctx: Context<TestInit>, params: TestInitParams) -> Result<()> {
@@ -135,6 +290,13 @@ pub mod perpetuals {
         instructions::add_liquidity(ctx, &params)
     }
 
+    pub fn add_liquidity_basket<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddLiquidityBasket<'info>>,
+        params: AddLiquidityBasketParams,
+    ) -> Result<()> {
+        instructions::add_liquidity_basket(ctx, &params)
+    }
+
     pub fn remove_liquidity(
         ctx: Context<RemoveLiquidity>,
         params: RemoveLiquidityParams,
@@ -142,6 +304,13 @@ pub mod perpetuals {
         instructions::remove_liquidity(ctx, &params)
     }
 
+    pub fn remove_liquidity_proportional<'info>(
+        ctx: Context<'_, '_, '_, 'info, RemoveLiquidityProportional<'info>>,
+        params: RemoveLiquidityProportionalParams,
+    ) -> Result<()> {
+        instructions::remove_liquidity_proportional(ctx, &params)
+    }
+
     pub fn open_position(ctx: Context<OpenPosition>, params: OpenPositionParams) -> Result<()> {
         instructions::open_position(ctx, &params)
     }
@@ -165,6 +334,140 @@ pub mod perpetuals {
         instructions::liquidate(ctx, &params)
     }
 
+    pub fn settle_position(
+        ctx: Context<SettlePosition>,
+        params: SettlePositionParams,
+    ) -> Result<()> {
+        instructions::settle_position(ctx, &params)
+    }
+
+    pub fn close_pool_day_stats(
+        ctx: Context<ClosePoolDayStats>,
+        params: ClosePoolDayStatsParams,
+    ) -> Result<()> {
+        instructions::close_pool_day_stats(ctx, &params)
+    }
+
+    pub fn init_margin_account(
+        ctx: Context<InitMarginAccount>,
+        params: InitMarginAccountParams,
+    ) -> Result<()> {
+        instructions::init_margin_account(ctx, &params)
+    }
+
+    pub fn deposit_margin(ctx: Context<DepositMargin>, params: DepositMarginParams) -> Result<()> {
+        instructions::deposit_margin(ctx, &params)
+    }
+
+    pub fn withdraw_margin(
+        ctx: Context<WithdrawMargin>,
+        params: WithdrawMarginParams,
+    ) -> Result<()> {
+        instructions::withdraw_margin(ctx, &params)
+    }
+
+    pub fn add_margin_position(
+        ctx: Context<AddMarginPosition>,
+        params: AddMarginPositionParams,
+    ) -> Result<()> {
+        instructions::add_margin_position(ctx, &params)
+    }
+
+    pub fn remove_margin_position(
+        ctx: Context<RemoveMarginPosition>,
+        params: RemoveMarginPositionParams,
+    ) -> Result<()> {
+        instructions::remove_margin_position(ctx, &params)
+    }
+
+    pub fn liquidate_margin_account(
+        ctx: Context<LiquidateMarginAccount>,
+        params: LiquidateMarginAccountParams,
+    ) -> Result<()> {
+        instructions::liquidate_margin_account(ctx, &params)
+    }
+
+    pub fn stake<'info>(
+        ctx: Context<'_, '_, '_, 'info, Stake<'info>>,
+        params: StakeParams,
+    ) -> Result<()> {
+        instructions::stake(ctx, &params)
+    }
+
+    pub fn unstake<'info>(
+        ctx: Context<'_, '_, '_, 'info, Unstake<'info>>,
+        params: UnstakeParams,
+    ) -> Result<()> {
+        instructions::unstake(ctx, &params)
+    }
+
+    pub fn claim_stake_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimStakeRewards<'info>>,
+        params: ClaimStakeRewardsParams,
+    ) -> Result<()> {
+        instructions::claim_stake_rewards(ctx, &params)
+    }
+
+    pub fn request_withdrawal(
+        ctx: Context<RequestWithdrawal>,
+        params: RequestWithdrawalParams,
+    ) -> Result<()> {
+        instructions::request_withdrawal(ctx, &params)
+    }
+
+    pub fn execute_withdrawal(
+        ctx: Context<ExecuteWithdrawal>,
+        params: ExecuteWithdrawalParams,
+    ) -> Result<()> {
+        instructions::execute_withdrawal(ctx, &params)
+    }
+
+    pub fn cancel_withdrawal(
+        ctx: Context<CancelWithdrawal>,
+        params: CancelWithdrawalParams,
+    ) -> Result<()> {
+        instructions::cancel_withdrawal(ctx, &params)
+    }
+
+    pub fn distribute_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributeFees<'info>>,
+        params: DistributeFeesParams,
+    ) -> Result<()> {
+        instructions::distribute_fees(ctx, &params)
+    }
+
+    pub fn execute_queued<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteQueued<'info>>,
+        params: ExecuteQueuedParams,
+    ) -> Result<()> {
+        instructions::execute_queued(ctx, &params)
+    }
+
+    pub fn cancel_queued<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelQueued<'info>>,
+        params: CancelQueuedParams,
+    ) -> Result<u8> {
+        instructions::cancel_queued(ctx, &params)
+    }
+
+    pub fn guardian_pause<'info>(
+        ctx: Context<'_, '_, '_, 'info, GuardianPause<'info>>,
+        params: GuardianPauseParams,
+    ) -> Result<()> {
+        instructions::guardian_pause(ctx, &params)
+    }
+
+    pub fn refresh_aum(ctx: Context<RefreshAum>, params: RefreshAumParams) -> Result<()> {
+        instructions::refresh_aum(ctx, &params)
+    }
+
+    pub fn migrate_account<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateAccount<'info>>,
+        params: MigrateAccountParams,
+    ) -> Result<()> {
+        instructions::migrate_account(ctx, &params)
+    }
+
     pub fn get_add_liquidity_amount_and_fee(
         ctx: Context<GetAddLiquidityAmountAndFee>,
         params: GetAddLiquidityAmountAndFeeParams,
@@ -211,6 +514,13 @@ pub mod perpetuals {
         instructions::get_liquidation_state(ctx, &params)
     }
 
+    pub fn get_position_health(
+        ctx: Context<GetPositionHealth>,
+        params: GetPositionHealthParams,
+    ) -> Result<PositionHealth> {
+        instructions::get_position_health(ctx, &params)
+    }
+
     pub fn get_oracle_price(
         ctx: Context<GetOraclePrice>,
         params: GetOraclePriceParams,
@@ -231,4 +541,11 @@ pub mod perpetuals {
     ) -> Result<u128> {
         instructions::get_assets_under_management(ctx, &params)
     }
+
+    pub fn get_pool_stats(
+        ctx: Context<GetPoolStats>,
+        params: GetPoolStatsParams,
+    ) -> Result<PoolStats> {
+        instructions::get_pool_stats(ctx, &params)
+    }
 }
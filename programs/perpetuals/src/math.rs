@@ -2,7 +2,159 @@
 
 #![allow(dead_code)]
 
-use {crate::error::PerpetualsError, anchor_lang::prelude::*, std::fmt::Display};
+use {
+    crate::error::PerpetualsError,
+    anchor_lang::prelude::*,
+    std::{cmp::Ordering, fmt::Display},
+};
+
+/// Rounding direction of fixed-point operations
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// Non-negative fixed-point decimal number equal to value * 10^-scale.
+/// Multiplication is exact, rounding only happens when the result is
+/// brought to a lower scale or divided.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Decimal {
+    pub value: u128,
+    pub scale: u8,
+}
+
+impl Decimal {
+    pub fn new(value: u128, scale: u8) -> Self {
+        Self { value, scale }
+    }
+
+    pub fn one() -> Self {
+        Self::new(1, 0)
+    }
+
+    /// Converts coefficient * 10^exponent, e.g. an oracle price
+    pub fn from_exponent(coefficient: u64, exponent: i32) -> Result<Self> {
+        if exponent > 0 {
+            Ok(Self::new(
+                checked_mul(coefficient as u128, checked_pow(10u128, exponent as usize)?)?,
+                0,
+            ))
+        } else {
+            Ok(Self::new(coefficient as u128, checked_as_u8(-exponent)?))
+        }
+    }
+
+    /// Returns the same number with the given scale
+    pub fn rescale(&self, scale: u8, rounding: Rounding) -> Result<Self> {
+        let value = match scale.cmp(&self.scale) {
+            Ordering::Equal => self.value,
+            Ordering::Greater => checked_mul(
+                self.value,
+                checked_pow(10u128, (scale - self.scale) as usize)?,
+            )?,
+            Ordering::Less => checked_rounding_div(
+                self.value,
+                checked_pow(10u128, (self.scale - scale) as usize)?,
+                rounding,
+            )?,
+        };
+        Ok(Self::new(value, scale))
+    }
+
+    /// Returns the number as an integer with implied scale decimals
+    pub fn to_u64(self, scale: u8, rounding: Rounding) -> Result<u64> {
+        checked_as_u64(self.rescale(scale, rounding)?.value)
+    }
+
+    /// Returns coefficient of the number at the given exponent
+    pub fn to_exponent(self, exponent: i32, rounding: Rounding) -> Result<u64> {
+        if exponent > 0 {
+            checked_as_u64(checked_rounding_div(
+                self.value,
+                checked_pow(10u128, self.scale as usize + exponent as usize)?,
+                rounding,
+            )?)
+        } else {
+            self.to_u64(checked_as_u8(-exponent)?, rounding)
+        }
+    }
+
+    pub fn checked_add(&self, other: &Decimal) -> Result<Self> {
+        let scale = std::cmp::max(self.scale, other.scale);
+        Ok(Self::new(
+            checked_add(
+                self.rescale(scale, Rounding::Down)?.value,
+                other.rescale(scale, Rounding::Down)?.value,
+            )?,
+            scale,
+        ))
+    }
+
+    pub fn checked_sub(&self, other: &Decimal) -> Result<Self> {
+        let scale = std::cmp::max(self.scale, other.scale);
+        Ok(Self::new(
+            checked_sub(
+                self.rescale(scale, Rounding::Down)?.value,
+                other.rescale(scale, Rounding::Down)?.value,
+            )?,
+            scale,
+        ))
+    }
+
+    /// Returns exact product, its scale is the sum of operand scales
+    pub fn checked_mul(&self, other: &Decimal) -> Result<Self> {
+        Ok(Self::new(
+            checked_mul(self.value, other.value)?,
+            checked_add(self.scale, other.scale)?,
+        ))
+    }
+
+    /// Returns quotient with the given scale
+    pub fn checked_div(&self, other: &Decimal, scale: u8, rounding: Rounding) -> Result<Self> {
+        if other.value == 0 {
+            msg!("Error: Overflow in {} / {}", self.value, other.value);
+            return err!(PerpetualsError::MathOverflow);
+        }
+        // quotient = self.value * 10^power / other.value
+        let power = scale as i32 + other.scale as i32 - self.scale as i32;
+        let value = if power >= 0 {
+            checked_rounding_div(
+                checked_mul(self.value, checked_pow(10u128, power as usize)?)?,
+                other.value,
+                rounding,
+            )?
+        } else {
+            checked_rounding_div(
+                self.value,
+                checked_mul(other.value, checked_pow(10u128, (-power) as usize)?)?,
+                rounding,
+            )?
+        };
+        Ok(Self::new(value, scale))
+    }
+
+    /// Raises the number to an integer power, negative powers are computed
+    /// with the given scale
+    pub fn checked_powi(&self, exp: i32, scale: u8, rounding: Rounding) -> Result<Self> {
+        let res = Self::new(
+            checked_pow(self.value, exp.unsigned_abs() as usize)?,
+            checked_as_u8(checked_mul(self.scale as u32, exp.unsigned_abs())?)?,
+        );
+        if exp >= 0 {
+            res.rescale(scale, rounding)
+        } else {
+            Self::one().checked_div(&res, scale, rounding)
+        }
+    }
+}
+
+fn checked_rounding_div(arg1: u128, arg2: u128, rounding: Rounding) -> Result<u128> {
+    match rounding {
+        Rounding::Down => checked_div(arg1, arg2),
+        Rounding::Up => checked_ceil_div(arg1, arg2),
+    }
+}
 
 pub fn checked_add<T>(arg1: T, arg2: T) -> Result<T>
 where
@@ -40,23 +192,6 @@ where
     }
 }
 
-pub fn checked_float_div<T>(arg1: T, arg2: T) -> Result<T>
-where
-    T: num_traits::Float + Display,
-{
-    if arg2 == T::zero() {
-        msg!("Error: Overflow in {} / {}", arg1, arg2);
-        return err!(PerpetualsError::MathOverflow);
-    }
-    let res = arg1 / arg2;
-    if !res.is_finite() {
-        msg!("Error: Overflow in {} / {}", arg1, arg2);
-        err!(PerpetualsError::MathOverflow)
-    } else {
-        Ok(res)
-    }
-}
-
 pub fn checked_ceil_div<T>(arg1: T, arg2: T) -> Result<T>
 where
     T: num_traits::PrimInt + Display,
@@ -210,19 +345,6 @@ where
     }
 }
 
-pub fn checked_float_mul<T>(arg1: T, arg2: T) -> Result<T>
-where
-    T: num_traits::Float + Display,
-{
-    let res = arg1 * arg2;
-    if !res.is_finite() {
-        msg!("Error: Overflow in {} * {}", arg1, arg2);
-        err!(PerpetualsError::MathOverflow)
-    } else {
-        Ok(res)
-    }
-}
-
 pub fn checked_decimal_mul(
     coefficient1: u64,
     exponent1: i32,
@@ -302,40 +424,28 @@ where
     }
 }
 
-pub fn checked_powf(arg: f64, exp: f64) -> Result<f64> {
-    let res = f64::powf(arg, exp);
-    if res.is_finite() {
-        Ok(res)
-    } else {
-        msg!("Error: Overflow in {} ^ {}", arg, exp);
-        err!(PerpetualsError::MathOverflow)
-    }
-}
-
-pub fn checked_powi(arg: f64, exp: i32) -> Result<f64> {
-    let res = if exp > 0 {
-        f64::powi(arg, exp)
-    } else {
-        // wrokaround due to f64::powi() not working properly on-chain with negative exponent
-        checked_float_div(1.0, f64::powi(arg, -exp))?
-    };
-    if res.is_finite() {
+pub fn checked_as_u64<T>(arg: T) -> Result<u64>
+where
+    T: Display + num_traits::ToPrimitive + Clone,
+{
+    let option: Option<u64> = num_traits::NumCast::from(arg.clone());
+    if let Some(res) = option {
         Ok(res)
     } else {
-        msg!("Error: Overflow in {} ^ {}", arg, exp);
+        msg!("Error: Overflow in {} as u64", arg);
         err!(PerpetualsError::MathOverflow)
     }
 }
 
-pub fn checked_as_u64<T>(arg: T) -> Result<u64>
+pub fn checked_as_u8<T>(arg: T) -> Result<u8>
 where
     T: Display + num_traits::ToPrimitive + Clone,
 {
-    let option: Option<u64> = num_traits::NumCast::from(arg.clone());
+    let option: Option<u8> = num_traits::NumCast::from(arg.clone());
     if let Some(res) = option {
         Ok(res)
     } else {
-        msg!("Error: Overflow in {} as u64", arg);
+        msg!("Error: Overflow in {} as u8", arg);
         err!(PerpetualsError::MathOverflow)
     }
 }
@@ -365,13 +475,245 @@ pub fn scale_to_exponent(arg: u64, exponent: i32, target_exponent: i32) -> Resul
     }
 }
 
-pub fn to_ui_amount(amount: u64, decimals: u8) -> Result<f64> {
-    checked_float_div(amount as f64, checked_powi(10.0, decimals as i32)?)
+pub fn to_ui_amount(amount: u64, decimals: u8) -> Decimal {
+    Decimal::new(amount as u128, decimals)
+}
+
+pub fn to_token_amount(ui_amount: &Decimal, decimals: u8) -> Result<u64> {
+    ui_amount.to_u64(decimals, Rounding::Down)
+}
+
+/// Returns the value linearly interpolated between start_value at start_time
+/// and end_value at end_time
+pub fn interpolate(
+    start_value: u64,
+    end_value: u64,
+    start_time: i64,
+    end_time: i64,
+    curtime: i64,
+) -> u64 {
+    if curtime >= end_time || start_value == end_value {
+        return end_value;
+    }
+    if curtime <= start_time {
+        return start_value;
+    }
+    let elapsed = curtime.abs_diff(start_time) as u128;
+    let duration = end_time.abs_diff(start_time) as u128;
+    if start_value < end_value {
+        start_value + ((end_value - start_value) as u128 * elapsed / duration) as u64
+    } else {
+        start_value - ((start_value - end_value) as u128 * elapsed / duration) as u64
+    }
 }
 
-pub fn to_token_amount(ui_amount: f64, decimals: u8) -> Result<u64> {
-    checked_as_u64(checked_float_mul(
-        ui_amount,
-        checked_powi(10.0, decimals as i32)?,
-    )?)
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const COEFFICIENTS: [u64; 6] = [1, 7, 999, 123_456_789, 2_512_345_678, u32::MAX as u64];
+    const EXPONENTS: [i32; 5] = [-9, -8, -6, -4, 0];
+
+    fn decimal_mul(
+        coefficient1: u64,
+        exponent1: i32,
+        coefficient2: u64,
+        exponent2: i32,
+        target_exponent: i32,
+        rounding: Rounding,
+    ) -> Result<u64> {
+        Decimal::from_exponent(coefficient1, exponent1)?
+            .checked_mul(&Decimal::from_exponent(coefficient2, exponent2)?)?
+            .to_exponent(target_exponent, rounding)
+    }
+
+    fn decimal_div(
+        coefficient1: u64,
+        exponent1: i32,
+        coefficient2: u64,
+        exponent2: i32,
+        target_exponent: i32,
+        rounding: Rounding,
+    ) -> Result<u64> {
+        Decimal::from_exponent(coefficient1, exponent1)?
+            .checked_div(
+                &Decimal::from_exponent(coefficient2, exponent2)?,
+                checked_as_u8(-target_exponent)?,
+                rounding,
+            )?
+            .to_exponent(target_exponent, rounding)
+    }
+
+    #[test]
+    fn test_from_exponent() {
+        let d = Decimal::from_exponent(12300, -3).unwrap();
+        assert_eq!((12300, 3), (d.value, d.scale));
+
+        let d = Decimal::from_exponent(7, 3).unwrap();
+        assert_eq!((7000, 0), (d.value, d.scale));
+        assert_eq!(7, d.to_exponent(3, Rounding::Down).unwrap());
+    }
+
+    #[test]
+    fn test_rescale() {
+        let d = Decimal::new(123_456, 3);
+        assert_eq!(1_234, d.rescale(1, Rounding::Down).unwrap().value);
+        assert_eq!(1_235, d.rescale(1, Rounding::Up).unwrap().value);
+        assert_eq!(12_345_600, d.rescale(5, Rounding::Down).unwrap().value);
+        assert_eq!(12, d.to_exponent(1, Rounding::Down).unwrap());
+        assert_eq!(13, d.to_exponent(1, Rounding::Up).unwrap());
+        assert_eq!(12_345, d.to_exponent(-2, Rounding::Down).unwrap());
+        assert_eq!(12_346, d.to_exponent(-2, Rounding::Up).unwrap());
+        assert!(Decimal::new(u64::MAX as u128 + 1, 0)
+            .to_u64(0, Rounding::Down)
+            .is_err());
+    }
+
+    #[test]
+    fn test_add_sub() {
+        let a = Decimal::new(15, 1);
+        let b = Decimal::new(25, 2);
+        let d = a.checked_add(&b).unwrap();
+        assert_eq!((175, 2), (d.value, d.scale));
+        let d = a.checked_sub(&b).unwrap();
+        assert_eq!((125, 2), (d.value, d.scale));
+        assert!(b.checked_sub(&a).is_err());
+    }
+
+    #[test]
+    fn test_powi() {
+        let d = Decimal::new(15, 1);
+        assert_eq!(3_375, d.checked_powi(3, 3, Rounding::Down).unwrap().value);
+        assert_eq!(3, d.checked_powi(3, 0, Rounding::Down).unwrap().value);
+        assert_eq!(4, d.checked_powi(3, 0, Rounding::Up).unwrap().value);
+        assert_eq!(
+            444_444,
+            d.checked_powi(-2, 6, Rounding::Down).unwrap().value
+        );
+        assert_eq!(444_445, d.checked_powi(-2, 6, Rounding::Up).unwrap().value);
+        assert_eq!(1, d.checked_powi(0, 0, Rounding::Down).unwrap().value);
+        assert!(Decimal::new(0, 0)
+            .checked_powi(-1, 6, Rounding::Down)
+            .is_err());
+    }
+
+    #[test]
+    fn test_ui_amount() {
+        let ui_amount = to_ui_amount(1_234_567, 6);
+        assert_eq!((1_234_567, 6), (ui_amount.value, ui_amount.scale));
+        assert_eq!(1_234, to_token_amount(&ui_amount, 3).unwrap());
+        assert_eq!(1_234_567_000, to_token_amount(&ui_amount, 9).unwrap());
+    }
+
+    #[test]
+    fn test_mul_matches_checked_decimal_mul() {
+        for coefficient1 in COEFFICIENTS {
+            for exponent1 in EXPONENTS {
+                for coefficient2 in COEFFICIENTS {
+                    for exponent2 in EXPONENTS {
+                        for target_exponent in EXPONENTS {
+                            if let Ok(expected) = checked_decimal_mul(
+                                coefficient1,
+                                exponent1,
+                                coefficient2,
+                                exponent2,
+                                target_exponent,
+                            ) {
+                                assert_eq!(
+                                    expected,
+                                    decimal_mul(
+                                        coefficient1,
+                                        exponent1,
+                                        coefficient2,
+                                        exponent2,
+                                        target_exponent,
+                                        Rounding::Down
+                                    )
+                                    .unwrap()
+                                );
+                            }
+                            if let Ok(expected) = checked_decimal_ceil_mul(
+                                coefficient1,
+                                exponent1,
+                                coefficient2,
+                                exponent2,
+                                target_exponent,
+                            ) {
+                                assert_eq!(
+                                    expected,
+                                    decimal_mul(
+                                        coefficient1,
+                                        exponent1,
+                                        coefficient2,
+                                        exponent2,
+                                        target_exponent,
+                                        Rounding::Up
+                                    )
+                                    .unwrap()
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_div_matches_checked_decimal_div() {
+        for coefficient1 in COEFFICIENTS {
+            for exponent1 in EXPONENTS {
+                for coefficient2 in COEFFICIENTS {
+                    for exponent2 in EXPONENTS {
+                        for target_exponent in EXPONENTS {
+                            if let Ok(expected) = checked_decimal_div(
+                                coefficient1,
+                                exponent1,
+                                coefficient2,
+                                exponent2,
+                                target_exponent,
+                            ) {
+                                assert_eq!(
+                                    expected,
+                                    decimal_div(
+                                        coefficient1,
+                                        exponent1,
+                                        coefficient2,
+                                        exponent2,
+                                        target_exponent,
+                                        Rounding::Down
+                                    )
+                                    .unwrap()
+                                );
+                            }
+                            // checked_decimal_ceil_div rounds down after the ceil
+                            // division, so Up is only checked against Down
+                            if let Ok(down) = decimal_div(
+                                coefficient1,
+                                exponent1,
+                                coefficient2,
+                                exponent2,
+                                target_exponent,
+                                Rounding::Down,
+                            ) {
+                                let up = decimal_div(
+                                    coefficient1,
+                                    exponent1,
+                                    coefficient2,
+                                    exponent2,
+                                    target_exponent,
+                                    Rounding::Up,
+                                )
+                                .unwrap();
+                                assert!(up == down || up == down + 1);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        assert!(Decimal::new(1, 0)
+            .checked_div(&Decimal::new(0, 0), 6, Rounding::Down)
+            .is_err());
+    }
 }
@@ -1,8 +1,15 @@
 // Program state handling.
 
 pub mod custody;
+pub mod fee_distribution;
+pub mod margin_account;
+pub mod migration;
 pub mod multisig;
 pub mod oracle;
 pub mod perpetuals;
 pub mod pool;
+pub mod pool_day_stats;
 pub mod position;
+pub mod staking;
+pub mod timelock;
+pub mod withdrawal_queue;
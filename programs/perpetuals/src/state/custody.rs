@@ -4,7 +4,7 @@ use {
         math,
         state::{
             oracle::{OraclePrice, OracleType},
-            perpetuals::{Permissions, Perpetuals},
+            perpetuals::{DeprecatedPermissions, Permissions, Perpetuals},
             position::{Position, Side},
         },
     },
@@ -17,6 +17,18 @@ pub enum FeesMode {
     Linear,
 }
 
+/// Delisting lifecycle of the custody
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Debug)]
+pub enum CustodyStatus {
+    Active,
+    // new positions and deposits are blocked, exits are allowed
+    ReduceOnly,
+    // remaining positions can be closed by anyone at the settlement price
+    Settlement,
+    // remaining tokens have been swept, custody can be removed
+    Delisted,
+}
+
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub struct Fees {
     pub mode: FeesMode,
@@ -37,6 +49,47 @@ pub struct Fees {
 
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub struct FeesStats {
+    pub swap_usd: u128,
+    pub add_liquidity_usd: u128,
+    pub remove_liquidity_usd: u128,
+    pub open_position_usd: u128,
+    pub close_position_usd: u128,
+    pub liquidation_usd: u128,
+}
+
+/// Collected fees in custody tokens
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct TokenFeesStats {
+    pub swap: u128,
+    pub add_liquidity: u128,
+    pub remove_liquidity: u128,
+    pub open_position: u128,
+    pub close_position: u128,
+    pub liquidation: u128,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct VolumeStats {
+    pub swap_usd: u128,
+    pub add_liquidity_usd: u128,
+    pub remove_liquidity_usd: u128,
+    pub open_position_usd: u128,
+    pub close_position_usd: u128,
+    pub liquidation_usd: u128,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct TradeStats {
+    pub profit_usd: u128,
+    pub loss_usd: u128,
+    // open interest
+    pub oi_long_usd: u128,
+    pub oi_short_usd: u128,
+}
+
+/// Stats layout before the counters were widened
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct DeprecatedFeesStats {
     pub swap_usd: u64,
     pub add_liquidity_usd: u64,
     pub remove_liquidity_usd: u64,
@@ -46,7 +99,7 @@ pub struct FeesStats {
 }
 
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
-pub struct VolumeStats {
+pub struct DeprecatedVolumeStats {
     pub swap_usd: u64,
     pub add_liquidity_usd: u64,
     pub remove_liquidity_usd: u64,
@@ -56,10 +109,9 @@ pub struct VolumeStats {
 }
 
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
-pub struct TradeStats {
+pub struct DeprecatedTradeStats {
     pub profit_usd: u64,
     pub loss_usd: u64,
-    // open interest
     pub oi_long_usd: u64,
     pub oi_short_usd: u64,
 }
@@ -104,6 +156,15 @@ pub struct PricingParams {
     pub max_total_locked_usd: u64,
 }
 
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct LeverageTier {
+    // tier applies to positions with size_usd >= min_size_usd (implied USD_DECIMALS decimals)
+    pub min_size_usd: u64,
+    // leverage limits have implied BPS_DECIMALS decimals
+    pub max_initial_leverage: u64,
+    pub max_leverage: u64,
+}
+
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub struct BorrowRateParams {
     // borrow rate params have implied RATE_DECIMALS decimals
@@ -113,6 +174,21 @@ pub struct BorrowRateParams {
     pub optimal_utilization: u64,
 }
 
+/// Linear transition of risk-sensitive params towards the values set in the
+/// custody config, which hold the ramp targets. Values here are the ones in
+/// effect at start_time.
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct CustodyRamp {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub max_leverage: u64,
+    pub trade_spread_long: u64,
+    pub trade_spread_short: u64,
+    pub swap_spread: u64,
+    pub slope1: u64,
+    pub slope2: u64,
+}
+
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub struct BorrowRateState {
     // borrow rates have implied RATE_DECIMALS decimals
@@ -136,6 +212,7 @@ pub struct PositionStats {
 #[account]
 #[derive(Default, Debug)]
 pub struct Custody {
+    pub version: u8,
     // static parameters
     pub pool: Pubkey,
     pub mint: Pubkey,
@@ -147,10 +224,14 @@ pub struct Custody {
     pub permissions: Permissions,
     pub fees: Fees,
     pub borrow_rate: BorrowRateParams,
+    // size brackets with decreasing leverage limits, unused entries are zeroed
+    pub leverage_tiers: [LeverageTier; 8], // Custody::MAX_LEVERAGE_TIERS
+    pub ramp: CustodyRamp,
 
     // dynamic variables
     pub assets: Assets,
     pub collected_fees: FeesStats,
+    pub collected_token_fees: TokenFeesStats,
     pub volume_stats: VolumeStats,
     pub trade_stats: TradeStats,
     pub long_positions: PositionStats,
@@ -160,6 +241,41 @@ pub struct Custody {
     // bumps for address validation
     pub bump: u8,
     pub token_account_bump: u8,
+
+    // delisting state, appended so that version 1 data remains a valid prefix
+    pub status: CustodyStatus,
+    // price used to settle remaining positions, has implied PRICE_DECIMALS decimals
+    pub settlement_price: u64,
+}
+
+/// Custody layout before account versioning was introduced (version 0)
+#[account]
+#[derive(Default, Debug)]
+pub struct CustodyV0 {
+    // static parameters
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub decimals: u8,
+    pub is_stable: bool,
+    pub oracle: OracleParams,
+    pub pricing: PricingParams,
+    pub permissions: DeprecatedPermissions,
+    pub fees: Fees,
+    pub borrow_rate: BorrowRateParams,
+
+    // dynamic variables
+    pub assets: Assets,
+    pub collected_fees: DeprecatedFeesStats,
+    pub volume_stats: DeprecatedVolumeStats,
+    pub trade_stats: DeprecatedTradeStats,
+    pub long_positions: PositionStats,
+    pub short_positions: PositionStats,
+    pub borrow_rate_state: BorrowRateState,
+
+    // bumps for address validation
+    pub bump: u8,
+    pub token_account_bump: u8,
 }
 
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
@@ -177,6 +293,7 @@ pub struct DeprecatedPricingParams {
     pub max_payoff_mult: u64,
 }
 
+/// Custody layout that predates version 0, upgraded with upgrade_custody
 #[account]
 #[derive(Default, Debug)]
 pub struct DeprecatedCustody {
@@ -188,15 +305,15 @@ pub struct DeprecatedCustody {
     pub is_stable: bool,
     pub oracle: OracleParams,
     pub pricing: DeprecatedPricingParams,
-    pub permissions: Permissions,
+    pub permissions: DeprecatedPermissions,
     pub fees: Fees,
     pub borrow_rate: BorrowRateParams,
 
     // dynamic variables
     pub assets: Assets,
-    pub collected_fees: FeesStats,
-    pub volume_stats: VolumeStats,
-    pub trade_stats: TradeStats,
+    pub collected_fees: DeprecatedFeesStats,
+    pub volume_stats: DeprecatedVolumeStats,
+    pub trade_stats: DeprecatedTradeStats,
     pub long_positions: PositionStats,
     pub short_positions: PositionStats,
     pub borrow_rate_state: BorrowRateState,
@@ -212,6 +329,12 @@ impl Default for FeesMode {
     }
 }
 
+impl Default for CustodyStatus {
+    fn default() -> Self {
+        Self::Active
+    }
+}
+
 impl Fees {
     pub fn validate(&self) -> bool {
         self.swap_in as u128 <= Perpetuals::BPS_POWER
@@ -246,6 +369,43 @@ impl PricingParams {
     }
 }
 
+impl DeprecatedFeesStats {
+    pub fn to_fees_stats(&self) -> FeesStats {
+        FeesStats {
+            swap_usd: self.swap_usd as u128,
+            add_liquidity_usd: self.add_liquidity_usd as u128,
+            remove_liquidity_usd: self.remove_liquidity_usd as u128,
+            open_position_usd: self.open_position_usd as u128,
+            close_position_usd: self.close_position_usd as u128,
+            liquidation_usd: self.liquidation_usd as u128,
+        }
+    }
+}
+
+impl DeprecatedVolumeStats {
+    pub fn to_volume_stats(&self) -> VolumeStats {
+        VolumeStats {
+            swap_usd: self.swap_usd as u128,
+            add_liquidity_usd: self.add_liquidity_usd as u128,
+            remove_liquidity_usd: self.remove_liquidity_usd as u128,
+            open_position_usd: self.open_position_usd as u128,
+            close_position_usd: self.close_position_usd as u128,
+            liquidation_usd: self.liquidation_usd as u128,
+        }
+    }
+}
+
+impl DeprecatedTradeStats {
+    pub fn to_trade_stats(&self) -> TradeStats {
+        TradeStats {
+            profit_usd: self.profit_usd as u128,
+            loss_usd: self.loss_usd as u128,
+            oi_long_usd: self.oi_long_usd as u128,
+            oi_short_usd: self.oi_short_usd as u128,
+        }
+    }
+}
+
 impl BorrowRateParams {
     pub fn validate(&self) -> bool {
         self.optimal_utilization > 0 && (self.optimal_utilization as u128) <= Perpetuals::RATE_POWER
@@ -253,7 +413,9 @@ impl BorrowRateParams {
 }
 
 impl Custody {
+    pub const VERSION: u8 = 4;
     pub const LEN: usize = 8 + std::mem::size_of::<Custody>();
+    pub const MAX_LEVERAGE_TIERS: usize = 8;
 
     pub fn validate(&self) -> bool {
         self.token_account != Pubkey::default()
@@ -262,6 +424,134 @@ impl Custody {
             && self.pricing.validate()
             && self.fees.validate()
             && self.borrow_rate.validate()
+            && self.validate_leverage_tiers()
+            && self.ramp.start_time <= self.ramp.end_time
+            && (self.status != CustodyStatus::Settlement || self.settlement_price > 0)
+    }
+
+    /// Returns true if new positions and deposits are not accepted
+    pub fn is_reduce_only(&self) -> bool {
+        self.status != CustodyStatus::Active
+    }
+
+    pub fn get_settlement_price(&self) -> OraclePrice {
+        OraclePrice::new(self.settlement_price, -(Perpetuals::PRICE_DECIMALS as i32))
+    }
+
+    fn validate_leverage_tiers(&self) -> bool {
+        // active tiers must come first, with increasing sizes and non-increasing leverage
+        let mut prev_tier = LeverageTier {
+            min_size_usd: 0,
+            max_initial_leverage: self.pricing.max_initial_leverage,
+            max_leverage: self.pricing.max_leverage,
+        };
+        let mut active = true;
+        for tier in self.leverage_tiers.iter() {
+            if tier.min_size_usd == 0 {
+                if *tier != LeverageTier::default() {
+                    return false;
+                }
+                active = false;
+                continue;
+            }
+            if !active
+                || tier.min_size_usd <= prev_tier.min_size_usd
+                || tier.max_initial_leverage > prev_tier.max_initial_leverage
+                || tier.max_leverage > prev_tier.max_leverage
+                || tier.max_initial_leverage < self.pricing.min_initial_leverage
+                || tier.max_initial_leverage > tier.max_leverage
+            {
+                return false;
+            }
+            prev_tier = *tier;
+        }
+        true
+    }
+
+    pub fn set_leverage_tiers(&mut self, leverage_tiers: &[LeverageTier]) -> Result<()> {
+        if leverage_tiers.len() > Custody::MAX_LEVERAGE_TIERS {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        self.leverage_tiers = [LeverageTier::default(); Custody::MAX_LEVERAGE_TIERS];
+        self.leverage_tiers[..leverage_tiers.len()].copy_from_slice(leverage_tiers);
+        Ok(())
+    }
+
+    /// Returns the leverage tier that applies to a position of the given size
+    /// together with its max initial and max leverage. Tier 0 stands for the
+    /// base pricing params, tier N for the N-th entry of the tier table.
+    /// Tier limits are capped by the ramped base max leverage.
+    pub fn get_leverage_tier(&self, size_usd: u64, curtime: i64) -> (u8, u64, u64) {
+        let max_leverage = self.get_max_leverage(curtime);
+        let mut res = (
+            0,
+            std::cmp::min(self.pricing.max_initial_leverage, max_leverage),
+            max_leverage,
+        );
+        for (idx, tier) in self.leverage_tiers.iter().enumerate() {
+            if tier.min_size_usd == 0 || size_usd < tier.min_size_usd {
+                break;
+            }
+            res = (
+                (idx + 1) as u8,
+                std::cmp::min(tier.max_initial_leverage, max_leverage),
+                std::cmp::min(tier.max_leverage, max_leverage),
+            );
+        }
+        res
+    }
+
+    /// Starts ramping pricing and borrow rate params from their current values
+    /// to the ones set in the config by end_time
+    pub fn start_ramp(&mut self, curtime: i64, end_time: i64) -> Result<()> {
+        if end_time < curtime {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        self.ramp = CustodyRamp {
+            start_time: curtime,
+            end_time,
+            max_leverage: self.get_max_leverage(curtime),
+            trade_spread_long: self.get_trade_spread(Side::Long, curtime),
+            trade_spread_short: self.get_trade_spread(Side::Short, curtime),
+            swap_spread: self.get_swap_spread(curtime),
+            slope1: self.get_ramped_value(self.ramp.slope1, self.borrow_rate.slope1, curtime),
+            slope2: self.get_ramped_value(self.ramp.slope2, self.borrow_rate.slope2, curtime),
+        };
+        Ok(())
+    }
+
+    pub fn get_max_leverage(&self, curtime: i64) -> u64 {
+        self.get_ramped_value(self.ramp.max_leverage, self.pricing.max_leverage, curtime)
+    }
+
+    pub fn get_trade_spread(&self, side: Side, curtime: i64) -> u64 {
+        if side == Side::Long {
+            self.get_ramped_value(
+                self.ramp.trade_spread_long,
+                self.pricing.trade_spread_long,
+                curtime,
+            )
+        } else {
+            self.get_ramped_value(
+                self.ramp.trade_spread_short,
+                self.pricing.trade_spread_short,
+                curtime,
+            )
+        }
+    }
+
+    pub fn get_swap_spread(&self, curtime: i64) -> u64 {
+        self.get_ramped_value(self.ramp.swap_spread, self.pricing.swap_spread, curtime)
+    }
+
+    fn get_ramped_value(&self, start_value: u64, target_value: u64, curtime: i64) -> u64 {
+        math::interpolate(
+            start_value,
+            target_value,
+            self.ramp.start_time,
+            self.ramp.end_time,
+            curtime,
+        )
     }
 
     pub fn lock_funds(&mut self, amount: u64) -> Result<()> {
@@ -337,11 +627,6 @@ impl Custody {
     }
 
     pub fn update_borrow_rate(&mut self, curtime: i64) -> Result<()> {
-        // if current_utilization < optimal_utilization:
-        //   rate = base_rate + (current_utilization / optimal_utilization) * slope1
-        // else:
-        //   rate = base_rate + slope1 + (current_utilization - optimal_utilization) / (1 - optimal_utilization) * slope2
-
         if self.assets.owned == 0 {
             self.borrow_rate_state.current_rate = 0;
             self.borrow_rate_state.last_update =
@@ -355,43 +640,66 @@ impl Custody {
             self.borrow_rate_state.last_update = curtime;
         }
 
-        // get current utilization
-        let current_utilization = math::checked_div(
+        // compute and save new borrow rate
+        self.borrow_rate_state.current_rate = self.get_borrow_rate(curtime)?;
+
+        Ok(())
+    }
+
+    /// Returns current utilization with implied RATE_DECIMALS decimals
+    pub fn get_utilization(&self) -> Result<u128> {
+        if self.assets.owned == 0 {
+            return Ok(0);
+        }
+        math::checked_div(
             math::checked_mul(self.assets.locked as u128, Perpetuals::RATE_POWER)?,
             self.assets.owned as u128,
-        )?;
+        )
+    }
+
+    /// Returns hourly borrow rate for the current utilization
+    pub fn get_borrow_rate(&self, curtime: i64) -> Result<u64> {
+        // if current_utilization < optimal_utilization:
+        //   rate = base_rate + (current_utilization / optimal_utilization) * slope1
+        // else:
+        //   rate = base_rate + slope1 + (current_utilization - optimal_utilization) / (1 - optimal_utilization) * slope2
+
+        if self.assets.owned == 0 {
+            return Ok(0);
+        }
+
+        // get current utilization
+        let current_utilization = self.get_utilization()?;
+        let slope1 = self.get_ramped_value(self.ramp.slope1, self.borrow_rate.slope1, curtime);
+        let slope2 = self.get_ramped_value(self.ramp.slope2, self.borrow_rate.slope2, curtime);
 
-        // compute and save new borrow rate
         let hourly_rate = if current_utilization < (self.borrow_rate.optimal_utilization as u128)
             || (self.borrow_rate.optimal_utilization as u128) >= Perpetuals::RATE_POWER
         {
             math::checked_div(
-                math::checked_mul(current_utilization, self.borrow_rate.slope1 as u128)?,
+                math::checked_mul(current_utilization, slope1 as u128)?,
                 self.borrow_rate.optimal_utilization as u128,
             )?
         } else {
             math::checked_add(
-                self.borrow_rate.slope1 as u128,
+                slope1 as u128,
                 math::checked_div(
                     math::checked_mul(
                         math::checked_sub(
                             current_utilization,
                             self.borrow_rate.optimal_utilization as u128,
                         )?,
-                        self.borrow_rate.slope2 as u128,
+                        slope2 as u128,
                     )?,
                     Perpetuals::RATE_POWER - self.borrow_rate.optimal_utilization as u128,
                 )?,
             )?
         };
-        let hourly_rate = math::checked_add(
+
+        math::checked_add(
             math::checked_as_u64(hourly_rate)?,
             self.borrow_rate.base_rate,
-        )?;
-
-        self.borrow_rate_state.current_rate = hourly_rate;
-
-        Ok(())
+        )
     }
 
     pub fn get_collective_position(&self, side: Side) -> Result<Position> {
@@ -555,8 +863,52 @@ impl Custody {
     }
 }
 
+impl CustodyV0 {
+    pub const LEN: usize = 8 + std::mem::size_of::<CustodyV0>();
+}
+
 impl DeprecatedCustody {
     pub const LEN: usize = 8 + std::mem::size_of::<DeprecatedCustody>();
+
+    /// Converts to the version 0 layout, position stats are not carried over
+    pub fn to_custody_v0(&self) -> CustodyV0 {
+        let pricing = PricingParams {
+            use_ema: self.pricing.use_ema,
+            use_unrealized_pnl_in_aum: self.pricing.use_unrealized_pnl_in_aum,
+            trade_spread_long: self.pricing.trade_spread_long,
+            trade_spread_short: self.pricing.trade_spread_short,
+            swap_spread: self.pricing.swap_spread,
+            min_initial_leverage: self.pricing.min_initial_leverage,
+            max_initial_leverage: self.pricing.max_leverage,
+            max_leverage: self.pricing.max_leverage,
+            max_payoff_mult: self.pricing.max_payoff_mult,
+            max_utilization: 0,
+            max_position_locked_usd: 0,
+            max_total_locked_usd: 0,
+        };
+
+        CustodyV0 {
+            pool: self.pool,
+            mint: self.mint,
+            token_account: self.token_account,
+            decimals: self.decimals,
+            is_stable: self.is_stable,
+            oracle: self.oracle,
+            pricing,
+            permissions: self.permissions,
+            fees: self.fees,
+            borrow_rate: self.borrow_rate,
+            assets: self.assets,
+            collected_fees: self.collected_fees,
+            volume_stats: self.volume_stats,
+            trade_stats: self.trade_stats,
+            long_positions: PositionStats::default(),
+            short_positions: PositionStats::default(),
+            borrow_rate_state: self.borrow_rate_state,
+            bump: self.bump,
+            token_account_bump: self.token_account_bump,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -679,4 +1031,106 @@ mod test {
         custody.update_borrow_rate(3600).unwrap();
         assert_eq!(custody.borrow_rate_state.current_rate, 199400);
     }
+
+    #[test]
+    fn test_get_borrow_rate() {
+        let mut custody = get_fixture();
+        assert_eq!(custody.get_utilization().unwrap(), 500000000);
+        assert_eq!(custody.get_borrow_rate(0).unwrap(), 50000);
+
+        custody.assets.locked = 900;
+        assert_eq!(custody.get_utilization().unwrap(), 900000000);
+        assert_eq!(custody.get_borrow_rate(0).unwrap(), 140000);
+
+        custody.assets.owned = 0;
+        assert_eq!(custody.get_utilization().unwrap(), 0);
+        assert_eq!(custody.get_borrow_rate(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_get_leverage_tier() {
+        let mut custody = get_fixture();
+        custody.pricing.min_initial_leverage = 10000;
+        custody.pricing.max_initial_leverage = 500000;
+        custody.pricing.max_leverage = 1000000;
+        assert_eq!(
+            custody.get_leverage_tier(1_000_000_000, 0),
+            (0, 500000, 1000000)
+        );
+
+        custody
+            .set_leverage_tiers(&[
+                LeverageTier {
+                    min_size_usd: 100_000_000_000,
+                    max_initial_leverage: 200000,
+                    max_leverage: 500000,
+                },
+                LeverageTier {
+                    min_size_usd: 1_000_000_000_000,
+                    max_initial_leverage: 50000,
+                    max_leverage: 100000,
+                },
+            ])
+            .unwrap();
+        assert!(custody.validate_leverage_tiers());
+
+        assert_eq!(
+            custody.get_leverage_tier(99_999_999_999, 0),
+            (0, 500000, 1000000)
+        );
+        assert_eq!(
+            custody.get_leverage_tier(100_000_000_000, 0),
+            (1, 200000, 500000)
+        );
+        assert_eq!(
+            custody.get_leverage_tier(999_999_999_999, 0),
+            (1, 200000, 500000)
+        );
+        assert_eq!(custody.get_leverage_tier(u64::MAX, 0), (2, 50000, 100000));
+
+        // leverage can't grow with size
+        custody.leverage_tiers[1].max_leverage = 600000;
+        assert!(!custody.validate_leverage_tiers());
+
+        // sizes must be increasing
+        custody.leverage_tiers[1].max_leverage = 100000;
+        custody.leverage_tiers[1].min_size_usd = 100_000_000_000;
+        assert!(!custody.validate_leverage_tiers());
+
+        // no gaps between active tiers
+        custody.leverage_tiers[1].min_size_usd = 0;
+        assert!(!custody.validate_leverage_tiers());
+
+        assert!(custody
+            .set_leverage_tiers(&[LeverageTier::default(); Custody::MAX_LEVERAGE_TIERS + 1])
+            .is_err());
+    }
+
+    #[test]
+    fn test_ramp() {
+        let mut custody = get_fixture();
+        custody.pricing.max_leverage = 1000000;
+        custody.pricing.trade_spread_long = 100;
+
+        custody.start_ramp(1000, 2000).unwrap();
+        custody.pricing.max_leverage = 500000;
+        custody.pricing.trade_spread_long = 300;
+        custody.borrow_rate.slope1 = 160000;
+
+        assert_eq!(custody.get_max_leverage(1000), 1000000);
+        assert_eq!(custody.get_max_leverage(1500), 750000);
+        assert_eq!(custody.get_max_leverage(2500), 500000);
+        assert_eq!(custody.get_trade_spread(Side::Long, 1500), 200);
+        assert_eq!(custody.get_borrow_rate(1500).unwrap(), 75000);
+        assert_eq!(custody.get_borrow_rate(2000).unwrap(), 100000);
+
+        // new ramp starts from the current values
+        custody.start_ramp(1500, 2500).unwrap();
+        custody.pricing.max_leverage = 1000000;
+        assert_eq!(custody.get_max_leverage(1500), 750000);
+        assert_eq!(custody.get_max_leverage(2000), 875000);
+        assert_eq!(custody.get_leverage_tier(0, 2000), (0, 0, 875000));
+
+        assert!(custody.start_ramp(1500, 1000).is_err());
+    }
 }
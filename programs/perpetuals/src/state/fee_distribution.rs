@@ -0,0 +1,177 @@
+use {
+    crate::{math, state::perpetuals::Perpetuals},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Debug)]
+pub enum FeeRecipientType {
+    Treasury,
+    Insurance,
+    Stakers,
+    Buyback,
+}
+
+impl Default for FeeRecipientType {
+    fn default() -> Self {
+        Self::Treasury
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct FeeRecipient {
+    pub recipient_type: FeeRecipientType,
+    // owner of the receiving token accounts, not used for stakers
+    pub owner: Pubkey,
+    // implied BPS_DECIMALS decimals
+    pub weight: u64,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct DistributionRecord {
+    pub custody: Pubkey,
+    pub total_distributed: u64,
+    pub last_distributed: u64,
+    pub last_distribution_time: i64,
+}
+
+/// Protocol fee distribution config of the pool
+/// Protocol fees accrued by custodies are split between recipients according
+/// to their weights. Stakers share is added to the pool staking rewards.
+#[account]
+#[derive(Default, Debug)]
+pub struct FeeDistribution {
+    pub version: u8,
+    pub pool: Pubkey,
+    pub recipients: Vec<FeeRecipient>,
+    pub records: Vec<DistributionRecord>,
+    pub distribution_count: u64,
+
+    pub bump: u8,
+}
+
+impl FeeDistribution {
+    pub const VERSION: u8 = 1;
+    pub const MAX_RECIPIENTS: usize = 8;
+    pub const MAX_RECORDS: usize = 16;
+    pub const LEN: usize = 8
+        + std::mem::size_of::<FeeDistribution>()
+        + FeeDistribution::MAX_RECIPIENTS * std::mem::size_of::<FeeRecipient>()
+        + FeeDistribution::MAX_RECORDS * std::mem::size_of::<DistributionRecord>();
+
+    pub fn validate(&self) -> bool {
+        if self.recipients.is_empty() || self.recipients.len() > Self::MAX_RECIPIENTS {
+            return false;
+        }
+        let mut total_weight: u128 = 0;
+        let mut stakers_count = 0;
+        for recipient in self.recipients.iter() {
+            if recipient.recipient_type == FeeRecipientType::Stakers {
+                stakers_count += 1;
+            } else if recipient.owner == Pubkey::default() {
+                return false;
+            }
+            total_weight += recipient.weight as u128;
+        }
+        stakers_count <= 1 && total_weight == Perpetuals::BPS_POWER
+    }
+
+    pub fn get_recipient_amount(total_amount: u64, weight: u64) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(total_amount as u128, weight as u128)?,
+            Perpetuals::BPS_POWER,
+        )?)
+    }
+
+    pub fn record_distribution(
+        &mut self,
+        custody_key: &Pubkey,
+        amount: u64,
+        curtime: i64,
+    ) -> Result<()> {
+        let idx = if let Some(idx) = self.records.iter().position(|x| x.custody == *custody_key) {
+            idx
+        } else {
+            if self.records.len() >= Self::MAX_RECORDS {
+                return Err(ProgramError::InvalidAccountData.into());
+            }
+            self.records.push(DistributionRecord {
+                custody: *custody_key,
+                ..DistributionRecord::default()
+            });
+            self.records.len() - 1
+        };
+        let record = &mut self.records[idx];
+        record.total_distributed = math::checked_add(record.total_distributed, amount)?;
+        record.last_distributed = amount;
+        record.last_distribution_time = curtime;
+
+        self.distribution_count = math::checked_add(self.distribution_count, 1)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_fixture() -> FeeDistribution {
+        FeeDistribution {
+            recipients: vec![
+                FeeRecipient {
+                    recipient_type: FeeRecipientType::Treasury,
+                    owner: Pubkey::new_unique(),
+                    weight: 6_000,
+                },
+                FeeRecipient {
+                    recipient_type: FeeRecipientType::Stakers,
+                    owner: Pubkey::default(),
+                    weight: 4_000,
+                },
+            ],
+            ..FeeDistribution::default()
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut fee_distribution = get_fixture();
+        assert!(fee_distribution.validate());
+
+        // weights don't add up
+        fee_distribution.recipients[0].weight = 5_000;
+        assert!(!fee_distribution.validate());
+
+        // missing recipient owner
+        fee_distribution.recipients[0].weight = 6_000;
+        fee_distribution.recipients[0].owner = Pubkey::default();
+        assert!(!fee_distribution.validate());
+
+        // multiple stakers recipients
+        fee_distribution.recipients[0].recipient_type = FeeRecipientType::Stakers;
+        assert!(!fee_distribution.validate());
+    }
+
+    #[test]
+    fn test_record_distribution() {
+        let mut fee_distribution = get_fixture();
+        let custody = Pubkey::new_unique();
+
+        fee_distribution
+            .record_distribution(&custody, 1_000, 100)
+            .unwrap();
+        fee_distribution
+            .record_distribution(&custody, 500, 200)
+            .unwrap();
+
+        assert_eq!(fee_distribution.records.len(), 1);
+        assert_eq!(fee_distribution.records[0].total_distributed, 1_500);
+        assert_eq!(fee_distribution.records[0].last_distributed, 500);
+        assert_eq!(fee_distribution.records[0].last_distribution_time, 200);
+        assert_eq!(fee_distribution.distribution_count, 2);
+        assert_eq!(
+            FeeDistribution::get_recipient_amount(1_001, 4_000).unwrap(),
+            400
+        );
+    }
+}
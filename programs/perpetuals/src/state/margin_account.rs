@@ -0,0 +1,144 @@
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{custody::Custody, oracle::OraclePrice, pool::Pool, position::Position},
+    },
+    anchor_lang::prelude::*,
+};
+
+/// Cross-margin account
+/// Holds collateral shared by all linked positions of the owner in the pool.
+/// Collateral is kept in a stable custody, linked positions can only be
+/// liquidated when the account as a whole is under-collateralized.
+#[account]
+#[derive(Default, Debug)]
+pub struct MarginAccount {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub collateral_custody: Pubkey,
+    pub collateral_amount: u64,
+    pub positions: Vec<Pubkey>,
+
+    pub bump: u8,
+}
+
+impl MarginAccount {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = 8 + std::mem::size_of::<MarginAccount>();
+    pub const MAX_POSITIONS: usize = 16;
+
+    pub fn get_position_index(&self, position: &Pubkey) -> Result<usize> {
+        self.positions
+            .iter()
+            .position(|&k| k == *position)
+            .ok_or_else(|| PerpetualsError::InvalidPositionState.into())
+    }
+
+    pub fn add_position(&mut self, position: &Pubkey) -> Result<()> {
+        if self.positions.contains(position) || self.positions.len() >= Self::MAX_POSITIONS {
+            return err!(PerpetualsError::InvalidPositionState);
+        }
+        self.positions.push(*position);
+        Ok(())
+    }
+
+    pub fn remove_position(&mut self, position: &Pubkey) -> Result<()> {
+        let position_idx = self.get_position_index(position)?;
+        self.positions.remove(position_idx);
+        Ok(())
+    }
+
+    /// Returns current account margin and maintenance margin required by
+    /// linked positions, both in USD.
+    /// accounts: positions.len() position accounts, followed by
+    ///           positions.len() custody accounts, followed by
+    ///           positions.len() oracle accounts
+    pub fn get_margin_usd(
+        &self,
+        pool: &Pool,
+        collateral_custody: &Custody,
+        collateral_price: &OraclePrice,
+        accounts: &[AccountInfo],
+        curtime: i64,
+    ) -> Result<(u64, u64)> {
+        let num_positions = self.positions.len();
+        if accounts.len() < num_positions * 3 {
+            return Err(ProgramError::NotEnoughAccountKeys.into());
+        }
+
+        let mut assets_usd = collateral_price
+            .get_asset_amount_usd(self.collateral_amount, collateral_custody.decimals)?;
+        let mut loss_usd: u64 = 0;
+        let mut required_margin_usd: u64 = 0;
+
+        for (idx, &position_key) in self.positions.iter().enumerate() {
+            let custody_idx = idx + num_positions;
+            let oracle_idx = custody_idx + num_positions;
+
+            require_keys_eq!(accounts[idx].key(), position_key);
+            let position = Account::<Position>::try_from(&accounts[idx])?;
+            require!(
+                position.version == Position::VERSION,
+                PerpetualsError::InvalidAccountVersion
+            );
+            require_keys_eq!(accounts[custody_idx].key(), position.custody);
+            let custody = Account::<Custody>::try_from(&accounts[custody_idx])?;
+            require!(
+                custody.version == Custody::VERSION,
+                PerpetualsError::InvalidAccountVersion
+            );
+            require_keys_eq!(accounts[oracle_idx].key(), custody.oracle.oracle_account);
+
+            let token_ema_price = OraclePrice::new_from_oracle(
+                custody.oracle.oracle_type,
+                &accounts[oracle_idx],
+                custody.oracle.max_price_error,
+                custody.oracle.max_price_age_sec,
+                curtime,
+                custody.pricing.use_ema,
+            )?;
+
+            let (position_profit_usd, position_loss_usd, _) = pool.get_pnl_usd(
+                &position,
+                &token_ema_price,
+                &token_ema_price,
+                &custody,
+                curtime,
+                false,
+            )?;
+
+            assets_usd = math::checked_add(
+                assets_usd,
+                math::checked_add(position.collateral_usd, position_profit_usd)?,
+            )?;
+            loss_usd = math::checked_add(loss_usd, position_loss_usd)?;
+            required_margin_usd = math::checked_add(
+                required_margin_usd,
+                Pool::get_maintenance_margin_usd(&position, &custody, curtime)?,
+            )?;
+        }
+
+        Ok((assets_usd.saturating_sub(loss_usd), required_margin_usd))
+    }
+
+    pub fn check_health(
+        &self,
+        pool: &Pool,
+        collateral_custody: &Custody,
+        collateral_price: &OraclePrice,
+        accounts: &[AccountInfo],
+        curtime: i64,
+    ) -> Result<bool> {
+        let (margin_usd, required_margin_usd) = self.get_margin_usd(
+            pool,
+            collateral_custody,
+            collateral_price,
+            accounts,
+            curtime,
+        )?;
+
+        Ok(margin_usd >= required_margin_usd)
+    }
+}
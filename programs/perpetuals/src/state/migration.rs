@@ -0,0 +1,1068 @@
+//! Account versioning and layout migrations
+
+use {
+    crate::{
+        error::PerpetualsError,
+        state::{
+            custody::{
+                Assets, BorrowRateParams, Custody, CustodyRamp, CustodyStatus, CustodyV0,
+                DeprecatedCustody, DeprecatedFeesStats, DeprecatedTradeStats,
+                DeprecatedVolumeStats, Fees, LeverageTier, OracleParams, PricingParams,
+                TokenFeesStats,
+            },
+            multisig::{DeprecatedMultisig, Multisig},
+            perpetuals::{
+                DeprecatedPermissions, Permissions, Perpetuals, PerpetualsV0, PerpetualsV1,
+            },
+            pool::{CustodyAum, Pool, PoolV0, PoolV1, PoolV2, RatiosRamp},
+            position::{Position, PositionV0},
+        },
+    },
+    anchor_lang::{prelude::*, Discriminator},
+};
+
+/// Converts serialized account data (including the discriminator) from one
+/// layout version to a newer one. Steps that need data of other accounts
+/// read it from the accounts passed along with the migrated one.
+pub type MigrationFn = fn(&[u8], &[AccountInfo]) -> Result<Vec<u8>>;
+
+/// Recognizes accounts created before versioning was introduced, they have
+/// no version field
+pub enum LegacyLayout {
+    // fixed size accounts with a legacy layout of distinct size
+    Len(usize),
+    // accounts that are resized or kept their size, the legacy layout is
+    // decoded and must re-derive the account address
+    Pda(fn(&Pubkey, &[u8]) -> bool),
+}
+
+impl LegacyLayout {
+    pub fn matches(&self, key: &Pubkey, data: &[u8]) -> bool {
+        match self {
+            LegacyLayout::Len(len) => *len == data.len(),
+            LegacyLayout::Pda(is_legacy) => is_legacy(key, data),
+        }
+    }
+}
+
+pub struct MigrationStep {
+    pub discriminator: [u8; 8],
+    pub from_version: u8,
+    pub legacy_layout: Option<LegacyLayout>,
+    pub migrate: MigrationFn,
+}
+
+/// Registry of supported migrations.
+/// When an account layout changes, bump its VERSION and append a step
+/// converting from the previous version.
+pub const MIGRATION_STEPS: &[MigrationStep] = &[
+    MigrationStep {
+        discriminator: Custody::DISCRIMINATOR,
+        from_version: 0,
+        legacy_layout: Some(LegacyLayout::Len(DeprecatedCustody::LEN)),
+        migrate: migrate_deprecated_custody,
+    },
+    MigrationStep {
+        discriminator: Custody::DISCRIMINATOR,
+        from_version: 0,
+        legacy_layout: Some(LegacyLayout::Len(CustodyV0::LEN)),
+        migrate: migrate_custody_v0,
+    },
+    MigrationStep {
+        discriminator: Custody::DISCRIMINATOR,
+        from_version: 1,
+        legacy_layout: None,
+        migrate: migrate_custody_v1,
+    },
+    MigrationStep {
+        discriminator: Custody::DISCRIMINATOR,
+        from_version: 2,
+        legacy_layout: None,
+        migrate: migrate_custody_v2,
+    },
+    MigrationStep {
+        discriminator: Custody::DISCRIMINATOR,
+        from_version: 3,
+        legacy_layout: None,
+        migrate: migrate_custody_v3,
+    },
+    MigrationStep {
+        discriminator: Pool::DISCRIMINATOR,
+        from_version: 0,
+        legacy_layout: Some(LegacyLayout::Pda(is_pool_v0)),
+        migrate: migrate_pool_v0,
+    },
+    MigrationStep {
+        discriminator: Pool::DISCRIMINATOR,
+        from_version: 1,
+        legacy_layout: None,
+        migrate: migrate_pool_v1,
+    },
+    MigrationStep {
+        discriminator: Pool::DISCRIMINATOR,
+        from_version: 2,
+        legacy_layout: None,
+        migrate: migrate_pool_v2,
+    },
+    MigrationStep {
+        discriminator: Perpetuals::DISCRIMINATOR,
+        from_version: 0,
+        legacy_layout: Some(LegacyLayout::Pda(is_perpetuals_v0)),
+        migrate: migrate_perpetuals_v0,
+    },
+    MigrationStep {
+        discriminator: Perpetuals::DISCRIMINATOR,
+        from_version: 1,
+        legacy_layout: None,
+        migrate: migrate_perpetuals_v1,
+    },
+    MigrationStep {
+        discriminator: Position::DISCRIMINATOR,
+        from_version: 0,
+        legacy_layout: Some(LegacyLayout::Pda(is_position_v0)),
+        migrate: migrate_position_v0,
+    },
+    MigrationStep {
+        discriminator: Multisig::DISCRIMINATOR,
+        from_version: 0,
+        legacy_layout: Some(LegacyLayout::Len(DeprecatedMultisig::LEN)),
+        migrate: migrate_multisig_v0,
+    },
+];
+
+/// Offset of the version field, it is the first field of every account
+pub const VERSION_OFFSET: usize = 8;
+
+/// Returns layout version of the serialized account data
+pub fn get_version(key: &Pubkey, data: &[u8]) -> Result<u8> {
+    if data.len() <= VERSION_OFFSET {
+        return Err(ProgramError::AccountDataTooSmall.into());
+    }
+    // legacy layouts are checked first, their first field takes the place
+    // of the version
+    for step in MIGRATION_STEPS {
+        if step.discriminator == data[..8]
+            && step
+                .legacy_layout
+                .as_ref()
+                .map_or(false, |layout| layout.matches(key, data))
+        {
+            return Ok(step.from_version);
+        }
+    }
+    Ok(data[VERSION_OFFSET])
+}
+
+/// Returns migration step applicable to the serialized account data
+pub fn get_migration_step(key: &Pubkey, data: &[u8]) -> Result<Option<&'static MigrationStep>> {
+    let version = get_version(key, data)?;
+    // there can be several legacy layouts of the same version
+    Ok(MIGRATION_STEPS.iter().find(|step| {
+        step.discriminator == data[..8]
+            && step.from_version == version
+            && step
+                .legacy_layout
+                .as_ref()
+                .map_or(true, |layout| layout.matches(key, data))
+    }))
+}
+
+/// Upgrades serialized account data to the latest layout by applying all
+/// matching migration steps in order
+pub fn migrate(key: &Pubkey, data: &[u8], accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let mut data = data.to_vec();
+    let mut migrated = false;
+
+    while let Some(step) = get_migration_step(key, &data)? {
+        let new_data = (step.migrate)(&data, accounts)?;
+        // every step must move the account forward
+        if get_version(key, &new_data)? <= step.from_version {
+            return err!(PerpetualsError::InvalidAccountVersion);
+        }
+        data = new_data;
+        migrated = true;
+    }
+
+    if !migrated {
+        msg!("Error: Account is up to date or has unknown layout");
+        return err!(PerpetualsError::InvalidAccountVersion);
+    }
+
+    Ok(data)
+}
+
+/// Migrates program account in place, extra space is paid by the funding account
+pub fn migrate_account<'a>(
+    funding_account: AccountInfo<'a>,
+    target_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+) -> Result<()> {
+    if target_account.owner != &crate::ID {
+        return Err(ProgramError::IllegalOwner.into());
+    }
+
+    let new_data = migrate(
+        target_account.key,
+        &target_account.try_borrow_data()?,
+        accounts,
+    )?;
+
+    if new_data.len() > target_account.try_data_len()? {
+        Perpetuals::realloc(
+            funding_account,
+            target_account.clone(),
+            system_program,
+            new_data.len(),
+            true,
+        )?;
+    }
+
+    let mut data = target_account.try_borrow_mut_data()?;
+    data[..new_data.len()].copy_from_slice(&new_data);
+    data[new_data.len()..].fill(0);
+
+    Ok(())
+}
+
+/// Returns true if the account address is derived from the given seeds
+fn is_pda(key: &Pubkey, seeds: &[&[u8]]) -> bool {
+    Pubkey::create_program_address(seeds, &crate::ID).map_or(false, |address| &address == key)
+}
+
+// legacy layouts are stored under the discriminator of the current type
+fn migrate_deprecated_custody(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let deprecated_custody = DeprecatedCustody::try_deserialize_unchecked(&mut &data[..])?;
+
+    get_custody_data(deprecated_custody.to_custody_v0())
+}
+
+fn migrate_custody_v0(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    get_custody_data(CustodyV0::try_deserialize_unchecked(&mut &data[..])?)
+}
+
+/// Returns serialized custody of the latest layout built from version 0 data
+fn get_custody_data(custody_v0: CustodyV0) -> Result<Vec<u8>> {
+    let custody = Custody {
+        version: Custody::VERSION,
+        pool: custody_v0.pool,
+        mint: custody_v0.mint,
+        token_account: custody_v0.token_account,
+        decimals: custody_v0.decimals,
+        is_stable: custody_v0.is_stable,
+        oracle: custody_v0.oracle,
+        pricing: custody_v0.pricing,
+        permissions: custody_v0.permissions.to_permissions(),
+        fees: custody_v0.fees,
+        borrow_rate: custody_v0.borrow_rate,
+        leverage_tiers: [LeverageTier::default(); Custody::MAX_LEVERAGE_TIERS],
+        ramp: CustodyRamp::default(),
+        assets: custody_v0.assets,
+        collected_fees: custody_v0.collected_fees.to_fees_stats(),
+        collected_token_fees: TokenFeesStats::default(),
+        volume_stats: custody_v0.volume_stats.to_volume_stats(),
+        trade_stats: custody_v0.trade_stats.to_trade_stats(),
+        long_positions: custody_v0.long_positions,
+        short_positions: custody_v0.short_positions,
+        borrow_rate_state: custody_v0.borrow_rate_state,
+        bump: custody_v0.bump,
+        token_account_bump: custody_v0.token_account_bump,
+        status: CustodyStatus::Active,
+        settlement_price: 0,
+    };
+
+    if !custody.validate() {
+        return err!(PerpetualsError::InvalidCustodyConfig);
+    }
+
+    let mut new_data = Vec::with_capacity(Custody::LEN);
+    custody.try_serialize(&mut new_data)?;
+    new_data.resize(Custody::LEN, 0);
+
+    Ok(new_data)
+}
+
+// delisting fields were appended to the end of the layout, they are read
+// from the zeroed tail of version 1 data as default values
+fn migrate_custody_v1(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let mut new_data = data.to_vec();
+    new_data[VERSION_OFFSET] = 2;
+    new_data.resize(std::cmp::max(new_data.len(), Custody::LEN), 0);
+
+    Ok(new_data)
+}
+
+// allow_liquidation was added to the permissions
+fn migrate_custody_v2(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let mut buf = &data[VERSION_OFFSET + 1..];
+    let _: (
+        Pubkey,
+        Pubkey,
+        Pubkey,
+        u8,
+        bool,
+        OracleParams,
+        PricingParams,
+    ) = AnchorDeserialize::deserialize(&mut buf).map_err(|_| ProgramError::InvalidAccountData)?;
+    let permissions_offset = data.len() - buf.len();
+    let permissions: DeprecatedPermissions =
+        AnchorDeserialize::deserialize(&mut buf).map_err(|_| ProgramError::InvalidAccountData)?;
+
+    // the layout grows by one byte, the next step reserializes the custody
+    // to its exact size
+    let mut new_data = data[..permissions_offset].to_vec();
+    permissions.to_permissions().serialize(&mut new_data)?;
+    new_data.extend_from_slice(buf);
+    new_data[VERSION_OFFSET] = 3;
+
+    Ok(new_data)
+}
+
+// stats counters were widened to u128 and token fee totals were added
+fn migrate_custody_v3(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let stats_offset = get_custody_stats_offset(data)?;
+    let mut buf = &data[stats_offset..];
+    let (collected_fees, volume_stats, trade_stats): (
+        DeprecatedFeesStats,
+        DeprecatedVolumeStats,
+        DeprecatedTradeStats,
+    ) = AnchorDeserialize::deserialize(&mut buf).map_err(|_| ProgramError::InvalidAccountData)?;
+
+    let mut custody_data = data[..stats_offset].to_vec();
+    collected_fees
+        .to_fees_stats()
+        .serialize(&mut custody_data)?;
+    TokenFeesStats::default().serialize(&mut custody_data)?;
+    volume_stats
+        .to_volume_stats()
+        .serialize(&mut custody_data)?;
+    trade_stats.to_trade_stats().serialize(&mut custody_data)?;
+    custody_data.extend_from_slice(buf);
+    custody_data[VERSION_OFFSET] = Custody::VERSION;
+
+    // fields after the stats are shifted, reserialize to get the exact layout
+    let custody = Custody::try_deserialize_unchecked(&mut &custody_data[..])?;
+
+    let mut new_data = Vec::with_capacity(Custody::LEN);
+    custody.try_serialize(&mut new_data)?;
+    new_data.resize(Custody::LEN, 0);
+
+    Ok(new_data)
+}
+
+/// Returns offset of the collected fees in the version 3 custody layout
+#[allow(clippy::type_complexity)]
+fn get_custody_stats_offset(data: &[u8]) -> Result<usize> {
+    let mut buf = &data[VERSION_OFFSET + 1..];
+    let _: (
+        Pubkey,
+        Pubkey,
+        Pubkey,
+        u8,
+        bool,
+        OracleParams,
+        PricingParams,
+        Permissions,
+        Fees,
+        BorrowRateParams,
+        [LeverageTier; Custody::MAX_LEVERAGE_TIERS],
+        CustodyRamp,
+        Assets,
+    ) = AnchorDeserialize::deserialize(&mut buf).map_err(|_| ProgramError::InvalidAccountData)?;
+
+    Ok(data.len() - buf.len())
+}
+
+// baseline pools are resized as custodies are added and the name length
+// takes the place of the version
+fn is_pool_v0(key: &Pubkey, data: &[u8]) -> bool {
+    PoolV0::try_deserialize_unchecked(&mut &data[..]).map_or(false, |pool| {
+        is_pda(key, &[b"pool", pool.name.as_bytes(), &[pool.bump]])
+    })
+}
+
+// expects pool custodies to be migrated already and passed in pool order
+fn migrate_pool_v0(data: &[u8], accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let pool_v0 = PoolV0::try_deserialize_unchecked(&mut &data[..])?;
+
+    // pool-level permissions and limits didn't exist, the pool is left open
+    // and unlimited
+    let mut pool = get_pool_from_v2(PoolV2 {
+        version: 2,
+        name: pool_v0.name,
+        custodies: pool_v0.custodies,
+        ratios: pool_v0.ratios,
+        aum_usd: pool_v0.aum_usd,
+        bump: pool_v0.bump,
+        lp_token_bump: pool_v0.lp_token_bump,
+        inception_time: pool_v0.inception_time,
+        withdrawal_queue_enabled: false,
+        permissions: Permissions {
+            allow_swap: true,
+            allow_add_liquidity: true,
+            allow_remove_liquidity: true,
+            allow_open_position: true,
+            allow_close_position: true,
+            allow_pnl_withdrawal: true,
+            allow_collateral_withdrawal: true,
+            allow_size_change: true,
+            allow_liquidation: true,
+        },
+        max_aum_usd: 0,
+        max_open_interest_usd: 0,
+        open_interest_usd: 0,
+        ratios_ramp: RatiosRamp::default(),
+    });
+
+    // open interest of existing positions is taken from the custody stats,
+    // otherwise closing them would underflow the counter
+    pool.open_interest_usd = pool.get_open_interest_usd(accounts)?;
+
+    get_pool_data(pool, data.len())
+}
+
+// allow_liquidation was added to the permissions
+fn migrate_pool_v1(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let pool_v1 = PoolV1::try_deserialize_unchecked(&mut &data[..])?;
+
+    let pool = get_pool_from_v2(PoolV2 {
+        version: 2,
+        name: pool_v1.name,
+        custodies: pool_v1.custodies,
+        ratios: pool_v1.ratios,
+        aum_usd: pool_v1.aum_usd,
+        bump: pool_v1.bump,
+        lp_token_bump: pool_v1.lp_token_bump,
+        inception_time: pool_v1.inception_time,
+        withdrawal_queue_enabled: pool_v1.withdrawal_queue_enabled,
+        permissions: pool_v1.permissions.to_permissions(),
+        max_aum_usd: pool_v1.max_aum_usd,
+        max_open_interest_usd: pool_v1.max_open_interest_usd,
+        open_interest_usd: pool_v1.open_interest_usd,
+        ratios_ramp: pool_v1.ratios_ramp,
+    });
+
+    get_pool_data(pool, data.len())
+}
+
+// custody aum cache was added, entries start stale and are filled in by the
+// refresh_aum crank
+fn migrate_pool_v2(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let pool_v2 = PoolV2::try_deserialize_unchecked(&mut &data[..])?;
+
+    get_pool_data(get_pool_from_v2(pool_v2), data.len())
+}
+
+fn get_pool_from_v2(pool_v2: PoolV2) -> Pool {
+    let custody_aum = vec![CustodyAum::default(); pool_v2.custodies.len()];
+
+    Pool {
+        version: Pool::VERSION,
+        name: pool_v2.name,
+        custodies: pool_v2.custodies,
+        ratios: pool_v2.ratios,
+        aum_usd: pool_v2.aum_usd,
+        bump: pool_v2.bump,
+        lp_token_bump: pool_v2.lp_token_bump,
+        inception_time: pool_v2.inception_time,
+        withdrawal_queue_enabled: pool_v2.withdrawal_queue_enabled,
+        permissions: pool_v2.permissions,
+        max_aum_usd: pool_v2.max_aum_usd,
+        max_open_interest_usd: pool_v2.max_open_interest_usd,
+        open_interest_usd: pool_v2.open_interest_usd,
+        ratios_ramp: pool_v2.ratios_ramp,
+        custody_aum,
+    }
+}
+
+/// Returns serialized pool of the latest layout, the account is never shrunk
+fn get_pool_data(pool: Pool, data_len: usize) -> Result<Vec<u8>> {
+    if !pool.validate() {
+        return err!(PerpetualsError::InvalidPoolConfig);
+    }
+
+    let new_len = std::cmp::max(data_len, Pool::get_len(pool.custodies.len()));
+    let mut new_data = Vec::with_capacity(new_len);
+    pool.try_serialize(&mut new_data)?;
+    new_data.resize(std::cmp::max(new_data.len(), new_len), 0);
+
+    Ok(new_data)
+}
+
+fn is_perpetuals_v0(key: &Pubkey, data: &[u8]) -> bool {
+    PerpetualsV0::try_deserialize_unchecked(&mut &data[..]).map_or(false, |perpetuals| {
+        is_pda(key, &[b"perpetuals", &[perpetuals.perpetuals_bump]])
+    })
+}
+
+// timelocks and guardians didn't exist, they start disabled
+fn migrate_perpetuals_v0(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let perpetuals_v0 = PerpetualsV0::try_deserialize_unchecked(&mut &data[..])?;
+
+    let perpetuals = Perpetuals {
+        version: Perpetuals::VERSION,
+        permissions: perpetuals_v0.permissions.to_permissions(),
+        pools: perpetuals_v0.pools,
+        transfer_authority_bump: perpetuals_v0.transfer_authority_bump,
+        perpetuals_bump: perpetuals_v0.perpetuals_bump,
+        inception_time: perpetuals_v0.inception_time,
+        timelock_delays: Default::default(),
+        guardians: Default::default(),
+    };
+
+    get_perpetuals_data(perpetuals, data.len())
+}
+
+// allow_liquidation was added to the permissions
+fn migrate_perpetuals_v1(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let perpetuals_v1 = PerpetualsV1::try_deserialize_unchecked(&mut &data[..])?;
+
+    let perpetuals = Perpetuals {
+        version: Perpetuals::VERSION,
+        permissions: perpetuals_v1.permissions.to_permissions(),
+        pools: perpetuals_v1.pools,
+        transfer_authority_bump: perpetuals_v1.transfer_authority_bump,
+        perpetuals_bump: perpetuals_v1.perpetuals_bump,
+        inception_time: perpetuals_v1.inception_time,
+        timelock_delays: perpetuals_v1.timelock_delays,
+        guardians: perpetuals_v1.guardians,
+    };
+
+    get_perpetuals_data(perpetuals, data.len())
+}
+
+/// Returns serialized perpetuals of the latest layout, the account is never shrunk
+fn get_perpetuals_data(perpetuals: Perpetuals, data_len: usize) -> Result<Vec<u8>> {
+    let new_len = std::cmp::max(
+        data_len,
+        Perpetuals::LEN + perpetuals.pools.len() * std::mem::size_of::<Pubkey>(),
+    );
+    let mut new_data = Vec::with_capacity(new_len);
+    perpetuals.try_serialize(&mut new_data)?;
+    new_data.resize(std::cmp::max(new_data.len(), new_len), 0);
+
+    Ok(new_data)
+}
+
+// owner key takes the place of the version and the layout kept its size
+fn is_position_v0(key: &Pubkey, data: &[u8]) -> bool {
+    PositionV0::try_deserialize_unchecked(&mut &data[..]).map_or(false, |position| {
+        is_pda(
+            key,
+            &[
+                b"position",
+                position.owner.as_ref(),
+                position.pool.as_ref(),
+                position.custody.as_ref(),
+                &[position.side as u8],
+                &[position.bump],
+            ],
+        )
+    })
+}
+
+fn migrate_position_v0(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let position_v0 = PositionV0::try_deserialize_unchecked(&mut &data[..])?;
+
+    let position = Position {
+        version: Position::VERSION,
+        owner: position_v0.owner,
+        pool: position_v0.pool,
+        custody: position_v0.custody,
+        open_time: position_v0.open_time,
+        update_time: position_v0.update_time,
+        side: position_v0.side,
+        price: position_v0.price,
+        size_usd: position_v0.size_usd,
+        collateral_usd: position_v0.collateral_usd,
+        unrealized_profit_usd: position_v0.unrealized_profit_usd,
+        unrealized_loss_usd: position_v0.unrealized_loss_usd,
+        cumulative_interest_snapshot: position_v0.cumulative_interest_snapshot,
+        locked_amount: position_v0.locked_amount,
+        collateral_amount: position_v0.collateral_amount,
+        bump: position_v0.bump,
+        is_cross_margin: false,
+    };
+
+    let new_len = std::cmp::max(data.len(), Position::LEN);
+    let mut new_data = Vec::with_capacity(new_len);
+    position.try_serialize(&mut new_data)?;
+    new_data.resize(new_len, 0);
+
+    Ok(new_data)
+}
+
+fn migrate_multisig_v0(data: &[u8], _accounts: &[AccountInfo]) -> Result<Vec<u8>> {
+    let deprecated_multisig = DeprecatedMultisig::try_deserialize_unchecked(&mut &data[..])?;
+
+    // signers and threshold are carried over, pending approvals are dropped
+    let multisig = Multisig {
+        version: Multisig::VERSION,
+        num_signers: deprecated_multisig.num_signers,
+        num_signed: 0,
+        min_signatures: deprecated_multisig.min_signatures,
+        instruction_accounts_len: 0,
+        instruction_data_len: 0,
+        instruction_hash: [0; 32],
+        signers: deprecated_multisig.signers,
+        signed: [false; Multisig::MAX_SIGNERS],
+        bump: deprecated_multisig.bump,
+    };
+
+    let mut new_data = Vec::with_capacity(Multisig::LEN);
+    new_data.extend_from_slice(&Multisig::DISCRIMINATOR);
+    new_data.extend_from_slice(bytemuck::bytes_of(&multisig));
+
+    Ok(new_data)
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::state::{
+            custody::{DeprecatedPricingParams, FeesStats, TradeStats, VolumeStats},
+            pool::TokenRatios,
+            position::Side,
+        },
+    };
+
+    fn serialize<T: AccountSerialize>(account: &T) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    // sets the given version and drops allow_liquidation to reproduce
+    // the layout that predates it
+    fn remove_allow_liquidation(
+        mut data: Vec<u8>,
+        version: u8,
+        permissions_offset: usize,
+        len: usize,
+    ) -> Vec<u8> {
+        data[VERSION_OFFSET] = version;
+        data.remove(permissions_offset + std::mem::size_of::<DeprecatedPermissions>());
+        data.resize(len, 0);
+        data
+    }
+
+    // serializes the custody with u64 stats counters of the version 3 layout
+    fn get_custody_v3_data(custody: &Custody) -> Vec<u8> {
+        let mut data = serialize(custody);
+        data[VERSION_OFFSET] = 3;
+
+        let stats_offset = get_custody_stats_offset(&data).unwrap();
+        let stats_len = (
+            custody.collected_fees,
+            custody.collected_token_fees,
+            custody.volume_stats,
+            custody.trade_stats,
+        )
+            .try_to_vec()
+            .unwrap()
+            .len();
+        let deprecated_stats = (
+            DeprecatedFeesStats {
+                swap_usd: custody.collected_fees.swap_usd as u64,
+                ..DeprecatedFeesStats::default()
+            },
+            DeprecatedVolumeStats {
+                swap_usd: custody.volume_stats.swap_usd as u64,
+                ..DeprecatedVolumeStats::default()
+            },
+            DeprecatedTradeStats {
+                oi_long_usd: custody.trade_stats.oi_long_usd as u64,
+                ..DeprecatedTradeStats::default()
+            },
+        )
+            .try_to_vec()
+            .unwrap();
+        data.splice(stats_offset..stats_offset + stats_len, deprecated_stats);
+
+        data
+    }
+
+    fn get_deprecated_multisig_data() -> Vec<u8> {
+        let deprecated_multisig = DeprecatedMultisig {
+            num_signers: 2,
+            num_signed: 1,
+            min_signatures: 2,
+            instruction_hash: 123,
+            signers: [Pubkey::new_unique(); Multisig::MAX_SIGNERS],
+            bump: 255,
+            ..DeprecatedMultisig::default()
+        };
+        let mut data = Vec::with_capacity(DeprecatedMultisig::LEN);
+        deprecated_multisig.try_serialize(&mut data).unwrap();
+        data.resize(DeprecatedMultisig::LEN, 0);
+        // legacy accounts keep the discriminator of the current type
+        data[..8].copy_from_slice(&Multisig::DISCRIMINATOR);
+        data
+    }
+
+    #[test]
+    fn test_get_version() {
+        let data = get_deprecated_multisig_data();
+        assert_eq!(get_version(&Pubkey::default(), &data).unwrap(), 0);
+
+        let mut data = Vec::new();
+        Custody {
+            version: Custody::VERSION,
+            ..Custody::default()
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        data.resize(Custody::LEN, 0);
+        assert_eq!(
+            get_version(&Pubkey::default(), &data).unwrap(),
+            Custody::VERSION
+        );
+
+        assert!(get_version(&Pubkey::default(), &[0; 8]).is_err());
+    }
+
+    #[test]
+    fn test_migrate_custody() {
+        let custody = Custody {
+            decimals: 6,
+            permissions: Permissions {
+                allow_close_position: true,
+                allow_liquidation: true,
+                ..Permissions::default()
+            },
+            bump: 255,
+            ..Custody::default()
+        };
+        let permissions_offset = 9
+            + 3 * 32
+            + 2
+            + OracleParams::default().try_to_vec().unwrap().len()
+            + PricingParams::default().try_to_vec().unwrap().len();
+        let data = remove_allow_liquidation(
+            get_custody_v3_data(&custody),
+            1,
+            permissions_offset,
+            Custody::LEN,
+        );
+
+        let new_data = migrate(&Pubkey::default(), &data, &[]).unwrap();
+        assert_eq!(new_data.len(), Custody::LEN);
+
+        let migrated = Custody::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(migrated.version, Custody::VERSION);
+        assert_eq!(migrated.decimals, 6);
+        assert_eq!(migrated.permissions, custody.permissions);
+        assert_eq!(migrated.bump, 255);
+        assert_eq!(migrated.status, CustodyStatus::Active);
+        assert_eq!(migrated.settlement_price, 0);
+    }
+
+    #[test]
+    fn test_migrate_deprecated_custody() {
+        let deprecated_custody = DeprecatedCustody {
+            mint: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            decimals: 9,
+            pricing: DeprecatedPricingParams {
+                min_initial_leverage: 10000,
+                max_leverage: 500000,
+                max_payoff_mult: 10000,
+                ..DeprecatedPricingParams::default()
+            },
+            borrow_rate: BorrowRateParams {
+                optimal_utilization: 800000000,
+                ..BorrowRateParams::default()
+            },
+            collected_fees: DeprecatedFeesStats {
+                swap_usd: 123,
+                ..DeprecatedFeesStats::default()
+            },
+            bump: 255,
+            ..DeprecatedCustody::default()
+        };
+        let mut data = serialize(&deprecated_custody);
+        data.resize(DeprecatedCustody::LEN, 0);
+        // legacy accounts keep the discriminator of the current type
+        data[..8].copy_from_slice(&Custody::DISCRIMINATOR);
+        assert_eq!(get_version(&Pubkey::default(), &data).unwrap(), 0);
+
+        let new_data = migrate(&Pubkey::default(), &data, &[]).unwrap();
+        assert_eq!(new_data.len(), Custody::LEN);
+
+        let migrated = Custody::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(migrated.version, Custody::VERSION);
+        assert_eq!(migrated.mint, deprecated_custody.mint);
+        assert_eq!(migrated.decimals, 9);
+        assert_eq!(migrated.pricing.min_initial_leverage, 10000);
+        assert_eq!(migrated.pricing.max_initial_leverage, 500000);
+        assert_eq!(migrated.pricing.max_leverage, 500000);
+        assert_eq!(migrated.collected_fees.swap_usd, 123);
+        assert_eq!(
+            migrated.leverage_tiers,
+            [LeverageTier::default(); Custody::MAX_LEVERAGE_TIERS]
+        );
+        assert_eq!(migrated.bump, 255);
+        assert!(migrated.validate());
+    }
+
+    #[test]
+    fn test_migrate_custody_stats() {
+        let custody = Custody {
+            decimals: 6,
+            collected_fees: FeesStats {
+                swap_usd: 123,
+                ..FeesStats::default()
+            },
+            volume_stats: VolumeStats {
+                swap_usd: u64::MAX as u128,
+                ..VolumeStats::default()
+            },
+            trade_stats: TradeStats {
+                oi_long_usd: 456,
+                ..TradeStats::default()
+            },
+            bump: 255,
+            token_account_bump: 254,
+            status: CustodyStatus::Settlement,
+            settlement_price: 789,
+            ..Custody::default()
+        };
+        let mut data = get_custody_v3_data(&custody);
+        data.resize(Custody::LEN, 0);
+
+        let new_data = migrate(&Pubkey::default(), &data, &[]).unwrap();
+        assert_eq!(new_data.len(), Custody::LEN);
+
+        let migrated = Custody::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(migrated.version, Custody::VERSION);
+        assert_eq!(migrated.decimals, 6);
+        assert_eq!(migrated.collected_fees, custody.collected_fees);
+        assert_eq!(migrated.collected_token_fees, TokenFeesStats::default());
+        assert_eq!(migrated.volume_stats, custody.volume_stats);
+        assert_eq!(migrated.trade_stats, custody.trade_stats);
+        assert_eq!(migrated.bump, 255);
+        assert_eq!(migrated.token_account_bump, 254);
+        assert_eq!(migrated.status, CustodyStatus::Settlement);
+        assert_eq!(migrated.settlement_price, 789);
+
+        assert!(migrate(&Pubkey::default(), &new_data, &[]).is_err());
+    }
+
+    #[test]
+    fn test_migrate_permissions() {
+        let permissions = Permissions {
+            allow_close_position: true,
+            allow_liquidation: true,
+            allow_size_change: true,
+            ..Permissions::default()
+        };
+
+        let perpetuals = Perpetuals {
+            permissions,
+            inception_time: 123,
+            ..Perpetuals::default()
+        };
+        let data = remove_allow_liquidation(serialize(&perpetuals), 1, 9, Perpetuals::LEN);
+        let new_data = migrate(&Pubkey::default(), &data, &[]).unwrap();
+        let migrated = Perpetuals::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(migrated.version, Perpetuals::VERSION);
+        assert_eq!(migrated.permissions, permissions);
+        assert_eq!(migrated.inception_time, 123);
+
+        let pool = Pool {
+            name: "Test Pool".to_string(),
+            custodies: vec![Pubkey::new_unique()],
+            ratios: vec![TokenRatios {
+                target: 10000,
+                min: 0,
+                max: 10000,
+            }],
+            inception_time: 123,
+            permissions,
+            max_aum_usd: 456,
+            ..Pool::default()
+        };
+        let permissions_offset = 9 + 4 + 9 + 4 + 32 + 4 + 24 + 16 + 1 + 1 + 8 + 1;
+        let data = remove_allow_liquidation(serialize(&pool), 1, permissions_offset, Pool::LEN);
+        let new_data = migrate(&Pubkey::default(), &data, &[]).unwrap();
+        let migrated = Pool::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(migrated.version, Pool::VERSION);
+        assert_eq!(migrated.name, pool.name);
+        assert_eq!(migrated.permissions, permissions);
+        assert_eq!(migrated.max_aum_usd, 456);
+        assert_eq!(migrated.custody_aum, vec![CustodyAum::default()]);
+    }
+
+    #[test]
+    fn test_migrate_pool_aum_cache() {
+        let pool = Pool {
+            version: 2,
+            name: "Test Pool".to_string(),
+            custodies: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            ratios: vec![
+                TokenRatios {
+                    target: 5000,
+                    min: 0,
+                    max: 10000,
+                };
+                2
+            ],
+            aum_usd: 789,
+            ..Pool::default()
+        };
+        // version 2 layout ends right before the cache
+        let mut data = serialize(&pool);
+        data.truncate(data.len() - 4);
+
+        let new_data = migrate(&Pubkey::default(), &data, &[]).unwrap();
+        assert_eq!(new_data.len(), Pool::get_len(2));
+
+        let migrated = Pool::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(migrated.version, Pool::VERSION);
+        assert_eq!(migrated.custodies, pool.custodies);
+        assert_eq!(migrated.aum_usd, 789);
+        assert_eq!(migrated.custody_aum, vec![CustodyAum::default(); 2]);
+        assert!(migrated.validate());
+
+        assert!(migrate(&Pubkey::default(), &new_data, &[]).is_err());
+    }
+
+    #[test]
+    fn test_migrate_multisig() {
+        let data = get_deprecated_multisig_data();
+        let new_data = migrate(&Pubkey::default(), &data, &[]).unwrap();
+
+        assert_eq!(new_data.len(), Multisig::LEN);
+        assert_eq!(
+            get_version(&Pubkey::default(), &new_data).unwrap(),
+            Multisig::VERSION
+        );
+
+        let multisig: &Multisig = bytemuck::from_bytes(&new_data[8..]);
+        assert_eq!({ multisig.num_signers }, 2);
+        assert_eq!({ multisig.num_signed }, 0);
+        assert_eq!({ multisig.min_signatures }, 2);
+        assert_eq!({ multisig.bump }, 255);
+
+        // latest layout has nothing to migrate
+        assert!(migrate(&Pubkey::default(), &new_data, &[]).is_err());
+    }
+
+    #[test]
+    fn test_migrate_legacy_pool() {
+        // single letter name makes the length prefix look like version 1
+        let (pool_key, bump) = Pubkey::find_program_address(&[b"pool", b"A"], &crate::ID);
+        let pool_v0 = PoolV0 {
+            name: "A".to_string(),
+            aum_usd: 123,
+            bump,
+            inception_time: 456,
+            ..PoolV0::default()
+        };
+        let mut data = serialize(&pool_v0);
+        data.resize(PoolV0::LEN, 0);
+        data[..8].copy_from_slice(&Pool::DISCRIMINATOR);
+        assert_eq!(data[VERSION_OFFSET], 1);
+        assert_eq!(get_version(&pool_key, &data).unwrap(), 0);
+
+        // legacy layout is only recognized at its own address
+        assert_eq!(get_version(&Pubkey::new_unique(), &data).unwrap(), 1);
+
+        let new_data = migrate(&pool_key, &data, &[]).unwrap();
+        assert_eq!(new_data.len(), Pool::get_len(0));
+
+        let migrated = Pool::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(migrated.version, Pool::VERSION);
+        assert_eq!(migrated.name, "A");
+        assert_eq!(migrated.aum_usd, 123);
+        assert_eq!(migrated.bump, bump);
+        assert_eq!(migrated.inception_time, 456);
+        assert!(migrated.permissions.allow_liquidation);
+        assert_eq!(migrated.open_interest_usd, 0);
+
+        assert!(migrate(&pool_key, &new_data, &[]).is_err());
+    }
+
+    #[test]
+    fn test_migrate_legacy_perpetuals() {
+        let (perpetuals_key, bump) = Pubkey::find_program_address(&[b"perpetuals"], &crate::ID);
+        let perpetuals_v0 = PerpetualsV0 {
+            permissions: DeprecatedPermissions {
+                allow_swap: true,
+                allow_close_position: true,
+                ..DeprecatedPermissions::default()
+            },
+            pools: vec![Pubkey::new_unique()],
+            perpetuals_bump: bump,
+            inception_time: 123,
+            ..PerpetualsV0::default()
+        };
+        let mut data = serialize(&perpetuals_v0);
+        data.resize(PerpetualsV0::LEN + 32, 0);
+        data[..8].copy_from_slice(&Perpetuals::DISCRIMINATOR);
+        assert_eq!(get_version(&perpetuals_key, &data).unwrap(), 0);
+
+        let new_data = migrate(&perpetuals_key, &data, &[]).unwrap();
+        assert_eq!(new_data.len(), Perpetuals::LEN + 32);
+
+        let migrated = Perpetuals::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(migrated.version, Perpetuals::VERSION);
+        assert_eq!(
+            migrated.permissions,
+            perpetuals_v0.permissions.to_permissions()
+        );
+        assert_eq!(migrated.pools, perpetuals_v0.pools);
+        assert_eq!(migrated.perpetuals_bump, bump);
+        assert_eq!(migrated.inception_time, 123);
+        assert_eq!(migrated.timelock_delays, Default::default());
+    }
+
+    #[test]
+    fn test_migrate_legacy_position() {
+        let owner = Pubkey::new_unique();
+        let pool = Pubkey::new_unique();
+        let custody = Pubkey::new_unique();
+        let (position_key, bump) = Pubkey::find_program_address(
+            &[
+                b"position",
+                owner.as_ref(),
+                pool.as_ref(),
+                custody.as_ref(),
+                &[Side::Long as u8],
+            ],
+            &crate::ID,
+        );
+        let position_v0 = PositionV0 {
+            owner,
+            pool,
+            custody,
+            side: Side::Long,
+            size_usd: 123,
+            collateral_amount: 456,
+            bump,
+            ..PositionV0::default()
+        };
+        let mut data = serialize(&position_v0);
+        data.resize(PositionV0::LEN, 0);
+        data[..8].copy_from_slice(&Position::DISCRIMINATOR);
+        assert_eq!(get_version(&position_key, &data).unwrap(), 0);
+
+        let new_data = migrate(&position_key, &data, &[]).unwrap();
+        assert_eq!(
+            new_data.len(),
+            std::cmp::max(PositionV0::LEN, Position::LEN)
+        );
+
+        let migrated = Position::try_deserialize(&mut &new_data[..]).unwrap();
+        assert_eq!(migrated.version, Position::VERSION);
+        assert_eq!(migrated.owner, owner);
+        assert_eq!(migrated.side, Side::Long);
+        assert_eq!(migrated.size_usd, 123);
+        assert_eq!(migrated.collateral_amount, 456);
+        assert_eq!(migrated.bump, bump);
+        assert!(!migrated.is_cross_margin);
+
+        assert!(migrate(&position_key, &new_data, &[]).is_err());
+    }
+}
@@ -2,15 +2,32 @@
 
 use {
     crate::{error::PerpetualsError, math},
-    ahash::AHasher,
     anchor_lang::prelude::*,
-    std::hash::Hasher,
+    solana_program::hash::Hasher,
 };
 
 #[repr(packed)]
 #[account(zero_copy)]
 #[derive(Default)]
 pub struct Multisig {
+    pub version: u8,
+    pub num_signers: u8,
+    // approvals, instruction and signers of the last executed proposal
+    pub num_signed: u8,
+    pub min_signatures: u8,
+    pub instruction_accounts_len: u8,
+    pub instruction_data_len: u16,
+    pub instruction_hash: [u8; 32],
+    pub signers: [Pubkey; 6], // Multisig::MAX_SIGNERS
+    pub signed: [bool; 6],    // Multisig::MAX_SIGNERS
+    pub bump: u8,
+}
+
+/// Multisig layout with the non-cryptographic instruction hash
+/// Borsh encoding of this struct matches the packed zero-copy layout.
+#[account]
+#[derive(Default, Debug)]
+pub struct DeprecatedMultisig {
     pub num_signers: u8,
     pub num_signed: u8,
     pub min_signatures: u8,
@@ -22,6 +39,31 @@ pub struct Multisig {
     pub bump: u8,
 }
 
+/// Pending admin instruction
+/// Signing flow: any signer creates the proposal (counted as the first approval),
+/// other signers approve it or revoke their approval with revoke_approval. Once
+/// approved by min_signatures signers, any signer executes the instruction by
+/// calling it with the proposal account appended to its remaining accounts.
+/// Any number of proposals can be pending.
+#[account]
+#[derive(Default, Debug)]
+pub struct Proposal {
+    pub version: u8,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub instruction_type: u8,
+    // serialized params followed by the instruction type, see Multisig::get_instruction_data
+    pub instruction_data: Vec<u8>,
+    // instruction accounts following the admin signer, without the proposal itself
+    pub instruction_accounts: Vec<Pubkey>,
+    pub instruction_hash: [u8; 32],
+    pub approvals: Vec<Pubkey>,
+    pub expiration_time: i64,
+    pub executed: bool,
+
+    pub bump: u8,
+}
+
 pub enum AdminInstruction {
     AddPool,
     RemovePool,
@@ -36,27 +78,45 @@ pub enum AdminInstruction {
     SetTestOraclePrice,
     SetTestTime,
     UpgradeCustody,
+    InitStaking,
+    SetStakingConfig,
+    SetWithdrawalQueueConfig,
+    SetFeeDistribution,
+    SetTimelockDelays,
+    QueueInstruction,
+    SetGuardians,
+    SetPoolConfig,
+    SetCustodyFees,
+    SetCustodyPricing,
+    SetCustodyOracle,
+    SetCustodyBorrowRate,
+    SetPoolRatios,
+    SetCustodyRamp,
+    SetPoolRatiosRamp,
+    SetCustodyStatus,
+    SweepCustody,
+    CancelQueued,
 }
 
 impl Multisig {
+    pub const VERSION: u8 = 1;
     pub const MAX_SIGNERS: usize = 6;
     pub const LEN: usize = 8 + std::mem::size_of::<Multisig>();
 
-    /// Returns instruction accounts and data hash.
-    /// Hash is not cryptographic and is meant to perform a fast check that admins are signing
-    /// the same instruction.
+    /// Returns SHA-256 hash of instruction accounts and data.
+    /// Admins approve the hash, so it must be collision resistant.
     pub fn get_instruction_hash(
-        instruction_accounts: &[AccountInfo],
+        instruction_accounts: &[Pubkey],
         instruction_data: &[u8],
-    ) -> u64 {
-        let mut hasher = AHasher::new_with_keys(697533735114380, 537268678243635);
+    ) -> [u8; 32] {
+        let mut hasher = Hasher::default();
         for account in instruction_accounts {
-            hasher.write(account.key.as_ref());
+            hasher.hash(account.as_ref());
         }
         if !instruction_data.is_empty() {
-            hasher.write(instruction_data);
+            hasher.hash(instruction_data);
         }
-        hasher.finish()
+        hasher.result().to_bytes()
     }
 
     /// Returns all accounts for the given context
@@ -115,12 +175,13 @@ impl Multisig {
         }
 
         *self = Multisig {
+            version: Multisig::VERSION,
             num_signers: admin_signers.len() as u8,
             num_signed: 0,
             min_signatures,
             instruction_accounts_len: 0,
             instruction_data_len: 0,
-            instruction_hash: 0,
+            instruction_hash: [0; 32],
             signers,
             signed,
             bump: self.bump,
@@ -130,92 +191,88 @@ impl Multisig {
     }
 
     /// Signs multisig and returns Ok(0) if there are enough signatures to continue or Ok(signatures_left) otherwise.
+    /// If more than one signature is required, the last instruction account must be the proposal
+    /// of this instruction. Proposal is marked as executed once it has enough approvals.
     /// If Err() is returned then signature was not recognized and transaction must be aborted.
     pub fn sign_multisig(
         &mut self,
         signer_account: &AccountInfo,
         instruction_accounts: &[AccountInfo],
         instruction_data: &[u8],
+        curtime: i64,
     ) -> Result<u8> {
         // return early if not a signer
         if !signer_account.is_signer {
             return Err(ProgramError::MissingRequiredSignature.into());
         }
 
-        // find index of current signer or return error if not found
-        let signer_idx = if let Ok(idx) = self.get_signer_index(signer_account.key) {
-            idx
-        } else {
-            return err!(PerpetualsError::MultisigAccountNotAuthorized);
-        };
+        // return error if not one of multisig signers
+        self.get_signer_index(signer_account.key)?;
 
-        // if single signer return Ok to continue
-        if self.num_signers <= 1 {
+        // if single signature is enough return Ok to continue
+        if self.num_signers <= 1 || self.min_signatures <= 1 {
             return Ok(0);
         }
 
-        let instruction_hash =
-            Multisig::get_instruction_hash(instruction_accounts, instruction_data);
-        if instruction_hash != self.instruction_hash
-            || instruction_accounts.len() != self.instruction_accounts_len as usize
-            || instruction_data.len() != self.instruction_data_len as usize
-        {
-            // if this is a new instruction reset the data
-            self.num_signed = 1;
-            self.instruction_accounts_len = instruction_accounts.len() as u8;
-            self.instruction_data_len = instruction_data.len() as u16;
-            self.instruction_hash = instruction_hash;
-            self.signed.fill(false);
-            self.signed[signer_idx] = true;
-            //multisig.pack(*multisig_account.try_borrow_mut_data()?)?;
-
-            math::checked_sub(self.min_signatures, 1)
-        } else if self.signed[signer_idx] {
-            err!(PerpetualsError::MultisigAlreadySigned)
-        } else if self.num_signed < self.min_signatures {
-            // count the signature in
-            self.num_signed += 1;
-            self.signed[signer_idx] = true;
-
-            if self.num_signed == self.min_signatures {
-                Ok(0)
-            } else {
-                math::checked_sub(self.min_signatures, self.num_signed)
-            }
-        } else {
-            err!(PerpetualsError::MultisigAlreadyExecuted)
+        // load the proposal
+        let (proposal_account, instruction_accounts) = instruction_accounts
+            .split_last()
+            .ok_or(PerpetualsError::MultisigProposalRequired)?;
+        if !Proposal::is_proposal(proposal_account) {
+            return err!(PerpetualsError::MultisigProposalRequired);
         }
-    }
+        let mut proposal = Account::<Proposal>::try_from(proposal_account)?;
 
-    /// Removes admin signature from the multisig
-    pub fn unsign_multisig(&mut self, signer_account: &AccountInfo) -> Result<()> {
-        // return early if not a signer
-        if !signer_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature.into());
+        // check that the proposal matches the instruction
+        let instruction_keys: Vec<Pubkey> = instruction_accounts.iter().map(|x| x.key()).collect();
+        let instruction_hash = Multisig::get_instruction_hash(&instruction_keys, instruction_data);
+        if instruction_hash != proposal.instruction_hash
+            || instruction_keys.len() != proposal.instruction_accounts.len()
+            || instruction_data.len() != proposal.instruction_data.len()
+        {
+            return err!(PerpetualsError::InvalidProposalState);
+        }
+        if proposal.executed {
+            return err!(PerpetualsError::MultisigAlreadyExecuted);
+        }
+        if proposal.is_expired(curtime) {
+            return err!(PerpetualsError::MultisigProposalExpired);
         }
 
-        // if single signer return
-        if self.num_signers <= 1 || self.num_signed == 0 {
-            return Ok(());
+        // count approvals of the current signers
+        let mut signed: [bool; Multisig::MAX_SIGNERS] = Default::default();
+        let mut num_signed = 0u8;
+        for approval in proposal.approvals.iter() {
+            if let Ok(idx) = self.get_signer_index(approval) {
+                signed[idx] = true;
+                num_signed += 1;
+            }
+        }
+        if num_signed < self.min_signatures {
+            return math::checked_sub(self.min_signatures, num_signed);
         }
 
-        // find index of current signer or return error if not found
-        let signer_idx = if let Ok(idx) = self.get_signer_index(signer_account.key) {
-            idx
-        } else {
-            return err!(PerpetualsError::MultisigAccountNotAuthorized);
-        };
+        // mark the proposal as executed
+        proposal.executed = true;
+        proposal.exit(&crate::ID)?;
 
-        // if not signed by this account return
-        if !self.signed[signer_idx] {
-            return Ok(());
-        }
+        self.num_signed = num_signed;
+        self.instruction_accounts_len = instruction_keys.len() as u8;
+        self.instruction_data_len = instruction_data.len() as u16;
+        self.instruction_hash = instruction_hash;
+        self.signed = signed;
 
-        // remove signature
-        self.num_signed -= 1;
-        self.signed[signer_idx] = false;
+        Ok(0)
+    }
 
-        Ok(())
+    /// Returns instruction accounts without the trailing proposal account, if any
+    pub fn strip_proposal<'a, 'info>(
+        accounts: &'a [AccountInfo<'info>],
+    ) -> &'a [AccountInfo<'info>] {
+        match accounts.split_last() {
+            Some((last, rest)) if Proposal::is_proposal(last) => rest,
+            _ => accounts,
+        }
     }
 
     /// Returns the array index of the provided signer
@@ -233,3 +290,59 @@ impl Multisig {
         Ok(self.get_signer_index(key).is_ok())
     }
 }
+
+impl DeprecatedMultisig {
+    pub const LEN: usize = 8 + 14 + Multisig::MAX_SIGNERS * 33 + 1;
+}
+
+impl AdminInstruction {
+    /// Checks if the instruction type byte maps to one of the variants,
+    /// must be updated when new variants are appended
+    pub fn is_valid(instruction_type: u8) -> bool {
+        instruction_type <= AdminInstruction::CancelQueued as u8
+    }
+}
+
+impl Proposal {
+    pub const VERSION: u8 = 1;
+    pub const MAX_INSTRUCTION_DATA_LEN: usize = 1024;
+    pub const MAX_INSTRUCTION_ACCOUNTS: usize = 64;
+
+    pub fn get_len(instruction_data_len: usize, instruction_accounts_len: usize) -> usize {
+        8 + std::mem::size_of::<Proposal>()
+            + instruction_data_len
+            + instruction_accounts_len * std::mem::size_of::<Pubkey>()
+            + Multisig::MAX_SIGNERS * std::mem::size_of::<Pubkey>()
+    }
+
+    pub fn is_proposal(account: &AccountInfo) -> bool {
+        account.owner == &crate::ID && Account::<Proposal>::try_from(account).is_ok()
+    }
+
+    pub fn is_expired(&self, curtime: i64) -> bool {
+        curtime >= self.expiration_time
+    }
+
+    /// Records approval of the signer
+    pub fn approve(&mut self, signer: &Pubkey) -> Result<()> {
+        if self.approvals.contains(signer) {
+            return err!(PerpetualsError::MultisigAlreadySigned);
+        }
+        if self.approvals.len() >= Multisig::MAX_SIGNERS {
+            return err!(PerpetualsError::InvalidProposalState);
+        }
+        self.approvals.push(*signer);
+        Ok(())
+    }
+
+    /// Removes approval of the signer, replaces unsigning of the single-slot multisig
+    pub fn revoke(&mut self, signer: &Pubkey) -> Result<()> {
+        let idx = self
+            .approvals
+            .iter()
+            .position(|x| x == signer)
+            .ok_or(PerpetualsError::MultisigAccountNotAuthorized)?;
+        self.approvals.remove(idx);
+        Ok(())
+    }
+}
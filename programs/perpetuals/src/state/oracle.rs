@@ -1,7 +1,11 @@
 //! Oracle price service handling
 
 use {
-    crate::{error::PerpetualsError, math, state::perpetuals::Perpetuals},
+    crate::{
+        error::PerpetualsError,
+        math::{self, Decimal, Rounding},
+        state::perpetuals::Perpetuals,
+    },
     anchor_lang::prelude::*,
     core::cmp::Ordering,
 };
@@ -32,6 +36,7 @@ pub struct OraclePrice {
 #[account]
 #[derive(Default, Debug)]
 pub struct TestOracle {
+    pub version: u8,
     pub price: u64,
     pub expo: i32,
     pub conf: u64,
@@ -39,6 +44,7 @@ pub struct TestOracle {
 }
 
 impl TestOracle {
+    pub const VERSION: u8 = 1;
     pub const LEN: usize = 8 + std::mem::size_of::<TestOracle>();
 }
 
@@ -101,21 +107,8 @@ impl OraclePrice {
     }
 
     // Converts token amount to USD using oracle price
-    pub fn get_asset_value_usd(&self, token_amount: u64, token_decimals: u8) -> Result<f64> {
-        if token_amount == 0 || self.price == 0 {
-            return Ok(0.0);
-        }
-        let res = token_amount as f64
-            * self.price as f64
-            * math::checked_powi(
-                10.0,
-                math::checked_sub(self.exponent, token_decimals as i32)?,
-            )?;
-        if res.is_finite() {
-            Ok(res)
-        } else {
-            err!(PerpetualsError::MathOverflow)
-        }
+    pub fn get_asset_value_usd(&self, token_amount: u64, token_decimals: u8) -> Result<Decimal> {
+        math::to_ui_amount(token_amount, token_decimals).checked_mul(&self.to_decimal()?)
     }
 
     // Converts token amount to USD with implied USD_DECIMALS decimals using oracle price
@@ -123,13 +116,8 @@ impl OraclePrice {
         if token_amount == 0 || self.price == 0 {
             return Ok(0);
         }
-        math::checked_decimal_mul(
-            token_amount,
-            -(token_decimals as i32),
-            self.price,
-            self.exponent,
-            -(Perpetuals::USD_DECIMALS as i32),
-        )
+        self.get_asset_value_usd(token_amount, token_decimals)?
+            .to_u64(Perpetuals::USD_DECIMALS, Rounding::Down)
     }
 
     // Converts USD amount with implied USD_DECIMALS decimals to token amount
@@ -137,12 +125,13 @@ impl OraclePrice {
         if asset_amount_usd == 0 || self.price == 0 {
             return Ok(0);
         }
-        math::checked_decimal_div(
-            asset_amount_usd,
-            -(Perpetuals::USD_DECIMALS as i32),
-            self.price,
-            self.exponent,
-            -(token_decimals as i32),
+        math::to_token_amount(
+            &math::to_ui_amount(asset_amount_usd, Perpetuals::USD_DECIMALS).checked_div(
+                &self.to_decimal()?,
+                token_decimals,
+                Rounding::Down,
+            )?,
+            token_decimals,
         )
     }
 
@@ -203,8 +192,8 @@ impl OraclePrice {
         }
     }
 
-    pub fn checked_as_f64(&self) -> Result<f64> {
-        math::checked_float_mul(self.price as f64, math::checked_powi(10.0, self.exponent)?)
+    pub fn to_decimal(self) -> Result<Decimal> {
+        Decimal::from_exponent(self.price, self.exponent)
     }
 
     // private helpers
@@ -292,12 +281,55 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_checked_as_f64() {
-        let price = OraclePrice::new(12300, -3);
-        assert_eq!(12.3, price.checked_as_f64().unwrap());
+    fn test_to_decimal() {
+        let price = OraclePrice::new(12300, -3).to_decimal().unwrap();
+        assert_eq!((12300, 3), (price.value, price.scale));
 
-        let price = OraclePrice::new(12300, 3);
-        assert_eq!(12300000.0, price.checked_as_f64().unwrap());
+        let price = OraclePrice::new(12300, 3).to_decimal().unwrap();
+        assert_eq!((12300000, 0), (price.value, price.scale));
+    }
+
+    #[test]
+    fn test_get_asset_amount_usd() {
+        let price = OraclePrice::new(2_512_345_678, -8);
+        let value = price.get_asset_value_usd(1_500_000_000, 9).unwrap();
+        assert_eq!((3_768_518_517_000_000_000, 17), (value.value, value.scale));
+        assert_eq!(
+            37_685_185,
+            price.get_asset_amount_usd(1_500_000_000, 9).unwrap()
+        );
+        assert_eq!(
+            1_499_999_993,
+            price.get_token_amount(37_685_185, 9).unwrap()
+        );
+
+        for (amount, decimals) in [(0, 6), (1, 0), (123_456_789, 6), (u32::MAX as u64, 9)] {
+            for (coefficient, exponent) in [(1, 0), (99_995_123, -8), (2_512_345_678, -8), (7, 3)] {
+                let price = OraclePrice::new(coefficient, exponent);
+                assert_eq!(
+                    math::checked_decimal_mul(
+                        amount,
+                        -(decimals as i32),
+                        coefficient,
+                        exponent,
+                        -(Perpetuals::USD_DECIMALS as i32),
+                    )
+                    .unwrap(),
+                    price.get_asset_amount_usd(amount, decimals).unwrap()
+                );
+                assert_eq!(
+                    math::checked_decimal_div(
+                        amount,
+                        -(Perpetuals::USD_DECIMALS as i32),
+                        coefficient,
+                        exponent,
+                        -(decimals as i32),
+                    )
+                    .unwrap(),
+                    price.get_token_amount(amount, decimals).unwrap()
+                );
+            }
+        }
     }
 
     #[test]
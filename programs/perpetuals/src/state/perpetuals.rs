@@ -1,6 +1,11 @@
 use {
+    crate::{math, state::multisig::AdminInstruction},
     anchor_lang::prelude::*,
-    anchor_spl::token::{Burn, MintTo, Transfer},
+    solana_program::{program_pack::Pack, system_instruction},
+    spl_token_2022::{
+        extension::StateWithExtensions,
+        state::{Account as TokenAccount, Mint},
+    },
 };
 
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
@@ -20,6 +25,10 @@ pub struct NewPositionPricesAndFee {
     pub entry_price: u64,
     pub liquidation_price: u64,
     pub fee: u64,
+    // leverage tier the position size falls in, 0 means base pricing params
+    pub leverage_tier: u8,
+    pub max_initial_leverage: u64,
+    pub max_leverage: u64,
 }
 
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
@@ -35,6 +44,46 @@ pub struct ProfitAndLoss {
     pub loss: u64,
 }
 
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct PositionHealth {
+    pub leverage: u64,
+    pub margin_usd: u64,
+    pub profit_usd: u64,
+    pub loss_usd: u64,
+    pub interest_usd: u64,
+    pub exit_price: u64,
+    pub exit_fee: u64,
+    pub liquidation_price: u64,
+    // distance from the current price to the liquidation price, 0 if already liquidatable
+    pub liquidation_distance_bps: u64,
+    // 0 - healthy, 1 - can be liquidated
+    pub liquidation_state: u8,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct CustodyStats {
+    // utilization and ratios have implied BPS_DECIMALS decimals
+    pub utilization: u64,
+    // borrow rates have implied RATE_DECIMALS decimals
+    pub hourly_borrow_rate: u64,
+    pub annual_borrow_rate: u64,
+    pub available_amount: u64,
+    pub current_ratio: u64,
+    pub target_ratio: u64,
+    pub oi_long_usd: u128,
+    pub oi_short_usd: u128,
+}
+
+#[derive(Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct PoolStats {
+    pub aum_usd: u128,
+    pub lp_supply: u64,
+    // lp token price has implied USD_DECIMALS decimals
+    pub lp_price_usd: u64,
+    // in the same order as pool.custodies
+    pub custodies: Vec<CustodyStats>,
+}
+
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub struct Permissions {
     pub allow_swap: bool,
@@ -45,11 +94,37 @@ pub struct Permissions {
     pub allow_pnl_withdrawal: bool,
     pub allow_collateral_withdrawal: bool,
     pub allow_size_change: bool,
+    pub allow_liquidation: bool,
+}
+
+/// Permissions layout before liquidations got a separate flag
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct DeprecatedPermissions {
+    pub allow_swap: bool,
+    pub allow_add_liquidity: bool,
+    pub allow_remove_liquidity: bool,
+    pub allow_open_position: bool,
+    pub allow_close_position: bool,
+    pub allow_pnl_withdrawal: bool,
+    pub allow_collateral_withdrawal: bool,
+    pub allow_size_change: bool,
+}
+
+/// Minimum time in seconds between queueing and execution of admin changes,
+/// zero allows the instruction to be executed directly.
+/// set_custody_config delay also applies to pool, fee distribution, staking and
+/// withdrawal queue configs and to withdrawals of pool funds.
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct TimelockDelays {
+    pub set_custody_config: i64,
+    pub set_permissions: i64,
+    pub set_admin_signers: i64,
 }
 
 #[account]
 #[derive(Default, Debug)]
 pub struct Perpetuals {
+    pub version: u8,
     pub permissions: Permissions,
     pub pools: Vec<Pubkey>,
 
@@ -57,6 +132,189 @@ pub struct Perpetuals {
     pub perpetuals_bump: u8,
     // time of inception, also used as current wall clock time for testing
     pub inception_time: i64,
+
+    pub timelock_delays: TimelockDelays,
+    // keys that can only disable permissions
+    pub guardians: [Pubkey; 6], // Perpetuals::MAX_GUARDIANS
+}
+
+/// Perpetuals layout before account versioning was introduced (version 0)
+#[account]
+#[derive(Default, Debug)]
+pub struct PerpetualsV0 {
+    pub permissions: DeprecatedPermissions,
+    pub pools: Vec<Pubkey>,
+
+    pub transfer_authority_bump: u8,
+    pub perpetuals_bump: u8,
+    pub inception_time: i64,
+}
+
+/// Perpetuals layout before liquidations got a separate permission (version 1)
+#[account]
+#[derive(Default, Debug)]
+pub struct PerpetualsV1 {
+    pub version: u8,
+    pub permissions: DeprecatedPermissions,
+    pub pools: Vec<Pubkey>,
+
+    pub transfer_authority_bump: u8,
+    pub perpetuals_bump: u8,
+    pub inception_time: i64,
+
+    pub timelock_delays: TimelockDelays,
+    pub guardians: [Pubkey; 6], // Perpetuals::MAX_GUARDIANS
+}
+
+impl Permissions {
+    /// Returns true if the new permissions don't enable anything that is
+    /// currently disabled
+    pub fn is_pause_of(&self, current: &Permissions) -> bool {
+        (!self.allow_swap || current.allow_swap)
+            && (!self.allow_add_liquidity || current.allow_add_liquidity)
+            && (!self.allow_remove_liquidity || current.allow_remove_liquidity)
+            && (!self.allow_open_position || current.allow_open_position)
+            && (!self.allow_close_position || current.allow_close_position)
+            && (!self.allow_pnl_withdrawal || current.allow_pnl_withdrawal)
+            && (!self.allow_collateral_withdrawal || current.allow_collateral_withdrawal)
+            && (!self.allow_size_change || current.allow_size_change)
+            && (!self.allow_liquidation || current.allow_liquidation)
+    }
+
+    /// Returns permissions with flags enabled only where both are enabled
+    pub fn intersect(&self, other: &Permissions) -> Permissions {
+        Permissions {
+            allow_swap: self.allow_swap && other.allow_swap,
+            allow_add_liquidity: self.allow_add_liquidity && other.allow_add_liquidity,
+            allow_remove_liquidity: self.allow_remove_liquidity && other.allow_remove_liquidity,
+            allow_open_position: self.allow_open_position && other.allow_open_position,
+            allow_close_position: self.allow_close_position && other.allow_close_position,
+            allow_pnl_withdrawal: self.allow_pnl_withdrawal && other.allow_pnl_withdrawal,
+            allow_collateral_withdrawal: self.allow_collateral_withdrawal
+                && other.allow_collateral_withdrawal,
+            allow_size_change: self.allow_size_change && other.allow_size_change,
+            allow_liquidation: self.allow_liquidation && other.allow_liquidation,
+        }
+    }
+
+    /// Returns current permissions with only the flags that differ between
+    /// `from` and `to` set to their `to` value
+    pub fn apply_changes(&self, from: &Permissions, to: &Permissions) -> Permissions {
+        let apply = |current: bool, from: bool, to: bool| if from != to { to } else { current };
+        Permissions {
+            allow_swap: apply(self.allow_swap, from.allow_swap, to.allow_swap),
+            allow_add_liquidity: apply(
+                self.allow_add_liquidity,
+                from.allow_add_liquidity,
+                to.allow_add_liquidity,
+            ),
+            allow_remove_liquidity: apply(
+                self.allow_remove_liquidity,
+                from.allow_remove_liquidity,
+                to.allow_remove_liquidity,
+            ),
+            allow_open_position: apply(
+                self.allow_open_position,
+                from.allow_open_position,
+                to.allow_open_position,
+            ),
+            allow_close_position: apply(
+                self.allow_close_position,
+                from.allow_close_position,
+                to.allow_close_position,
+            ),
+            allow_pnl_withdrawal: apply(
+                self.allow_pnl_withdrawal,
+                from.allow_pnl_withdrawal,
+                to.allow_pnl_withdrawal,
+            ),
+            allow_collateral_withdrawal: apply(
+                self.allow_collateral_withdrawal,
+                from.allow_collateral_withdrawal,
+                to.allow_collateral_withdrawal,
+            ),
+            allow_size_change: apply(
+                self.allow_size_change,
+                from.allow_size_change,
+                to.allow_size_change,
+            ),
+            allow_liquidation: apply(
+                self.allow_liquidation,
+                from.allow_liquidation,
+                to.allow_liquidation,
+            ),
+        }
+    }
+}
+
+impl DeprecatedPermissions {
+    /// Liquidations used to be gated by allow_close_position
+    pub fn to_permissions(&self) -> Permissions {
+        Permissions {
+            allow_swap: self.allow_swap,
+            allow_add_liquidity: self.allow_add_liquidity,
+            allow_remove_liquidity: self.allow_remove_liquidity,
+            allow_open_position: self.allow_open_position,
+            allow_close_position: self.allow_close_position,
+            allow_pnl_withdrawal: self.allow_pnl_withdrawal,
+            allow_collateral_withdrawal: self.allow_collateral_withdrawal,
+            allow_size_change: self.allow_size_change,
+            allow_liquidation: self.allow_close_position,
+        }
+    }
+}
+
+impl PerpetualsV0 {
+    pub const LEN: usize = 8 + std::mem::size_of::<PerpetualsV0>();
+}
+
+impl TimelockDelays {
+    pub fn validate(&self) -> bool {
+        self.set_custody_config >= 0 && self.set_permissions >= 0 && self.set_admin_signers >= 0
+    }
+
+    /// Returns true if none of the delays is reduced
+    pub fn is_extension_of(&self, current: &TimelockDelays) -> bool {
+        self.set_custody_config >= current.set_custody_config
+            && self.set_permissions >= current.set_permissions
+            && self.set_admin_signers >= current.set_admin_signers
+    }
+
+    /// Returns timelock delay of the given admin instruction type or None
+    /// if the instruction can't be queued
+    pub fn get_delay(&self, instruction_type: u8) -> Option<i64> {
+        if instruction_type == AdminInstruction::SetCustodyConfig as u8
+            || instruction_type == AdminInstruction::SetCustodyFees as u8
+            || instruction_type == AdminInstruction::SetCustodyPricing as u8
+            || instruction_type == AdminInstruction::SetCustodyOracle as u8
+            || instruction_type == AdminInstruction::SetCustodyBorrowRate as u8
+            || instruction_type == AdminInstruction::SetPoolRatios as u8
+            || instruction_type == AdminInstruction::SetCustodyRamp as u8
+            || instruction_type == AdminInstruction::SetPoolRatiosRamp as u8
+            || instruction_type == AdminInstruction::SetCustodyStatus as u8
+            || instruction_type == AdminInstruction::SetPoolConfig as u8
+            || instruction_type == AdminInstruction::SetFeeDistribution as u8
+            || instruction_type == AdminInstruction::SetStakingConfig as u8
+            || instruction_type == AdminInstruction::SetWithdrawalQueueConfig as u8
+            || instruction_type == AdminInstruction::WithdrawFees as u8
+            || instruction_type == AdminInstruction::WithdrawSolFees as u8
+            || instruction_type == AdminInstruction::SweepCustody as u8
+        {
+            Some(self.set_custody_config)
+        } else if instruction_type == AdminInstruction::SetPermissions as u8 {
+            Some(self.set_permissions)
+        } else if instruction_type == AdminInstruction::SetAdminSigners as u8 {
+            Some(self.set_admin_signers)
+        } else if instruction_type == AdminInstruction::SetTimelockDelays as u8 {
+            // reducing delays is subject to the longest one
+            Some(std::cmp::max(
+                self.set_custody_config,
+                std::cmp::max(self.set_permissions, self.set_admin_signers),
+            ))
+        } else {
+            None
+        }
+    }
 }
 
 impl anchor_lang::Id for Perpetuals {
@@ -66,6 +324,7 @@ impl anchor_lang::Id for Perpetuals {
 }
 
 impl Perpetuals {
+    pub const VERSION: u8 = 2;
     pub const LEN: usize = 8 + std::mem::size_of::<Perpetuals>();
     pub const BPS_DECIMALS: u8 = 4;
     pub const BPS_POWER: u128 = 10i64.pow(Self::BPS_DECIMALS as u32) as u128;
@@ -74,9 +333,14 @@ impl Perpetuals {
     pub const LP_DECIMALS: u8 = Self::USD_DECIMALS;
     pub const RATE_DECIMALS: u8 = 9;
     pub const RATE_POWER: u128 = 10i64.pow(Self::RATE_DECIMALS as u32) as u128;
+    pub const MAX_GUARDIANS: usize = 6;
 
     pub fn validate(&self) -> bool {
-        true
+        self.timelock_delays.validate()
+    }
+
+    pub fn is_guardian(&self, key: &Pubkey) -> bool {
+        *key != Pubkey::default() && self.guardians.contains(key)
     }
 
     #[cfg(feature = "test")]
@@ -94,10 +358,52 @@ impl Perpetuals {
         }
     }
 
+    pub fn is_token_program(key: &Pubkey) -> bool {
+        *key == anchor_spl::token::ID || *key == spl_token_2022::ID
+    }
+
+    /// Unpacks SPL Token or Token-2022 account, extensions are ignored
+    pub fn get_token_account(token_account: &AccountInfo) -> Result<TokenAccount> {
+        require!(
+            Self::is_token_program(token_account.owner),
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        let data = token_account.try_borrow_data()?;
+        Ok(StateWithExtensions::<TokenAccount>::unpack(&data)?.base)
+    }
+
+    pub fn is_token_account(token_account: &AccountInfo, mint: &Pubkey, owner: &Pubkey) -> bool {
+        if let Ok(account) = Self::get_token_account(token_account) {
+            account.mint == *mint && account.owner == *owner
+        } else {
+            false
+        }
+    }
+
+    /// Native SOL is accepted in place of a token account for the native mint,
+    /// in which case the owner's wallet is passed as the token account
+    pub fn is_native_account(account: &AccountInfo, mint: &Pubkey, owner: &Pubkey) -> bool {
+        *mint == anchor_spl::token::spl_token::native_mint::ID && account.key == owner
+    }
+
+    pub fn get_token_balance(token_account: &AccountInfo) -> Result<u64> {
+        Ok(Self::get_token_account(token_account)?.amount)
+    }
+
+    pub fn get_mint_decimals(mint: &AccountInfo) -> Result<u8> {
+        require!(
+            Self::is_token_program(mint.owner),
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        let data = mint.try_borrow_data()?;
+        Ok(StateWithExtensions::<Mint>::unpack(&data)?.base.decimals)
+    }
+
     pub fn transfer_tokens<'info>(
         &self,
         from: AccountInfo<'info>,
         to: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
         authority: AccountInfo<'info>,
         token_program: AccountInfo<'info>,
         amount: u64,
@@ -105,36 +411,120 @@ impl Perpetuals {
         let authority_seeds: &[&[&[u8]]] =
             &[&[b"transfer_authority", &[self.transfer_authority_bump]]];
 
-        let context = CpiContext::new(
+        Self::transfer_checked(
+            from,
+            to,
+            mint,
+            authority,
             token_program,
-            Transfer {
-                from,
-                to,
-                authority,
-            },
+            amount,
+            authority_seeds,
         )
-        .with_signer(authority_seeds);
-
-        anchor_spl::token::transfer(context, amount)
     }
 
+    /// Returns the amount credited to the destination account,
+    /// which is less than the requested amount for mints with transfer fees
     pub fn transfer_tokens_from_user<'info>(
         &self,
         from: AccountInfo<'info>,
         to: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
         authority: AccountInfo<'info>,
         token_program: AccountInfo<'info>,
         amount: u64,
-    ) -> Result<()> {
-        let context = CpiContext::new(
+    ) -> Result<u64> {
+        let balance_before = Self::get_token_balance(&to)?;
+
+        Self::transfer_checked(
+            from,
+            to.clone(),
+            mint,
+            authority,
             token_program,
-            Transfer {
-                from,
-                to,
-                authority,
-            },
-        );
-        anchor_spl::token::transfer(context, amount)
+            amount,
+            &[],
+        )?;
+
+        math::checked_sub(Self::get_token_balance(&to)?, balance_before)
+    }
+
+    /// Wraps lamports of the user through a temporary token account
+    /// and transfers them to the destination token account
+    #[allow(clippy::too_many_arguments)]
+    pub fn wrap_sol_from_user<'info>(
+        &self,
+        owner: AccountInfo<'info>,
+        wsol_account: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        wsol_account_seeds: &[&[u8]],
+        amount: u64,
+    ) -> Result<u64> {
+        Self::create_wsol_account(
+            owner.clone(),
+            wsol_account.clone(),
+            mint.clone(),
+            authority.clone(),
+            token_program.clone(),
+            system_program,
+            wsol_account_seeds,
+            amount,
+        )?;
+
+        self.transfer_tokens(
+            wsol_account.clone(),
+            to,
+            mint,
+            authority.clone(),
+            token_program.clone(),
+            amount,
+        )?;
+
+        // returns the rent-exempt reserve to the user
+        self.close_wsol_account(owner, wsol_account, authority, token_program)?;
+
+        Ok(amount)
+    }
+
+    /// Transfers tokens from the source token account to a temporary token account
+    /// and unwraps them to lamports of the user
+    #[allow(clippy::too_many_arguments)]
+    pub fn unwrap_sol_to_user<'info>(
+        &self,
+        from: AccountInfo<'info>,
+        owner: AccountInfo<'info>,
+        wsol_account: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        wsol_account_seeds: &[&[u8]],
+        amount: u64,
+    ) -> Result<()> {
+        Self::create_wsol_account(
+            owner.clone(),
+            wsol_account.clone(),
+            mint.clone(),
+            authority.clone(),
+            token_program.clone(),
+            system_program,
+            wsol_account_seeds,
+            0,
+        )?;
+
+        self.transfer_tokens(
+            from,
+            wsol_account.clone(),
+            mint,
+            authority.clone(),
+            token_program.clone(),
+            amount,
+        )?;
+
+        self.close_wsol_account(owner, wsol_account, authority, token_program)
     }
 
     pub fn mint_tokens<'info>(
@@ -148,17 +538,21 @@ impl Perpetuals {
         let authority_seeds: &[&[&[u8]]] =
             &[&[b"transfer_authority", &[self.transfer_authority_bump]]];
 
-        let context = CpiContext::new(
-            token_program,
-            MintTo {
-                mint,
-                to,
-                authority,
-            },
-        )
-        .with_signer(authority_seeds);
+        let instruction = spl_token_2022::instruction::mint_to(
+            token_program.key,
+            mint.key,
+            to.key,
+            authority.key,
+            &[],
+            amount,
+        )?;
 
-        anchor_spl::token::mint_to(context, amount)
+        solana_program::program::invoke_signed(
+            &instruction,
+            &[mint, to, authority, token_program],
+            authority_seeds,
+        )
+        .map_err(|e| e.into())
     }
 
     pub fn burn_tokens<'info>(
@@ -169,16 +563,24 @@ impl Perpetuals {
         token_program: AccountInfo<'info>,
         amount: u64,
     ) -> Result<()> {
-        let context = CpiContext::new(
-            token_program,
-            Burn {
-                mint,
-                from,
-                authority,
-            },
-        );
+        let authority_seeds: &[&[&[u8]]] =
+            &[&[b"transfer_authority", &[self.transfer_authority_bump]]];
+
+        let instruction = spl_token_2022::instruction::burn(
+            token_program.key,
+            from.key,
+            mint.key,
+            authority.key,
+            &[],
+            amount,
+        )?;
 
-        anchor_spl::token::burn(context, amount)
+        solana_program::program::invoke_signed(
+            &instruction,
+            &[from, mint, authority, token_program],
+            authority_seeds,
+        )
+        .map_err(|e| e.into())
     }
 
     pub fn is_empty_account(account_info: &AccountInfo) -> Result<bool> {
@@ -192,14 +594,20 @@ impl Perpetuals {
         authority: AccountInfo<'info>,
         seeds: &[&[&[u8]]],
     ) -> Result<()> {
-        let cpi_accounts = anchor_spl::token::CloseAccount {
-            account: token_account,
-            destination: receiver,
-            authority,
-        };
-        let cpi_context = anchor_lang::context::CpiContext::new(token_program, cpi_accounts);
+        let instruction = spl_token_2022::instruction::close_account(
+            token_program.key,
+            token_account.key,
+            receiver.key,
+            authority.key,
+            &[],
+        )?;
 
-        anchor_spl::token::close_account(cpi_context.with_signer(seeds))
+        solana_program::program::invoke_signed(
+            &instruction,
+            &[token_account, receiver, authority, token_program],
+            seeds,
+        )
+        .map_err(|e| e.into())
     }
 
     pub fn transfer_sol_from_owned<'a>(
@@ -256,4 +664,112 @@ impl Perpetuals {
             .realloc(new_len, zero_init)
             .map_err(|_| ProgramError::InvalidRealloc.into())
     }
+
+    // creates the token account of the native mint funded with the given amount of lamports,
+    // the address can be pre-funded by anyone so it is topped up rather than created in that case
+    #[allow(clippy::too_many_arguments)]
+    fn create_wsol_account<'info>(
+        payer: AccountInfo<'info>,
+        wsol_account: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        wsol_account_seeds: &[&[u8]],
+        amount: u64,
+    ) -> Result<()> {
+        let space = TokenAccount::LEN;
+        let lamports = math::checked_add(Rent::get()?.minimum_balance(space), amount)?;
+        let current_lamports = wsol_account.try_lamports()?;
+
+        if current_lamports == 0 {
+            solana_program::program::invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    wsol_account.key,
+                    lamports,
+                    space as u64,
+                    token_program.key,
+                ),
+                &[payer, wsol_account.clone(), system_program],
+                &[wsol_account_seeds],
+            )?;
+        } else {
+            Self::transfer_sol(
+                payer,
+                wsol_account.clone(),
+                system_program.clone(),
+                lamports.saturating_sub(current_lamports),
+            )?;
+            solana_program::program::invoke_signed(
+                &system_instruction::allocate(wsol_account.key, space as u64),
+                &[wsol_account.clone(), system_program.clone()],
+                &[wsol_account_seeds],
+            )?;
+            solana_program::program::invoke_signed(
+                &system_instruction::assign(wsol_account.key, token_program.key),
+                &[wsol_account.clone(), system_program],
+                &[wsol_account_seeds],
+            )?;
+        }
+
+        let instruction = spl_token_2022::instruction::initialize_account3(
+            token_program.key,
+            wsol_account.key,
+            mint.key,
+            authority.key,
+        )?;
+
+        solana_program::program::invoke(&instruction, &[wsol_account, mint, token_program])
+            .map_err(|e| e.into())
+    }
+
+    fn close_wsol_account<'info>(
+        &self,
+        receiver: AccountInfo<'info>,
+        wsol_account: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+    ) -> Result<()> {
+        let authority_seeds: &[&[&[u8]]] =
+            &[&[b"transfer_authority", &[self.transfer_authority_bump]]];
+
+        Self::close_token_account(
+            receiver,
+            wsol_account,
+            token_program,
+            authority,
+            authority_seeds,
+        )
+    }
+
+    // transfer_checked is supported by both token programs and is required by Token-2022
+    // for mints with transfer fees
+    fn transfer_checked<'info>(
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        amount: u64,
+        seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let instruction = spl_token_2022::instruction::transfer_checked(
+            token_program.key,
+            from.key,
+            mint.key,
+            to.key,
+            authority.key,
+            &[],
+            amount,
+            Self::get_mint_decimals(&mint)?,
+        )?;
+
+        solana_program::program::invoke_signed(
+            &instruction,
+            &[from, mint, to, authority, token_program],
+            seeds,
+        )
+        .map_err(|e| e.into())
+    }
 }
@@ -1,11 +1,11 @@
 use {
     crate::{
         error::PerpetualsError,
-        math,
+        math::{self, Decimal, Rounding},
         state::{
             custody::{Custody, FeesMode},
             oracle::OraclePrice,
-            perpetuals::Perpetuals,
+            perpetuals::{CustodyStats, DeprecatedPermissions, Permissions, Perpetuals},
             position::{Position, Side},
         },
     },
@@ -28,9 +28,61 @@ pub struct TokenRatios {
     pub max: u64,
 }
 
+/// Linear transition of token ratio targets towards the values set in
+/// pool.ratios. Holds the targets in effect at start_time.
+#[derive(Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct RatiosRamp {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub start_targets: Vec<u64>,
+}
+
+/// Cached contribution of a custody to the pool AUM. It is refreshed
+/// whenever the custody is touched or by the refresh_aum crank.
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct CustodyAum {
+    // value of owned tokens at the last and ema prices
+    pub last_usd: u64,
+    pub ema_usd: u64,
+    // aggregate unrealized pnl of open positions, if included in aum
+    pub profit_usd: u64,
+    pub loss_usd: u64,
+    // time the prices were read at
+    pub price_time: i64,
+    // entry can't be used past this age, follows custody oracle config
+    pub max_age_sec: u32,
+}
+
 #[account]
 #[derive(Default, Debug)]
 pub struct Pool {
+    pub version: u8,
+    pub name: String,
+    pub custodies: Vec<Pubkey>,
+    pub ratios: Vec<TokenRatios>,
+    pub aum_usd: u128,
+
+    pub bump: u8,
+    pub lp_token_bump: u8,
+    pub inception_time: i64,
+    // LP tokens can only be redeemed through the withdrawal queue
+    pub withdrawal_queue_enabled: bool,
+
+    pub permissions: Permissions,
+    // pool-wide limits, zero means no limit
+    pub max_aum_usd: u128,
+    pub max_open_interest_usd: u64,
+    // total size of open positions across pool custodies
+    pub open_interest_usd: u64,
+    pub ratios_ramp: RatiosRamp,
+    // parallel to custodies
+    pub custody_aum: Vec<CustodyAum>,
+}
+
+/// Pool layout before account versioning was introduced (version 0)
+#[account]
+#[derive(Default, Debug)]
+pub struct PoolV0 {
     pub name: String,
     pub custodies: Vec<Pubkey>,
     pub ratios: Vec<TokenRatios>,
@@ -41,6 +93,50 @@ pub struct Pool {
     pub inception_time: i64,
 }
 
+/// Pool layout before liquidations got a separate permission (version 1)
+#[account]
+#[derive(Default, Debug)]
+pub struct PoolV1 {
+    pub version: u8,
+    pub name: String,
+    pub custodies: Vec<Pubkey>,
+    pub ratios: Vec<TokenRatios>,
+    pub aum_usd: u128,
+
+    pub bump: u8,
+    pub lp_token_bump: u8,
+    pub inception_time: i64,
+    pub withdrawal_queue_enabled: bool,
+
+    pub permissions: DeprecatedPermissions,
+    pub max_aum_usd: u128,
+    pub max_open_interest_usd: u64,
+    pub open_interest_usd: u64,
+    pub ratios_ramp: RatiosRamp,
+}
+
+/// Pool layout before the custody aum cache was added (version 2)
+#[account]
+#[derive(Default, Debug)]
+pub struct PoolV2 {
+    pub version: u8,
+    pub name: String,
+    pub custodies: Vec<Pubkey>,
+    pub ratios: Vec<TokenRatios>,
+    pub aum_usd: u128,
+
+    pub bump: u8,
+    pub lp_token_bump: u8,
+    pub inception_time: i64,
+    pub withdrawal_queue_enabled: bool,
+
+    pub permissions: Permissions,
+    pub max_aum_usd: u128,
+    pub max_open_interest_usd: u64,
+    pub open_interest_usd: u64,
+    pub ratios_ramp: RatiosRamp,
+}
+
 impl TokenRatios {
     pub fn validate(&self) -> bool {
         (self.target as u128) <= Perpetuals::BPS_POWER
@@ -51,13 +147,49 @@ impl TokenRatios {
     }
 }
 
+impl CustodyAum {
+    pub fn is_stale(&self, curtime: i64) -> bool {
+        curtime.saturating_sub(self.price_time) > self.max_age_sec as i64
+    }
+
+    /// Adds custody contribution to the pool amount
+    pub fn add_to_pool_amount(
+        &self,
+        pool_amount_usd: u128,
+        aum_calc_mode: AumCalcMode,
+    ) -> Result<u128> {
+        let token_amount_usd = match aum_calc_mode {
+            AumCalcMode::Last => self.last_usd,
+            AumCalcMode::EMA => self.ema_usd,
+            AumCalcMode::Min => std::cmp::min(self.last_usd, self.ema_usd),
+            AumCalcMode::Max => std::cmp::max(self.last_usd, self.ema_usd),
+        };
+
+        let pool_amount_usd = math::checked_add(pool_amount_usd, token_amount_usd as u128)?;
+        let pool_amount_usd = math::checked_add(pool_amount_usd, self.profit_usd as u128)?;
+
+        Ok(pool_amount_usd.saturating_sub(self.loss_usd as u128))
+    }
+}
+
 /// Token Pool
 /// All returned prices are scaled to PRICE_DECIMALS.
 /// All returned amounts are scaled to corresponding custody decimals.
 ///
 impl Pool {
+    pub const VERSION: u8 = 3;
     pub const LEN: usize = 8 + std::mem::size_of::<Pool>();
 
+    /// Returns account size required for the given number of custodies
+    pub fn get_len(num_custodies: usize) -> usize {
+        Pool::LEN
+            + num_custodies
+                * (std::mem::size_of::<Pubkey>()
+                    + std::mem::size_of::<TokenRatios>()
+                    + std::mem::size_of::<u64>()
+                    + std::mem::size_of::<CustodyAum>())
+    }
+
     pub fn validate(&self) -> bool {
         for ratio in &self.ratios {
             if !ratio.validate() {
@@ -84,7 +216,86 @@ impl Pool {
             }
         }
 
-        !self.name.is_empty() && self.name.len() <= 64 && self.custodies.len() == self.ratios.len()
+        !self.name.is_empty()
+            && self.name.len() <= 64
+            && self.custodies.len() == self.ratios.len()
+            && self.custodies.len() == self.custody_aum.len()
+            && self.ratios_ramp.start_time <= self.ratios_ramp.end_time
+    }
+
+    pub fn check_aum_limit(&self, aum_usd: u128) -> bool {
+        self.max_aum_usd == 0 || aum_usd <= self.max_aum_usd
+    }
+
+    pub fn add_open_interest(&mut self, size_usd: u64) -> Result<()> {
+        let open_interest_usd = math::checked_add(self.open_interest_usd, size_usd)?;
+        require!(
+            self.max_open_interest_usd == 0 || open_interest_usd <= self.max_open_interest_usd,
+            PerpetualsError::PoolAmountLimit
+        );
+        self.open_interest_usd = open_interest_usd;
+        Ok(())
+    }
+
+    pub fn remove_open_interest(&mut self, size_usd: u64) -> Result<()> {
+        self.open_interest_usd = math::checked_sub(self.open_interest_usd, size_usd)?;
+        Ok(())
+    }
+
+    /// Computes pool open interest from the position stats of the pool
+    /// custodies, used to initialize the counter for existing pools
+    pub fn get_open_interest_usd(&self, accounts: &[AccountInfo]) -> Result<u64> {
+        if accounts.len() < self.custodies.len() {
+            return Err(ProgramError::NotEnoughAccountKeys.into());
+        }
+        let mut open_interest_usd: u64 = 0;
+        for (idx, &custody) in self.custodies.iter().enumerate() {
+            require_keys_eq!(accounts[idx].key(), custody);
+            let custody = Account::<Custody>::try_from(&accounts[idx])?;
+            require!(
+                custody.version == Custody::VERSION,
+                PerpetualsError::InvalidAccountVersion
+            );
+            open_interest_usd = math::checked_add(
+                open_interest_usd,
+                math::checked_add(
+                    custody.long_positions.size_usd,
+                    custody.short_positions.size_usd,
+                )?,
+            )?;
+        }
+        Ok(open_interest_usd)
+    }
+
+    /// Starts ramping token ratio targets from their current values to the
+    /// ones set in pool.ratios by end_time
+    pub fn start_ratios_ramp(&mut self, curtime: i64, end_time: i64) -> Result<()> {
+        if end_time < curtime {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let start_targets = (0..self.ratios.len())
+            .map(|token_id| self.get_target_ratio(token_id, curtime))
+            .collect();
+        self.ratios_ramp = RatiosRamp {
+            start_time: curtime,
+            end_time,
+            start_targets,
+        };
+        Ok(())
+    }
+
+    pub fn get_target_ratio(&self, token_id: usize, curtime: i64) -> u64 {
+        let target = self.ratios[token_id].target;
+        if self.ratios_ramp.start_targets.len() != self.ratios.len() {
+            return target;
+        }
+        math::interpolate(
+            self.ratios_ramp.start_targets[token_id],
+            target,
+            self.ratios_ramp.start_time,
+            self.ratios_ramp.end_time,
+            curtime,
+        )
     }
 
     pub fn get_token_id(&self, custody: &Pubkey) -> Result<usize> {
@@ -100,16 +311,13 @@ impl Pool {
         token_ema_price: &OraclePrice,
         side: Side,
         custody: &Custody,
+        curtime: i64,
     ) -> Result<u64> {
         let price = self.get_price(
             token_price,
             token_ema_price,
             side,
-            if side == Side::Long {
-                custody.pricing.trade_spread_long
-            } else {
-                custody.pricing.trade_spread_short
-            },
+            custody.get_trade_spread(side, curtime),
         )?;
         require_gt!(price.price, 0, PerpetualsError::MaxPriceSlippage);
 
@@ -177,20 +385,18 @@ impl Pool {
         token_ema_price: &OraclePrice,
         side: Side,
         custody: &Custody,
+        curtime: i64,
     ) -> Result<u64> {
+        let exit_side = if side == Side::Long {
+            Side::Short
+        } else {
+            Side::Long
+        };
         let price = self.get_price(
             token_price,
             token_ema_price,
-            if side == Side::Long {
-                Side::Short
-            } else {
-                Side::Long
-            },
-            if side == Side::Long {
-                custody.pricing.trade_spread_short
-            } else {
-                custody.pricing.trade_spread_long
-            },
+            exit_side,
+            custody.get_trade_spread(exit_side, curtime),
         )?;
 
         Ok(price
@@ -252,6 +458,7 @@ impl Pool {
         token_out_price: &OraclePrice,
         token_out_ema_price: &OraclePrice,
         custody_in: &Custody,
+        curtime: i64,
     ) -> Result<OraclePrice> {
         let min_price = if token_in_price < token_in_ema_price {
             token_in_price
@@ -271,7 +478,7 @@ impl Pool {
             &pair_price,
             &pair_price,
             Side::Short,
-            custody_in.pricing.swap_spread,
+            custody_in.get_swap_spread(curtime),
         )
     }
 
@@ -285,6 +492,7 @@ impl Pool {
         custody_in: &Custody,
         custody_out: &Custody,
         amount_in: u64,
+        curtime: i64,
     ) -> Result<u64> {
         let swap_price = self.get_swap_price(
             token_in_price,
@@ -292,15 +500,12 @@ impl Pool {
             token_out_price,
             token_out_ema_price,
             custody_in,
+            curtime,
         )?;
 
-        math::checked_decimal_mul(
-            amount_in,
-            -(custody_in.decimals as i32),
-            swap_price.price,
-            swap_price.exponent,
-            -(custody_out.decimals as i32),
-        )
+        math::to_ui_amount(amount_in, custody_in.decimals)
+            .checked_mul(&swap_price.to_decimal()?)?
+            .to_u64(custody_out.decimals, Rounding::Down)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -314,6 +519,7 @@ impl Pool {
         token_price_in: &OraclePrice,
         custody_out: &Custody,
         token_price_out: &OraclePrice,
+        curtime: i64,
     ) -> Result<(u64, u64)> {
         let stable_swap = custody_in.is_stable && custody_out.is_stable;
 
@@ -328,6 +534,7 @@ impl Pool {
             0u64,
             custody_in,
             token_price_in,
+            curtime,
         )?;
 
         let swap_out_fee = self.get_fee(
@@ -341,6 +548,7 @@ impl Pool {
             amount_out,
             custody_out,
             token_price_out,
+            curtime,
         )?;
 
         Ok((swap_in_fee, swap_out_fee))
@@ -352,6 +560,7 @@ impl Pool {
         amount: u64,
         custody: &Custody,
         token_price: &OraclePrice,
+        curtime: i64,
     ) -> Result<u64> {
         self.get_fee(
             token_id,
@@ -360,6 +569,7 @@ impl Pool {
             0u64,
             custody,
             token_price,
+            curtime,
         )
     }
 
@@ -369,6 +579,7 @@ impl Pool {
         amount: u64,
         custody: &Custody,
         token_price: &OraclePrice,
+        curtime: i64,
     ) -> Result<u64> {
         self.get_fee(
             token_id,
@@ -377,6 +588,7 @@ impl Pool {
             amount,
             custody,
             token_price,
+            curtime,
         )
     }
 
@@ -394,21 +606,75 @@ impl Pool {
     ) -> Result<bool> {
         let new_ratio = self.get_new_ratio(amount_add, amount_remove, custody, token_price)?;
 
+        Ok(self.check_ratio_change(
+            token_id,
+            self.get_current_ratio(custody, token_price)?,
+            new_ratio,
+        ))
+    }
+
+    /// Returns true if the new ratio is within limits or moves towards them
+    pub fn check_ratio_change(&self, token_id: usize, current_ratio: u64, new_ratio: u64) -> bool {
         if new_ratio < self.ratios[token_id].min {
-            Ok(new_ratio >= self.get_current_ratio(custody, token_price)?)
+            new_ratio >= current_ratio
         } else if new_ratio > self.ratios[token_id].max {
-            Ok(new_ratio <= self.get_current_ratio(custody, token_price)?)
+            new_ratio <= current_ratio
         } else {
-            Ok(true)
+            true
         }
     }
 
     pub fn check_available_amount(&self, amount: u64, custody: &Custody) -> Result<bool> {
-        let available_amount = math::checked_sub(
+        let available_amount = self.get_available_amount(custody)?;
+        Ok(available_amount >= amount)
+    }
+
+    pub fn get_available_amount(&self, custody: &Custody) -> Result<u64> {
+        math::checked_sub(
             math::checked_add(custody.assets.owned, custody.assets.collateral)?,
             custody.assets.locked,
-        )?;
-        Ok(available_amount >= amount)
+        )
+    }
+
+    /// Returns utilization, borrow rates and ratios of the custody,
+    /// pool AUM is expected in USD with implied USD_DECIMALS decimals
+    pub fn get_custody_stats(
+        &self,
+        token_id: usize,
+        custody: &Custody,
+        token_ema_price: &OraclePrice,
+        aum_usd: u128,
+        curtime: i64,
+    ) -> Result<CustodyStats> {
+        let utilization = math::checked_as_u64(math::checked_div(
+            math::checked_mul(custody.get_utilization()?, Perpetuals::BPS_POWER)?,
+            Perpetuals::RATE_POWER,
+        )?)?;
+
+        let hourly_borrow_rate = custody.get_borrow_rate(curtime)?;
+        let annual_borrow_rate = math::checked_mul(hourly_borrow_rate, 24 * 365)?;
+
+        let current_ratio = if aum_usd > 0 {
+            let custody_usd =
+                token_ema_price.get_asset_amount_usd(custody.assets.owned, custody.decimals)?;
+            math::checked_as_u64(math::checked_div(
+                math::checked_mul(custody_usd as u128, Perpetuals::BPS_POWER)?,
+                aum_usd,
+            )?)?
+        } else {
+            0
+        };
+
+        Ok(CustodyStats {
+            utilization,
+            hourly_borrow_rate,
+            annual_borrow_rate,
+            available_amount: self.get_available_amount(custody)?,
+            current_ratio,
+            target_ratio: self.get_target_ratio(token_id, curtime),
+            oi_long_usd: custody.trade_stats.oi_long_usd,
+            oi_short_usd: custody.trade_stats.oi_short_usd,
+        })
     }
 
     pub fn get_leverage(
@@ -449,11 +715,25 @@ impl Pool {
         initial: bool,
     ) -> Result<bool> {
         let current_leverage = self.get_leverage(position, token_price, custody, curtime)?;
+        let (_, max_initial_leverage, max_leverage) =
+            custody.get_leverage_tier(position.size_usd, curtime);
 
-        Ok(current_leverage <= custody.pricing.max_leverage
+        Ok(current_leverage <= max_leverage
             && (!initial
                 || (current_leverage >= custody.pricing.min_initial_leverage
-                    && current_leverage <= custody.pricing.max_initial_leverage)))
+                    && current_leverage <= max_initial_leverage)))
+    }
+
+    pub fn get_maintenance_margin_usd(
+        position: &Position,
+        custody: &Custody,
+        curtime: i64,
+    ) -> Result<u64> {
+        let (_, _, max_leverage) = custody.get_leverage_tier(position.size_usd, curtime);
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(position.size_usd as u128, Perpetuals::BPS_POWER)?,
+            max_leverage as u128,
+        )?)
     }
 
     pub fn get_liquidation_price(
@@ -478,10 +758,7 @@ impl Pool {
             position.unrealized_loss_usd,
         )?;
 
-        let max_loss_usd = math::checked_as_u64(math::checked_div(
-            math::checked_mul(position.size_usd as u128, Perpetuals::BPS_POWER)?,
-            custody.pricing.max_leverage as u128,
-        )?)?;
+        let max_loss_usd = Self::get_maintenance_margin_usd(position, custody, curtime)?;
         let max_loss_usd = math::checked_add(max_loss_usd, unrealized_loss_usd)?;
 
         let margin_usd =
@@ -549,8 +826,13 @@ impl Pool {
             token_ema_price
         };
 
-        let exit_price =
-            self.get_exit_price(token_price, token_ema_price, position.side, custody)?;
+        let exit_price = self.get_exit_price(
+            token_price,
+            token_ema_price,
+            position.side,
+            custody,
+            curtime,
+        )?;
 
         let size = token_ema_price.get_token_amount(position.size_usd, custody.decimals)?;
 
@@ -645,6 +927,20 @@ impl Pool {
         curtime: i64,
     ) -> Result<u128> {
         let mut pool_amount_usd: u128 = 0;
+        for custody_aum in self.load_custody_aum(accounts, curtime)? {
+            pool_amount_usd = custody_aum.add_to_pool_amount(pool_amount_usd, aum_calc_mode)?;
+        }
+        Ok(pool_amount_usd)
+    }
+
+    /// Computes contributions of all custodies to the pool AUM from
+    /// custody and oracle accounts
+    pub fn load_custody_aum(
+        &self,
+        accounts: &[AccountInfo],
+        curtime: i64,
+    ) -> Result<Vec<CustodyAum>> {
+        let mut custody_aum = Vec::with_capacity(self.custodies.len());
         for (idx, &custody) in self.custodies.iter().enumerate() {
             let oracle_idx = idx + self.custodies.len();
             if oracle_idx >= accounts.len() {
@@ -653,6 +949,10 @@ impl Pool {
 
             require_keys_eq!(accounts[idx].key(), custody);
             let custody = Account::<Custody>::try_from(&accounts[idx])?;
+            require!(
+                custody.version == Custody::VERSION,
+                PerpetualsError::InvalidAccountVersion
+            );
             require_keys_eq!(accounts[oracle_idx].key(), custody.oracle.oracle_account);
 
             let token_price = OraclePrice::new_from_oracle(
@@ -673,71 +973,140 @@ impl Pool {
                 custody.pricing.use_ema,
             )?;
 
-            let aum_token_price = match aum_calc_mode {
-                AumCalcMode::Last => token_price,
-                AumCalcMode::EMA => token_ema_price,
-                AumCalcMode::Min => {
-                    if token_price < token_ema_price {
-                        token_price
-                    } else {
-                        token_ema_price
-                    }
-                }
-                AumCalcMode::Max => {
-                    if token_price > token_ema_price {
-                        token_price
-                    } else {
-                        token_ema_price
-                    }
-                }
-            };
+            custody_aum.push(self.get_custody_aum(
+                &custody,
+                &token_price,
+                &token_ema_price,
+                curtime,
+            )?);
+        }
+        Ok(custody_aum)
+    }
 
-            let token_amount_usd =
-                aum_token_price.get_asset_amount_usd(custody.assets.owned, custody.decimals)?;
-
-            pool_amount_usd = math::checked_add(pool_amount_usd, token_amount_usd as u128)?;
-
-            if custody.pricing.use_unrealized_pnl_in_aum {
-                // compute aggregate unrealized pnl
-                let (long_profit, long_loss, _) = self.get_pnl_usd(
-                    &custody.get_collective_position(Side::Long)?,
-                    &token_price,
-                    &token_ema_price,
-                    &custody,
-                    curtime,
-                    false,
-                )?;
-                let (short_profit, short_loss, _) = self.get_pnl_usd(
-                    &custody.get_collective_position(Side::Short)?,
-                    &token_price,
-                    &token_ema_price,
-                    &custody,
-                    curtime,
-                    false,
-                )?;
-
-                // adjust pool amount by collective profit/loss
-                pool_amount_usd = math::checked_add(pool_amount_usd, long_profit as u128)?;
-                pool_amount_usd = math::checked_add(pool_amount_usd, short_profit as u128)?;
-                pool_amount_usd = pool_amount_usd.saturating_sub(long_loss as u128);
-                pool_amount_usd = pool_amount_usd.saturating_sub(short_loss as u128);
-            }
+    /// Computes contribution of the custody to the pool AUM
+    pub fn get_custody_aum(
+        &self,
+        custody: &Custody,
+        token_price: &OraclePrice,
+        token_ema_price: &OraclePrice,
+        curtime: i64,
+    ) -> Result<CustodyAum> {
+        let last_usd = token_price.get_asset_amount_usd(custody.assets.owned, custody.decimals)?;
+        let ema_usd =
+            token_ema_price.get_asset_amount_usd(custody.assets.owned, custody.decimals)?;
+
+        let (profit_usd, loss_usd) = if custody.pricing.use_unrealized_pnl_in_aum {
+            // compute aggregate unrealized pnl
+            let (long_profit, long_loss, _) = self.get_pnl_usd(
+                &custody.get_collective_position(Side::Long)?,
+                token_price,
+                token_ema_price,
+                custody,
+                curtime,
+                false,
+            )?;
+            let (short_profit, short_loss, _) = self.get_pnl_usd(
+                &custody.get_collective_position(Side::Short)?,
+                token_price,
+                token_ema_price,
+                custody,
+                curtime,
+                false,
+            )?;
+            (
+                math::checked_add(long_profit, short_profit)?,
+                math::checked_add(long_loss, short_loss)?,
+            )
+        } else {
+            (0, 0)
+        };
+
+        Ok(CustodyAum {
+            last_usd,
+            ema_usd,
+            profit_usd,
+            loss_usd,
+            price_time: curtime,
+            max_age_sec: custody.oracle.max_price_age_sec,
+        })
+    }
+
+    /// Refreshes cached AUM contribution of the custody
+    pub fn update_custody_aum(
+        &mut self,
+        token_id: usize,
+        custody: &Custody,
+        token_price: &OraclePrice,
+        token_ema_price: &OraclePrice,
+        curtime: i64,
+    ) -> Result<()> {
+        let custody_aum = self.get_custody_aum(custody, token_price, token_ema_price, curtime)?;
+        *self
+            .custody_aum
+            .get_mut(token_id)
+            .ok_or(PerpetualsError::InvalidCustodyAumIndex)? = custody_aum;
+        Ok(())
+    }
+
+    /// Sums cached custody contributions, all of them must be within
+    /// their max age
+    pub fn get_cached_assets_under_management_usd(
+        &self,
+        aum_calc_mode: AumCalcMode,
+        curtime: i64,
+    ) -> Result<u128> {
+        require_eq!(
+            self.custody_aum.len(),
+            self.custodies.len(),
+            PerpetualsError::InvalidAccountVersion
+        );
+        let mut pool_amount_usd: u128 = 0;
+        for custody_aum in &self.custody_aum {
+            require!(
+                !custody_aum.is_stale(curtime),
+                PerpetualsError::StaleAumCache
+            );
+            pool_amount_usd = custody_aum.add_to_pool_amount(pool_amount_usd, aum_calc_mode)?;
         }
         Ok(pool_amount_usd)
     }
 
+    /// Recomputes pool.aum_usd, all cached custody contributions are reloaded
+    /// first if custody and oracle accounts are provided
+    pub fn update_aum_usd(&mut self, accounts: &[AccountInfo], curtime: i64) -> Result<()> {
+        if !accounts.is_empty() {
+            self.custody_aum = self.load_custody_aum(accounts, curtime)?;
+        }
+        self.aum_usd = self.get_cached_assets_under_management_usd(AumCalcMode::EMA, curtime)?;
+        Ok(())
+    }
+
+    /// Computes AUM from custody and oracle accounts if they are provided,
+    /// falls back to cached custody contributions otherwise
+    pub fn get_aum_usd(
+        &self,
+        aum_calc_mode: AumCalcMode,
+        accounts: &[AccountInfo],
+        curtime: i64,
+    ) -> Result<u128> {
+        if accounts.is_empty() {
+            self.get_cached_assets_under_management_usd(aum_calc_mode, curtime)
+        } else {
+            self.get_assets_under_management_usd(aum_calc_mode, accounts, curtime)
+        }
+    }
+
     pub fn get_fee_amount(fee: u64, amount: u64) -> Result<u64> {
         if fee == 0 || amount == 0 {
             return Ok(0);
         }
-        math::checked_as_u64(math::checked_ceil_div(
-            math::checked_mul(amount as u128, fee as u128)?,
-            Perpetuals::BPS_POWER,
-        )?)
+        Decimal::new(amount as u128, 0)
+            .checked_mul(&Decimal::new(fee as u128, Perpetuals::BPS_DECIMALS))?
+            .to_u64(0, Rounding::Up)
     }
 
     // private helpers
-    fn get_current_ratio(&self, custody: &Custody, token_price: &OraclePrice) -> Result<u64> {
+    pub fn get_current_ratio(&self, custody: &Custody, token_price: &OraclePrice) -> Result<u64> {
         if self.aum_usd == 0 {
             return Ok(0);
         }
@@ -820,13 +1189,10 @@ impl Pool {
             Ok(OraclePrice {
                 price: math::checked_add(
                     max_price.price,
-                    math::checked_decimal_ceil_mul(
-                        max_price.price,
-                        max_price.exponent,
-                        spread,
-                        -(Perpetuals::BPS_DECIMALS as i32),
-                        max_price.exponent,
-                    )?,
+                    max_price
+                        .to_decimal()?
+                        .checked_mul(&Decimal::new(spread as u128, Perpetuals::BPS_DECIMALS))?
+                        .to_exponent(max_price.exponent, Rounding::Up)?,
                 )?,
                 exponent: max_price.exponent,
             })
@@ -837,13 +1203,10 @@ impl Pool {
                 token_ema_price
             };
 
-            let spread = math::checked_decimal_mul(
-                min_price.price,
-                min_price.exponent,
-                spread,
-                -(Perpetuals::BPS_DECIMALS as i32),
-                min_price.exponent,
-            )?;
+            let spread = min_price
+                .to_decimal()?
+                .checked_mul(&Decimal::new(spread as u128, Perpetuals::BPS_DECIMALS))?
+                .to_exponent(min_price.exponent, Rounding::Down)?;
 
             let price = if spread < min_price.price {
                 math::checked_sub(min_price.price, spread)?
@@ -858,6 +1221,7 @@ impl Pool {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn get_fee(
         &self,
         token_id: usize,
@@ -866,6 +1230,7 @@ impl Pool {
         amount_remove: u64,
         custody: &Custody,
         token_price: &OraclePrice,
+        curtime: i64,
     ) -> Result<u64> {
         if custody.fees.mode == FeesMode::Fixed {
             return Self::get_fee_amount(base_fee, std::cmp::max(amount_add, amount_remove));
@@ -881,7 +1246,10 @@ impl Pool {
         //   otherwise:
         //     ratio_fee = 1 + custody.fees.ratio_mult * (new_ratio - ratios.target) / (ratios.max - ratios.target);
 
-        let ratios = &self.ratios[token_id];
+        let ratios = &TokenRatios {
+            target: self.get_target_ratio(token_id, curtime),
+            ..self.ratios[token_id]
+        };
         let current_ratio = self.get_current_ratio(custody, token_price)?;
         let new_ratio = self.get_new_ratio(amount_add, amount_remove, custody, token_price)?;
 
@@ -948,6 +1316,10 @@ impl Pool {
     }
 }
 
+impl PoolV0 {
+    pub const LEN: usize = 8 + std::mem::size_of::<PoolV0>();
+}
+
 #[cfg(test)]
 mod test {
     use {
@@ -997,6 +1369,7 @@ mod test {
             allow_pnl_withdrawal: true,
             allow_collateral_withdrawal: true,
             allow_size_change: true,
+            allow_liquidation: true,
         };
 
         let fees = Fees {
@@ -1063,10 +1436,7 @@ mod test {
     }
 
     fn scale_f64(amount: f64, decimals: u8) -> u64 {
-        math::checked_as_u64(
-            math::checked_float_mul(amount, 10u64.pow(decimals as u32) as f64).unwrap(),
-        )
-        .unwrap()
+        math::checked_as_u64(amount * 10u64.pow(decimals as u32) as f64).unwrap()
     }
 
     #[test]
@@ -1216,7 +1586,8 @@ mod test {
                 scale(20, custody.decimals),
                 0,
                 &custody,
-                &token_price
+                &token_price,
+                0
             )
             .unwrap()
         );
@@ -1238,6 +1609,7 @@ mod test {
                 0,
                 &custody,
                 &token_price,
+                0,
             )
             .unwrap()
         );
@@ -1251,6 +1623,7 @@ mod test {
                 scale(2, custody.decimals),
                 &custody,
                 &token_price,
+                0,
             )
             .unwrap()
         );
@@ -1264,6 +1637,7 @@ mod test {
                 scale(10, custody.decimals),
                 &custody,
                 &token_price,
+                0,
             )
             .unwrap()
         );
@@ -1279,6 +1653,7 @@ mod test {
                 0,
                 &custody,
                 &token_price,
+                0,
             )
             .unwrap()
         );
@@ -1292,6 +1667,7 @@ mod test {
                 scale(5, custody.decimals),
                 &custody,
                 &token_price,
+                0,
             )
             .unwrap()
         );
@@ -1305,6 +1681,7 @@ mod test {
                 0,
                 &custody,
                 &token_price,
+                0,
             )
             .unwrap()
         );
@@ -1409,6 +1786,22 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_get_maintenance_margin_usd() {
+        let (_pool, mut custody, position, _token_price, _token_ema_price) = get_fixture();
+
+        assert_eq!(
+            scale(100, Perpetuals::USD_DECIMALS),
+            Pool::get_maintenance_margin_usd(&position, &custody, 0).unwrap()
+        );
+
+        custody.pricing.max_leverage = 500000;
+        assert_eq!(
+            scale(20, Perpetuals::USD_DECIMALS),
+            Pool::get_maintenance_margin_usd(&position, &custody, 0).unwrap()
+        );
+    }
+
     #[test]
     fn test_get_liquidation_price() {
         let (pool, custody, mut position, token_price, _token_ema_price) = get_fixture();
@@ -1506,4 +1899,126 @@ mod test {
         let interest = custody.get_interest_amount_usd(&position, 7200).unwrap();
         assert_eq!(interest, scale_f64(0.07, Perpetuals::USD_DECIMALS));
     }
+
+    #[test]
+    fn test_get_target_ratio() {
+        let (mut pool, _custody, _position, _token_price, _token_ema_price) = get_fixture();
+
+        assert_eq!(pool.get_target_ratio(0, 1000), 5000);
+
+        pool.start_ratios_ramp(1000, 2000).unwrap();
+        pool.ratios[0].target = 3000;
+        pool.ratios[1].target = 7000;
+
+        assert_eq!(pool.get_target_ratio(0, 1000), 5000);
+        assert_eq!(pool.get_target_ratio(0, 1500), 4000);
+        assert_eq!(pool.get_target_ratio(1, 1500), 6000);
+        assert_eq!(pool.get_target_ratio(0, 3000), 3000);
+
+        assert!(pool.start_ratios_ramp(1500, 1000).is_err());
+    }
+
+    #[test]
+    fn test_get_custody_stats() {
+        let (mut pool, mut custody, _position, _token_price, token_ema_price) = get_fixture();
+        custody.borrow_rate = BorrowRateParams {
+            base_rate: 0,
+            slope1: 80000,
+            slope2: 120000,
+            optimal_utilization: 800_000_000,
+        };
+        custody.assets.owned = scale(10, 5);
+        custody.assets.locked = scale(4, 5);
+
+        let aum_usd = scale(2440, Perpetuals::USD_DECIMALS) as u128;
+        let stats = pool
+            .get_custody_stats(0, &custody, &token_ema_price, aum_usd, 0)
+            .unwrap();
+        assert_eq!(stats.utilization, 4000);
+        assert_eq!(stats.hourly_borrow_rate, 40000);
+        assert_eq!(stats.annual_borrow_rate, 40000 * 24 * 365);
+        assert_eq!(stats.available_amount, scale(6, 5));
+        assert_eq!(stats.current_ratio, 5000);
+        assert_eq!(stats.target_ratio, 5000);
+
+        // above optimal utilization the second slope applies
+        custody.assets.locked = scale(9, 5);
+        let stats = pool
+            .get_custody_stats(0, &custody, &token_ema_price, aum_usd, 0)
+            .unwrap();
+        assert_eq!(stats.utilization, 9000);
+        assert_eq!(stats.hourly_borrow_rate, 140000);
+        assert_eq!(stats.annual_borrow_rate, 140000 * 24 * 365);
+        assert_eq!(stats.available_amount, scale(1, 5));
+
+        // empty pool and ramping target
+        pool.start_ratios_ramp(1000, 2000).unwrap();
+        pool.ratios[0].target = 3000;
+        let stats = pool
+            .get_custody_stats(0, &custody, &token_ema_price, 0, 1500)
+            .unwrap();
+        assert_eq!(stats.current_ratio, 0);
+        assert_eq!(stats.target_ratio, 4000);
+    }
+
+    #[test]
+    fn test_get_cached_assets_under_management_usd() {
+        let (mut pool, mut custody, _position, token_price, token_ema_price) = get_fixture();
+        pool.custodies = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        pool.custody_aum = vec![CustodyAum::default(); 2];
+
+        assert!(pool
+            .get_cached_assets_under_management_usd(AumCalcMode::EMA, 100)
+            .is_err());
+
+        custody.assets.owned = scale(10, 5);
+        pool.update_custody_aum(0, &custody, &token_price, &token_ema_price, 100)
+            .unwrap();
+        custody.assets.owned = scale(1, 5);
+        pool.update_custody_aum(1, &custody, &token_price, &token_ema_price, 100)
+            .unwrap();
+        assert!(pool
+            .update_custody_aum(2, &custody, &token_price, &token_ema_price, 100)
+            .is_err());
+
+        assert_eq!(
+            pool.get_cached_assets_under_management_usd(AumCalcMode::Last, 100)
+                .unwrap(),
+            scale_f64(1353.0, Perpetuals::USD_DECIMALS) as u128
+        );
+        assert_eq!(
+            pool.get_cached_assets_under_management_usd(AumCalcMode::EMA, 100)
+                .unwrap(),
+            scale_f64(1342.0, Perpetuals::USD_DECIMALS) as u128
+        );
+        assert_eq!(
+            pool.get_cached_assets_under_management_usd(AumCalcMode::Min, 101)
+                .unwrap(),
+            scale_f64(1342.0, Perpetuals::USD_DECIMALS) as u128
+        );
+        assert_eq!(
+            pool.get_cached_assets_under_management_usd(AumCalcMode::Max, 101)
+                .unwrap(),
+            scale_f64(1353.0, Perpetuals::USD_DECIMALS) as u128
+        );
+
+        // entries expire after oracle max price age
+        assert!(pool
+            .get_cached_assets_under_management_usd(AumCalcMode::EMA, 102)
+            .is_err());
+    }
+
+    #[test]
+    fn test_open_interest() {
+        let (mut pool, _custody, _position, _token_price, _token_ema_price) = get_fixture();
+        pool.max_open_interest_usd = 1_000;
+
+        pool.add_open_interest(600).unwrap();
+        assert!(pool.add_open_interest(500).is_err());
+        pool.remove_open_interest(200).unwrap();
+        assert_eq!(pool.open_interest_usd, 400);
+
+        // removing more than was added must fail instead of hiding the drift
+        assert!(pool.remove_open_interest(500).is_err());
+    }
 }
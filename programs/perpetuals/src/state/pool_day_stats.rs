@@ -0,0 +1,127 @@
+use {
+    crate::{
+        math,
+        state::{
+            custody::{FeesStats, VolumeStats},
+            pool::Pool,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+/// Daily statistics snapshot of the pool
+/// Created on demand by the first trading instruction of the day (UTC), the payer
+/// of the rent can close the account once it is older than MIN_CLOSE_AGE_DAYS.
+#[account]
+#[derive(Default, Debug)]
+pub struct PoolDayStats {
+    pub version: u8,
+    pub pool: Pubkey,
+    // days since unix epoch
+    pub day: i64,
+    pub payer: Pubkey,
+
+    pub volume: VolumeStats,
+    pub fees: FeesStats,
+    // realized by closed and liquidated positions
+    pub profit_usd: u128,
+    pub loss_usd: u128,
+    // high-water mark of the pool open interest
+    pub max_open_interest_usd: u64,
+    // pool AUM as of the first and the last update of the day
+    pub aum_open_usd: u128,
+    pub aum_close_usd: u128,
+
+    pub bump: u8,
+}
+
+impl PoolDayStats {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = 8 + std::mem::size_of::<PoolDayStats>();
+    pub const SECONDS_PER_DAY: i64 = 86_400;
+    pub const MIN_CLOSE_AGE_DAYS: i64 = 30;
+
+    pub fn get_day(time: i64) -> i64 {
+        time.div_euclid(Self::SECONDS_PER_DAY)
+    }
+
+    /// Initializes the account on the first update of the day
+    /// and records the current state of the pool
+    pub fn update_pool_state(
+        &mut self,
+        pool: &Pool,
+        pool_key: Pubkey,
+        payer: Pubkey,
+        curtime: i64,
+        bump: u8,
+    ) {
+        if self.version == 0 {
+            self.version = Self::VERSION;
+            self.pool = pool_key;
+            self.day = Self::get_day(curtime);
+            self.payer = payer;
+            self.aum_open_usd = pool.aum_usd;
+            self.bump = bump;
+        }
+        self.aum_close_usd = pool.aum_usd;
+        self.max_open_interest_usd =
+            std::cmp::max(self.max_open_interest_usd, pool.open_interest_usd);
+    }
+
+    pub fn add_pnl(&mut self, profit_usd: u64, loss_usd: u64) -> Result<()> {
+        self.profit_usd = math::checked_add(self.profit_usd, profit_usd as u128)?;
+        self.loss_usd = math::checked_add(self.loss_usd, loss_usd as u128)?;
+        Ok(())
+    }
+
+    pub fn can_close(&self, curtime: i64) -> bool {
+        Self::get_day(curtime) - self.day >= Self::MIN_CLOSE_AGE_DAYS
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_day() {
+        assert_eq!(0, PoolDayStats::get_day(0));
+        assert_eq!(0, PoolDayStats::get_day(86_399));
+        assert_eq!(1, PoolDayStats::get_day(86_400));
+        assert_eq!(19_000, PoolDayStats::get_day(19_000 * 86_400 + 1));
+    }
+
+    #[test]
+    fn test_update_pool_state() {
+        let mut stats = PoolDayStats::default();
+        let pool = Pool {
+            aum_usd: 1_000,
+            open_interest_usd: 100,
+            ..Pool::default()
+        };
+        let pool_key = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+
+        stats.update_pool_state(&pool, pool_key, payer, 86_400 * 2 + 5, 255);
+        assert_eq!(stats.version, PoolDayStats::VERSION);
+        assert_eq!(stats.day, 2);
+        assert_eq!(stats.payer, payer);
+        assert_eq!(stats.aum_open_usd, 1_000);
+        assert_eq!(stats.aum_close_usd, 1_000);
+        assert_eq!(stats.max_open_interest_usd, 100);
+
+        let pool = Pool {
+            aum_usd: 2_000,
+            open_interest_usd: 50,
+            ..Pool::default()
+        };
+        stats.update_pool_state(&pool, pool_key, Pubkey::new_unique(), 86_400 * 2 + 10, 255);
+        assert_eq!(stats.payer, payer);
+        assert_eq!(stats.aum_open_usd, 1_000);
+        assert_eq!(stats.aum_close_usd, 2_000);
+        assert_eq!(stats.max_open_interest_usd, 100);
+
+        assert!(!stats.can_close(86_400 * 31));
+        assert!(stats.can_close(86_400 * 32));
+    }
+}
@@ -32,6 +32,31 @@ impl Default for CollateralChange {
 #[account]
 #[derive(Default, Debug)]
 pub struct Position {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub custody: Pubkey,
+
+    pub open_time: i64,
+    pub update_time: i64,
+    pub side: Side,
+    pub price: u64,
+    pub size_usd: u64,
+    pub collateral_usd: u64,
+    pub unrealized_profit_usd: u64,
+    pub unrealized_loss_usd: u64,
+    pub cumulative_interest_snapshot: u128,
+    pub locked_amount: u64,
+    pub collateral_amount: u64,
+
+    pub bump: u8,
+    pub is_cross_margin: bool,
+}
+
+/// Position layout before account versioning was introduced (version 0)
+#[account]
+#[derive(Default, Debug)]
+pub struct PositionV0 {
     pub owner: Pubkey,
     pub pool: Pubkey,
     pub custody: Pubkey,
@@ -52,6 +77,7 @@ pub struct Position {
 }
 
 impl Position {
+    pub const VERSION: u8 = 1;
     pub const LEN: usize = 8 + std::mem::size_of::<Position>();
 
     pub fn get_initial_leverage(&self) -> Result<u64> {
@@ -61,3 +87,7 @@ impl Position {
         )?)
     }
 }
+
+impl PositionV0 {
+    pub const LEN: usize = 8 + std::mem::size_of::<PositionV0>();
+}
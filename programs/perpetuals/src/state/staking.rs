@@ -0,0 +1,212 @@
+use {
+    crate::{
+        error::PerpetualsError,
+        math,
+        state::{custody::Custody, perpetuals::Perpetuals, pool::Pool},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct StakingReward {
+    pub custody: Pubkey,
+    // custody protocol fees at the time of the last update
+    pub protocol_fees_snapshot: u64,
+    // tokens set aside for stakers and not yet claimed
+    pub reserved_amount: u64,
+    // cumulative rewards per staked LP token, implied REWARD_INDEX_DECIMALS decimals
+    pub reward_index: u128,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct StakeReward {
+    pub custody: Pubkey,
+    pub reward_index_snapshot: u128,
+    pub pending_amount: u64,
+}
+
+/// LP staking state of the pool
+/// A fee_share slice of custody protocol fees accrued since the last update is
+/// moved out of assets.protocol_fees and set aside for stakers. Fees withdrawn
+/// by admins before the update are not shared.
+#[account]
+#[derive(Default, Debug)]
+pub struct Staking {
+    pub version: u8,
+    pub pool: Pubkey,
+    pub stake_token_account: Pubkey,
+    pub total_staked: u64,
+    // share of protocol fees routed to stakers, implied BPS_DECIMALS decimals
+    pub fee_share: u64,
+    pub rewards: Vec<StakingReward>,
+
+    pub bump: u8,
+    pub stake_token_account_bump: u8,
+}
+
+#[account]
+#[derive(Default, Debug)]
+pub struct StakeAccount {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub rewards: Vec<StakeReward>,
+
+    pub bump: u8,
+}
+
+impl Staking {
+    pub const VERSION: u8 = 1;
+    pub const MAX_REWARDS: usize = 16;
+    pub const LEN: usize = 8
+        + std::mem::size_of::<Staking>()
+        + Staking::MAX_REWARDS * std::mem::size_of::<StakingReward>();
+    pub const REWARD_INDEX_DECIMALS: u8 = 18;
+    pub const REWARD_INDEX_POWER: u128 = 10u128.pow(Self::REWARD_INDEX_DECIMALS as u32);
+
+    pub fn validate(&self) -> bool {
+        self.fee_share as u128 <= Perpetuals::BPS_POWER
+    }
+
+    pub fn get_reward_index(&self, custody: &Pubkey) -> Option<usize> {
+        self.rewards.iter().position(|x| x.custody == *custody)
+    }
+
+    /// Moves stakers share of newly collected protocol fees out of the custody
+    /// and into the reward index.
+    pub fn update_rewards(&mut self, custody_key: &Pubkey, custody: &mut Custody) -> Result<()> {
+        let idx = if let Some(idx) = self.get_reward_index(custody_key) {
+            idx
+        } else {
+            require!(
+                self.rewards.len() < Self::MAX_REWARDS,
+                PerpetualsError::InvalidStakingState
+            );
+            self.rewards.push(StakingReward {
+                custody: *custody_key,
+                protocol_fees_snapshot: custody.assets.protocol_fees,
+                ..StakingReward::default()
+            });
+            return Ok(());
+        };
+        let total_staked = self.total_staked;
+        let fee_share = self.fee_share;
+        let reward = &mut self.rewards[idx];
+
+        if custody.assets.protocol_fees > reward.protocol_fees_snapshot && total_staked > 0 {
+            let new_fees =
+                math::checked_sub(custody.assets.protocol_fees, reward.protocol_fees_snapshot)?;
+            let reward_amount = math::checked_as_u64(math::checked_div(
+                math::checked_mul(new_fees as u128, fee_share as u128)?,
+                Perpetuals::BPS_POWER,
+            )?)?;
+
+            Self::add_reward_amount(reward, custody, reward_amount, total_staked)?;
+        }
+        reward.protocol_fees_snapshot = custody.assets.protocol_fees;
+
+        Ok(())
+    }
+
+    /// Moves the given amount of custody protocol fees to stakers. Returns the
+    /// amount moved, which is zero if nothing is staked.
+    pub fn add_rewards(
+        &mut self,
+        custody_key: &Pubkey,
+        custody: &mut Custody,
+        amount: u64,
+    ) -> Result<u64> {
+        self.update_rewards(custody_key, custody)?;
+        let total_staked = self.total_staked;
+        let idx = self
+            .get_reward_index(custody_key)
+            .ok_or(PerpetualsError::InvalidStakingState)?;
+        let reward = &mut self.rewards[idx];
+
+        let reward_amount = if total_staked > 0 { amount } else { 0 };
+        if reward_amount > 0 {
+            Self::add_reward_amount(reward, custody, reward_amount, total_staked)?;
+        }
+        reward.protocol_fees_snapshot = custody.assets.protocol_fees;
+
+        Ok(reward_amount)
+    }
+
+    fn add_reward_amount(
+        reward: &mut StakingReward,
+        custody: &mut Custody,
+        amount: u64,
+        total_staked: u64,
+    ) -> Result<()> {
+        custody.assets.protocol_fees = math::checked_sub(custody.assets.protocol_fees, amount)?;
+        reward.reserved_amount = math::checked_add(reward.reserved_amount, amount)?;
+        reward.reward_index = math::checked_add(
+            reward.reward_index,
+            math::checked_div(
+                math::checked_mul(amount as u128, Self::REWARD_INDEX_POWER)?,
+                total_staked as u128,
+            )?,
+        )?;
+        Ok(())
+    }
+
+    /// Updates rewards for all custodies of the pool
+    /// accounts: pool.custodies.len() custody accounts (write)
+    pub fn update_pool_rewards(&mut self, pool: &Pool, accounts: &[AccountInfo]) -> Result<()> {
+        if accounts.len() < pool.custodies.len() {
+            return Err(ProgramError::NotEnoughAccountKeys.into());
+        }
+        for (idx, &custody_key) in pool.custodies.iter().enumerate() {
+            require_keys_eq!(accounts[idx].key(), custody_key);
+            let mut custody = Account::<Custody>::try_from(&accounts[idx])?;
+            require!(
+                custody.version == Custody::VERSION,
+                PerpetualsError::InvalidAccountVersion
+            );
+            self.update_rewards(&custody_key, &mut custody)?;
+            custody.exit(&crate::ID)?;
+        }
+        Ok(())
+    }
+}
+
+impl StakeAccount {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = 8
+        + std::mem::size_of::<StakeAccount>()
+        + Staking::MAX_REWARDS * std::mem::size_of::<StakeReward>();
+
+    /// Accrues rewards earned since the last update, must be called before
+    /// the staked amount changes.
+    pub fn update_rewards(&mut self, staking: &Staking) -> Result<()> {
+        for staking_reward in staking.rewards.iter() {
+            let idx = if let Some(idx) = self
+                .rewards
+                .iter()
+                .position(|x| x.custody == staking_reward.custody)
+            {
+                idx
+            } else {
+                self.rewards.push(StakeReward {
+                    custody: staking_reward.custody,
+                    ..StakeReward::default()
+                });
+                self.rewards.len() - 1
+            };
+            let reward = &mut self.rewards[idx];
+
+            let index_diff =
+                math::checked_sub(staking_reward.reward_index, reward.reward_index_snapshot)?;
+            let earned_amount = math::checked_as_u64(math::checked_div(
+                math::checked_mul(index_diff, self.amount as u128)?,
+                Staking::REWARD_INDEX_POWER,
+            )?)?;
+
+            reward.pending_amount = math::checked_add(reward.pending_amount, earned_amount)?;
+            reward.reward_index_snapshot = staking_reward.reward_index;
+        }
+
+        Ok(())
+    }
+}
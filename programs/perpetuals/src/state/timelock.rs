@@ -0,0 +1,60 @@
+//! Timelocked admin instructions
+
+use {
+    crate::{
+        math,
+        state::{multisig::Proposal, perpetuals::Permissions},
+    },
+    anchor_lang::prelude::*,
+};
+
+/// Admin instruction waiting for its timelock delay to elapse
+/// Instruction is approved by the multisig when queued and can be executed
+/// by anyone after the activation time.
+#[account]
+#[derive(Default, Debug)]
+pub struct QueuedInstruction {
+    pub version: u8,
+    pub queue_id: u64,
+    // receives the rent back on execution
+    pub payer: Pubkey,
+    pub instruction_type: u8,
+    // serialized params followed by the instruction type, see Multisig::get_instruction_data
+    pub instruction_data: Vec<u8>,
+    // accounts the instruction is applied to, see execute_queued
+    pub instruction_accounts: Vec<Pubkey>,
+    pub queued_time: i64,
+    pub activation_time: i64,
+    // global permissions at queue time, a queued SetPermissions only applies
+    // the flags it changes so that later pauses are kept
+    pub permissions: Permissions,
+
+    pub bump: u8,
+}
+
+impl QueuedInstruction {
+    pub const VERSION: u8 = 1;
+    pub fn get_len(instruction_data_len: usize, instruction_accounts_len: usize) -> usize {
+        8 + std::mem::size_of::<QueuedInstruction>()
+            + instruction_data_len
+            + instruction_accounts_len * std::mem::size_of::<Pubkey>()
+    }
+
+    pub fn validate(&self) -> bool {
+        !self.instruction_data.is_empty()
+            && self.instruction_data.len() <= Proposal::MAX_INSTRUCTION_DATA_LEN
+            && self.instruction_accounts.len() <= Proposal::MAX_INSTRUCTION_ACCOUNTS
+            && self.activation_time >= self.queued_time
+    }
+
+    pub fn is_active(&self, curtime: i64) -> bool {
+        curtime >= self.activation_time
+    }
+
+    /// Returns instruction params deserialized from the queued data
+    pub fn get_params<T: AnchorDeserialize>(&self) -> Result<T> {
+        let params_len = math::checked_sub(self.instruction_data.len(), 1)?;
+        T::try_from_slice(&self.instruction_data[..params_len])
+            .map_err(|_| ProgramError::InvalidInstructionData.into())
+    }
+}
@@ -0,0 +1,223 @@
+use {
+    crate::{math, state::perpetuals::Perpetuals},
+    anchor_lang::prelude::*,
+};
+
+/// Delayed LP redemption settings and epoch state of the pool
+/// When enabled, LP tokens can only be redeemed through a withdrawal request.
+/// Requested LP tokens are held in escrow until the cooldown period is over.
+/// With non-zero epoch_duration, requests are settled in the epoch following
+/// the request at the AUM snapshot taken at the start of that epoch, and total
+/// redemptions per epoch are capped to max_epoch_withdrawal share of that AUM.
+#[account]
+#[derive(Default, Debug)]
+pub struct WithdrawalQueue {
+    pub version: u8,
+    pub pool: Pubkey,
+    pub escrow_token_account: Pubkey,
+    pub cooldown_period: i64,
+    pub epoch_duration: i64,
+    // implied BPS_DECIMALS decimals
+    pub max_epoch_withdrawal: u64,
+
+    // current epoch state
+    pub epoch_start_time: i64,
+    pub epoch_aum_usd: u128,
+    pub epoch_lp_supply: u64,
+    pub epoch_withdrawn_usd: u64,
+    pub pending_lp_amount: u64,
+
+    pub bump: u8,
+    pub escrow_token_account_bump: u8,
+}
+
+#[account]
+#[derive(Default, Debug)]
+pub struct WithdrawalRequest {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub lp_amount: u64,
+    pub request_time: i64,
+
+    pub bump: u8,
+}
+
+impl WithdrawalQueue {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = 8 + std::mem::size_of::<WithdrawalQueue>();
+
+    pub fn validate(&self) -> bool {
+        self.cooldown_period >= 0
+            && self.epoch_duration >= 0
+            && (self.max_epoch_withdrawal as u128) <= Perpetuals::BPS_POWER
+    }
+
+    /// Starts a new epoch if the current one is over, snapshotting pool AUM
+    /// and LP supply. Epochs are aligned to the first epoch start time.
+    pub fn update_epoch(&mut self, aum_usd: u128, lp_supply: u64, curtime: i64) -> Result<()> {
+        if self.epoch_duration == 0 {
+            return Ok(());
+        }
+        if self.epoch_start_time == 0 {
+            self.epoch_start_time = curtime;
+        } else if curtime >= math::checked_add(self.epoch_start_time, self.epoch_duration)? {
+            let elapsed = math::checked_sub(curtime, self.epoch_start_time)?;
+            self.epoch_start_time = math::checked_sub(curtime, elapsed % self.epoch_duration)?;
+        } else {
+            return Ok(());
+        }
+        self.epoch_aum_usd = aum_usd;
+        self.epoch_lp_supply = lp_supply;
+        self.epoch_withdrawn_usd = 0;
+
+        Ok(())
+    }
+
+    pub fn is_request_ready(&self, request: &WithdrawalRequest, curtime: i64) -> Result<bool> {
+        if curtime < math::checked_add(request.request_time, self.cooldown_period)? {
+            return Ok(false);
+        }
+        // requests are settled in the next epoch
+        Ok(self.epoch_duration == 0 || request.request_time < self.epoch_start_time)
+    }
+
+    /// Returns USD value of the redeemed LP tokens. In epoch mode the lower of
+    /// the epoch snapshot and the current value is used, so the remaining LPs
+    /// don't cover losses that happened during the epoch.
+    pub fn get_withdrawal_amount_usd(
+        &self,
+        lp_amount: u64,
+        aum_usd: u128,
+        lp_supply: u64,
+    ) -> Result<u64> {
+        let current_amount_usd = math::checked_as_u64(math::checked_div(
+            math::checked_mul(aum_usd, lp_amount as u128)?,
+            lp_supply as u128,
+        )?)?;
+        if self.epoch_duration == 0 || self.epoch_lp_supply == 0 {
+            return Ok(current_amount_usd);
+        }
+        let epoch_amount_usd = math::checked_as_u64(math::checked_div(
+            math::checked_mul(self.epoch_aum_usd, lp_amount as u128)?,
+            self.epoch_lp_supply as u128,
+        )?)?;
+
+        Ok(std::cmp::min(current_amount_usd, epoch_amount_usd))
+    }
+
+    pub fn check_epoch_limit(&self, amount_usd: u64) -> Result<bool> {
+        if self.epoch_duration == 0 {
+            return Ok(true);
+        }
+        let max_withdrawal_usd = math::checked_div(
+            math::checked_mul(self.epoch_aum_usd, self.max_epoch_withdrawal as u128)?,
+            Perpetuals::BPS_POWER,
+        )?;
+
+        Ok(math::checked_add(self.epoch_withdrawn_usd, amount_usd)? as u128 <= max_withdrawal_usd)
+    }
+}
+
+impl WithdrawalRequest {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = 8 + std::mem::size_of::<WithdrawalRequest>();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_fixture() -> (WithdrawalQueue, WithdrawalRequest) {
+        let queue = WithdrawalQueue {
+            cooldown_period: 3600,
+            epoch_duration: 86400,
+            max_epoch_withdrawal: 1000,
+            epoch_start_time: 100_000,
+            epoch_aum_usd: 1_000_000_000_000,
+            epoch_lp_supply: 1_000_000_000,
+            ..WithdrawalQueue::default()
+        };
+        let request = WithdrawalRequest {
+            lp_amount: 1_000_000,
+            request_time: 110_000,
+            ..WithdrawalRequest::default()
+        };
+        (queue, request)
+    }
+
+    #[test]
+    fn test_update_epoch() {
+        let (mut queue, _) = get_fixture();
+        queue.epoch_withdrawn_usd = 1000;
+
+        queue.update_epoch(2_000, 3_000, 150_000).unwrap();
+        assert_eq!(queue.epoch_start_time, 100_000);
+        assert_eq!(queue.epoch_withdrawn_usd, 1000);
+
+        queue
+            .update_epoch(2_000, 3_000, 100_000 + 86400 * 2 + 5)
+            .unwrap();
+        assert_eq!(queue.epoch_start_time, 100_000 + 86400 * 2);
+        assert_eq!(queue.epoch_aum_usd, 2_000);
+        assert_eq!(queue.epoch_lp_supply, 3_000);
+        assert_eq!(queue.epoch_withdrawn_usd, 0);
+    }
+
+    #[test]
+    fn test_is_request_ready() {
+        let (mut queue, request) = get_fixture();
+
+        // same epoch
+        assert!(!queue.is_request_ready(&request, 120_000).unwrap());
+
+        // next epoch
+        queue.update_epoch(0, 0, 200_000).unwrap();
+        assert!(queue.is_request_ready(&request, 200_000).unwrap());
+
+        // cooldown is not over
+        queue.epoch_duration = 0;
+        assert!(!queue.is_request_ready(&request, 113_599).unwrap());
+        assert!(queue.is_request_ready(&request, 113_600).unwrap());
+    }
+
+    #[test]
+    fn test_get_withdrawal_amount_usd() {
+        let (mut queue, _) = get_fixture();
+
+        // current value is lower
+        assert_eq!(
+            queue
+                .get_withdrawal_amount_usd(1_000_000, 900_000_000_000, 1_000_000_000)
+                .unwrap(),
+            900_000_000
+        );
+
+        // epoch value is lower
+        assert_eq!(
+            queue
+                .get_withdrawal_amount_usd(1_000_000, 1_100_000_000_000, 1_000_000_000)
+                .unwrap(),
+            1_000_000_000
+        );
+
+        queue.epoch_duration = 0;
+        assert_eq!(
+            queue
+                .get_withdrawal_amount_usd(1_000_000, 1_100_000_000_000, 1_000_000_000)
+                .unwrap(),
+            1_100_000_000
+        );
+    }
+
+    #[test]
+    fn test_check_epoch_limit() {
+        let (mut queue, _) = get_fixture();
+
+        assert!(queue.check_epoch_limit(100_000_000_000).unwrap());
+        assert!(!queue.check_epoch_limit(100_000_000_001).unwrap());
+
+        queue.epoch_withdrawn_usd = 50_000_000_000;
+        assert!(!queue.check_epoch_limit(60_000_000_000).unwrap());
+    }
+}
@@ -28,6 +28,7 @@ describe("perpetuals", () => {
     assert(err.logs[3].includes("already in use"));
 
     perpetualsExpected = {
+      version: 2,
       permissions: {
         allowSwap: true,
         allowAddLiquidity: true,
@@ -37,20 +38,28 @@ describe("perpetuals", () => {
         allowPnlWithdrawal: true,
         allowCollateralWithdrawal: true,
         allowSizeChange: true,
+        allowLiquidation: true,
       },
       pools: [],
       transferAuthorityBump: tc.authority.bump,
       perpetualsBump: tc.perpetuals.bump,
       inceptionTime: new BN(0),
+      timelockDelays: {
+        setCustodyConfig: new BN(0),
+        setPermissions: new BN(0),
+        setAdminSigners: new BN(0),
+      },
+      guardians: new Array(6).fill(PublicKey.default),
     };
 
     multisigExpected = {
+      version: 1,
       numSigners: 2,
       numSigned: 0,
       minSignatures: 2,
       instructionAccountsLen: 0,
       instructionDataLen: 0,
-      instructionHash: new anchor.BN(0),
+      instructionHash: new Array(32).fill(0),
       signers: [
         tc.admins[0].publicKey,
         tc.admins[1].publicKey,
@@ -96,6 +105,7 @@ describe("perpetuals", () => {
       allowPnlWithdrawal: true,
       allowCollateralWithdrawal: true,
       allowSizeChange: true,
+      allowLiquidation: true,
     };
     await tc.setPermissions(perpetualsExpected.permissions);
 
@@ -112,6 +122,7 @@ describe("perpetuals", () => {
 
     let pool = await tc.program.account.pool.fetch(tc.pool.publicKey);
     let poolExpected = {
+      version: 3,
       name: "test pool",
       custodies: [],
       ratios: [],
@@ -119,6 +130,27 @@ describe("perpetuals", () => {
       bump: tc.pool.bump,
       lpTokenBump: pool.lpTokenBump,
       inceptionTime: new BN(0),
+      withdrawalQueueEnabled: false,
+      permissions: {
+        allowSwap: true,
+        allowAddLiquidity: true,
+        allowRemoveLiquidity: true,
+        allowOpenPosition: true,
+        allowClosePosition: true,
+        allowPnlWithdrawal: true,
+        allowCollateralWithdrawal: true,
+        allowSizeChange: true,
+        allowLiquidation: true,
+      },
+      maxAumUsd: new BN(0),
+      maxOpenInterestUsd: new BN(0),
+      openInterestUsd: new BN(0),
+      ratiosRamp: {
+        startTime: new BN(0),
+        endTime: new BN(0),
+        startTargets: [],
+      },
+      custodyAum: [],
     };
     expect(JSON.stringify(pool)).to.equal(JSON.stringify(poolExpected));
 
@@ -158,6 +190,7 @@ describe("perpetuals", () => {
       allowPnlWithdrawal: true,
       allowCollateralWithdrawal: true,
       allowSizeChange: true,
+      allowLiquidation: true,
     };
     fees = {
       mode: { linear: {} },
@@ -213,6 +246,7 @@ describe("perpetuals", () => {
 
     let token = await tc.program.account.custody.fetch(tc.custodies[0].custody);
     tokenExpected = {
+      version: 4,
       pool: tc.pool.publicKey,
       mint: tc.custodies[0].mint.publicKey,
       tokenAccount: tc.custodies[0].tokenAccount,
@@ -247,6 +281,7 @@ describe("perpetuals", () => {
         allowPnlWithdrawal: true,
         allowCollateralWithdrawal: true,
         allowSizeChange: true,
+        allowLiquidation: true,
       },
       fees: {
         mode: { linear: {} },
@@ -269,6 +304,21 @@ describe("perpetuals", () => {
         slope2: "120000",
         optimalUtilization: "800000000",
       },
+      leverageTiers: Array(8).fill({
+        minSizeUsd: "0",
+        maxInitialLeverage: "0",
+        maxLeverage: "0",
+      }),
+      ramp: {
+        startTime: "0",
+        endTime: "0",
+        maxLeverage: "0",
+        tradeSpreadLong: "0",
+        tradeSpreadShort: "0",
+        swapSpread: "0",
+        slope1: "0",
+        slope2: "0",
+      },
       assets: {
         collateral: "0",
         protocolFees: "0",
@@ -283,6 +333,14 @@ describe("perpetuals", () => {
         closePositionUsd: "0",
         liquidationUsd: "0",
       },
+      collectedTokenFees: {
+        swap: "0",
+        addLiquidity: "0",
+        removeLiquidity: "0",
+        openPosition: "0",
+        closePosition: "0",
+        liquidation: "0",
+      },
       volumeStats: {
         swapUsd: "0",
         addLiquidityUsd: "0",
@@ -324,6 +382,8 @@ describe("perpetuals", () => {
       },
       bump: token.bump,
       tokenAccountBump: token.tokenAccountBump,
+      status: { active: {} },
+      settlementPrice: "0",
     };
     expect(JSON.stringify(token)).to.equal(JSON.stringify(tokenExpected));
 
@@ -386,6 +446,7 @@ describe("perpetuals", () => {
       tc.custodies[0].oracleAccount
     );
     let oracleExpected = {
+      version: 1,
       price: new BN(123000),
       expo: -3,
       conf: new BN(0),
@@ -467,6 +528,7 @@ describe("perpetuals", () => {
       tc.users[0].positionAccountsLong[0]
     );
     positionExpected = {
+      version: 1,
       owner: tc.users[0].wallet.publicKey.toBase58(),
       pool: tc.pool.publicKey.toBase58(),
       custody: tc.custodies[0].custody.toBase58(),
@@ -482,6 +544,7 @@ describe("perpetuals", () => {
       lockedAmount: "7000000000",
       collateralAmount: "1000000000",
       bump: position.bump,
+      isCrossMargin: false,
     };
 
     expect(JSON.stringify(position)).to.equal(JSON.stringify(positionExpected));
@@ -243,6 +243,18 @@ export class TestClient {
     return { publicKey: res[0], bump: res[1] };
   };
 
+  // the program runs in test mode, so the current day derives from the perpetuals time
+  getPoolDayStatsKey = async () => {
+    let perpetuals = await this.program.account.perpetuals.fetch(
+      this.perpetuals.publicKey
+    );
+    let day = perpetuals.inceptionTime.div(new BN(86400));
+    return this.findProgramAddress("pool_day_stats", [
+      this.pool.publicKey,
+      day.toArray("le", 8),
+    ]).publicKey;
+  };
+
   confirmTx = async (txSignature: anchor.web3.TransactionSignature) => {
     const latestBlockHash = await this.provider.connection.getLatestBlockhash();
 
@@ -343,6 +355,7 @@ export class TestClient {
           allowPnlWithdrawal: true,
           allowCollateralWithdrawal: true,
           allowSizeChange: true,
+          allowLiquidation: true,
         })
         .accounts({
           upgradeAuthority: this.provider.wallet.publicKey,
@@ -366,26 +379,104 @@ export class TestClient {
     let multisig = await this.program.account.multisig.fetch(
       this.multisig.publicKey
     );
-    for (let i = 0; i < multisig.minSignatures; ++i) {
-      try {
-        await this.program.methods
-          .setAdminSigners({
-            minSignatures,
-          })
-          .accounts({
-            admin: this.admins[i].publicKey,
-            multisig: this.multisig.publicKey,
-          })
-          .remainingAccounts(this.adminMetas)
-          .signers([this.admins[i]])
-          .rpc();
-      } catch (err) {
-        if (this.printErrors) {
-          console.log(err);
-        }
-        throw err;
+    try {
+      let remainingAccounts = this.adminMetas;
+      if (multisig.minSignatures > 1) {
+        let proposal = await this.proposeAdminInstruction(
+          await this.program.methods
+            .setAdminSigners({
+              minSignatures,
+            })
+            .accounts({
+              admin: this.admins[0].publicKey,
+              multisig: this.multisig.publicKey,
+              perpetuals: this.perpetuals.publicKey,
+            })
+            .remainingAccounts(this.adminMetas)
+            .instruction(),
+          // AdminInstruction::SetAdminSigners
+          4,
+          multisig.minSignatures
+        );
+        remainingAccounts = [
+          ...this.adminMetas,
+          { isSigner: false, isWritable: true, pubkey: proposal },
+        ];
       }
+      await this.program.methods
+        .setAdminSigners({
+          minSignatures,
+        })
+        .accounts({
+          admin: this.admins[0].publicKey,
+          multisig: this.multisig.publicKey,
+          perpetuals: this.perpetuals.publicKey,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([this.admins[0]])
+        .rpc();
+    } catch (err) {
+      if (this.printErrors) {
+        console.log(err);
+      }
+      throw err;
+    }
+  };
+
+  // creates a proposal for the admin instruction and collects required approvals
+  // signing flow when more than one signature is required:
+  //   1. any admin calls createProposal with the instruction params followed by
+  //      the AdminInstruction type byte, this counts as the first approval
+  //   2. other admins call approveProposal (or revokeApproval to withdraw it)
+  //   3. once minSignatures approvals are collected, any admin sends the admin
+  //      instruction itself with the proposal appended to its remaining accounts,
+  //      the proposal is marked as executed and can't be replayed
+  //   4. the proposer can cancelProposal while it is pending, any admin can
+  //      close executed or expired proposals
+  proposeAdminInstruction = async (
+    instruction: anchor.web3.TransactionInstruction,
+    instructionType: number,
+    minSignatures: number
+  ) => {
+    let proposalId = new BN(Date.now());
+    let proposal = this.findProgramAddress("proposal", [
+      proposalId.toArray("le", 8),
+    ]);
+
+    await this.program.methods
+      .createProposal({
+        proposalId,
+        // params without the discriminator, followed by the instruction type
+        instructionData: Buffer.concat([
+          instruction.data.subarray(8),
+          Buffer.from([instructionType]),
+        ]),
+        // accounts following the admin signer
+        instructionAccounts: instruction.keys.slice(1).map((x) => x.pubkey),
+        expirationTime: new BN(Math.floor(Date.now() / 1000) + 3600),
+      })
+      .accounts({
+        proposer: this.admins[0].publicKey,
+        multisig: this.multisig.publicKey,
+        proposal: proposal.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([this.admins[0]])
+      .rpc();
+
+    for (let i = 1; i < minSignatures; ++i) {
+      await this.program.methods
+        .approveProposal({})
+        .accounts({
+          admin: this.admins[i].publicKey,
+          multisig: this.multisig.publicKey,
+          proposal: proposal.publicKey,
+        })
+        .signers([this.admins[i]])
+        .rpc();
     }
+
+    return proposal.publicKey;
   };
 
   setPermissions = async (permissions) => {
@@ -503,6 +594,7 @@ export class TestClient {
             permissions,
             fees,
             borrowRate,
+            leverageTiers: [],
             ratios,
           })
           .accounts({
@@ -582,11 +674,13 @@ export class TestClient {
             permissions,
             fees,
             borrowRate,
+            leverageTiers: [],
             ratios,
           })
           .accounts({
             admin: this.admins[i].publicKey,
             multisig: this.multisig.publicKey,
+            perpetuals: this.perpetuals.publicKey,
             pool: this.pool.publicKey,
             custody: custody.custody,
           })
@@ -619,6 +713,7 @@ export class TestClient {
             pool: this.pool.publicKey,
             custody: custody.custody,
             custodyTokenAccount: custody.tokenAccount,
+            custodyTokenMint: custody.mint.publicKey,
             receivingTokenAccount: receivingTokenAccount,
             tokenProgram: spl.TOKEN_PROGRAM_ID,
           })
@@ -739,16 +834,24 @@ export class TestClient {
           owner: user.wallet.publicKey,
           fundingAccount: fundingAccount,
           receivingAccount,
+          wsolAccount: this.findProgramAddress("wsol_account", [
+            user.wallet.publicKey,
+          ]).publicKey,
           transferAuthority: this.authority.publicKey,
           perpetuals: this.perpetuals.publicKey,
           pool: this.pool.publicKey,
+          poolDayStats: await this.getPoolDayStatsKey(),
           receivingCustody: custodyIn.custody,
           receivingCustodyOracleAccount: custodyIn.oracleAccount,
           receivingCustodyTokenAccount: custodyIn.tokenAccount,
+          receivingCustodyTokenMint: custodyIn.mint.publicKey,
           dispensingCustody: custodyOut.custody,
           dispensingCustodyOracleAccount: custodyOut.oracleAccount,
           dispensingCustodyTokenAccount: custodyOut.tokenAccount,
-          tokenProgram: spl.TOKEN_PROGRAM_ID,
+          dispensingCustodyTokenMint: custodyOut.mint.publicKey,
+          systemProgram: SystemProgram.programId,
+          receivingTokenProgram: spl.TOKEN_PROGRAM_ID,
+          dispensingTokenProgram: spl.TOKEN_PROGRAM_ID,
         })
         .signers([user.wallet])
         .rpc();
@@ -776,13 +879,20 @@ export class TestClient {
         .accounts({
           owner: user.wallet.publicKey,
           fundingAccount,
+          wsolAccount: this.findProgramAddress("wsol_account", [
+            user.wallet.publicKey,
+          ]).publicKey,
           lpTokenAccount: user.lpTokenAccount,
           transferAuthority: this.authority.publicKey,
           perpetuals: this.perpetuals.publicKey,
           pool: this.pool.publicKey,
+          poolDayStats: await this.getPoolDayStatsKey(),
           custody: custody.custody,
           custodyOracleAccount: custody.oracleAccount,
           custodyTokenAccount: custody.tokenAccount,
+          custodyTokenMint: custody.mint.publicKey,
+          systemProgram: SystemProgram.programId,
+          custodyTokenProgram: spl.TOKEN_PROGRAM_ID,
           lpTokenMint: this.lpToken.publicKey,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
         })
@@ -813,13 +923,20 @@ export class TestClient {
         .accounts({
           owner: user.wallet.publicKey,
           receivingAccount: receivingAccount,
+          wsolAccount: this.findProgramAddress("wsol_account", [
+            user.wallet.publicKey,
+          ]).publicKey,
           lpTokenAccount: user.lpTokenAccount,
           transferAuthority: this.authority.publicKey,
           perpetuals: this.perpetuals.publicKey,
           pool: this.pool.publicKey,
+          poolDayStats: await this.getPoolDayStatsKey(),
           custody: custody.custody,
           custodyOracleAccount: custody.oracleAccount,
           custodyTokenAccount: custody.tokenAccount,
+          custodyTokenMint: custody.mint.publicKey,
+          systemProgram: SystemProgram.programId,
+          custodyTokenProgram: spl.TOKEN_PROGRAM_ID,
           lpTokenMint: this.lpToken.publicKey,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
         })
@@ -855,13 +972,18 @@ export class TestClient {
         .accounts({
           owner: user.wallet.publicKey,
           fundingAccount,
+          wsolAccount: this.findProgramAddress("wsol_account", [
+            user.wallet.publicKey,
+          ]).publicKey,
           transferAuthority: this.authority.publicKey,
           perpetuals: this.perpetuals.publicKey,
           pool: this.pool.publicKey,
+          poolDayStats: await this.getPoolDayStatsKey(),
           position: positionAccount,
           custody: custody.custody,
           custodyOracleAccount: custody.oracleAccount,
           custodyTokenAccount: custody.tokenAccount,
+          custodyTokenMint: custody.mint.publicKey,
           systemProgram: SystemProgram.programId,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
         })
@@ -890,6 +1012,9 @@ export class TestClient {
         .accounts({
           owner: user.wallet.publicKey,
           fundingAccount,
+          wsolAccount: this.findProgramAddress("wsol_account", [
+            user.wallet.publicKey,
+          ]).publicKey,
           transferAuthority: this.authority.publicKey,
           perpetuals: this.perpetuals.publicKey,
           pool: this.pool.publicKey,
@@ -897,6 +1022,8 @@ export class TestClient {
           custody: custody.custody,
           custodyOracleAccount: custody.oracleAccount,
           custodyTokenAccount: custody.tokenAccount,
+          custodyTokenMint: custody.mint.publicKey,
+          systemProgram: SystemProgram.programId,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
         })
         .signers([user.wallet])
@@ -924,6 +1051,9 @@ export class TestClient {
         .accounts({
           owner: user.wallet.publicKey,
           receivingAccount,
+          wsolAccount: this.findProgramAddress("wsol_account", [
+            user.wallet.publicKey,
+          ]).publicKey,
           transferAuthority: this.authority.publicKey,
           perpetuals: this.perpetuals.publicKey,
           pool: this.pool.publicKey,
@@ -931,6 +1061,8 @@ export class TestClient {
           custody: custody.custody,
           custodyOracleAccount: custody.oracleAccount,
           custodyTokenAccount: custody.tokenAccount,
+          custodyTokenMint: custody.mint.publicKey,
+          systemProgram: SystemProgram.programId,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
         })
         .signers([user.wallet])
@@ -958,13 +1090,19 @@ export class TestClient {
         .accounts({
           owner: user.wallet.publicKey,
           receivingAccount,
+          wsolAccount: this.findProgramAddress("wsol_account", [
+            user.wallet.publicKey,
+          ]).publicKey,
           transferAuthority: this.authority.publicKey,
           perpetuals: this.perpetuals.publicKey,
           pool: this.pool.publicKey,
+          poolDayStats: await this.getPoolDayStatsKey(),
           position: positionAccount,
           custody: custody.custody,
           custodyOracleAccount: custody.oracleAccount,
           custodyTokenAccount: custody.tokenAccount,
+          custodyTokenMint: custody.mint.publicKey,
+          systemProgram: SystemProgram.programId,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
         })
         .signers([user.wallet])
@@ -993,10 +1131,13 @@ export class TestClient {
           transferAuthority: this.authority.publicKey,
           perpetuals: this.perpetuals.publicKey,
           pool: this.pool.publicKey,
+          poolDayStats: await this.getPoolDayStatsKey(),
           position: positionAccount,
           custody: custody.custody,
           custodyOracleAccount: custody.oracleAccount,
           custodyTokenAccount: custody.tokenAccount,
+          custodyTokenMint: custody.mint.publicKey,
+          systemProgram: SystemProgram.programId,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
         })
         .signers([user.wallet])
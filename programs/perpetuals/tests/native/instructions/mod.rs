@@ -1,17 +1,67 @@
 pub mod test_add_custody;
 pub mod test_add_liquidity;
+pub mod test_add_liquidity_basket;
+pub mod test_add_margin_position;
 pub mod test_add_pool;
+pub mod test_approve_proposal;
+pub mod test_cancel_queued;
+pub mod test_claim_stake_rewards;
+pub mod test_close_pool_day_stats;
 pub mod test_close_position;
+pub mod test_create_proposal;
+pub mod test_deposit_margin;
+pub mod test_distribute_fees;
+pub mod test_execute_queued;
+pub mod test_execute_withdrawal;
+pub mod test_get_pool_stats;
+pub mod test_get_position_health;
+pub mod test_guardian_pause;
 pub mod test_init;
+pub mod test_init_margin_account;
+pub mod test_init_staking;
 pub mod test_liquidate;
+pub mod test_liquidate_margin_account;
+pub mod test_migrate_account;
 pub mod test_open_position;
+pub mod test_queue_instruction;
+pub mod test_refresh_aum;
 pub mod test_remove_liquidity;
+pub mod test_remove_liquidity_proportional;
+pub mod test_request_withdrawal;
+pub mod test_revoke_approval;
 pub mod test_set_custody_config;
+pub mod test_set_custody_fees;
+pub mod test_set_custody_ramp;
+pub mod test_set_custody_status;
+pub mod test_set_fee_distribution;
+pub mod test_set_guardians;
+pub mod test_set_permissions;
+pub mod test_set_pool_config;
+pub mod test_set_pool_ratios;
 pub mod test_set_test_oracle_price;
+pub mod test_set_test_time;
+pub mod test_set_timelock_delays;
+pub mod test_set_withdrawal_queue_config;
+pub mod test_settle_position;
+pub mod test_stake;
 pub mod test_swap;
+pub mod test_sweep_custody;
+pub mod test_upgrade_multisig;
 
 pub use {
-    test_add_custody::*, test_add_liquidity::*, test_add_pool::*, test_close_position::*,
-    test_init::*, test_liquidate::*, test_open_position::*, test_remove_liquidity::*,
-    test_set_custody_config::*, test_set_test_oracle_price::*, test_swap::*,
+    test_add_custody::*, test_add_liquidity::*, test_add_liquidity_basket::*,
+    test_add_margin_position::*, test_add_pool::*, test_approve_proposal::*, test_cancel_queued::*,
+    test_claim_stake_rewards::*, test_close_pool_day_stats::*, test_close_position::*,
+    test_create_proposal::*, test_deposit_margin::*, test_distribute_fees::*,
+    test_execute_queued::*, test_execute_withdrawal::*, test_get_pool_stats::*,
+    test_get_position_health::*, test_guardian_pause::*, test_init::*, test_init_margin_account::*,
+    test_init_staking::*, test_liquidate::*, test_liquidate_margin_account::*,
+    test_migrate_account::*, test_open_position::*, test_queue_instruction::*, test_refresh_aum::*,
+    test_remove_liquidity::*, test_remove_liquidity_proportional::*, test_request_withdrawal::*,
+    test_revoke_approval::*, test_set_custody_config::*, test_set_custody_fees::*,
+    test_set_custody_ramp::*, test_set_custody_status::*, test_set_fee_distribution::*,
+    test_set_guardians::*, test_set_permissions::*, test_set_pool_config::*,
+    test_set_pool_ratios::*, test_set_test_oracle_price::*, test_set_test_time::*,
+    test_set_timelock_delays::*, test_set_withdrawal_queue_config::*, test_settle_position::*,
+    test_stake::*, test_swap::*, test_sweep_custody::*, test_upgrade_multisig::*,
 };
@@ -31,6 +31,8 @@ pub async fn test_add_custody(
     let (custody_token_account_pda, custody_token_account_bump) =
         pda::get_custody_token_account_pda(pool_pda, custody_token_mint);
 
+    let token_program = utils::get_token_program(program_test_ctx, custody_token_mint).await;
+
     let multisig_account = utils::get_account::<Multisig>(program_test_ctx, multisig_pda).await;
 
     // One Tx per multisig signer
@@ -48,7 +50,7 @@ pub async fn test_add_custody(
                 custody_token_account: custody_token_account_pda,
                 custody_token_mint: *custody_token_mint,
                 system_program: anchor_lang::system_program::ID,
-                token_program: anchor_spl::token::ID,
+                token_program,
                 rent: solana_program::sysvar::rent::ID,
             };
 
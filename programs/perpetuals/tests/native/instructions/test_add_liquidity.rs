@@ -4,7 +4,6 @@ use {
         prelude::{AccountMeta, Pubkey},
         ToAccountMetas,
     },
-    bonfida_test_utils::ProgramTestContextExt,
     perpetuals::{
         instructions::AddLiquidityParams,
         state::{custody::Custody, pool::Pool},
@@ -30,8 +29,11 @@ pub async fn test_add_liquidity(
         pda::get_custody_token_account_pda(pool_pda, custody_token_mint).0;
     let lp_token_mint_pda = pda::get_lp_token_mint_pda(pool_pda).0;
 
+    let custody_token_program =
+        utils::get_token_program(program_test_ctx, custody_token_mint).await;
+
     let funding_account_address =
-        utils::find_associated_token_account(&owner.pubkey(), custody_token_mint).0;
+        utils::find_user_token_account(&owner.pubkey(), custody_token_mint, &custody_token_program);
     let lp_token_account_address =
         utils::find_associated_token_account(&owner.pubkey(), &lp_token_mint_pda).0;
 
@@ -39,31 +41,37 @@ pub async fn test_add_liquidity(
     let custody_oracle_account_address = custody_account.oracle.oracle_account;
 
     // Save account state before tx execution
-    let owner_funding_account_before = program_test_ctx
-        .get_token_account(funding_account_address)
-        .await
-        .unwrap();
-    let owner_lp_token_account_before = program_test_ctx
-        .get_token_account(lp_token_account_address)
-        .await
-        .unwrap();
-    let custody_token_account_before = program_test_ctx
-        .get_token_account(custody_token_account_pda)
-        .await
-        .unwrap();
+    let owner_funding_balance_before = utils::get_user_token_balance(
+        program_test_ctx,
+        funding_account_address,
+        custody_token_mint,
+    )
+    .await;
+    let owner_lp_token_account_before =
+        utils::get_token_account(program_test_ctx, lp_token_account_address).await;
+    let custody_token_account_before =
+        utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
+
+    let pool_day_stats_pda =
+        utils::get_current_pool_day_stats_pda(program_test_ctx, pool_pda).await;
 
     let accounts_meta = {
         let accounts = perpetuals::accounts::AddLiquidity {
             owner: owner.pubkey(),
             funding_account: funding_account_address,
+            wsol_account: pda::get_wsol_account_pda(&owner.pubkey()).0,
             lp_token_account: lp_token_account_address,
             transfer_authority: transfer_authority_pda,
             perpetuals: perpetuals_pda,
             pool: *pool_pda,
+            pool_day_stats: pool_day_stats_pda,
             custody: custody_pda,
             custody_oracle_account: custody_oracle_account_address,
             custody_token_account: custody_token_account_pda,
+            custody_token_mint: *custody_token_mint,
             lp_token_mint: lp_token_mint_pda,
+            system_program: anchor_lang::system_program::ID,
+            custody_token_program,
             token_program: anchor_spl::token::ID,
         };
 
@@ -104,20 +112,18 @@ pub async fn test_add_liquidity(
     .await?;
 
     // ==== THEN ==============================================================
-    let owner_funding_account_after = program_test_ctx
-        .get_token_account(funding_account_address)
-        .await
-        .unwrap();
-    let owner_lp_token_account_after = program_test_ctx
-        .get_token_account(lp_token_account_address)
-        .await
-        .unwrap();
-    let custody_token_account_after = program_test_ctx
-        .get_token_account(custody_token_account_pda)
-        .await
-        .unwrap();
-
-    assert!(owner_funding_account_after.amount < owner_funding_account_before.amount);
+    let owner_funding_balance_after = utils::get_user_token_balance(
+        program_test_ctx,
+        funding_account_address,
+        custody_token_mint,
+    )
+    .await;
+    let owner_lp_token_account_after =
+        utils::get_token_account(program_test_ctx, lp_token_account_address).await;
+    let custody_token_account_after =
+        utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
+
+    assert!(owner_funding_balance_after < owner_funding_balance_before);
     assert!(owner_lp_token_account_after.amount > owner_lp_token_account_before.amount);
     assert!(custody_token_account_after.amount > custody_token_account_before.amount);
 
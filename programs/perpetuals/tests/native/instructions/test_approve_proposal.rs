@@ -0,0 +1,45 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{prelude::Pubkey, ToAccountMetas},
+    perpetuals::{instructions::ApproveProposalParams, state::multisig::Proposal},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_approve_proposal(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    proposal_pda: &Pubkey,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let multisig_pda = pda::get_multisig_pda().0;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::ApproveProposal {
+            admin: admin.pubkey(),
+            multisig: multisig_pda,
+            proposal: *proposal_pda,
+        };
+
+        accounts.to_account_metas(None)
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::ApproveProposal {
+            params: ApproveProposalParams {},
+        },
+        Some(&payer.pubkey()),
+        &[admin, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let proposal_account = utils::get_account::<Proposal>(program_test_ctx, *proposal_pda).await;
+
+    assert!(proposal_account.approvals.contains(&admin.pubkey()));
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{prelude::Pubkey, ToAccountMetas},
+    perpetuals::{instructions::CancelQueuedParams, state::timelock::QueuedInstruction},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_cancel_queued(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    queued_instruction_pda: &Pubkey,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let queued_instruction_account =
+        utils::get_account::<QueuedInstruction>(program_test_ctx, *queued_instruction_pda).await;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::CancelQueued {
+            admin: admin.pubkey(),
+            multisig: pda::get_multisig_pda().0,
+            perpetuals: pda::get_perpetuals_pda().0,
+            payer: queued_instruction_account.payer,
+            queued_instruction: *queued_instruction_pda,
+        };
+
+        accounts.to_account_metas(None)
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::CancelQueued {
+            params: CancelQueuedParams {},
+        },
+        Some(&payer.pubkey()),
+        &[admin, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    // queued instruction is closed
+    assert!(program_test_ctx
+        .banks_client
+        .get_account(*queued_instruction_pda)
+        .await
+        .unwrap()
+        .is_none());
+
+    Ok(())
+}
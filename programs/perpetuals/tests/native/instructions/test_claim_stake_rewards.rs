@@ -0,0 +1,135 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::ClaimStakeRewardsParams,
+        state::{custody::Custody, pool::Pool, staking::StakeAccount},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_claim_stake_rewards(
+    program_test_ctx: &mut ProgramTestContext,
+    owner: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+
+    // Prepare PDA and addresses
+    let transfer_authority_pda = pda::get_transfer_authority_pda().0;
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let staking_pda = pda::get_staking_pda(pool_pda).0;
+    let stake_account_pda = pda::get_stake_account_pda(&owner.pubkey(), pool_pda).0;
+
+    let pool_account = utils::get_account::<Pool>(program_test_ctx, *pool_pda).await;
+
+    let mut custody_accounts = vec![];
+    for custody in &pool_account.custodies {
+        custody_accounts.push(utils::get_account::<Custody>(program_test_ctx, *custody).await);
+    }
+
+    let mut custody_token_programs = vec![];
+    for custody_account in &custody_accounts {
+        custody_token_programs
+            .push(utils::get_token_program(program_test_ctx, &custody_account.mint).await);
+    }
+
+    let receiving_account_addresses: Vec<Pubkey> = custody_accounts
+        .iter()
+        .zip(custody_token_programs.iter())
+        .map(|(custody, token_program)| {
+            utils::find_associated_token_account_with_program(
+                &owner.pubkey(),
+                &custody.mint,
+                token_program,
+            )
+            .0
+        })
+        .collect();
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::ClaimStakeRewards {
+            owner: owner.pubkey(),
+            transfer_authority: transfer_authority_pda,
+            perpetuals: perpetuals_pda,
+            pool: *pool_pda,
+            staking: staking_pda,
+            stake_account: stake_account_pda,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        // For each token, add custody account as remaining_account
+        for custody in &pool_account.custodies {
+            accounts_meta.push(AccountMeta {
+                pubkey: *custody,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        // For each token, add custody token account as remaining_account
+        for custody_account in &custody_accounts {
+            accounts_meta.push(AccountMeta {
+                pubkey: custody_account.token_account,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        // For each token, add receiving account as remaining_account
+        for receiving_account_address in &receiving_account_addresses {
+            accounts_meta.push(AccountMeta {
+                pubkey: *receiving_account_address,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        // For each token, add custody token mint as remaining_account
+        for custody_account in &custody_accounts {
+            accounts_meta.push(AccountMeta {
+                pubkey: custody_account.mint,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // For each token, add token program of the custody mint as remaining_account
+        for token_program in &custody_token_programs {
+            accounts_meta.push(AccountMeta {
+                pubkey: *token_program,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::ClaimStakeRewards {
+            params: ClaimStakeRewardsParams {},
+        },
+        Some(&payer.pubkey()),
+        &[owner, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let stake_account =
+        utils::get_account::<StakeAccount>(program_test_ctx, stake_account_pda).await;
+
+    for reward in stake_account.rewards.iter() {
+        assert_eq!(reward.pending_amount, 0);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{prelude::Pubkey, ToAccountMetas},
+    perpetuals::instructions::ClosePoolDayStatsParams,
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_close_pool_day_stats(
+    program_test_ctx: &mut ProgramTestContext,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    pool_day_stats_pda: &Pubkey,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        perpetuals::accounts::ClosePoolDayStats {
+            payer: payer.pubkey(),
+            perpetuals: perpetuals_pda,
+            pool: *pool_pda,
+            pool_day_stats: *pool_day_stats_pda,
+        }
+        .to_account_metas(None),
+        perpetuals::instruction::ClosePoolDayStats {
+            params: ClosePoolDayStatsParams {},
+        },
+        Some(&payer.pubkey()),
+        &[payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    assert!(program_test_ctx
+        .banks_client
+        .get_account(*pool_day_stats_pda)
+        .await
+        .unwrap()
+        .is_none());
+
+    Ok(())
+}
@@ -1,8 +1,13 @@
 use {
     crate::utils::{self, pda},
-    anchor_lang::{prelude::Pubkey, ToAccountMetas},
-    bonfida_test_utils::ProgramTestContextExt,
-    perpetuals::{instructions::ClosePositionParams, state::custody::Custody},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::ClosePositionParams,
+        state::{custody::Custody, margin_account::MarginAccount, position::Position},
+    },
     solana_program_test::{BanksClientError, ProgramTestContext},
     solana_sdk::signer::{keypair::Keypair, Signer},
 };
@@ -25,37 +30,119 @@ pub async fn test_close_position(
     let custody_token_account_pda =
         pda::get_custody_token_account_pda(pool_pda, custody_token_mint).0;
 
-    let receiving_account_address =
-        utils::find_associated_token_account(&owner.pubkey(), custody_token_mint).0;
+    let custody_token_program =
+        utils::get_token_program(program_test_ctx, custody_token_mint).await;
+
+    let receiving_account_address = utils::find_associated_token_account_with_program(
+        &owner.pubkey(),
+        custody_token_mint,
+        &custody_token_program,
+    )
+    .0;
 
     let custody_account = utils::get_account::<Custody>(program_test_ctx, custody_pda).await;
     let custody_oracle_account_address = custody_account.oracle.oracle_account;
 
     // Save account state before tx execution
-    let owner_receiving_account_before = program_test_ctx
-        .get_token_account(receiving_account_address)
-        .await
-        .unwrap();
-    let custody_token_account_before = program_test_ctx
-        .get_token_account(custody_token_account_pda)
-        .await
-        .unwrap();
+    let owner_receiving_account_before =
+        utils::get_token_account(program_test_ctx, receiving_account_address).await;
+    let custody_token_account_before =
+        utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
+
+    let pool_day_stats_pda =
+        utils::get_current_pool_day_stats_pda(program_test_ctx, pool_pda).await;
+
+    let position_account = utils::get_account::<Position>(program_test_ctx, *position_pda).await;
+
+    let mut accounts_meta = perpetuals::accounts::ClosePosition {
+        owner: owner.pubkey(),
+        receiving_account: receiving_account_address,
+        wsol_account: pda::get_wsol_account_pda(&owner.pubkey()).0,
+        transfer_authority: transfer_authority_pda,
+        perpetuals: perpetuals_pda,
+        pool: *pool_pda,
+        pool_day_stats: pool_day_stats_pda,
+        position: *position_pda,
+        custody: custody_pda,
+        custody_oracle_account: custody_oracle_account_address,
+        custody_token_account: custody_token_account_pda,
+        custody_token_mint: *custody_token_mint,
+        system_program: anchor_lang::system_program::ID,
+        token_program: custody_token_program,
+    }
+    .to_account_metas(None);
+
+    // Linked positions are settled against the margin account
+    if position_account.is_cross_margin {
+        let margin_account_pda = pda::get_margin_account_pda(&owner.pubkey(), pool_pda).0;
+        let margin_account =
+            utils::get_account::<MarginAccount>(program_test_ctx, margin_account_pda).await;
+        let collateral_custody_account =
+            utils::get_account::<Custody>(program_test_ctx, margin_account.collateral_custody)
+                .await;
+
+        accounts_meta.push(AccountMeta {
+            pubkey: margin_account_pda,
+            is_signer: false,
+            is_writable: true,
+        });
+        accounts_meta.push(AccountMeta {
+            pubkey: margin_account.collateral_custody,
+            is_signer: false,
+            is_writable: true,
+        });
+        accounts_meta.push(AccountMeta {
+            pubkey: collateral_custody_account.oracle.oracle_account,
+            is_signer: false,
+            is_writable: false,
+        });
+
+        let other_positions: Vec<Pubkey> = margin_account
+            .positions
+            .iter()
+            .filter(|&position| position != position_pda)
+            .copied()
+            .collect();
+
+        let mut linked_positions = vec![];
+        for position in &other_positions {
+            linked_positions
+                .push(utils::get_account::<Position>(program_test_ctx, *position).await);
+        }
+
+        // For each other linked position, add position account as remaining_account
+        for position in &other_positions {
+            accounts_meta.push(AccountMeta {
+                pubkey: *position,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // For each other linked position, add custody account as remaining_account
+        for position in &linked_positions {
+            accounts_meta.push(AccountMeta {
+                pubkey: position.custody,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // For each other linked position, add custody oracle account as remaining_account
+        for position in &linked_positions {
+            let custody = utils::get_account::<Custody>(program_test_ctx, position.custody).await;
+
+            accounts_meta.push(AccountMeta {
+                pubkey: custody.oracle.oracle_account,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+    }
 
     utils::create_and_execute_perpetuals_ix(
         program_test_ctx,
-        perpetuals::accounts::ClosePosition {
-            owner: owner.pubkey(),
-            receiving_account: receiving_account_address,
-            transfer_authority: transfer_authority_pda,
-            perpetuals: perpetuals_pda,
-            pool: *pool_pda,
-            position: *position_pda,
-            custody: custody_pda,
-            custody_oracle_account: custody_oracle_account_address,
-            custody_token_account: custody_token_account_pda,
-            token_program: anchor_spl::token::ID,
-        }
-        .to_account_metas(None),
+        accounts_meta,
         perpetuals::instruction::ClosePosition { params },
         Some(&payer.pubkey()),
         &[owner, payer],
@@ -65,17 +152,19 @@ pub async fn test_close_position(
     // ==== THEN ==============================================================
     // Check the balance change
     {
-        let owner_receiving_account_after = program_test_ctx
-            .get_token_account(receiving_account_address)
-            .await
-            .unwrap();
-        let custody_token_account_after = program_test_ctx
-            .get_token_account(custody_token_account_pda)
-            .await
-            .unwrap();
-
-        assert!(owner_receiving_account_after.amount > owner_receiving_account_before.amount);
-        assert!(custody_token_account_after.amount < custody_token_account_before.amount);
+        let owner_receiving_account_after =
+            utils::get_token_account(program_test_ctx, receiving_account_address).await;
+        let custody_token_account_after =
+            utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
+
+        // Linked positions in loss may have nothing left to pay out
+        if position_account.is_cross_margin {
+            assert!(owner_receiving_account_after.amount >= owner_receiving_account_before.amount);
+            assert!(custody_token_account_after.amount <= custody_token_account_before.amount);
+        } else {
+            assert!(owner_receiving_account_after.amount > owner_receiving_account_before.amount);
+            assert!(custody_token_account_after.amount < custody_token_account_before.amount);
+        }
     }
 
     Ok(())
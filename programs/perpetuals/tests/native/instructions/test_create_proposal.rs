@@ -0,0 +1,60 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{prelude::Pubkey, ToAccountMetas},
+    perpetuals::{instructions::CreateProposalParams, state::multisig::Proposal},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_create_proposal(
+    program_test_ctx: &mut ProgramTestContext,
+    proposer: &Keypair,
+    payer: &Keypair,
+    params: CreateProposalParams,
+) -> std::result::Result<Pubkey, BanksClientError> {
+    // ==== WHEN ==============================================================
+    let multisig_pda = pda::get_multisig_pda().0;
+    let proposal_pda = pda::get_proposal_pda(params.proposal_id).0;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::CreateProposal {
+            proposer: proposer.pubkey(),
+            multisig: multisig_pda,
+            proposal: proposal_pda,
+            system_program: anchor_lang::system_program::ID,
+        };
+
+        accounts.to_account_metas(None)
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::CreateProposal {
+            params: CreateProposalParams {
+                proposal_id: params.proposal_id,
+                instruction_data: params.instruction_data.clone(),
+                instruction_accounts: params.instruction_accounts.clone(),
+                expiration_time: params.expiration_time,
+            },
+        },
+        Some(&payer.pubkey()),
+        &[proposer, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let proposal_account = utils::get_account::<Proposal>(program_test_ctx, proposal_pda).await;
+
+    assert_eq!(proposal_account.proposal_id, params.proposal_id);
+    assert_eq!(proposal_account.proposer, proposer.pubkey());
+    assert_eq!(proposal_account.instruction_data, params.instruction_data);
+    assert_eq!(
+        proposal_account.instruction_accounts,
+        params.instruction_accounts
+    );
+    assert_eq!(proposal_account.approvals, vec![proposer.pubkey()]);
+    assert!(!proposal_account.executed);
+
+    Ok(proposal_pda)
+}
@@ -0,0 +1,98 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::DistributeFeesParams,
+        state::{
+            custody::Custody,
+            fee_distribution::{FeeDistribution, FeeRecipientType},
+        },
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_distribute_fees(
+    program_test_ctx: &mut ProgramTestContext,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    custody_token_mint: &Pubkey,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+
+    // Prepare PDA and addresses
+    let transfer_authority_pda = pda::get_transfer_authority_pda().0;
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let fee_distribution_pda = pda::get_fee_distribution_pda(pool_pda).0;
+    let custody_pda = pda::get_custody_pda(pool_pda, custody_token_mint).0;
+    let custody_token_account_pda =
+        pda::get_custody_token_account_pda(pool_pda, custody_token_mint).0;
+    let custody_token_program =
+        utils::get_token_program(program_test_ctx, custody_token_mint).await;
+
+    let fee_distribution_account =
+        utils::get_account::<FeeDistribution>(program_test_ctx, fee_distribution_pda).await;
+
+    // Save account state before tx execution
+    let custody_account_before = utils::get_account::<Custody>(program_test_ctx, custody_pda).await;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::DistributeFees {
+            transfer_authority: transfer_authority_pda,
+            perpetuals: perpetuals_pda,
+            pool: *pool_pda,
+            fee_distribution: fee_distribution_pda,
+            custody: custody_pda,
+            custody_token_account: custody_token_account_pda,
+            custody_token_mint: *custody_token_mint,
+            token_program: custody_token_program,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        // For each recipient, add staking or receiving account as remaining_account
+        for recipient in &fee_distribution_account.recipients {
+            let pubkey = if recipient.recipient_type == FeeRecipientType::Stakers {
+                pda::get_staking_pda(pool_pda).0
+            } else {
+                utils::find_associated_token_account_with_program(
+                    &recipient.owner,
+                    custody_token_mint,
+                    &custody_token_program,
+                )
+                .0
+            };
+
+            accounts_meta.push(AccountMeta {
+                pubkey,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::DistributeFees {
+            params: DistributeFeesParams {},
+        },
+        Some(&payer.pubkey()),
+        &[payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let custody_account_after = utils::get_account::<Custody>(program_test_ctx, custody_pda).await;
+
+    assert!(
+        custody_account_after.assets.protocol_fees < custody_account_before.assets.protocol_fees
+    );
+
+    Ok(())
+}
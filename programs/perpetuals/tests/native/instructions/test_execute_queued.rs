@@ -0,0 +1,63 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{instructions::ExecuteQueuedParams, state::timelock::QueuedInstruction},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_execute_queued(
+    program_test_ctx: &mut ProgramTestContext,
+    payer: &Keypair,
+    queued_instruction_pda: &Pubkey,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let queued_instruction_account =
+        utils::get_account::<QueuedInstruction>(program_test_ctx, *queued_instruction_pda).await;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::ExecuteQueued {
+            payer: queued_instruction_account.payer,
+            multisig: pda::get_multisig_pda().0,
+            perpetuals: pda::get_perpetuals_pda().0,
+            queued_instruction: *queued_instruction_pda,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        for key in queued_instruction_account.instruction_accounts.iter() {
+            accounts_meta.push(AccountMeta {
+                pubkey: *key,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::ExecuteQueued {
+            params: ExecuteQueuedParams {},
+        },
+        Some(&payer.pubkey()),
+        &[payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    // queued instruction is closed after execution
+    assert!(program_test_ctx
+        .banks_client
+        .get_account(*queued_instruction_pda)
+        .await
+        .unwrap()
+        .is_none());
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::ExecuteWithdrawalParams,
+        state::{custody::Custody, pool::Pool},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_execute_withdrawal(
+    program_test_ctx: &mut ProgramTestContext,
+    owner: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    custody_token_mint: &Pubkey,
+    params: ExecuteWithdrawalParams,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+
+    // Prepare PDA and addresses
+    let transfer_authority_pda = pda::get_transfer_authority_pda().0;
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let custody_pda = pda::get_custody_pda(pool_pda, custody_token_mint).0;
+    let custody_token_account_pda =
+        pda::get_custody_token_account_pda(pool_pda, custody_token_mint).0;
+    let lp_token_mint_pda = pda::get_lp_token_mint_pda(pool_pda).0;
+
+    let custody_token_program =
+        utils::get_token_program(program_test_ctx, custody_token_mint).await;
+
+    let receiving_account_address = utils::find_associated_token_account_with_program(
+        &owner.pubkey(),
+        custody_token_mint,
+        &custody_token_program,
+    )
+    .0;
+    let withdrawal_queue_pda = pda::get_withdrawal_queue_pda(pool_pda).0;
+    let withdrawal_request_pda = pda::get_withdrawal_request_pda(&owner.pubkey(), pool_pda).0;
+    let escrow_token_account_pda = pda::get_withdrawal_escrow_account_pda(pool_pda).0;
+
+    let custody_account = utils::get_account::<Custody>(program_test_ctx, custody_pda).await;
+    let custody_oracle_account_address = custody_account.oracle.oracle_account;
+
+    // Save account state before tx execution
+    let owner_receiving_account_before =
+        utils::get_token_account(program_test_ctx, receiving_account_address).await;
+    let escrow_token_account_before =
+        utils::get_token_account(program_test_ctx, escrow_token_account_pda).await;
+    let custody_token_account_before =
+        utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
+
+    let pool_day_stats_pda =
+        utils::get_current_pool_day_stats_pda(program_test_ctx, pool_pda).await;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::ExecuteWithdrawal {
+            owner: owner.pubkey(),
+            receiving_account: receiving_account_address,
+            transfer_authority: transfer_authority_pda,
+            perpetuals: perpetuals_pda,
+            pool: *pool_pda,
+            withdrawal_queue: withdrawal_queue_pda,
+            withdrawal_request: withdrawal_request_pda,
+            escrow_token_account: escrow_token_account_pda,
+            custody: custody_pda,
+            custody_oracle_account: custody_oracle_account_address,
+            custody_token_account: custody_token_account_pda,
+            custody_token_mint: *custody_token_mint,
+            lp_token_mint: lp_token_mint_pda,
+            pool_day_stats: pool_day_stats_pda,
+            system_program: anchor_lang::system_program::ID,
+            custody_token_program,
+            token_program: anchor_spl::token::ID,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        let pool_account = utils::get_account::<Pool>(program_test_ctx, *pool_pda).await;
+
+        // For each token, add custody account as remaining_account
+        for custody in &pool_account.custodies {
+            accounts_meta.push(AccountMeta {
+                pubkey: *custody,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // For each token, add custody oracle account as remaining_account
+        for custody in &pool_account.custodies {
+            let custody_account = utils::get_account::<Custody>(program_test_ctx, *custody).await;
+
+            accounts_meta.push(AccountMeta {
+                pubkey: custody_account.oracle.oracle_account,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::ExecuteWithdrawal { params },
+        Some(&payer.pubkey()),
+        &[owner, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let owner_receiving_account_after =
+        utils::get_token_account(program_test_ctx, receiving_account_address).await;
+    let escrow_token_account_after =
+        utils::get_token_account(program_test_ctx, escrow_token_account_pda).await;
+    let custody_token_account_after =
+        utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
+
+    assert!(owner_receiving_account_after.amount > owner_receiving_account_before.amount);
+    assert!(escrow_token_account_after.amount < escrow_token_account_before.amount);
+    assert!(program_test_ctx
+        .banks_client
+        .get_account(withdrawal_request_pda)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(custody_token_account_after.amount < custody_token_account_before.amount);
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::GetPoolStatsParams,
+        state::{custody::Custody, perpetuals::PoolStats, pool::Pool},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_get_pool_stats(
+    program_test_ctx: &mut ProgramTestContext,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+) -> std::result::Result<PoolStats, BanksClientError> {
+    // ==== WHEN ==============================================================
+
+    // Prepare PDA and addresses
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let lp_token_mint_pda = pda::get_lp_token_mint_pda(pool_pda).0;
+
+    let pool_account = utils::get_account::<Pool>(program_test_ctx, *pool_pda).await;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::GetPoolStats {
+            perpetuals: perpetuals_pda,
+            pool: *pool_pda,
+            lp_token_mint: lp_token_mint_pda,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        // For each token, add custody account as remaining_account
+        for custody in &pool_account.custodies {
+            accounts_meta.push(AccountMeta {
+                pubkey: *custody,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // For each token, add custody oracle account as remaining_account
+        for custody in &pool_account.custodies {
+            let custody_account = utils::get_account::<Custody>(program_test_ctx, *custody).await;
+
+            accounts_meta.push(AccountMeta {
+                pubkey: custody_account.oracle.oracle_account,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        accounts_meta
+    };
+
+    let pool_stats = utils::simulate_perpetuals_ix::<_, _, PoolStats>(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::GetPoolStats {
+            params: GetPoolStatsParams {},
+        },
+        Some(&payer.pubkey()),
+        &[payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    assert_eq!(pool_stats.custodies.len(), pool_account.custodies.len());
+
+    Ok(pool_stats)
+}
@@ -0,0 +1,96 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::GetPositionHealthParams,
+        state::{
+            custody::Custody, margin_account::MarginAccount, perpetuals::PositionHealth,
+            position::Position,
+        },
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_get_position_health(
+    program_test_ctx: &mut ProgramTestContext,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    position_pda: &Pubkey,
+) -> std::result::Result<PositionHealth, BanksClientError> {
+    // ==== WHEN ==============================================================
+    let position_account = utils::get_account::<Position>(program_test_ctx, *position_pda).await;
+    let custody_account =
+        utils::get_account::<Custody>(program_test_ctx, position_account.custody).await;
+
+    // Prepare PDA and addresses
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+
+    let mut accounts_meta = perpetuals::accounts::GetPositionHealth {
+        perpetuals: perpetuals_pda,
+        pool: *pool_pda,
+        position: *position_pda,
+        custody: position_account.custody,
+        custody_oracle_account: custody_account.oracle.oracle_account,
+    }
+    .to_account_metas(None);
+
+    // Linked positions are reported against the whole margin account
+    if position_account.is_cross_margin {
+        let margin_account_pda = pda::get_margin_account_pda(&position_account.owner, pool_pda).0;
+        let margin_account =
+            utils::get_account::<MarginAccount>(program_test_ctx, margin_account_pda).await;
+        let collateral_custody_account =
+            utils::get_account::<Custody>(program_test_ctx, margin_account.collateral_custody)
+                .await;
+
+        let mut linked_positions = vec![];
+        for position in &margin_account.positions {
+            linked_positions
+                .push(utils::get_account::<Position>(program_test_ctx, *position).await);
+        }
+
+        let mut linked_oracles = vec![];
+        for position in &linked_positions {
+            let custody = utils::get_account::<Custody>(program_test_ctx, position.custody).await;
+            linked_oracles.push(custody.oracle.oracle_account);
+        }
+
+        let remaining_accounts = [
+            margin_account_pda,
+            margin_account.collateral_custody,
+            collateral_custody_account.oracle.oracle_account,
+        ]
+        .into_iter()
+        .chain(margin_account.positions.iter().copied())
+        .chain(linked_positions.iter().map(|position| position.custody))
+        .chain(linked_oracles);
+
+        for pubkey in remaining_accounts {
+            accounts_meta.push(AccountMeta {
+                pubkey,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+    }
+
+    let position_health = utils::simulate_perpetuals_ix::<_, _, PositionHealth>(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::GetPositionHealth {
+            params: GetPositionHealthParams {},
+        },
+        Some(&payer.pubkey()),
+        &[payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    assert!(position_health.liquidation_state <= 1);
+
+    Ok(position_health)
+}
@@ -0,0 +1,49 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::instructions::GuardianPauseParams,
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_guardian_pause(
+    program_test_ctx: &mut ProgramTestContext,
+    guardian: &Keypair,
+    payer: &Keypair,
+    params: GuardianPauseParams,
+    target_accounts: &[Pubkey],
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::GuardianPause {
+            guardian: guardian.pubkey(),
+            perpetuals: pda::get_perpetuals_pda().0,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        for key in target_accounts.iter() {
+            accounts_meta.push(AccountMeta {
+                pubkey: *key,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::GuardianPause { params },
+        Some(&payer.pubkey()),
+        &[guardian, payer],
+    )
+    .await?;
+
+    Ok(())
+}
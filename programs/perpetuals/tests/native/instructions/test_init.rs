@@ -74,6 +74,7 @@ pub async fn test_init(
             params.allow_collateral_withdrawal
         );
         assert_eq!(p.allow_size_change, params.allow_size_change);
+        assert_eq!(p.allow_liquidation, params.allow_liquidation);
     }
 
     assert_eq!(
@@ -0,0 +1,90 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::InitStakingParams,
+        state::{multisig::Multisig, staking::Staking},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_init_staking(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    params: InitStakingParams,
+    multisig_signers: &[&Keypair],
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let multisig_pda = pda::get_multisig_pda().0;
+    let transfer_authority_pda = pda::get_transfer_authority_pda().0;
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let lp_token_mint_pda = pda::get_lp_token_mint_pda(pool_pda).0;
+    let (staking_pda, staking_bump) = pda::get_staking_pda(pool_pda);
+    let (stake_token_account_pda, stake_token_account_bump) =
+        pda::get_stake_token_account_pda(pool_pda);
+
+    let multisig_account = utils::get_account::<Multisig>(program_test_ctx, multisig_pda).await;
+
+    // One Tx per multisig signer
+    for i in 0..multisig_account.min_signatures {
+        let signer: &Keypair = multisig_signers[i as usize];
+
+        let accounts_meta = {
+            let accounts = perpetuals::accounts::InitStaking {
+                admin: admin.pubkey(),
+                multisig: multisig_pda,
+                transfer_authority: transfer_authority_pda,
+                perpetuals: perpetuals_pda,
+                pool: *pool_pda,
+                staking: staking_pda,
+                stake_token_account: stake_token_account_pda,
+                lp_token_mint: lp_token_mint_pda,
+                system_program: anchor_lang::system_program::ID,
+                token_program: anchor_spl::token::ID,
+                rent: solana_program::sysvar::rent::ID,
+            };
+
+            let mut accounts_meta = accounts.to_account_metas(None);
+
+            accounts_meta.push(AccountMeta {
+                pubkey: signer.pubkey(),
+                is_signer: true,
+                is_writable: false,
+            });
+
+            accounts_meta
+        };
+
+        utils::create_and_execute_perpetuals_ix(
+            program_test_ctx,
+            accounts_meta,
+            perpetuals::instruction::InitStaking {
+                params: params.clone(),
+            },
+            Some(&payer.pubkey()),
+            &[admin, payer, signer],
+        )
+        .await?;
+    }
+
+    // ==== THEN ==============================================================
+    let staking_account = utils::get_account::<Staking>(program_test_ctx, staking_pda).await;
+
+    assert_eq!(staking_account.pool, *pool_pda);
+    assert_eq!(staking_account.stake_token_account, stake_token_account_pda);
+    assert_eq!(staking_account.total_staked, 0);
+    assert_eq!(staking_account.fee_share, params.fee_share);
+    assert_eq!(staking_account.bump, staking_bump);
+    assert_eq!(
+        staking_account.stake_token_account_bump,
+        stake_token_account_bump
+    );
+
+    Ok(())
+}
@@ -1,7 +1,6 @@
 use {
     crate::utils::{self, pda},
     anchor_lang::{prelude::Pubkey, ToAccountMetas},
-    bonfida_test_utils::ProgramTestContextExt,
     perpetuals::{
         instructions::LiquidateParams,
         state::{custody::Custody, position::Position},
@@ -32,28 +31,36 @@ pub async fn test_liquidate(
     let custody_token_account_pda =
         pda::get_custody_token_account_pda(pool_pda, custody_token_mint).0;
 
-    let receiving_account_address =
-        utils::find_associated_token_account(&owner, custody_token_mint).0;
+    let custody_token_program =
+        utils::get_token_program(program_test_ctx, custody_token_mint).await;
 
-    let rewards_receiving_account_address =
-        utils::find_associated_token_account(&liquidator.pubkey(), custody_token_mint).0;
+    let receiving_account_address = utils::find_associated_token_account_with_program(
+        &owner,
+        custody_token_mint,
+        &custody_token_program,
+    )
+    .0;
+
+    let rewards_receiving_account_address = utils::find_associated_token_account_with_program(
+        &liquidator.pubkey(),
+        custody_token_mint,
+        &custody_token_program,
+    )
+    .0;
 
     let custody_account = utils::get_account::<Custody>(program_test_ctx, custody_pda).await;
     let custody_oracle_account_address = custody_account.oracle.oracle_account;
 
     // Save account state before tx execution
-    let receiving_account_before = program_test_ctx
-        .get_token_account(receiving_account_address)
-        .await
-        .unwrap();
-    let custody_token_account_before = program_test_ctx
-        .get_token_account(custody_token_account_pda)
-        .await
-        .unwrap();
-    let rewards_receiving_account_before = program_test_ctx
-        .get_token_account(rewards_receiving_account_address)
-        .await
-        .unwrap();
+    let receiving_account_before =
+        utils::get_token_account(program_test_ctx, receiving_account_address).await;
+    let custody_token_account_before =
+        utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
+    let rewards_receiving_account_before =
+        utils::get_token_account(program_test_ctx, rewards_receiving_account_address).await;
+
+    let pool_day_stats_pda =
+        utils::get_current_pool_day_stats_pda(program_test_ctx, pool_pda).await;
 
     utils::create_and_execute_perpetuals_ix(
         program_test_ctx,
@@ -64,11 +71,14 @@ pub async fn test_liquidate(
             transfer_authority: transfer_authority_pda,
             perpetuals: perpetuals_pda,
             pool: *pool_pda,
+            pool_day_stats: pool_day_stats_pda,
             position: *position_pda,
             custody: custody_pda,
             custody_oracle_account: custody_oracle_account_address,
             custody_token_account: custody_token_account_pda,
-            token_program: anchor_spl::token::ID,
+            custody_token_mint: *custody_token_mint,
+            system_program: anchor_lang::system_program::ID,
+            token_program: custody_token_program,
         }
         .to_account_metas(None),
         perpetuals::instruction::Liquidate {
@@ -82,18 +92,12 @@ pub async fn test_liquidate(
     // ==== THEN ==============================================================
     // Check the balance change
     {
-        let receiving_account_after = program_test_ctx
-            .get_token_account(receiving_account_address)
-            .await
-            .unwrap();
-        let custody_token_account_after = program_test_ctx
-            .get_token_account(custody_token_account_pda)
-            .await
-            .unwrap();
-        let rewards_receiving_account_after = program_test_ctx
-            .get_token_account(rewards_receiving_account_address)
-            .await
-            .unwrap();
+        let receiving_account_after =
+            utils::get_token_account(program_test_ctx, receiving_account_address).await;
+        let custody_token_account_after =
+            utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
+        let rewards_receiving_account_after =
+            utils::get_token_account(program_test_ctx, rewards_receiving_account_address).await;
 
         assert!(receiving_account_after.amount >= receiving_account_before.amount);
         assert!(custody_token_account_after.amount <= custody_token_account_before.amount);
@@ -0,0 +1,156 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::LiquidateMarginAccountParams,
+        state::{custody::Custody, margin_account::MarginAccount, position::Position},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_liquidate_margin_account(
+    program_test_ctx: &mut ProgramTestContext,
+    liquidator: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    position_pda: &Pubkey,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let position_account = utils::get_account::<Position>(program_test_ctx, *position_pda).await;
+    let owner = position_account.owner;
+
+    // Prepare PDA and addresses
+    let transfer_authority_pda = pda::get_transfer_authority_pda().0;
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let margin_account_pda = pda::get_margin_account_pda(&owner, pool_pda).0;
+
+    let custody_account =
+        utils::get_account::<Custody>(program_test_ctx, position_account.custody).await;
+    let custody_token_account_pda =
+        pda::get_custody_token_account_pda(pool_pda, &custody_account.mint).0;
+
+    let custody_token_program =
+        utils::get_token_program(program_test_ctx, &custody_account.mint).await;
+
+    let receiving_account_address = utils::find_associated_token_account_with_program(
+        &owner,
+        &custody_account.mint,
+        &custody_token_program,
+    )
+    .0;
+    let rewards_receiving_account_address = utils::find_associated_token_account_with_program(
+        &liquidator.pubkey(),
+        &custody_account.mint,
+        &custody_token_program,
+    )
+    .0;
+
+    let margin_account_before =
+        utils::get_account::<MarginAccount>(program_test_ctx, margin_account_pda).await;
+    let collateral_custody_account =
+        utils::get_account::<Custody>(program_test_ctx, margin_account_before.collateral_custody)
+            .await;
+
+    let owner_lamports_before = program_test_ctx
+        .banks_client
+        .get_balance(owner)
+        .await
+        .unwrap();
+
+    let pool_day_stats_pda =
+        utils::get_current_pool_day_stats_pda(program_test_ctx, pool_pda).await;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::LiquidateMarginAccount {
+            signer: liquidator.pubkey(),
+            receiving_account: receiving_account_address,
+            rewards_receiving_account: rewards_receiving_account_address,
+            owner,
+            transfer_authority: transfer_authority_pda,
+            perpetuals: perpetuals_pda,
+            pool: *pool_pda,
+            pool_day_stats: pool_day_stats_pda,
+            margin_account: margin_account_pda,
+            position: *position_pda,
+            custody: position_account.custody,
+            custody_oracle_account: custody_account.oracle.oracle_account,
+            custody_token_account: custody_token_account_pda,
+            custody_token_mint: custody_account.mint,
+            collateral_custody: margin_account_before.collateral_custody,
+            collateral_custody_oracle_account: collateral_custody_account.oracle.oracle_account,
+            system_program: anchor_lang::system_program::ID,
+            token_program: custody_token_program,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        let mut linked_positions = vec![];
+        for position in &margin_account_before.positions {
+            linked_positions
+                .push(utils::get_account::<Position>(program_test_ctx, *position).await);
+        }
+
+        // For each linked position, add position account as remaining_account
+        for position in &margin_account_before.positions {
+            accounts_meta.push(AccountMeta {
+                pubkey: *position,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // For each linked position, add custody account as remaining_account
+        for position in &linked_positions {
+            accounts_meta.push(AccountMeta {
+                pubkey: position.custody,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // For each linked position, add custody oracle account as remaining_account
+        for position in &linked_positions {
+            let custody = utils::get_account::<Custody>(program_test_ctx, position.custody).await;
+
+            accounts_meta.push(AccountMeta {
+                pubkey: custody.oracle.oracle_account,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::LiquidateMarginAccount {
+            params: LiquidateMarginAccountParams {},
+        },
+        Some(&payer.pubkey()),
+        &[liquidator, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let margin_account_after =
+        utils::get_account::<MarginAccount>(program_test_ctx, margin_account_pda).await;
+
+    assert!(!margin_account_after.positions.contains(position_pda));
+    assert!(margin_account_after.collateral_amount <= margin_account_before.collateral_amount);
+
+    // Rent released by the margin account shrink goes back to the owner
+    let owner_lamports_after = program_test_ctx
+        .banks_client
+        .get_balance(owner)
+        .await
+        .unwrap();
+    assert!(owner_lamports_after >= owner_lamports_before);
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use {
+    crate::utils,
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{instructions::MigrateAccountParams, state::migration},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_migrate_account(
+    program_test_ctx: &mut ProgramTestContext,
+    payer: &Keypair,
+    account: &Pubkey,
+    remaining_accounts: &[Pubkey],
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::MigrateAccount {
+            payer: payer.pubkey(),
+            account: *account,
+            system_program: anchor_lang::system_program::ID,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        for remaining_account in remaining_accounts {
+            accounts_meta.push(AccountMeta {
+                pubkey: *remaining_account,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::MigrateAccount {
+            params: MigrateAccountParams {},
+        },
+        Some(&payer.pubkey()),
+        &[payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let account_data = program_test_ctx
+        .banks_client
+        .get_account(*account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+
+    // fully migrated accounts have no applicable steps left
+    assert!(migration::get_migration_step(account, &account_data)
+        .unwrap()
+        .is_none());
+
+    Ok(())
+}
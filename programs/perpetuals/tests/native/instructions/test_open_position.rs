@@ -1,7 +1,6 @@
 use {
     crate::utils::{self, pda},
     anchor_lang::{prelude::Pubkey, ToAccountMetas},
-    bonfida_test_utils::ProgramTestContextExt,
     perpetuals::{
         instructions::OpenPositionParams,
         state::{custody::Custody, perpetuals::Perpetuals, position::Position},
@@ -30,36 +29,45 @@ pub async fn test_open_position(
     let (position_pda, position_bump) =
         pda::get_position_pda(&owner.pubkey(), pool_pda, &custody_pda, params.side);
 
-    let funding_account_address =
-        utils::find_associated_token_account(&owner.pubkey(), custody_token_mint).0;
+    let custody_token_program =
+        utils::get_token_program(program_test_ctx, custody_token_mint).await;
+
+    let funding_account_address = utils::find_associated_token_account_with_program(
+        &owner.pubkey(),
+        custody_token_mint,
+        &custody_token_program,
+    )
+    .0;
 
     let custody_account = utils::get_account::<Custody>(program_test_ctx, custody_pda).await;
     let custody_oracle_account_address = custody_account.oracle.oracle_account;
 
     // Save account state before tx execution
-    let owner_funding_account_before = program_test_ctx
-        .get_token_account(funding_account_address)
-        .await
-        .unwrap();
-    let custody_token_account_before = program_test_ctx
-        .get_token_account(custody_token_account_pda)
-        .await
-        .unwrap();
+    let owner_funding_account_before =
+        utils::get_token_account(program_test_ctx, funding_account_address).await;
+    let custody_token_account_before =
+        utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
+
+    let pool_day_stats_pda =
+        utils::get_current_pool_day_stats_pda(program_test_ctx, pool_pda).await;
 
     utils::create_and_execute_perpetuals_ix(
         program_test_ctx,
         perpetuals::accounts::OpenPosition {
             owner: owner.pubkey(),
             funding_account: funding_account_address,
+            wsol_account: pda::get_wsol_account_pda(&owner.pubkey()).0,
             transfer_authority: transfer_authority_pda,
             perpetuals: perpetuals_pda,
             pool: *pool_pda,
+            pool_day_stats: pool_day_stats_pda,
             position: position_pda,
             custody: custody_pda,
             custody_oracle_account: custody_oracle_account_address,
             custody_token_account: custody_token_account_pda,
+            custody_token_mint: *custody_token_mint,
             system_program: anchor_lang::system_program::ID,
-            token_program: anchor_spl::token::ID,
+            token_program: custody_token_program,
         }
         .to_account_metas(None),
         perpetuals::instruction::OpenPosition { params },
@@ -70,19 +78,19 @@ pub async fn test_open_position(
 
     // ==== THEN ==============================================================
     // Check the balance change
-    {
-        let owner_funding_account_after = program_test_ctx
-            .get_token_account(funding_account_address)
-            .await
-            .unwrap();
-        let custody_token_account_after = program_test_ctx
-            .get_token_account(custody_token_account_pda)
-            .await
-            .unwrap();
+    let transfer_fee = {
+        let owner_funding_account_after =
+            utils::get_token_account(program_test_ctx, funding_account_address).await;
+        let custody_token_account_after =
+            utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
 
         assert!(owner_funding_account_after.amount < owner_funding_account_before.amount);
         assert!(custody_token_account_after.amount > custody_token_account_before.amount);
-    }
+
+        // amount withheld by the Token-2022 transfer fee extension
+        (owner_funding_account_before.amount - owner_funding_account_after.amount)
+            - (custody_token_account_after.amount - custody_token_account_before.amount)
+    };
 
     // Check the position
     {
@@ -101,7 +109,10 @@ pub async fn test_open_position(
         assert_eq!(position_account.side, params.side);
         assert_eq!(position_account.unrealized_profit_usd, 0);
         assert_eq!(position_account.unrealized_loss_usd, 0);
-        assert_eq!(position_account.collateral_amount, params.collateral);
+        assert_eq!(
+            position_account.collateral_amount,
+            params.collateral - transfer_fee
+        );
         assert_eq!(position_account.bump, position_bump);
     }
 
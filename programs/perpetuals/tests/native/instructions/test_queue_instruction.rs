@@ -0,0 +1,61 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{prelude::Pubkey, ToAccountMetas},
+    perpetuals::{instructions::QueueInstructionParams, state::timelock::QueuedInstruction},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_queue_instruction(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    params: QueueInstructionParams,
+) -> std::result::Result<Pubkey, BanksClientError> {
+    // ==== WHEN ==============================================================
+    let queued_instruction_pda = pda::get_queued_instruction_pda(params.queue_id).0;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::QueueInstruction {
+            admin: admin.pubkey(),
+            multisig: pda::get_multisig_pda().0,
+            perpetuals: pda::get_perpetuals_pda().0,
+            queued_instruction: queued_instruction_pda,
+            system_program: anchor_lang::system_program::ID,
+        };
+
+        accounts.to_account_metas(None)
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::QueueInstruction {
+            params: QueueInstructionParams {
+                queue_id: params.queue_id,
+                instruction_data: params.instruction_data.clone(),
+                instruction_accounts: params.instruction_accounts.clone(),
+            },
+        },
+        Some(&payer.pubkey()),
+        &[admin, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let queued_instruction_account =
+        utils::get_account::<QueuedInstruction>(program_test_ctx, queued_instruction_pda).await;
+
+    assert_eq!(queued_instruction_account.queue_id, params.queue_id);
+    assert_eq!(queued_instruction_account.payer, admin.pubkey());
+    assert_eq!(
+        queued_instruction_account.instruction_data,
+        params.instruction_data
+    );
+    assert_eq!(
+        queued_instruction_account.instruction_accounts,
+        params.instruction_accounts
+    );
+
+    Ok(queued_instruction_pda)
+}
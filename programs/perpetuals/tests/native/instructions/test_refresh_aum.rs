@@ -0,0 +1,84 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::RefreshAumParams,
+        state::{custody::Custody, perpetuals::Perpetuals, pool::Pool},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_refresh_aum(
+    program_test_ctx: &mut ProgramTestContext,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    custodies: &[Pubkey],
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::RefreshAum {
+            perpetuals: perpetuals_pda,
+            pool: *pool_pda,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        // For each refreshed custody, add custody account as remaining_account
+        for custody in custodies {
+            accounts_meta.push(AccountMeta {
+                pubkey: *custody,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // For each refreshed custody, add custody oracle account as remaining_account
+        for custody in custodies {
+            let custody_account = utils::get_account::<Custody>(program_test_ctx, *custody).await;
+
+            accounts_meta.push(AccountMeta {
+                pubkey: custody_account.oracle.oracle_account,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::RefreshAum {
+            params: RefreshAumParams {},
+        },
+        Some(&payer.pubkey()),
+        &[payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let perpetuals_account =
+        utils::get_account::<Perpetuals>(program_test_ctx, perpetuals_pda).await;
+    let pool_account = utils::get_account::<Pool>(program_test_ctx, *pool_pda).await;
+
+    for custody in custodies {
+        let token_id = pool_account
+            .custodies
+            .iter()
+            .position(|k| k == custody)
+            .unwrap();
+        assert_eq!(
+            pool_account.custody_aum[token_id].price_time,
+            perpetuals_account.inception_time
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::RemoveLiquidityProportionalParams,
+        state::{custody::Custody, pool::Pool},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_remove_liquidity_proportional(
+    program_test_ctx: &mut ProgramTestContext,
+    owner: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    params: RemoveLiquidityProportionalParams,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+
+    // Prepare PDA and addresses
+    let transfer_authority_pda = pda::get_transfer_authority_pda().0;
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let lp_token_mint_pda = pda::get_lp_token_mint_pda(pool_pda).0;
+
+    let lp_token_account_address =
+        utils::find_associated_token_account(&owner.pubkey(), &lp_token_mint_pda).0;
+
+    let pool_account = utils::get_account::<Pool>(program_test_ctx, *pool_pda).await;
+
+    let mut custody_accounts = vec![];
+    for custody in &pool_account.custodies {
+        custody_accounts.push(utils::get_account::<Custody>(program_test_ctx, *custody).await);
+    }
+
+    let mut custody_token_programs = vec![];
+    for custody_account in &custody_accounts {
+        custody_token_programs
+            .push(utils::get_token_program(program_test_ctx, &custody_account.mint).await);
+    }
+
+    let receiving_account_addresses: Vec<Pubkey> = custody_accounts
+        .iter()
+        .zip(custody_token_programs.iter())
+        .map(|(custody, token_program)| {
+            utils::find_associated_token_account_with_program(
+                &owner.pubkey(),
+                &custody.mint,
+                token_program,
+            )
+            .0
+        })
+        .collect();
+
+    // Save account state before tx execution
+    let owner_lp_token_account_before =
+        utils::get_token_account(program_test_ctx, lp_token_account_address).await;
+    let mut owner_receiving_accounts_before = vec![];
+    for receiving_account_address in &receiving_account_addresses {
+        owner_receiving_accounts_before
+            .push(utils::get_token_account(program_test_ctx, *receiving_account_address).await);
+    }
+
+    let pool_day_stats_pda =
+        utils::get_current_pool_day_stats_pda(program_test_ctx, pool_pda).await;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::RemoveLiquidityProportional {
+            owner: owner.pubkey(),
+            lp_token_account: lp_token_account_address,
+            transfer_authority: transfer_authority_pda,
+            perpetuals: perpetuals_pda,
+            pool: *pool_pda,
+            lp_token_mint: lp_token_mint_pda,
+            pool_day_stats: pool_day_stats_pda,
+            system_program: anchor_lang::system_program::ID,
+            token_program: anchor_spl::token::ID,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        // For each token, add custody account as remaining_account
+        for custody in &pool_account.custodies {
+            accounts_meta.push(AccountMeta {
+                pubkey: *custody,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        // For each token, add custody oracle account as remaining_account
+        for custody_account in &custody_accounts {
+            accounts_meta.push(AccountMeta {
+                pubkey: custody_account.oracle.oracle_account,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // For each token, add custody token account as remaining_account
+        for custody_account in &custody_accounts {
+            accounts_meta.push(AccountMeta {
+                pubkey: custody_account.token_account,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        // For each token, add receiving account as remaining_account
+        for receiving_account_address in &receiving_account_addresses {
+            accounts_meta.push(AccountMeta {
+                pubkey: *receiving_account_address,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        // For each token, add custody token mint as remaining_account
+        for custody_account in &custody_accounts {
+            accounts_meta.push(AccountMeta {
+                pubkey: custody_account.mint,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        // For each token, add token program of the custody mint as remaining_account
+        for token_program in &custody_token_programs {
+            accounts_meta.push(AccountMeta {
+                pubkey: *token_program,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::RemoveLiquidityProportional { params },
+        Some(&payer.pubkey()),
+        &[owner, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let owner_lp_token_account_after =
+        utils::get_token_account(program_test_ctx, lp_token_account_address).await;
+
+    assert!(owner_lp_token_account_after.amount < owner_lp_token_account_before.amount);
+
+    for (idx, receiving_account_address) in receiving_account_addresses.iter().enumerate() {
+        let owner_receiving_account_after =
+            utils::get_token_account(program_test_ctx, *receiving_account_address).await;
+
+        assert!(owner_receiving_account_after.amount > owner_receiving_accounts_before[idx].amount);
+    }
+
+    Ok(())
+}
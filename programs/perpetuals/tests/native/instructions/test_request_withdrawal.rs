@@ -0,0 +1,85 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{prelude::Pubkey, ToAccountMetas},
+    perpetuals::{
+        instructions::RequestWithdrawalParams, state::withdrawal_queue::WithdrawalRequest,
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_request_withdrawal(
+    program_test_ctx: &mut ProgramTestContext,
+    owner: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    params: RequestWithdrawalParams,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+
+    // Prepare PDA and addresses
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let lp_token_mint_pda = pda::get_lp_token_mint_pda(pool_pda).0;
+    let withdrawal_queue_pda = pda::get_withdrawal_queue_pda(pool_pda).0;
+    let withdrawal_request_pda = pda::get_withdrawal_request_pda(&owner.pubkey(), pool_pda).0;
+    let escrow_token_account_pda = pda::get_withdrawal_escrow_account_pda(pool_pda).0;
+
+    let lp_token_account_address =
+        utils::find_associated_token_account(&owner.pubkey(), &lp_token_mint_pda).0;
+
+    // Save account state before tx execution
+    let owner_lp_token_account_before =
+        utils::get_token_account_balance(program_test_ctx, lp_token_account_address).await;
+    let escrow_token_account_before =
+        utils::get_token_account_balance(program_test_ctx, escrow_token_account_pda).await;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::RequestWithdrawal {
+            owner: owner.pubkey(),
+            lp_token_account: lp_token_account_address,
+            perpetuals: perpetuals_pda,
+            pool: *pool_pda,
+            withdrawal_queue: withdrawal_queue_pda,
+            withdrawal_request: withdrawal_request_pda,
+            escrow_token_account: escrow_token_account_pda,
+            lp_token_mint: lp_token_mint_pda,
+            system_program: anchor_lang::system_program::ID,
+            token_program: anchor_spl::token::ID,
+        };
+
+        accounts.to_account_metas(None)
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::RequestWithdrawal {
+            params: RequestWithdrawalParams {
+                lp_amount: params.lp_amount,
+            },
+        },
+        Some(&payer.pubkey()),
+        &[owner, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let withdrawal_request_account =
+        utils::get_account::<WithdrawalRequest>(program_test_ctx, withdrawal_request_pda).await;
+    let owner_lp_token_account_after =
+        utils::get_token_account_balance(program_test_ctx, lp_token_account_address).await;
+    let escrow_token_account_after =
+        utils::get_token_account_balance(program_test_ctx, escrow_token_account_pda).await;
+
+    assert_eq!(withdrawal_request_account.owner, owner.pubkey());
+    assert_eq!(
+        owner_lp_token_account_after,
+        owner_lp_token_account_before - params.lp_amount
+    );
+    assert_eq!(
+        escrow_token_account_after,
+        escrow_token_account_before + params.lp_amount
+    );
+
+    Ok(())
+}
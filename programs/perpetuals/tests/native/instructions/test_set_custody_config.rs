@@ -23,6 +23,7 @@ pub async fn test_set_custody_config(
 ) -> std::result::Result<(), BanksClientError> {
     // ==== WHEN ==============================================================
     let multisig_pda = pda::get_multisig_pda().0;
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
     let multisig_account = utils::get_account::<Multisig>(program_test_ctx, multisig_pda).await;
 
     // One Tx per multisig signer
@@ -33,6 +34,7 @@ pub async fn test_set_custody_config(
             let accounts = perpetuals::accounts::SetCustodyConfig {
                 admin: admin.pubkey(),
                 multisig: multisig_pda,
+                perpetuals: perpetuals_pda,
                 pool: *pool_pda,
                 custody: *custody_pda,
             };
@@ -0,0 +1,50 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::ToAccountMetas,
+    perpetuals::{instructions::SetCustodyFeesParams, state::custody::Custody},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signer::{keypair::Keypair, Signer},
+    },
+};
+
+pub async fn test_set_custody_fees(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    custody_pda: &Pubkey,
+    params: SetCustodyFeesParams,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::SetCustodyFees {
+            admin: admin.pubkey(),
+            multisig: pda::get_multisig_pda().0,
+            perpetuals: pda::get_perpetuals_pda().0,
+            pool: *pool_pda,
+            custody: *custody_pda,
+        };
+
+        accounts.to_account_metas(None)
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::SetCustodyFees {
+            params: params.clone(),
+        },
+        Some(&payer.pubkey()),
+        &[admin, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let custody_account = utils::get_account::<Custody>(program_test_ctx, *custody_pda).await;
+
+    assert_eq!(custody_account.fees, params.fees);
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::SetFeeDistributionParams,
+        state::{fee_distribution::FeeDistribution, multisig::Multisig},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_set_fee_distribution(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    params: SetFeeDistributionParams,
+    multisig_signers: &[&Keypair],
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let multisig_pda = pda::get_multisig_pda().0;
+    let fee_distribution_pda = pda::get_fee_distribution_pda(pool_pda).0;
+
+    let multisig_account = utils::get_account::<Multisig>(program_test_ctx, multisig_pda).await;
+
+    // One Tx per multisig signer
+    for i in 0..multisig_account.min_signatures {
+        let signer: &Keypair = multisig_signers[i as usize];
+
+        let accounts_meta = {
+            let accounts = perpetuals::accounts::SetFeeDistribution {
+                admin: admin.pubkey(),
+                multisig: multisig_pda,
+                perpetuals: pda::get_perpetuals_pda().0,
+                pool: *pool_pda,
+                fee_distribution: fee_distribution_pda,
+                system_program: anchor_lang::system_program::ID,
+            };
+
+            let mut accounts_meta = accounts.to_account_metas(None);
+
+            accounts_meta.push(AccountMeta {
+                pubkey: signer.pubkey(),
+                is_signer: true,
+                is_writable: false,
+            });
+
+            accounts_meta
+        };
+
+        utils::create_and_execute_perpetuals_ix(
+            program_test_ctx,
+            accounts_meta,
+            perpetuals::instruction::SetFeeDistribution {
+                params: params.clone(),
+            },
+            Some(&payer.pubkey()),
+            &[admin, payer, signer],
+        )
+        .await?;
+    }
+
+    // ==== THEN ==============================================================
+    let fee_distribution_account =
+        utils::get_account::<FeeDistribution>(program_test_ctx, fee_distribution_pda).await;
+
+    assert_eq!(fee_distribution_account.pool, *pool_pda);
+    assert_eq!(fee_distribution_account.recipients, params.recipients);
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::ToAccountMetas,
+    perpetuals::{instructions::SetGuardiansParams, state::perpetuals::Perpetuals},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_set_guardians(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    params: SetGuardiansParams,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::SetGuardians {
+            admin: admin.pubkey(),
+            multisig: pda::get_multisig_pda().0,
+            perpetuals: perpetuals_pda,
+        };
+
+        accounts.to_account_metas(None)
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::SetGuardians {
+            params: SetGuardiansParams {
+                guardians: params.guardians.clone(),
+            },
+        },
+        Some(&payer.pubkey()),
+        &[admin, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let perpetuals_account =
+        utils::get_account::<Perpetuals>(program_test_ctx, perpetuals_pda).await;
+
+    for guardian in params.guardians.iter() {
+        assert!(perpetuals_account.is_guardian(guardian));
+    }
+
+    Ok(())
+}
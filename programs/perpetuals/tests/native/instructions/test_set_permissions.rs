@@ -0,0 +1,65 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{instructions::SetPermissionsParams, state::perpetuals::Perpetuals},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub fn get_set_permissions_accounts(admin: &Pubkey) -> Vec<AccountMeta> {
+    perpetuals::accounts::SetPermissions {
+        admin: *admin,
+        multisig: pda::get_multisig_pda().0,
+        perpetuals: pda::get_perpetuals_pda().0,
+    }
+    .to_account_metas(None)
+}
+
+pub async fn test_set_permissions(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    params: SetPermissionsParams,
+    proposal_pda: Option<&Pubkey>,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+
+    let accounts_meta = {
+        let mut accounts_meta = get_set_permissions_accounts(&admin.pubkey());
+
+        if let Some(proposal_pda) = proposal_pda {
+            accounts_meta.push(AccountMeta {
+                pubkey: *proposal_pda,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::SetPermissions { params },
+        Some(&payer.pubkey()),
+        &[admin, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let perpetuals_account =
+        utils::get_account::<Perpetuals>(program_test_ctx, perpetuals_pda).await;
+
+    assert_eq!(perpetuals_account.permissions.allow_swap, params.allow_swap);
+    assert_eq!(
+        perpetuals_account.permissions.allow_open_position,
+        params.allow_open_position
+    );
+
+    Ok(())
+}
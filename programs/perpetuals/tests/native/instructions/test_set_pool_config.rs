@@ -0,0 +1,57 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::ToAccountMetas,
+    perpetuals::{instructions::SetPoolConfigParams, state::pool::Pool},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signer::{keypair::Keypair, Signer},
+    },
+};
+
+pub async fn test_set_pool_config(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    params: SetPoolConfigParams,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::SetPoolConfig {
+            admin: admin.pubkey(),
+            multisig: pda::get_multisig_pda().0,
+            perpetuals: pda::get_perpetuals_pda().0,
+            pool: *pool_pda,
+        };
+
+        accounts.to_account_metas(None)
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::SetPoolConfig {
+            params: SetPoolConfigParams {
+                permissions: params.permissions,
+                max_aum_usd: params.max_aum_usd,
+                max_open_interest_usd: params.max_open_interest_usd,
+            },
+        },
+        Some(&payer.pubkey()),
+        &[admin, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let pool_account = utils::get_account::<Pool>(program_test_ctx, *pool_pda).await;
+
+    assert_eq!(pool_account.permissions, params.permissions);
+    assert_eq!(pool_account.max_aum_usd, params.max_aum_usd);
+    assert_eq!(
+        pool_account.max_open_interest_usd,
+        params.max_open_interest_usd
+    );
+
+    Ok(())
+}
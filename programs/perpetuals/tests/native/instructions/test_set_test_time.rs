@@ -0,0 +1,41 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::ToAccountMetas,
+    perpetuals::{instructions::SetTestTimeParams, state::perpetuals::Perpetuals},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_set_test_time(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    params: SetTestTimeParams,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let multisig_pda = pda::get_multisig_pda().0;
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let time = params.time;
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        perpetuals::accounts::SetTestTime {
+            admin: admin.pubkey(),
+            multisig: multisig_pda,
+            perpetuals: perpetuals_pda,
+        }
+        .to_account_metas(None),
+        perpetuals::instruction::SetTestTime { params },
+        Some(&payer.pubkey()),
+        &[admin, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let perpetuals_account =
+        utils::get_account::<Perpetuals>(program_test_ctx, perpetuals_pda).await;
+
+    assert_eq!(perpetuals_account.inception_time, time);
+
+    Ok(())
+}
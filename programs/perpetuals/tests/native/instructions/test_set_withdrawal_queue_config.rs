@@ -0,0 +1,100 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::SetWithdrawalQueueConfigParams,
+        state::{multisig::Multisig, pool::Pool, withdrawal_queue::WithdrawalQueue},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_set_withdrawal_queue_config(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    params: SetWithdrawalQueueConfigParams,
+    multisig_signers: &[&Keypair],
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let multisig_pda = pda::get_multisig_pda().0;
+    let transfer_authority_pda = pda::get_transfer_authority_pda().0;
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let lp_token_mint_pda = pda::get_lp_token_mint_pda(pool_pda).0;
+    let withdrawal_queue_pda = pda::get_withdrawal_queue_pda(pool_pda).0;
+    let escrow_token_account_pda = pda::get_withdrawal_escrow_account_pda(pool_pda).0;
+
+    let multisig_account = utils::get_account::<Multisig>(program_test_ctx, multisig_pda).await;
+
+    // One Tx per multisig signer
+    for i in 0..multisig_account.min_signatures {
+        let signer: &Keypair = multisig_signers[i as usize];
+
+        let accounts_meta = {
+            let accounts = perpetuals::accounts::SetWithdrawalQueueConfig {
+                admin: admin.pubkey(),
+                multisig: multisig_pda,
+                transfer_authority: transfer_authority_pda,
+                perpetuals: perpetuals_pda,
+                pool: *pool_pda,
+                withdrawal_queue: withdrawal_queue_pda,
+                escrow_token_account: escrow_token_account_pda,
+                lp_token_mint: lp_token_mint_pda,
+                system_program: anchor_lang::system_program::ID,
+                token_program: anchor_spl::token::ID,
+                rent: solana_program::sysvar::rent::ID,
+            };
+
+            let mut accounts_meta = accounts.to_account_metas(None);
+
+            accounts_meta.push(AccountMeta {
+                pubkey: signer.pubkey(),
+                is_signer: true,
+                is_writable: false,
+            });
+
+            accounts_meta
+        };
+
+        utils::create_and_execute_perpetuals_ix(
+            program_test_ctx,
+            accounts_meta,
+            perpetuals::instruction::SetWithdrawalQueueConfig {
+                params: params.clone(),
+            },
+            Some(&payer.pubkey()),
+            &[admin, payer, signer],
+        )
+        .await?;
+    }
+
+    // ==== THEN ==============================================================
+    let withdrawal_queue_account =
+        utils::get_account::<WithdrawalQueue>(program_test_ctx, withdrawal_queue_pda).await;
+    let pool_account = utils::get_account::<Pool>(program_test_ctx, *pool_pda).await;
+
+    assert_eq!(withdrawal_queue_account.pool, *pool_pda);
+    assert_eq!(
+        withdrawal_queue_account.escrow_token_account,
+        escrow_token_account_pda
+    );
+    assert_eq!(
+        withdrawal_queue_account.cooldown_period,
+        params.cooldown_period
+    );
+    assert_eq!(
+        withdrawal_queue_account.epoch_duration,
+        params.epoch_duration
+    );
+    assert_eq!(
+        withdrawal_queue_account.max_epoch_withdrawal,
+        params.max_epoch_withdrawal
+    );
+    assert_eq!(pool_account.withdrawal_queue_enabled, params.enabled);
+
+    Ok(())
+}
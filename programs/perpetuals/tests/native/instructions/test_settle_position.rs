@@ -0,0 +1,130 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::SettlePositionParams,
+        state::{custody::Custody, margin_account::MarginAccount, position::Position},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_settle_position(
+    program_test_ctx: &mut ProgramTestContext,
+    signer: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    custody_token_mint: &Pubkey,
+    position_pda: &Pubkey,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let position_account = utils::get_account::<Position>(program_test_ctx, *position_pda).await;
+    let owner = position_account.owner;
+
+    // Prepare PDA and addresses
+    let transfer_authority_pda = pda::get_transfer_authority_pda().0;
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let custody_pda = pda::get_custody_pda(pool_pda, custody_token_mint).0;
+    let custody_token_account_pda =
+        pda::get_custody_token_account_pda(pool_pda, custody_token_mint).0;
+
+    let custody_token_program =
+        utils::get_token_program(program_test_ctx, custody_token_mint).await;
+
+    let receiving_account_address = utils::find_associated_token_account_with_program(
+        &owner,
+        custody_token_mint,
+        &custody_token_program,
+    )
+    .0;
+
+    let pool_day_stats_pda =
+        utils::get_current_pool_day_stats_pda(program_test_ctx, pool_pda).await;
+
+    // Save account state before tx execution
+    let custody_account_before = utils::get_account::<Custody>(program_test_ctx, custody_pda).await;
+
+    let mut accounts_meta = perpetuals::accounts::SettlePosition {
+        signer: signer.pubkey(),
+        owner,
+        receiving_account: receiving_account_address,
+        transfer_authority: transfer_authority_pda,
+        perpetuals: perpetuals_pda,
+        pool: *pool_pda,
+        pool_day_stats: pool_day_stats_pda,
+        position: *position_pda,
+        custody: custody_pda,
+        custody_token_account: custody_token_account_pda,
+        custody_token_mint: *custody_token_mint,
+        system_program: anchor_lang::system_program::ID,
+        token_program: custody_token_program,
+    }
+    .to_account_metas(None);
+
+    // Linked positions are settled against the margin account
+    if position_account.is_cross_margin {
+        let margin_account_pda = pda::get_margin_account_pda(&owner, pool_pda).0;
+        let margin_account =
+            utils::get_account::<MarginAccount>(program_test_ctx, margin_account_pda).await;
+        let collateral_custody_account =
+            utils::get_account::<Custody>(program_test_ctx, margin_account.collateral_custody)
+                .await;
+
+        accounts_meta.push(AccountMeta {
+            pubkey: margin_account_pda,
+            is_signer: false,
+            is_writable: true,
+        });
+        accounts_meta.push(AccountMeta {
+            pubkey: margin_account.collateral_custody,
+            is_signer: false,
+            is_writable: true,
+        });
+        accounts_meta.push(AccountMeta {
+            pubkey: collateral_custody_account.oracle.oracle_account,
+            is_signer: false,
+            is_writable: false,
+        });
+    }
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::SettlePosition {
+            params: SettlePositionParams {},
+        },
+        Some(&payer.pubkey()),
+        &[signer, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    // Position is closed
+    assert!(program_test_ctx
+        .banks_client
+        .get_account(*position_pda)
+        .await
+        .unwrap()
+        .is_none());
+
+    let custody_account = utils::get_account::<Custody>(program_test_ctx, custody_pda).await;
+    let positions_before = custody_account_before.long_positions.open_positions
+        + custody_account_before.short_positions.open_positions;
+    let positions_after = custody_account.long_positions.open_positions
+        + custody_account.short_positions.open_positions;
+
+    assert_eq!(positions_after + 1, positions_before);
+
+    // Linked position is removed from the margin account
+    if position_account.is_cross_margin {
+        let margin_account_pda = pda::get_margin_account_pda(&owner, pool_pda).0;
+        let margin_account =
+            utils::get_account::<MarginAccount>(program_test_ctx, margin_account_pda).await;
+        assert!(!margin_account.positions.contains(position_pda));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        ToAccountMetas,
+    },
+    perpetuals::{
+        instructions::StakeParams,
+        state::{
+            pool::Pool,
+            staking::{StakeAccount, Staking},
+        },
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_stake(
+    program_test_ctx: &mut ProgramTestContext,
+    owner: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    params: StakeParams,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+
+    // Prepare PDA and addresses
+    let perpetuals_pda = pda::get_perpetuals_pda().0;
+    let lp_token_mint_pda = pda::get_lp_token_mint_pda(pool_pda).0;
+    let staking_pda = pda::get_staking_pda(pool_pda).0;
+    let stake_account_pda = pda::get_stake_account_pda(&owner.pubkey(), pool_pda).0;
+    let stake_token_account_pda = pda::get_stake_token_account_pda(pool_pda).0;
+
+    let funding_account_address =
+        utils::find_associated_token_account(&owner.pubkey(), &lp_token_mint_pda).0;
+
+    // Save account state before tx execution
+    let staking_account_before = utils::get_account::<Staking>(program_test_ctx, staking_pda).await;
+    let owner_funding_account_before =
+        utils::get_token_account_balance(program_test_ctx, funding_account_address).await;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::Stake {
+            owner: owner.pubkey(),
+            funding_account: funding_account_address,
+            perpetuals: perpetuals_pda,
+            pool: *pool_pda,
+            staking: staking_pda,
+            stake_account: stake_account_pda,
+            stake_token_account: stake_token_account_pda,
+            lp_token_mint: lp_token_mint_pda,
+            system_program: anchor_lang::system_program::ID,
+            token_program: anchor_spl::token::ID,
+        };
+
+        let mut accounts_meta = accounts.to_account_metas(None);
+
+        let pool_account = utils::get_account::<Pool>(program_test_ctx, *pool_pda).await;
+
+        // For each token, add custody account as remaining_account
+        for custody in &pool_account.custodies {
+            accounts_meta.push(AccountMeta {
+                pubkey: *custody,
+                is_signer: false,
+                is_writable: true,
+            });
+        }
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::Stake {
+            params: StakeParams {
+                amount: params.amount,
+            },
+        },
+        Some(&payer.pubkey()),
+        &[owner, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let staking_account_after = utils::get_account::<Staking>(program_test_ctx, staking_pda).await;
+    let stake_account =
+        utils::get_account::<StakeAccount>(program_test_ctx, stake_account_pda).await;
+    let owner_funding_account_after =
+        utils::get_token_account_balance(program_test_ctx, funding_account_address).await;
+
+    assert_eq!(stake_account.owner, owner.pubkey());
+    assert_eq!(
+        staking_account_after.total_staked,
+        staking_account_before.total_staked + params.amount
+    );
+    assert_eq!(
+        owner_funding_account_after,
+        owner_funding_account_before - params.amount
+    );
+
+    Ok(())
+}
@@ -1,7 +1,6 @@
 use {
     crate::utils::{self, pda},
     anchor_lang::{prelude::Pubkey, ToAccountMetas},
-    bonfida_test_utils::ProgramTestContextExt,
     perpetuals::{instructions::SwapParams, state::custody::Custody},
     solana_program_test::{BanksClientError, ProgramTestContext},
     solana_sdk::signer::{keypair::Keypair, Signer},
@@ -29,10 +28,23 @@ pub async fn test_swap(
     let receiving_custody_token_account_pda =
         pda::get_custody_token_account_pda(pool_pda, receiving_custody_token_mint).0;
 
-    let funding_account_address =
-        utils::find_associated_token_account(&owner.pubkey(), receiving_custody_token_mint).0;
-    let receiving_account_address =
-        utils::find_associated_token_account(&owner.pubkey(), dispensing_custody_token_mint).0;
+    let receiving_token_program =
+        utils::get_token_program(program_test_ctx, receiving_custody_token_mint).await;
+    let dispensing_token_program =
+        utils::get_token_program(program_test_ctx, dispensing_custody_token_mint).await;
+
+    let funding_account_address = utils::find_associated_token_account_with_program(
+        &owner.pubkey(),
+        receiving_custody_token_mint,
+        &receiving_token_program,
+    )
+    .0;
+    let receiving_account_address = utils::find_associated_token_account_with_program(
+        &owner.pubkey(),
+        dispensing_custody_token_mint,
+        &dispensing_token_program,
+    )
+    .0;
 
     let dispensing_custody_account =
         utils::get_account::<Custody>(program_test_ctx, dispensing_custody_pda).await;
@@ -44,14 +56,13 @@ pub async fn test_swap(
     let receiving_custody_oracle_account_address = receiving_custody_account.oracle.oracle_account;
 
     // Save account state before tx execution
-    let owner_funding_account_before = program_test_ctx
-        .get_token_account(funding_account_address)
-        .await
-        .unwrap();
-    let custody_receiving_account_before = program_test_ctx
-        .get_token_account(receiving_account_address)
-        .await
-        .unwrap();
+    let owner_funding_account_before =
+        utils::get_token_account(program_test_ctx, funding_account_address).await;
+    let custody_receiving_account_before =
+        utils::get_token_account(program_test_ctx, receiving_account_address).await;
+
+    let pool_day_stats_pda =
+        utils::get_current_pool_day_stats_pda(program_test_ctx, pool_pda).await;
 
     utils::create_and_execute_perpetuals_ix(
         program_test_ctx,
@@ -59,16 +70,22 @@ pub async fn test_swap(
             owner: owner.pubkey(),
             funding_account: funding_account_address,
             receiving_account: receiving_account_address,
+            wsol_account: pda::get_wsol_account_pda(&owner.pubkey()).0,
             transfer_authority: transfer_authority_pda,
             perpetuals: perpetuals_pda,
             pool: *pool_pda,
+            pool_day_stats: pool_day_stats_pda,
             receiving_custody: receiving_custody_pda,
             receiving_custody_oracle_account: receiving_custody_oracle_account_address,
             receiving_custody_token_account: receiving_custody_token_account_pda,
+            receiving_custody_token_mint: *receiving_custody_token_mint,
             dispensing_custody: dispensing_custody_pda,
             dispensing_custody_oracle_account: dispensing_custody_oracle_account_address,
             dispensing_custody_token_account: dispensing_custody_token_account_pda,
-            token_program: anchor_spl::token::ID,
+            dispensing_custody_token_mint: *dispensing_custody_token_mint,
+            system_program: anchor_lang::system_program::ID,
+            receiving_token_program,
+            dispensing_token_program,
         }
         .to_account_metas(None),
         perpetuals::instruction::Swap { params },
@@ -79,14 +96,10 @@ pub async fn test_swap(
 
     // ==== THEN ==============================================================
     // Check the balance change
-    let owner_funding_account_after = program_test_ctx
-        .get_token_account(funding_account_address)
-        .await
-        .unwrap();
-    let custody_receiving_account_after = program_test_ctx
-        .get_token_account(receiving_account_address)
-        .await
-        .unwrap();
+    let owner_funding_account_after =
+        utils::get_token_account(program_test_ctx, funding_account_address).await;
+    let custody_receiving_account_after =
+        utils::get_token_account(program_test_ctx, receiving_account_address).await;
 
     assert!(owner_funding_account_after.amount < owner_funding_account_before.amount);
     assert!(custody_receiving_account_after.amount > custody_receiving_account_before.amount);
@@ -0,0 +1,69 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::ToAccountMetas,
+    perpetuals::{
+        instructions::SweepCustodyParams,
+        state::custody::{Custody, CustodyStatus},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signer::{keypair::Keypair, Signer},
+    },
+};
+
+pub async fn test_sweep_custody(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+    payer: &Keypair,
+    pool_pda: &Pubkey,
+    custody_token_mint: &Pubkey,
+    receiving_token_account: &Pubkey,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let custody_pda = pda::get_custody_pda(pool_pda, custody_token_mint).0;
+    let custody_token_account_pda =
+        pda::get_custody_token_account_pda(pool_pda, custody_token_mint).0;
+    let custody_token_program =
+        utils::get_token_program(program_test_ctx, custody_token_mint).await;
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::SweepCustody {
+            admin: admin.pubkey(),
+            multisig: pda::get_multisig_pda().0,
+            transfer_authority: pda::get_transfer_authority_pda().0,
+            perpetuals: pda::get_perpetuals_pda().0,
+            pool: *pool_pda,
+            fee_distribution: pda::get_fee_distribution_pda(pool_pda).0,
+            staking: pda::get_staking_pda(pool_pda).0,
+            custody: custody_pda,
+            custody_token_account: custody_token_account_pda,
+            custody_token_mint: *custody_token_mint,
+            receiving_token_account: *receiving_token_account,
+            token_program: custody_token_program,
+        };
+
+        accounts.to_account_metas(None)
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::SweepCustody {
+            params: SweepCustodyParams {},
+        },
+        Some(&payer.pubkey()),
+        &[admin, payer],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let custody_account = utils::get_account::<Custody>(program_test_ctx, custody_pda).await;
+    let custody_token_account =
+        utils::get_token_account(program_test_ctx, custody_token_account_pda).await;
+
+    assert_eq!(custody_account.status, CustodyStatus::Delisted);
+    assert_eq!(custody_token_account.amount, 0);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use {
+    crate::utils::{self, pda},
+    anchor_lang::ToAccountMetas,
+    perpetuals::{
+        instructions::UpgradeMultisigParams,
+        state::multisig::{DeprecatedMultisig, Multisig},
+    },
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+};
+
+pub async fn test_upgrade_multisig(
+    program_test_ctx: &mut ProgramTestContext,
+    admin: &Keypair,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let multisig_pda = pda::get_multisig_pda().0;
+
+    let deprecated_multisig_data = program_test_ctx
+        .banks_client
+        .get_account(multisig_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let deprecated_multisig: DeprecatedMultisig =
+        anchor_lang::AccountDeserialize::try_deserialize_unchecked(
+            &mut deprecated_multisig_data.as_slice(),
+        )
+        .unwrap();
+
+    let accounts_meta = {
+        let accounts = perpetuals::accounts::UpgradeMultisig {
+            admin: admin.pubkey(),
+            multisig: multisig_pda,
+            system_program: anchor_lang::system_program::ID,
+        };
+
+        accounts.to_account_metas(None)
+    };
+
+    utils::create_and_execute_perpetuals_ix(
+        program_test_ctx,
+        accounts_meta,
+        perpetuals::instruction::UpgradeMultisig {
+            params: UpgradeMultisigParams {},
+        },
+        Some(&admin.pubkey()),
+        &[admin],
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let multisig_account = program_test_ctx
+        .banks_client
+        .get_account(multisig_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(multisig_account.data.len(), Multisig::LEN);
+
+    let multisig: &Multisig = bytemuck::from_bytes(&multisig_account.data[8..]);
+    assert_eq!({ multisig.version }, Multisig::VERSION);
+    assert_eq!({ multisig.num_signers }, deprecated_multisig.num_signers);
+    assert_eq!(
+        { multisig.min_signatures },
+        deprecated_multisig.min_signatures
+    );
+    assert_eq!({ multisig.signers }, deprecated_multisig.signers);
+    assert_eq!({ multisig.bump }, deprecated_multisig.bump);
+
+    Ok(())
+}
@@ -11,8 +11,27 @@ pub async fn test_integration() {
     tests_suite::liquidity::fixed_fees().await;
     tests_suite::liquidity::insuffisient_fund().await;
     tests_suite::liquidity::min_max_ratio().await;
+    tests_suite::liquidity::pool_limits().await;
+    tests_suite::liquidity::withdrawal_queue().await;
+    tests_suite::liquidity::transfer_fees().await;
+    tests_suite::liquidity::native_sol().await;
+    tests_suite::liquidity::pool_day_stats().await;
+    tests_suite::liquidity::cached_aum().await;
+    tests_suite::liquidity::pool_stats().await;
 
     tests_suite::position::min_max_leverage().await;
+    tests_suite::position::leverage_tiers().await;
     tests_suite::position::liquidate_position().await;
     tests_suite::position::max_user_profit().await;
+    tests_suite::position::cross_margin().await;
+    tests_suite::position::delisting().await;
+
+    tests_suite::staking::fee_sharing().await;
+
+    tests_suite::multisig::concurrent_proposals().await;
+    tests_suite::multisig::timelock().await;
+    tests_suite::multisig::guardian().await;
+    tests_suite::multisig::custody_config().await;
+    tests_suite::multisig::account_migration().await;
+    tests_suite::multisig::multisig_upgrade().await;
 }
@@ -0,0 +1,236 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures, pda},
+    },
+    bonfida_test_utils::ProgramTestExt,
+    perpetuals::{
+        instructions::{SetTestOraclePriceParams, SetTestTimeParams},
+        state::{perpetuals::Perpetuals, pool::Pool},
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::signer::Signer,
+};
+
+const ROOT_AUTHORITY: usize = 0;
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 1;
+const MULTISIG_MEMBER_A: usize = 2;
+const MULTISIG_MEMBER_B: usize = 3;
+const MULTISIG_MEMBER_C: usize = 4;
+const PAYER: usize = 5;
+const USER_ALICE: usize = 6;
+
+const KEYPAIRS_COUNT: usize = 7;
+
+const USDC_DECIMALS: u8 = 6;
+const ETH_DECIMALS: u8 = 9;
+
+pub async fn cached_aum() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Initialize mints
+    let usdc_mint = program_test
+        .add_mint(None, USDC_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+    let eth_mint = program_test
+        .add_mint(None, ETH_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[MULTISIG_MEMBER_C],
+    ];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(1),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    // Initialize and fund associated token accounts
+    {
+        // Alice: mint 100k USDC and 50 ETH
+        {
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &usdc_mint,
+                &keypairs[USER_ALICE].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(100_000, USDC_DECIMALS),
+            )
+            .await;
+
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &eth_mint,
+                &keypairs[USER_ALICE].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(50, ETH_DECIMALS),
+            )
+            .await;
+        }
+    }
+
+    // Set the pool with 50%/50% ETH/USDC liquidity
+    let (pool_pda, _, _, _, custodies_infos) = utils::setup_pool_with_custodies_and_liquidity(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        "FOO",
+        &keypairs[PAYER],
+        multisig_signers,
+        vec![
+            utils::SetupCustodyWithLiquidityParams {
+                setup_custody_params: utils::SetupCustodyParams {
+                    mint: usdc_mint,
+                    decimals: USDC_DECIMALS,
+                    is_stable: true,
+                    target_ratio: utils::ratio_from_percentage(50.0),
+                    min_ratio: utils::ratio_from_percentage(0.0),
+                    max_ratio: utils::ratio_from_percentage(100.0),
+                    initial_price: utils::scale(1, USDC_DECIMALS),
+                    initial_conf: utils::scale_f64(0.01, USDC_DECIMALS),
+                    pricing_params: None,
+                    permissions: None,
+                    fees: None,
+                    borrow_rate: None,
+                },
+                liquidity_amount: utils::scale(15_000, USDC_DECIMALS),
+                payer: utils::copy_keypair(&keypairs[USER_ALICE]),
+            },
+            utils::SetupCustodyWithLiquidityParams {
+                setup_custody_params: utils::SetupCustodyParams {
+                    mint: eth_mint,
+                    decimals: ETH_DECIMALS,
+                    is_stable: false,
+                    target_ratio: utils::ratio_from_percentage(50.0),
+                    min_ratio: utils::ratio_from_percentage(0.0),
+                    max_ratio: utils::ratio_from_percentage(100.0),
+                    initial_price: utils::scale(1_500, ETH_DECIMALS),
+                    initial_conf: utils::scale(10, ETH_DECIMALS),
+                    pricing_params: None,
+                    permissions: None,
+                    fees: None,
+                    borrow_rate: None,
+                },
+                liquidity_amount: utils::scale(10, ETH_DECIMALS),
+                payer: utils::copy_keypair(&keypairs[USER_ALICE]),
+            },
+        ],
+    )
+    .await;
+
+    let usdc_custody_pda = custodies_infos[0].custody_pda;
+    let eth_custody_pda = custodies_infos[1].custody_pda;
+
+    // Liquidity deposits keep the cache in sync with the pool AUM
+    let pool_account = utils::get_account::<Pool>(&mut program_test_ctx, pool_pda).await;
+    assert_eq!(pool_account.custody_aum.len(), 2);
+    assert_eq!(
+        pool_account.aum_usd,
+        pool_account
+            .custody_aum
+            .iter()
+            .map(|x| x.ema_usd as u128)
+            .sum::<u128>()
+    );
+
+    // Let cached contributions expire and move ETH price up
+    let perpetuals_account =
+        utils::get_account::<Perpetuals>(&mut program_test_ctx, pda::get_perpetuals_pda().0).await;
+    let publish_time = perpetuals_account.inception_time + 60;
+
+    instructions::test_set_test_time(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        SetTestTimeParams { time: publish_time },
+    )
+    .await
+    .unwrap();
+
+    instructions::test_set_test_oracle_price(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &usdc_custody_pda,
+        &custodies_infos[0].test_oracle_pda,
+        SetTestOraclePriceParams {
+            price: utils::scale(1, USDC_DECIMALS),
+            expo: -(USDC_DECIMALS as i32),
+            conf: utils::scale_f64(0.01, USDC_DECIMALS),
+            publish_time,
+        },
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    instructions::test_set_test_oracle_price(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_custody_pda,
+        &custodies_infos[1].test_oracle_pda,
+        SetTestOraclePriceParams {
+            price: utils::scale(1_600, ETH_DECIMALS),
+            expo: -(ETH_DECIMALS as i32),
+            conf: utils::scale(10, ETH_DECIMALS),
+            publish_time,
+        },
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    // Pool AUM is kept until every cached contribution is fresh
+    instructions::test_refresh_aum(
+        &mut program_test_ctx,
+        &keypairs[PAYER],
+        &pool_pda,
+        &[eth_custody_pda],
+    )
+    .await
+    .unwrap();
+
+    let pool_account_after = utils::get_account::<Pool>(&mut program_test_ctx, pool_pda).await;
+    assert_eq!(pool_account_after.aum_usd, pool_account.aum_usd);
+    assert!(pool_account_after.custody_aum[1].ema_usd > pool_account.custody_aum[1].ema_usd);
+
+    instructions::test_refresh_aum(
+        &mut program_test_ctx,
+        &keypairs[PAYER],
+        &pool_pda,
+        &[usdc_custody_pda],
+    )
+    .await
+    .unwrap();
+
+    let pool_account_after = utils::get_account::<Pool>(&mut program_test_ctx, pool_pda).await;
+    assert!(pool_account_after.aum_usd > pool_account.aum_usd);
+    assert_eq!(
+        pool_account_after.aum_usd,
+        pool_account_after
+            .custody_aum
+            .iter()
+            .map(|x| x.ema_usd as u128)
+            .sum::<u128>()
+    );
+}
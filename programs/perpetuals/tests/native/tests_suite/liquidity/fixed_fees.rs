@@ -140,7 +140,12 @@ pub async fn fixed_fees() {
 
             assert_eq!(
                 custody_account.collected_fees.add_liquidity_usd,
-                utils::scale(20, USDC_DECIMALS),
+                utils::scale(20, USDC_DECIMALS).into(),
+            );
+
+            assert_eq!(
+                custody_account.collected_token_fees.add_liquidity,
+                utils::scale(20, USDC_DECIMALS).into(),
             );
 
             assert_eq!(
@@ -179,7 +184,7 @@ pub async fn fixed_fees() {
 
             assert_eq!(
                 custody_account.collected_fees.remove_liquidity_usd,
-                utils::scale_f64(3.061072, USDC_DECIMALS),
+                utils::scale_f64(3.061072, USDC_DECIMALS).into(),
             );
 
             assert_eq!(
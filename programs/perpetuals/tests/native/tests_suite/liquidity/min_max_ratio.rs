@@ -4,7 +4,10 @@ use {
         utils::{self, fixtures},
     },
     bonfida_test_utils::ProgramTestExt,
-    perpetuals::instructions::{AddLiquidityParams, RemoveLiquidityParams},
+    perpetuals::instructions::{
+        AddLiquidityBasketParams, AddLiquidityParams, RemoveLiquidityParams,
+        RemoveLiquidityProportionalParams,
+    },
     solana_program_test::ProgramTest,
     solana_sdk::signer::Signer,
 };
@@ -147,13 +150,30 @@ pub async fn min_max_ratio() {
     .await
     .is_err());
 
+    // Same USDC amount deposited along with ETH keeps ratios in range
+    instructions::test_add_liquidity_basket(
+        &mut program_test_ctx,
+        &keypairs[USER_ALICE],
+        &keypairs[PAYER],
+        &pool_pda,
+        AddLiquidityBasketParams {
+            amounts_in: vec![
+                utils::scale(1_000, USDC_DECIMALS),
+                utils::scale(1, ETH_DECIMALS),
+            ],
+            min_lp_amount_out: 1,
+        },
+    )
+    .await
+    .unwrap();
+
     let alice_lp_token_mint_pda =
         utils::find_associated_token_account(&keypairs[USER_ALICE].pubkey(), &lp_token_mint_pda).0;
 
     let alice_lp_token_account_balance =
         utils::get_token_account_balance(&mut program_test_ctx, alice_lp_token_mint_pda).await;
 
-    // Try to remove 35% of LP token as USDC (~1,050 USDC), lowering USDC ratio to ~23%
+    // Try to remove 35% of LP token as USDC (~1,925 USDC), lowering USDC ratio to ~16%
     // Going under 30% ratio should trigger error
     assert!(instructions::test_remove_liquidity(
         &mut program_test_ctx,
@@ -168,4 +188,18 @@ pub async fn min_max_ratio() {
     )
     .await
     .is_err());
+
+    // Removing the same share in-kind keeps the ratios and should succeed
+    instructions::test_remove_liquidity_proportional(
+        &mut program_test_ctx,
+        &keypairs[USER_ALICE],
+        &keypairs[PAYER],
+        &pool_pda,
+        RemoveLiquidityProportionalParams {
+            lp_amount_in: alice_lp_token_account_balance * 35 / 100,
+            min_amounts_out: vec![1, 1],
+        },
+    )
+    .await
+    .unwrap();
 }
@@ -1,5 +1,15 @@
+pub mod cached_aum;
 pub mod fixed_fees;
 pub mod insuffisient_fund;
 pub mod min_max_ratio;
+pub mod native_sol;
+pub mod pool_day_stats;
+pub mod pool_limits;
+pub mod pool_stats;
+pub mod transfer_fees;
+pub mod withdrawal_queue;
 
-pub use {fixed_fees::*, insuffisient_fund::*, min_max_ratio::*};
+pub use {
+    cached_aum::*, fixed_fees::*, insuffisient_fund::*, min_max_ratio::*, native_sol::*,
+    pool_day_stats::*, pool_limits::*, pool_stats::*, transfer_fees::*, withdrawal_queue::*,
+};
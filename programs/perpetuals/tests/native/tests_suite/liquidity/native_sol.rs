@@ -0,0 +1,173 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures},
+    },
+    anchor_spl::token::spl_token::native_mint,
+    perpetuals::{
+        instructions::{AddLiquidityParams, RemoveLiquidityParams},
+        state::{
+            custody::{Custody, Fees, FeesMode},
+            perpetuals::Perpetuals,
+        },
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::signer::Signer,
+};
+
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 0;
+const MULTISIG_MEMBER_A: usize = 1;
+const MULTISIG_MEMBER_B: usize = 2;
+const MULTISIG_MEMBER_C: usize = 3;
+const PAYER: usize = 4;
+const USER_ALICE: usize = 5;
+
+const KEYPAIRS_COUNT: usize = 6;
+
+const SOL_DECIMALS: u8 = 9;
+
+pub async fn native_sol() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[MULTISIG_MEMBER_C],
+    ];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(1),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    let (pool_pda, _, _, _, custodies_info) = utils::setup_pool_with_custodies_and_liquidity(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        "FOO",
+        &keypairs[PAYER],
+        multisig_signers,
+        vec![utils::SetupCustodyWithLiquidityParams {
+            setup_custody_params: utils::SetupCustodyParams {
+                mint: native_mint::ID,
+                decimals: SOL_DECIMALS,
+                is_stable: false,
+                target_ratio: utils::ratio_from_percentage(50.0),
+                min_ratio: utils::ratio_from_percentage(0.0),
+                max_ratio: utils::ratio_from_percentage(100.0),
+                initial_price: utils::scale(20, SOL_DECIMALS),
+                initial_conf: utils::scale_f64(0.01, SOL_DECIMALS),
+                pricing_params: None,
+                permissions: None,
+                borrow_rate: None,
+                fees: Some(Fees {
+                    mode: FeesMode::Fixed,
+                    add_liquidity: 0,
+                    remove_liquidity: 0,
+                    protocol_share: 0,
+                    ..fixtures::fees_linear_regular()
+                }),
+            },
+            liquidity_amount: 0,
+            payer: utils::copy_keypair(&keypairs[USER_ALICE]),
+        }],
+    )
+    .await;
+
+    let custody_pda = custodies_info[0].custody_pda;
+    let alice = keypairs[USER_ALICE].pubkey();
+
+    // Lamports of the user are wrapped into the custody
+    {
+        let alice_balance_before = program_test_ctx
+            .banks_client
+            .get_balance(alice)
+            .await
+            .unwrap();
+
+        instructions::test_add_liquidity(
+            &mut program_test_ctx,
+            &keypairs[USER_ALICE],
+            &keypairs[PAYER],
+            &pool_pda,
+            &native_mint::ID,
+            AddLiquidityParams {
+                amount_in: utils::scale_f64(0.5, SOL_DECIMALS),
+                min_lp_amount_out: 1,
+            },
+        )
+        .await
+        .unwrap();
+
+        let alice_balance_after = program_test_ctx
+            .banks_client
+            .get_balance(alice)
+            .await
+            .unwrap();
+        let custody_account =
+            utils::get_account::<Custody>(&mut program_test_ctx, custody_pda).await;
+
+        // rent of the temporary account is returned
+        assert_eq!(
+            alice_balance_before - alice_balance_after,
+            utils::scale_f64(0.5, SOL_DECIMALS)
+        );
+        assert_eq!(
+            custody_account.assets.owned,
+            utils::scale_f64(0.5, SOL_DECIMALS)
+        );
+    }
+
+    // Tokens of the custody are unwrapped to the user
+    {
+        let alice_balance_before = program_test_ctx
+            .banks_client
+            .get_balance(alice)
+            .await
+            .unwrap();
+        let custody_account_before =
+            utils::get_account::<Custody>(&mut program_test_ctx, custody_pda).await;
+
+        instructions::test_remove_liquidity(
+            &mut program_test_ctx,
+            &keypairs[USER_ALICE],
+            &keypairs[PAYER],
+            &pool_pda,
+            &native_mint::ID,
+            RemoveLiquidityParams {
+                lp_amount_in: utils::scale(1, Perpetuals::LP_DECIMALS),
+                min_amount_out: 1,
+            },
+        )
+        .await
+        .unwrap();
+
+        let alice_balance_after = program_test_ctx
+            .banks_client
+            .get_balance(alice)
+            .await
+            .unwrap();
+        let custody_account =
+            utils::get_account::<Custody>(&mut program_test_ctx, custody_pda).await;
+
+        assert_eq!(
+            alice_balance_after - alice_balance_before,
+            custody_account_before.assets.owned - custody_account.assets.owned
+        );
+    }
+}
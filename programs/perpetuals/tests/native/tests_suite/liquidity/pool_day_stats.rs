@@ -0,0 +1,188 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures},
+    },
+    bonfida_test_utils::ProgramTestExt,
+    perpetuals::{
+        instructions::{AddLiquidityParams, SetTestTimeParams},
+        state::{
+            custody::{Fees, FeesMode},
+            perpetuals::Perpetuals,
+            pool::Pool,
+            pool_day_stats::PoolDayStats,
+        },
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::signer::Signer,
+};
+
+const ROOT_AUTHORITY: usize = 0;
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 1;
+const MULTISIG_MEMBER_A: usize = 2;
+const MULTISIG_MEMBER_B: usize = 3;
+const MULTISIG_MEMBER_C: usize = 4;
+const PAYER: usize = 5;
+const USER_ALICE: usize = 6;
+
+const KEYPAIRS_COUNT: usize = 7;
+
+const USDC_DECIMALS: u8 = 6;
+
+pub async fn pool_day_stats() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Initialize mints
+    let usdc_mint = program_test
+        .add_mint(None, USDC_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[MULTISIG_MEMBER_C],
+    ];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(1),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    // Initialize and fund associated token accounts
+    {
+        // Alice: mint 100k USDC
+        {
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &usdc_mint,
+                &keypairs[USER_ALICE].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(100_000, USDC_DECIMALS),
+            )
+            .await;
+        }
+    }
+
+    let (pool_pda, _, _, _, _) = utils::setup_pool_with_custodies_and_liquidity(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        "FOO",
+        &keypairs[PAYER],
+        multisig_signers,
+        vec![utils::SetupCustodyWithLiquidityParams {
+            setup_custody_params: utils::SetupCustodyParams {
+                mint: usdc_mint,
+                decimals: USDC_DECIMALS,
+                is_stable: true,
+                target_ratio: utils::ratio_from_percentage(50.0),
+                min_ratio: utils::ratio_from_percentage(0.0),
+                max_ratio: utils::ratio_from_percentage(100.0),
+                initial_price: utils::scale(1, USDC_DECIMALS),
+                initial_conf: utils::scale_f64(0.01, USDC_DECIMALS),
+                pricing_params: None,
+                permissions: None,
+                borrow_rate: None,
+                fees: Some(Fees {
+                    mode: FeesMode::Fixed,
+                    add_liquidity: 200,
+                    remove_liquidity: 300,
+                    protocol_share: 25,
+                    ..fixtures::fees_linear_regular()
+                }),
+            },
+            liquidity_amount: utils::scale(0, USDC_DECIMALS),
+            payer: utils::copy_keypair(&keypairs[USER_ALICE]),
+        }],
+    )
+    .await;
+
+    let pool_day_stats_pda =
+        utils::get_current_pool_day_stats_pda(&mut program_test_ctx, &pool_pda).await;
+
+    // First trade of the day creates the stats account
+    {
+        instructions::test_add_liquidity(
+            &mut program_test_ctx,
+            &keypairs[USER_ALICE],
+            &keypairs[PAYER],
+            &pool_pda,
+            &usdc_mint,
+            AddLiquidityParams {
+                amount_in: utils::scale(1_000, USDC_DECIMALS),
+                min_lp_amount_out: 1,
+            },
+        )
+        .await
+        .unwrap();
+
+        let pool_account = utils::get_account::<Pool>(&mut program_test_ctx, pool_pda).await;
+        let pool_day_stats_account =
+            utils::get_account::<PoolDayStats>(&mut program_test_ctx, pool_day_stats_pda).await;
+
+        assert_eq!(pool_day_stats_account.pool, pool_pda);
+        assert_eq!(pool_day_stats_account.payer, keypairs[USER_ALICE].pubkey());
+        assert_eq!(
+            pool_day_stats_account.volume.add_liquidity_usd,
+            utils::scale(1_000, USDC_DECIMALS).into(),
+        );
+        assert_eq!(
+            pool_day_stats_account.fees.add_liquidity_usd,
+            utils::scale(20, USDC_DECIMALS).into(),
+        );
+        assert_eq!(pool_day_stats_account.aum_close_usd, pool_account.aum_usd);
+    }
+
+    // Recent days can't be closed
+    assert!(instructions::test_close_pool_day_stats(
+        &mut program_test_ctx,
+        &keypairs[USER_ALICE],
+        &pool_pda,
+        &pool_day_stats_pda,
+    )
+    .await
+    .is_err());
+
+    // Rent is reclaimed by the payer once the day is old enough
+    {
+        let perpetuals_account =
+            utils::get_account::<Perpetuals>(&mut program_test_ctx, utils::get_perpetuals_pda().0)
+                .await;
+
+        instructions::test_set_test_time(
+            &mut program_test_ctx,
+            &keypairs[MULTISIG_MEMBER_A],
+            &keypairs[PAYER],
+            SetTestTimeParams {
+                time: perpetuals_account.inception_time
+                    + PoolDayStats::MIN_CLOSE_AGE_DAYS * PoolDayStats::SECONDS_PER_DAY,
+            },
+        )
+        .await
+        .unwrap();
+
+        instructions::test_close_pool_day_stats(
+            &mut program_test_ctx,
+            &keypairs[USER_ALICE],
+            &pool_pda,
+            &pool_day_stats_pda,
+        )
+        .await
+        .unwrap();
+    }
+}
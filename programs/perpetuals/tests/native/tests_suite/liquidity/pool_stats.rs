@@ -0,0 +1,233 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures},
+    },
+    anchor_spl::token::Mint,
+    bonfida_test_utils::ProgramTestExt,
+    perpetuals::{
+        instructions::OpenPositionParams,
+        state::{custody::Custody, perpetuals::Perpetuals, position::Side},
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::signer::Signer,
+};
+
+const ROOT_AUTHORITY: usize = 0;
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 1;
+const MULTISIG_MEMBER_A: usize = 2;
+const MULTISIG_MEMBER_B: usize = 3;
+const MULTISIG_MEMBER_C: usize = 4;
+const PAYER: usize = 5;
+const USER_ALICE: usize = 6;
+const USER_MARTIN: usize = 7;
+
+const KEYPAIRS_COUNT: usize = 8;
+
+const USDC_DECIMALS: u8 = 6;
+const ETH_DECIMALS: u8 = 9;
+
+pub async fn pool_stats() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Initialize mints
+    let usdc_mint = program_test
+        .add_mint(None, USDC_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+    let eth_mint = program_test
+        .add_mint(None, ETH_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[MULTISIG_MEMBER_C],
+    ];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(1),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    // Initialize and fund associated token accounts
+    {
+        // Alice: mint 100k USDC and 50 ETH
+        {
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &usdc_mint,
+                &keypairs[USER_ALICE].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(100_000, USDC_DECIMALS),
+            )
+            .await;
+
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &eth_mint,
+                &keypairs[USER_ALICE].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(50, ETH_DECIMALS),
+            )
+            .await;
+        }
+
+        // Martin: mint 2 ETH
+        {
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &eth_mint,
+                &keypairs[USER_MARTIN].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(2, ETH_DECIMALS),
+            )
+            .await;
+        }
+    }
+
+    // Set the pool with 50%/50% ETH/USDC liquidity
+    let (pool_pda, _, lp_token_mint_pda, _, custodies_infos) =
+        utils::setup_pool_with_custodies_and_liquidity(
+            &mut program_test_ctx,
+            &keypairs[MULTISIG_MEMBER_A],
+            "FOO",
+            &keypairs[PAYER],
+            multisig_signers,
+            vec![
+                utils::SetupCustodyWithLiquidityParams {
+                    setup_custody_params: utils::SetupCustodyParams {
+                        mint: usdc_mint,
+                        decimals: USDC_DECIMALS,
+                        is_stable: true,
+                        target_ratio: utils::ratio_from_percentage(50.0),
+                        min_ratio: utils::ratio_from_percentage(0.0),
+                        max_ratio: utils::ratio_from_percentage(100.0),
+                        initial_price: utils::scale(1, USDC_DECIMALS),
+                        initial_conf: utils::scale_f64(0.01, USDC_DECIMALS),
+                        pricing_params: None,
+                        permissions: None,
+                        fees: None,
+                        borrow_rate: None,
+                    },
+                    liquidity_amount: utils::scale(15_000, USDC_DECIMALS),
+                    payer: utils::copy_keypair(&keypairs[USER_ALICE]),
+                },
+                utils::SetupCustodyWithLiquidityParams {
+                    setup_custody_params: utils::SetupCustodyParams {
+                        mint: eth_mint,
+                        decimals: ETH_DECIMALS,
+                        is_stable: false,
+                        target_ratio: utils::ratio_from_percentage(50.0),
+                        min_ratio: utils::ratio_from_percentage(0.0),
+                        max_ratio: utils::ratio_from_percentage(100.0),
+                        initial_price: utils::scale(1_500, ETH_DECIMALS),
+                        initial_conf: utils::scale(10, ETH_DECIMALS),
+                        pricing_params: None,
+                        permissions: None,
+                        fees: None,
+                        borrow_rate: None,
+                    },
+                    liquidity_amount: utils::scale(10, ETH_DECIMALS),
+                    payer: utils::copy_keypair(&keypairs[USER_ALICE]),
+                },
+            ],
+        )
+        .await;
+
+    let usdc_custody_pda = custodies_infos[0].custody_pda;
+    let eth_custody_pda = custodies_infos[1].custody_pda;
+
+    // Martin: Open 2 ETH long position x2 to lock ETH custody funds
+    instructions::test_open_position(
+        &mut program_test_ctx,
+        &keypairs[USER_MARTIN],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        OpenPositionParams {
+            // max price paid (slippage implied)
+            price: utils::scale(1_550, ETH_DECIMALS),
+            collateral: utils::scale(1, ETH_DECIMALS),
+            size: utils::scale(2, ETH_DECIMALS),
+            side: Side::Long,
+        },
+    )
+    .await
+    .unwrap();
+
+    let pool_stats =
+        instructions::test_get_pool_stats(&mut program_test_ctx, &keypairs[PAYER], &pool_pda)
+            .await
+            .unwrap();
+
+    let lp_token_mint = utils::get_account::<Mint>(&mut program_test_ctx, lp_token_mint_pda).await;
+    assert!(pool_stats.aum_usd > 0);
+    assert_eq!(pool_stats.lp_supply, lp_token_mint.supply);
+
+    // USDC custody has no locked funds
+    {
+        let usdc_custody =
+            utils::get_account::<Custody>(&mut program_test_ctx, usdc_custody_pda).await;
+        let usdc_stats = &pool_stats.custodies[0];
+
+        assert_eq!(usdc_stats.utilization, 0);
+        assert_eq!(usdc_stats.hourly_borrow_rate, 0);
+        assert_eq!(usdc_stats.annual_borrow_rate, 0);
+        assert_eq!(usdc_stats.available_amount, usdc_custody.assets.owned);
+        assert_eq!(usdc_stats.target_ratio, utils::ratio_from_percentage(50.0));
+        assert_eq!(usdc_stats.oi_long_usd, 0);
+    }
+
+    // ETH custody reports utilization and borrow rate of the locked funds
+    {
+        let eth_custody =
+            utils::get_account::<Custody>(&mut program_test_ctx, eth_custody_pda).await;
+        let eth_stats = &pool_stats.custodies[1];
+
+        assert!(eth_custody.assets.locked > 0);
+        assert_eq!(
+            eth_stats.utilization as u128,
+            eth_custody.assets.locked as u128 * Perpetuals::BPS_POWER
+                / eth_custody.assets.owned as u128
+        );
+        assert!(eth_stats.hourly_borrow_rate > 0);
+        assert_eq!(
+            eth_stats.hourly_borrow_rate,
+            eth_custody.borrow_rate_state.current_rate
+        );
+        assert_eq!(
+            eth_stats.annual_borrow_rate,
+            eth_stats.hourly_borrow_rate * 24 * 365
+        );
+        assert_eq!(
+            eth_stats.available_amount,
+            eth_custody.assets.owned + eth_custody.assets.collateral - eth_custody.assets.locked
+        );
+        assert_eq!(eth_stats.target_ratio, utils::ratio_from_percentage(50.0));
+        assert_eq!(eth_stats.oi_long_usd, eth_custody.trade_stats.oi_long_usd);
+        assert!(eth_stats.oi_long_usd > 0);
+    }
+
+    // Current ratios split the pool between the two custodies
+    let ratios_sum = pool_stats.custodies[0].current_ratio + pool_stats.custodies[1].current_ratio;
+    assert!(pool_stats.custodies[0].current_ratio > 0);
+    assert!(pool_stats.custodies[1].current_ratio > 0);
+    assert!(ratios_sum <= Perpetuals::BPS_POWER as u64 + 100);
+    assert!(ratios_sum >= Perpetuals::BPS_POWER as u64 - 100);
+}
@@ -0,0 +1,179 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures},
+    },
+    perpetuals::{
+        instructions::{AddLiquidityParams, RemoveLiquidityParams},
+        state::{
+            custody::{Custody, Fees, FeesMode},
+            perpetuals::Perpetuals,
+        },
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::signer::Signer,
+};
+
+const ROOT_AUTHORITY: usize = 0;
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 1;
+const MULTISIG_MEMBER_A: usize = 2;
+const MULTISIG_MEMBER_B: usize = 3;
+const MULTISIG_MEMBER_C: usize = 4;
+const PAYER: usize = 5;
+const USER_ALICE: usize = 6;
+
+const KEYPAIRS_COUNT: usize = 7;
+
+const FEE_TOKEN_DECIMALS: u8 = 6;
+
+// 1% transfer fee
+const TRANSFER_FEE_BPS: u16 = 100;
+
+pub async fn transfer_fees() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[MULTISIG_MEMBER_C],
+    ];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(1),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    // Initialize Token-2022 mint with transfer fees
+    let fee_token_mint = utils::create_mint_with_transfer_fee(
+        &mut program_test_ctx,
+        &keypairs[ROOT_AUTHORITY].pubkey(),
+        FEE_TOKEN_DECIMALS,
+        TRANSFER_FEE_BPS,
+        u64::MAX,
+    )
+    .await;
+
+    // Initialize and fund associated token accounts
+    {
+        // Alice: mint 100k FEE
+        {
+            utils::initialize_and_fund_token_2022_account(
+                &mut program_test_ctx,
+                &fee_token_mint,
+                &keypairs[USER_ALICE].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(100_000, FEE_TOKEN_DECIMALS),
+            )
+            .await;
+        }
+    }
+
+    let (pool_pda, _, _, _, custodies_info) = utils::setup_pool_with_custodies_and_liquidity(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        "FOO",
+        &keypairs[PAYER],
+        multisig_signers,
+        vec![utils::SetupCustodyWithLiquidityParams {
+            setup_custody_params: utils::SetupCustodyParams {
+                mint: fee_token_mint,
+                decimals: FEE_TOKEN_DECIMALS,
+                is_stable: true,
+                target_ratio: utils::ratio_from_percentage(50.0),
+                min_ratio: utils::ratio_from_percentage(0.0),
+                max_ratio: utils::ratio_from_percentage(100.0),
+                initial_price: utils::scale(1, FEE_TOKEN_DECIMALS),
+                initial_conf: utils::scale_f64(0.01, FEE_TOKEN_DECIMALS),
+                pricing_params: None,
+                permissions: None,
+                borrow_rate: None,
+                fees: Some(Fees {
+                    mode: FeesMode::Fixed,
+                    add_liquidity: 0,
+                    remove_liquidity: 0,
+                    protocol_share: 0,
+                    ..fixtures::fees_linear_regular()
+                }),
+            },
+            liquidity_amount: utils::scale(0, FEE_TOKEN_DECIMALS),
+            payer: utils::copy_keypair(&keypairs[USER_ALICE]),
+        }],
+    )
+    .await;
+
+    let custody_pda = custodies_info[0].custody_pda;
+    let custody_token_account_pda = {
+        let custody_account =
+            utils::get_account::<Custody>(&mut program_test_ctx, custody_pda).await;
+        custody_account.token_account
+    };
+
+    // Only the amount received after the transfer fee is credited to the custody
+    {
+        instructions::test_add_liquidity(
+            &mut program_test_ctx,
+            &keypairs[USER_ALICE],
+            &keypairs[PAYER],
+            &pool_pda,
+            &fee_token_mint,
+            AddLiquidityParams {
+                amount_in: utils::scale(1_000, FEE_TOKEN_DECIMALS),
+                min_lp_amount_out: 1,
+            },
+        )
+        .await
+        .unwrap();
+
+        let custody_account =
+            utils::get_account::<Custody>(&mut program_test_ctx, custody_pda).await;
+        let custody_token_account =
+            utils::get_token_account(&mut program_test_ctx, custody_token_account_pda).await;
+
+        assert_eq!(
+            custody_account.assets.owned,
+            utils::scale(990, FEE_TOKEN_DECIMALS)
+        );
+        assert_eq!(custody_token_account.amount, custody_account.assets.owned);
+    }
+
+    // Transfer fee on the way out is paid by the user
+    {
+        instructions::test_remove_liquidity(
+            &mut program_test_ctx,
+            &keypairs[USER_ALICE],
+            &keypairs[PAYER],
+            &pool_pda,
+            &fee_token_mint,
+            RemoveLiquidityParams {
+                lp_amount_in: utils::scale(100, Perpetuals::LP_DECIMALS),
+                min_amount_out: 1,
+            },
+        )
+        .await
+        .unwrap();
+
+        let custody_account =
+            utils::get_account::<Custody>(&mut program_test_ctx, custody_pda).await;
+        let custody_token_account =
+            utils::get_token_account(&mut program_test_ctx, custody_token_account_pda).await;
+
+        assert!(custody_account.assets.owned < utils::scale(990, FEE_TOKEN_DECIMALS));
+        assert_eq!(custody_token_account.amount, custody_account.assets.owned);
+    }
+}
@@ -1,6 +1,8 @@
 pub mod basic_interactions;
 pub mod liquidity;
+pub mod multisig;
 pub mod position;
+pub mod staking;
 pub mod swap;
 
-pub use {basic_interactions::*, liquidity::*, position::*, swap::*};
+pub use {basic_interactions::*, liquidity::*, multisig::*, position::*, staking::*, swap::*};
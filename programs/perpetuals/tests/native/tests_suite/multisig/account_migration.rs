@@ -0,0 +1,273 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, pda},
+    },
+    anchor_lang::{AccountSerialize, Discriminator},
+    perpetuals::state::{
+        custody::{Custody, PositionStats},
+        multisig::{DeprecatedMultisig, Multisig},
+        perpetuals::{DeprecatedPermissions, Perpetuals, PerpetualsV0},
+        pool::{Pool, PoolV0, TokenRatios},
+        position::{Position, PositionV0, Side},
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::{account, pubkey::Pubkey, rent::Rent, signer::Signer},
+};
+
+// serializes the account with its legacy layout under the discriminator of
+// the current type, the way accounts created before versioning are stored
+fn get_legacy_account<T: AccountSerialize>(
+    account: &T,
+    discriminator: [u8; 8],
+    len: usize,
+) -> account::Account {
+    let mut data = Vec::with_capacity(len);
+    account.try_serialize(&mut data).unwrap();
+    data.resize(len, 0);
+    data[..8].copy_from_slice(&discriminator);
+
+    account::Account {
+        lamports: Rent::default().minimum_balance(len),
+        data,
+        owner: perpetuals::id(),
+        ..account::Account::default()
+    }
+}
+
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 0;
+const MULTISIG_MEMBER_A: usize = 1;
+const MULTISIG_MEMBER_B: usize = 2;
+const PAYER: usize = 3;
+
+const KEYPAIRS_COUNT: usize = 4;
+
+pub async fn account_migration() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Create multisig account with the layout that predates versioning
+    let (multisig_pda, multisig_bump) = pda::get_multisig_pda();
+
+    let mut signers = [Default::default(); Multisig::MAX_SIGNERS];
+    signers[0] = keypairs[MULTISIG_MEMBER_A].pubkey();
+    signers[1] = keypairs[MULTISIG_MEMBER_B].pubkey();
+
+    let deprecated_multisig = DeprecatedMultisig {
+        num_signers: 2,
+        num_signed: 1,
+        min_signatures: 2,
+        signers,
+        bump: multisig_bump,
+        ..DeprecatedMultisig::default()
+    };
+
+    let mut data = Vec::with_capacity(DeprecatedMultisig::LEN);
+    deprecated_multisig.try_serialize(&mut data).unwrap();
+    data.resize(DeprecatedMultisig::LEN, 0);
+    data[..8].copy_from_slice(&Multisig::DISCRIMINATOR);
+
+    program_test.add_account(
+        multisig_pda,
+        account::Account {
+            lamports: Rent::default().minimum_balance(DeprecatedMultisig::LEN),
+            data,
+            owner: perpetuals::id(),
+            ..account::Account::default()
+        },
+    );
+
+    // Create perpetuals, pool and position accounts with the baseline layouts
+    let (perpetuals_pda, perpetuals_bump) = pda::get_perpetuals_pda();
+    let (pool_pda, pool_bump) = pda::get_pool_pda("A".to_string());
+    let custody_key = Pubkey::new_unique();
+    let (position_pda, position_bump) = pda::get_position_pda(
+        &keypairs[PAYER].pubkey(),
+        &pool_pda,
+        &custody_key,
+        Side::Long,
+    );
+
+    program_test.add_account(
+        perpetuals_pda,
+        get_legacy_account(
+            &PerpetualsV0 {
+                permissions: DeprecatedPermissions {
+                    allow_swap: true,
+                    allow_close_position: true,
+                    ..DeprecatedPermissions::default()
+                },
+                pools: vec![pool_pda],
+                perpetuals_bump,
+                ..PerpetualsV0::default()
+            },
+            Perpetuals::DISCRIMINATOR,
+            PerpetualsV0::LEN + 32,
+        ),
+    );
+
+    // single letter name makes the length prefix look like version 1
+    program_test.add_account(
+        pool_pda,
+        get_legacy_account(
+            &PoolV0 {
+                name: "A".to_string(),
+                custodies: vec![custody_key],
+                ratios: vec![TokenRatios {
+                    target: 10_000,
+                    min: 0,
+                    max: 10_000,
+                }],
+                bump: pool_bump,
+                ..PoolV0::default()
+            },
+            Pool::DISCRIMINATOR,
+            PoolV0::LEN + 32 + 24,
+        ),
+    );
+
+    // pool open interest is initialized from the (already migrated) custody
+    let custody = Custody {
+        version: Custody::VERSION,
+        pool: pool_pda,
+        long_positions: PositionStats {
+            size_usd: 3_000_000_000,
+            ..PositionStats::default()
+        },
+        short_positions: PositionStats {
+            size_usd: 1_000_000_000,
+            ..PositionStats::default()
+        },
+        ..Custody::default()
+    };
+    let mut custody_data = Vec::with_capacity(Custody::LEN);
+    custody.try_serialize(&mut custody_data).unwrap();
+    custody_data.resize(Custody::LEN, 0);
+    program_test.add_account(
+        custody_key,
+        account::Account {
+            lamports: Rent::default().minimum_balance(Custody::LEN),
+            data: custody_data,
+            owner: perpetuals::id(),
+            ..account::Account::default()
+        },
+    );
+
+    // owner key takes the place of the version field
+    program_test.add_account(
+        position_pda,
+        get_legacy_account(
+            &PositionV0 {
+                owner: keypairs[PAYER].pubkey(),
+                pool: pool_pda,
+                custody: custody_key,
+                side: Side::Long,
+                size_usd: 3_000_000_000,
+                bump: position_bump,
+                ..PositionV0::default()
+            },
+            Position::DISCRIMINATOR,
+            PositionV0::LEN,
+        ),
+    );
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    // Anyone can migrate the account
+    instructions::test_migrate_account(&mut program_test_ctx, &keypairs[PAYER], &multisig_pda, &[])
+        .await
+        .unwrap();
+
+    let multisig_account = program_test_ctx
+        .banks_client
+        .get_account(multisig_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(multisig_account.data.len(), Multisig::LEN);
+
+    let multisig: &Multisig = bytemuck::from_bytes(&multisig_account.data[8..]);
+    assert_eq!({ multisig.version }, Multisig::VERSION);
+    assert_eq!({ multisig.num_signers }, 2);
+    assert_eq!({ multisig.num_signed }, 0);
+    assert_eq!({ multisig.min_signatures }, 2);
+    assert_eq!({ multisig.signers }, signers);
+    assert_eq!({ multisig.bump }, multisig_bump);
+
+    // Latest layout can't be migrated again
+    assert!(instructions::test_migrate_account(
+        &mut program_test_ctx,
+        &keypairs[PAYER],
+        &multisig_pda,
+        &[]
+    )
+    .await
+    .is_err());
+
+    // Perpetuals
+    instructions::test_migrate_account(
+        &mut program_test_ctx,
+        &keypairs[PAYER],
+        &perpetuals_pda,
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let perpetuals_account =
+        utils::get_account::<Perpetuals>(&mut program_test_ctx, perpetuals_pda).await;
+    assert_eq!(perpetuals_account.version, Perpetuals::VERSION);
+    assert!(perpetuals_account.permissions.allow_swap);
+    assert!(perpetuals_account.permissions.allow_liquidation);
+    assert!(!perpetuals_account.permissions.allow_open_position);
+    assert_eq!(perpetuals_account.pools, vec![pool_pda]);
+    assert_eq!(perpetuals_account.perpetuals_bump, perpetuals_bump);
+
+    // Pool can't be migrated without its custodies
+    assert!(instructions::test_migrate_account(
+        &mut program_test_ctx,
+        &keypairs[PAYER],
+        &pool_pda,
+        &[]
+    )
+    .await
+    .is_err());
+
+    instructions::test_migrate_account(
+        &mut program_test_ctx,
+        &keypairs[PAYER],
+        &pool_pda,
+        &[custody_key],
+    )
+    .await
+    .unwrap();
+
+    let pool_account = utils::get_account::<Pool>(&mut program_test_ctx, pool_pda).await;
+    assert_eq!(pool_account.version, Pool::VERSION);
+    assert_eq!(pool_account.name, "A");
+    assert_eq!(pool_account.custodies, vec![custody_key]);
+    assert_eq!(pool_account.bump, pool_bump);
+    assert_eq!(pool_account.open_interest_usd, 4_000_000_000);
+    assert_eq!(pool_account.custody_aum.len(), 1);
+
+    // Position
+    instructions::test_migrate_account(&mut program_test_ctx, &keypairs[PAYER], &position_pda, &[])
+        .await
+        .unwrap();
+
+    let position_account =
+        utils::get_account::<Position>(&mut program_test_ctx, position_pda).await;
+    assert_eq!(position_account.version, Position::VERSION);
+    assert_eq!(position_account.owner, keypairs[PAYER].pubkey());
+    assert_eq!(position_account.side, Side::Long);
+    assert_eq!(position_account.size_usd, 3_000_000_000);
+    assert_eq!(position_account.bump, position_bump);
+    assert!(!position_account.is_cross_margin);
+}
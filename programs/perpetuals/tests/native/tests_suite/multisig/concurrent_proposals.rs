@@ -0,0 +1,220 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures},
+    },
+    perpetuals::{
+        instructions::{CreateProposalParams, SetPermissionsParams},
+        state::multisig::{AdminInstruction, Multisig},
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::signer::Signer,
+};
+
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 0;
+const MULTISIG_MEMBER_A: usize = 1;
+const MULTISIG_MEMBER_B: usize = 2;
+const MULTISIG_MEMBER_C: usize = 3;
+const PAYER: usize = 4;
+
+const KEYPAIRS_COUNT: usize = 5;
+
+fn get_permissions_params(allow_swap: bool, allow_open_position: bool) -> SetPermissionsParams {
+    SetPermissionsParams {
+        allow_swap,
+        allow_add_liquidity: true,
+        allow_remove_liquidity: true,
+        allow_open_position,
+        allow_close_position: true,
+        allow_pnl_withdrawal: true,
+        allow_collateral_withdrawal: true,
+        allow_size_change: true,
+        allow_liquidation: true,
+    }
+}
+
+pub async fn concurrent_proposals() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[MULTISIG_MEMBER_C],
+    ];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(2),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    let expiration_time = utils::get_current_unix_timestamp(&mut program_test_ctx).await + 3_600;
+    let instruction_accounts: Vec<_> =
+        instructions::get_set_permissions_accounts(&keypairs[MULTISIG_MEMBER_A].pubkey())[1..]
+            .iter()
+            .map(|x| x.pubkey)
+            .collect();
+
+    // Two proposals are created and approved independently
+    let params_1 = get_permissions_params(false, true);
+    let params_2 = get_permissions_params(true, false);
+
+    let mut proposals = vec![];
+    for (proposal_id, params) in [(1u64, &params_1), (2u64, &params_2)] {
+        let proposal_pda = instructions::test_create_proposal(
+            &mut program_test_ctx,
+            &keypairs[MULTISIG_MEMBER_A],
+            &keypairs[PAYER],
+            CreateProposalParams {
+                proposal_id,
+                instruction_data: Multisig::get_instruction_data(
+                    AdminInstruction::SetPermissions,
+                    params,
+                )
+                .unwrap(),
+                instruction_accounts: instruction_accounts.clone(),
+                expiration_time,
+            },
+        )
+        .await
+        .unwrap();
+
+        proposals.push(proposal_pda);
+    }
+
+    for proposal_pda in proposals.iter() {
+        instructions::test_approve_proposal(
+            &mut program_test_ctx,
+            &keypairs[MULTISIG_MEMBER_B],
+            &keypairs[PAYER],
+            proposal_pda,
+        )
+        .await
+        .unwrap();
+    }
+
+    // Approving the first proposal doesn't interfere with the second one,
+    // execute them in reverse order
+    instructions::test_set_permissions(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_C],
+        &keypairs[PAYER],
+        params_2,
+        Some(&proposals[1]),
+    )
+    .await
+    .unwrap();
+
+    instructions::test_set_permissions(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[PAYER],
+        params_1,
+        Some(&proposals[0]),
+    )
+    .await
+    .unwrap();
+
+    // Executed proposals can't be replayed
+    assert!(instructions::test_set_permissions(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        params_2,
+        Some(&proposals[1]),
+    )
+    .await
+    .is_err());
+
+    // Proposal is required when more than one signature is needed
+    assert!(instructions::test_set_permissions(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        params_2,
+        None,
+    )
+    .await
+    .is_err());
+
+    // Revoked approvals are not counted
+    let proposal_pda = instructions::test_create_proposal(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        CreateProposalParams {
+            proposal_id: 3,
+            instruction_data: Multisig::get_instruction_data(
+                AdminInstruction::SetPermissions,
+                &params_1,
+            )
+            .unwrap(),
+            instruction_accounts: instruction_accounts.clone(),
+            expiration_time,
+        },
+    )
+    .await
+    .unwrap();
+
+    instructions::test_approve_proposal(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[PAYER],
+        &proposal_pda,
+    )
+    .await
+    .unwrap();
+
+    instructions::test_revoke_approval(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[PAYER],
+        &proposal_pda,
+    )
+    .await
+    .unwrap();
+
+    assert!(instructions::test_set_permissions(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[PAYER],
+        params_1,
+        Some(&proposal_pda),
+    )
+    .await
+    .is_err());
+
+    // Instruction type must be one of admin instructions
+    let mut instruction_data =
+        Multisig::get_instruction_data(AdminInstruction::SetPermissions, &params_1).unwrap();
+    *instruction_data.last_mut().unwrap() = u8::MAX;
+
+    assert!(instructions::test_create_proposal(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        CreateProposalParams {
+            proposal_id: 4,
+            instruction_data,
+            instruction_accounts,
+            expiration_time,
+        },
+    )
+    .await
+    .is_err());
+}
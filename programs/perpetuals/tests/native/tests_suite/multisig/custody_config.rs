@@ -0,0 +1,335 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures, pda},
+    },
+    bonfida_test_utils::ProgramTestExt,
+    perpetuals::{
+        instructions::{
+            QueueInstructionParams, SetCustodyFeesParams, SetCustodyRampParams,
+            SetPoolConfigParams, SetPoolRatiosParams, SetTestTimeParams, SetTimelockDelaysParams,
+        },
+        state::{
+            custody::{Custody, Fees},
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::{Permissions, Perpetuals},
+            pool::{Pool, TokenRatios},
+        },
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::signer::Signer,
+};
+
+const ROOT_AUTHORITY: usize = 0;
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 1;
+const MULTISIG_MEMBER_A: usize = 2;
+const MULTISIG_MEMBER_B: usize = 3;
+const PAYER: usize = 4;
+
+const KEYPAIRS_COUNT: usize = 5;
+
+const USDC_DECIMALS: u8 = 6;
+const ETH_DECIMALS: u8 = 9;
+
+pub async fn custody_config() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Initialize mints
+    let usdc_mint = program_test
+        .add_mint(None, USDC_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+    let eth_mint = program_test
+        .add_mint(None, ETH_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[&keypairs[MULTISIG_MEMBER_A], &keypairs[MULTISIG_MEMBER_B]];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(1),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    let (pool_pda, _, _, _, custodies_info) = utils::setup_pool_with_custodies(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        "FOO",
+        &keypairs[PAYER],
+        multisig_signers,
+        vec![
+            utils::SetupCustodyParams {
+                mint: usdc_mint,
+                decimals: USDC_DECIMALS,
+                is_stable: true,
+                target_ratio: utils::ratio_from_percentage(50.0),
+                min_ratio: utils::ratio_from_percentage(0.0),
+                max_ratio: utils::ratio_from_percentage(100.0),
+                initial_price: utils::scale(1, USDC_DECIMALS),
+                initial_conf: utils::scale_f64(0.01, USDC_DECIMALS),
+                pricing_params: None,
+                permissions: None,
+                fees: None,
+                borrow_rate: None,
+            },
+            utils::SetupCustodyParams {
+                mint: eth_mint,
+                decimals: ETH_DECIMALS,
+                is_stable: false,
+                target_ratio: utils::ratio_from_percentage(50.0),
+                min_ratio: utils::ratio_from_percentage(0.0),
+                max_ratio: utils::ratio_from_percentage(100.0),
+                initial_price: utils::scale(1_500, ETH_DECIMALS),
+                initial_conf: utils::scale(10, ETH_DECIMALS),
+                pricing_params: None,
+                permissions: None,
+                fees: None,
+                borrow_rate: None,
+            },
+        ],
+    )
+    .await;
+
+    let usdc_custody_pda = custodies_info[0].custody_pda;
+
+    let custody_before =
+        utils::get_account::<Custody>(&mut program_test_ctx, usdc_custody_pda).await;
+
+    // Fees can be updated without touching the rest of the config
+    instructions::test_set_custody_fees(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &usdc_custody_pda,
+        SetCustodyFeesParams {
+            fees: Fees {
+                swap_in: 50,
+                swap_out: 50,
+                ..fixtures::fees_linear_regular()
+            },
+        },
+    )
+    .await
+    .unwrap();
+
+    let custody_after =
+        utils::get_account::<Custody>(&mut program_test_ctx, usdc_custody_pda).await;
+
+    assert_eq!(custody_after.oracle, custody_before.oracle);
+    assert_eq!(custody_after.pricing, custody_before.pricing);
+    assert_eq!(custody_after.permissions, custody_before.permissions);
+    assert_eq!(custody_after.borrow_rate, custody_before.borrow_rate);
+
+    // Ratios must cover every custody of the pool
+    let pool_account = utils::get_account::<Pool>(&mut program_test_ctx, pool_pda).await;
+
+    assert!(instructions::test_set_pool_ratios(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        SetPoolRatiosParams {
+            ratios: vec![pool_account.ratios[0]],
+        },
+    )
+    .await
+    .is_err());
+
+    instructions::test_set_pool_ratios(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        SetPoolRatiosParams {
+            ratios: vec![
+                TokenRatios {
+                    target: utils::ratio_from_percentage(40.0),
+                    min: utils::ratio_from_percentage(10.0),
+                    max: utils::ratio_from_percentage(90.0),
+                },
+                TokenRatios {
+                    target: utils::ratio_from_percentage(60.0),
+                    min: utils::ratio_from_percentage(10.0),
+                    max: utils::ratio_from_percentage(90.0),
+                },
+            ],
+        },
+    )
+    .await
+    .unwrap();
+
+    // Leverage is lowered gradually, starting from the current value
+    instructions::test_set_custody_ramp(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &usdc_custody_pda,
+        SetCustodyRampParams {
+            max_leverage: custody_after.pricing.max_initial_leverage,
+            trade_spread_long: custody_after.pricing.trade_spread_long,
+            trade_spread_short: custody_after.pricing.trade_spread_short,
+            swap_spread: custody_after.pricing.swap_spread,
+            slope1: custody_after.borrow_rate.slope1,
+            slope2: custody_after.borrow_rate.slope2,
+            ramp_end_time: i64::MAX,
+        },
+    )
+    .await
+    .unwrap();
+
+    let custody_ramped =
+        utils::get_account::<Custody>(&mut program_test_ctx, usdc_custody_pda).await;
+
+    assert_eq!(
+        custody_ramped.ramp.max_leverage,
+        custody_after.pricing.max_leverage
+    );
+
+    // Granular updates share the custody config timelock
+    instructions::test_set_timelock_delays(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        SetTimelockDelaysParams {
+            set_custody_config: 3_600,
+            set_permissions: 0,
+            set_admin_signers: 0,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(instructions::test_set_custody_fees(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &usdc_custody_pda,
+        SetCustodyFeesParams {
+            fees: fixtures::fees_linear_regular(),
+        },
+    )
+    .await
+    .is_err());
+
+    // Pool can be paused instantly
+    let pool_account = utils::get_account::<Pool>(&mut program_test_ctx, pool_pda).await;
+    let paused_config = SetPoolConfigParams {
+        permissions: Permissions {
+            allow_swap: false,
+            ..pool_account.permissions
+        },
+        max_aum_usd: pool_account.max_aum_usd,
+        max_open_interest_usd: pool_account.max_open_interest_usd,
+    };
+    let resumed_config = SetPoolConfigParams {
+        permissions: pool_account.permissions,
+        ..paused_config.clone()
+    };
+
+    instructions::test_set_pool_config(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        paused_config,
+    )
+    .await
+    .unwrap();
+
+    // Resuming must go through the queue
+    assert!(instructions::test_set_pool_config(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        resumed_config.clone(),
+    )
+    .await
+    .is_err());
+
+    let queue_params = |queue_id: u64| QueueInstructionParams {
+        queue_id,
+        instruction_data: Multisig::get_instruction_data(
+            AdminInstruction::SetPoolConfig,
+            &resumed_config,
+        )
+        .unwrap(),
+        instruction_accounts: vec![pool_pda],
+    };
+
+    // Queued instructions can be cancelled by the multisig
+    let queued_config_pda = instructions::test_queue_instruction(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        queue_params(1),
+    )
+    .await
+    .unwrap();
+
+    instructions::test_cancel_queued(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[PAYER],
+        &queued_config_pda,
+    )
+    .await
+    .unwrap();
+
+    // Queued config is applied once the delay elapses
+    let queued_config_pda = instructions::test_queue_instruction(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        queue_params(2),
+    )
+    .await
+    .unwrap();
+
+    assert!(instructions::test_execute_queued(
+        &mut program_test_ctx,
+        &keypairs[PAYER],
+        &queued_config_pda,
+    )
+    .await
+    .is_err());
+
+    let perpetuals_account =
+        utils::get_account::<Perpetuals>(&mut program_test_ctx, pda::get_perpetuals_pda().0).await;
+
+    instructions::test_set_test_time(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        SetTestTimeParams {
+            time: perpetuals_account.inception_time + 3_600,
+        },
+    )
+    .await
+    .unwrap();
+
+    instructions::test_execute_queued(&mut program_test_ctx, &keypairs[PAYER], &queued_config_pda)
+        .await
+        .unwrap();
+
+    let pool_account = utils::get_account::<Pool>(&mut program_test_ctx, pool_pda).await;
+
+    assert!(pool_account.permissions.allow_swap);
+}
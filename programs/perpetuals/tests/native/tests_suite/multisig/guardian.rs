@@ -0,0 +1,213 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures, pda},
+    },
+    bonfida_test_utils::ProgramTestExt,
+    perpetuals::{
+        instructions::{GuardianPauseParams, PauseTarget, SetGuardiansParams},
+        state::{
+            custody::Custody,
+            perpetuals::{Permissions, Perpetuals},
+            pool::Pool,
+        },
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::signer::Signer,
+};
+
+const ROOT_AUTHORITY: usize = 0;
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 1;
+const MULTISIG_MEMBER_A: usize = 2;
+const MULTISIG_MEMBER_B: usize = 3;
+const GUARDIAN: usize = 4;
+const PAYER: usize = 5;
+
+const KEYPAIRS_COUNT: usize = 6;
+
+const USDC_DECIMALS: u8 = 6;
+const ETH_DECIMALS: u8 = 9;
+
+fn get_pause_params(
+    target: PauseTarget,
+    allow_swap: bool,
+    allow_open_position: bool,
+) -> GuardianPauseParams {
+    GuardianPauseParams {
+        target,
+        permissions: Permissions {
+            allow_swap,
+            allow_add_liquidity: true,
+            allow_remove_liquidity: true,
+            allow_open_position,
+            allow_close_position: true,
+            allow_pnl_withdrawal: true,
+            allow_collateral_withdrawal: true,
+            allow_size_change: true,
+            allow_liquidation: true,
+        },
+    }
+}
+
+pub async fn guardian() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Initialize mints
+    let usdc_mint = program_test
+        .add_mint(None, USDC_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+    let eth_mint = program_test
+        .add_mint(None, ETH_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[&keypairs[MULTISIG_MEMBER_A], &keypairs[MULTISIG_MEMBER_B]];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(1),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    let (pool_pda, _, _, _, custodies_info) = utils::setup_pool_with_custodies(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        "FOO",
+        &keypairs[PAYER],
+        multisig_signers,
+        vec![
+            utils::SetupCustodyParams {
+                mint: usdc_mint,
+                decimals: USDC_DECIMALS,
+                is_stable: true,
+                target_ratio: utils::ratio_from_percentage(50.0),
+                min_ratio: utils::ratio_from_percentage(0.0),
+                max_ratio: utils::ratio_from_percentage(100.0),
+                initial_price: utils::scale(1, USDC_DECIMALS),
+                initial_conf: utils::scale_f64(0.01, USDC_DECIMALS),
+                pricing_params: None,
+                permissions: None,
+                fees: None,
+                borrow_rate: None,
+            },
+            utils::SetupCustodyParams {
+                mint: eth_mint,
+                decimals: ETH_DECIMALS,
+                is_stable: false,
+                target_ratio: utils::ratio_from_percentage(50.0),
+                min_ratio: utils::ratio_from_percentage(0.0),
+                max_ratio: utils::ratio_from_percentage(100.0),
+                initial_price: utils::scale(1_500, ETH_DECIMALS),
+                initial_conf: utils::scale(10, ETH_DECIMALS),
+                pricing_params: None,
+                permissions: None,
+                fees: None,
+                borrow_rate: None,
+            },
+        ],
+    )
+    .await;
+
+    let usdc_custody_pda = custodies_info[0].custody_pda;
+    let eth_custody_pda = custodies_info[1].custody_pda;
+
+    instructions::test_set_guardians(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        SetGuardiansParams {
+            guardians: vec![keypairs[GUARDIAN].pubkey()],
+        },
+    )
+    .await
+    .unwrap();
+
+    // Only guardians can pause
+    assert!(instructions::test_guardian_pause(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[PAYER],
+        get_pause_params(PauseTarget::Global, false, true),
+        &[],
+    )
+    .await
+    .is_err());
+
+    // Pause swaps globally
+    instructions::test_guardian_pause(
+        &mut program_test_ctx,
+        &keypairs[GUARDIAN],
+        &keypairs[PAYER],
+        get_pause_params(PauseTarget::Global, false, true),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // Pause opening positions on ETH custody
+    instructions::test_guardian_pause(
+        &mut program_test_ctx,
+        &keypairs[GUARDIAN],
+        &keypairs[PAYER],
+        get_pause_params(PauseTarget::Custody, true, false),
+        &[eth_custody_pda],
+    )
+    .await
+    .unwrap();
+
+    // Pause swaps on the pool
+    instructions::test_guardian_pause(
+        &mut program_test_ctx,
+        &keypairs[GUARDIAN],
+        &keypairs[PAYER],
+        get_pause_params(PauseTarget::Pool, false, true),
+        &[pool_pda],
+    )
+    .await
+    .unwrap();
+
+    // Guardian can't enable anything back
+    instructions::test_guardian_pause(
+        &mut program_test_ctx,
+        &keypairs[GUARDIAN],
+        &keypairs[PAYER],
+        get_pause_params(PauseTarget::Global, true, true),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let perpetuals_account =
+        utils::get_account::<Perpetuals>(&mut program_test_ctx, pda::get_perpetuals_pda().0).await;
+    assert!(!perpetuals_account.permissions.allow_swap);
+    assert!(perpetuals_account.permissions.allow_open_position);
+
+    let pool_account = utils::get_account::<Pool>(&mut program_test_ctx, pool_pda).await;
+    assert!(!pool_account.permissions.allow_swap);
+    assert!(pool_account.permissions.allow_open_position);
+
+    let usdc_custody_account =
+        utils::get_account::<Custody>(&mut program_test_ctx, usdc_custody_pda).await;
+    assert!(usdc_custody_account.permissions.allow_swap);
+    assert!(usdc_custody_account.permissions.allow_open_position);
+
+    let eth_custody_account =
+        utils::get_account::<Custody>(&mut program_test_ctx, eth_custody_pda).await;
+    assert!(eth_custody_account.permissions.allow_swap);
+    assert!(!eth_custody_account.permissions.allow_open_position);
+    assert!(eth_custody_account.permissions.allow_close_position);
+}
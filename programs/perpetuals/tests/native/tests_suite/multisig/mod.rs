@@ -0,0 +1,11 @@
+pub mod account_migration;
+pub mod concurrent_proposals;
+pub mod custody_config;
+pub mod guardian;
+pub mod multisig_upgrade;
+pub mod timelock;
+
+pub use {
+    account_migration::*, concurrent_proposals::*, custody_config::*, guardian::*,
+    multisig_upgrade::*, timelock::*,
+};
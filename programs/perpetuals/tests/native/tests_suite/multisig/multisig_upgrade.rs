@@ -0,0 +1,82 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, pda},
+    },
+    anchor_lang::{AccountSerialize, Discriminator},
+    perpetuals::state::multisig::{DeprecatedMultisig, Multisig},
+    solana_program_test::ProgramTest,
+    solana_sdk::{account, rent::Rent, signer::Signer},
+};
+
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 0;
+const MULTISIG_MEMBER_A: usize = 1;
+const MULTISIG_MEMBER_B: usize = 2;
+const USER_ALICE: usize = 3;
+
+const KEYPAIRS_COUNT: usize = 4;
+
+pub async fn multisig_upgrade() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Create multisig account with the layout that predates versioning
+    let (multisig_pda, multisig_bump) = pda::get_multisig_pda();
+
+    let mut signers = [Default::default(); Multisig::MAX_SIGNERS];
+    signers[0] = keypairs[MULTISIG_MEMBER_A].pubkey();
+    signers[1] = keypairs[MULTISIG_MEMBER_B].pubkey();
+
+    let deprecated_multisig = DeprecatedMultisig {
+        num_signers: 2,
+        num_signed: 1,
+        min_signatures: 2,
+        signers,
+        bump: multisig_bump,
+        ..DeprecatedMultisig::default()
+    };
+
+    let mut data = Vec::with_capacity(DeprecatedMultisig::LEN);
+    deprecated_multisig.try_serialize(&mut data).unwrap();
+    data.resize(DeprecatedMultisig::LEN, 0);
+    data[..8].copy_from_slice(&Multisig::DISCRIMINATOR);
+
+    program_test.add_account(
+        multisig_pda,
+        account::Account {
+            lamports: Rent::default().minimum_balance(DeprecatedMultisig::LEN),
+            data,
+            owner: perpetuals::id(),
+            ..account::Account::default()
+        },
+    );
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    // Only multisig signers can upgrade the account
+    assert!(
+        instructions::test_upgrade_multisig(&mut program_test_ctx, &keypairs[USER_ALICE])
+            .await
+            .is_err()
+    );
+
+    // Signers and threshold are carried over unchanged
+    instructions::test_upgrade_multisig(&mut program_test_ctx, &keypairs[MULTISIG_MEMBER_B])
+        .await
+        .unwrap();
+
+    // Latest layout can't be upgraded again
+    assert!(instructions::test_upgrade_multisig(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A]
+    )
+    .await
+    .is_err());
+}
@@ -0,0 +1,220 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures, pda},
+    },
+    perpetuals::{
+        instructions::{
+            QueueInstructionParams, SetAdminSignersParams, SetPermissionsParams, SetTestTimeParams,
+            SetTimelockDelaysParams,
+        },
+        state::{
+            multisig::{AdminInstruction, Multisig},
+            perpetuals::Perpetuals,
+        },
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::signer::Signer,
+};
+
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 0;
+const MULTISIG_MEMBER_A: usize = 1;
+const MULTISIG_MEMBER_B: usize = 2;
+const MULTISIG_MEMBER_C: usize = 3;
+const PAYER: usize = 4;
+
+const KEYPAIRS_COUNT: usize = 5;
+
+pub async fn timelock() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[&keypairs[MULTISIG_MEMBER_A], &keypairs[MULTISIG_MEMBER_B]];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(1),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    instructions::test_set_timelock_delays(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        SetTimelockDelaysParams {
+            set_custody_config: 3_600,
+            set_permissions: 3_600,
+            set_admin_signers: 0,
+        },
+    )
+    .await
+    .unwrap();
+
+    // Delays can't be reduced without the timelock
+    assert!(instructions::test_set_timelock_delays(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        SetTimelockDelaysParams {
+            set_custody_config: 0,
+            set_permissions: 0,
+            set_admin_signers: 0,
+        },
+    )
+    .await
+    .is_err());
+
+    let paused_permissions = SetPermissionsParams {
+        allow_swap: false,
+        allow_add_liquidity: true,
+        allow_remove_liquidity: true,
+        allow_open_position: false,
+        allow_close_position: true,
+        allow_pnl_withdrawal: true,
+        allow_collateral_withdrawal: true,
+        allow_size_change: true,
+        allow_liquidation: true,
+    };
+    let full_permissions = SetPermissionsParams {
+        allow_swap: true,
+        allow_open_position: true,
+        ..paused_permissions
+    };
+
+    // Emergency pause is applied instantly
+    instructions::test_set_permissions(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        paused_permissions,
+        None,
+    )
+    .await
+    .unwrap();
+
+    // Resuming must go through the queue
+    assert!(instructions::test_set_permissions(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        full_permissions,
+        None,
+    )
+    .await
+    .is_err());
+
+    let queued_permissions_pda = instructions::test_queue_instruction(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        QueueInstructionParams {
+            queue_id: 1,
+            instruction_data: Multisig::get_instruction_data(
+                AdminInstruction::SetPermissions,
+                &full_permissions,
+            )
+            .unwrap(),
+            instruction_accounts: vec![],
+        },
+    )
+    .await
+    .unwrap();
+
+    // Can't be executed before the delay elapses
+    assert!(instructions::test_execute_queued(
+        &mut program_test_ctx,
+        &keypairs[PAYER],
+        &queued_permissions_pda,
+    )
+    .await
+    .is_err());
+
+    // Instructions without delay can be executed right away by anyone
+    let queued_signers_pda = instructions::test_queue_instruction(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        QueueInstructionParams {
+            queue_id: 2,
+            instruction_data: Multisig::get_instruction_data(
+                AdminInstruction::SetAdminSigners,
+                &SetAdminSignersParams { min_signatures: 1 },
+            )
+            .unwrap(),
+            instruction_accounts: vec![
+                keypairs[MULTISIG_MEMBER_A].pubkey(),
+                keypairs[MULTISIG_MEMBER_B].pubkey(),
+                keypairs[MULTISIG_MEMBER_C].pubkey(),
+            ],
+        },
+    )
+    .await
+    .unwrap();
+
+    instructions::test_execute_queued(&mut program_test_ctx, &keypairs[PAYER], &queued_signers_pda)
+        .await
+        .unwrap();
+
+    let multisig_account =
+        utils::get_account::<Multisig>(&mut program_test_ctx, pda::get_multisig_pda().0).await;
+
+    assert_eq!(multisig_account.num_signers, 3);
+    assert_eq!(multisig_account.min_signatures, 1);
+
+    // Pause made after queueing is kept by the queued resume
+    instructions::test_set_permissions(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        SetPermissionsParams {
+            allow_add_liquidity: false,
+            ..paused_permissions
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let perpetuals_account =
+        utils::get_account::<Perpetuals>(&mut program_test_ctx, pda::get_perpetuals_pda().0).await;
+
+    instructions::test_set_test_time(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        SetTestTimeParams {
+            time: perpetuals_account.inception_time + 3_600,
+        },
+    )
+    .await
+    .unwrap();
+
+    instructions::test_execute_queued(
+        &mut program_test_ctx,
+        &keypairs[PAYER],
+        &queued_permissions_pda,
+    )
+    .await
+    .unwrap();
+
+    let perpetuals_account =
+        utils::get_account::<Perpetuals>(&mut program_test_ctx, pda::get_perpetuals_pda().0).await;
+
+    assert!(perpetuals_account.permissions.allow_swap);
+    assert!(perpetuals_account.permissions.allow_open_position);
+    assert!(!perpetuals_account.permissions.allow_add_liquidity);
+}
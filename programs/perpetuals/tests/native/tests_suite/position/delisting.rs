@@ -0,0 +1,315 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures},
+    },
+    bonfida_test_utils::ProgramTestExt,
+    perpetuals::{
+        instructions::{OpenPositionParams, SetCustodyStatusParams, SetFeeDistributionParams},
+        state::{
+            custody::{CustodyStatus, PricingParams},
+            fee_distribution::{FeeRecipient, FeeRecipientType},
+            perpetuals::Perpetuals,
+            pool::Pool,
+            position::Side,
+        },
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::signer::Signer,
+};
+
+const ROOT_AUTHORITY: usize = 0;
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 1;
+const MULTISIG_MEMBER_A: usize = 2;
+const MULTISIG_MEMBER_B: usize = 3;
+const MULTISIG_MEMBER_C: usize = 4;
+const PAYER: usize = 5;
+const USER_ALICE: usize = 6;
+const USER_MARTIN: usize = 7;
+const USER_EXECUTIONER: usize = 8;
+
+const KEYPAIRS_COUNT: usize = 9;
+
+const ETH_DECIMALS: u8 = 9;
+
+pub async fn delisting() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Initialize mints
+    let eth_mint = program_test
+        .add_mint(None, ETH_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[MULTISIG_MEMBER_C],
+    ];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(1),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    // Initialize and fund associated token accounts
+    {
+        // Alice: mint 100 ETH
+        {
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &eth_mint,
+                &keypairs[USER_ALICE].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(100, ETH_DECIMALS),
+            )
+            .await;
+        }
+
+        // Martin: mint 2 ETH
+        {
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &eth_mint,
+                &keypairs[USER_MARTIN].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(2, ETH_DECIMALS),
+            )
+            .await;
+        }
+
+        // Multisig member A: init ETH token account to receive swept tokens
+        {
+            utils::initialize_token_account(
+                &mut program_test_ctx,
+                &eth_mint,
+                &keypairs[MULTISIG_MEMBER_A].pubkey(),
+            )
+            .await;
+        }
+    }
+
+    let (pool_pda, _, _, _, custodies_infos) = utils::setup_pool_with_custodies_and_liquidity(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        "FOO",
+        &keypairs[PAYER],
+        multisig_signers,
+        vec![utils::SetupCustodyWithLiquidityParams {
+            setup_custody_params: utils::SetupCustodyParams {
+                mint: eth_mint,
+                decimals: ETH_DECIMALS,
+                is_stable: false,
+                target_ratio: utils::ratio_from_percentage(100.0),
+                min_ratio: utils::ratio_from_percentage(0.0),
+                max_ratio: utils::ratio_from_percentage(100.0),
+                initial_price: utils::scale(1_500, ETH_DECIMALS),
+                initial_conf: utils::scale(10, ETH_DECIMALS),
+                pricing_params: Some(PricingParams {
+                    max_leverage: 100_000,
+                    ..fixtures::pricing_params_regular(false)
+                }),
+                permissions: None,
+                fees: None,
+                borrow_rate: None,
+            },
+            liquidity_amount: utils::scale(100, ETH_DECIMALS),
+            payer: utils::copy_keypair(&keypairs[USER_ALICE]),
+        }],
+    )
+    .await;
+
+    let eth_custody_pda = custodies_infos[0].custody_pda;
+
+    let open_position_params = OpenPositionParams {
+        // max price paid (slippage implied)
+        price: utils::scale(1_550, ETH_DECIMALS),
+        collateral: utils::scale(1, ETH_DECIMALS),
+        size: utils::scale(5, ETH_DECIMALS),
+        side: Side::Long,
+    };
+
+    // Martin: Open 1 ETH long position x5
+    let position_pda = instructions::test_open_position(
+        &mut program_test_ctx,
+        &keypairs[USER_MARTIN],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        open_position_params,
+    )
+    .await
+    .unwrap()
+    .0;
+
+    // Settlement requires the custody to be reduce-only first
+    assert!(instructions::test_set_custody_status(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_custody_pda,
+        SetCustodyStatusParams {
+            status: CustodyStatus::Settlement,
+            settlement_price: utils::scale(1_500, Perpetuals::PRICE_DECIMALS),
+        },
+    )
+    .await
+    .is_err());
+
+    instructions::test_set_custody_status(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_custody_pda,
+        SetCustodyStatusParams {
+            status: CustodyStatus::ReduceOnly,
+            settlement_price: 0,
+        },
+    )
+    .await
+    .unwrap();
+
+    // Alice: Try and fail to open a position on the reduce-only custody
+    assert!(instructions::test_open_position(
+        &mut program_test_ctx,
+        &keypairs[USER_ALICE],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        open_position_params,
+    )
+    .await
+    .is_err());
+
+    // Positions can't be settled before the settlement price is set
+    assert!(instructions::test_settle_position(
+        &mut program_test_ctx,
+        &keypairs[USER_EXECUTIONER],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        &position_pda,
+    )
+    .await
+    .is_err());
+
+    instructions::test_set_custody_status(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_custody_pda,
+        SetCustodyStatusParams {
+            status: CustodyStatus::Settlement,
+            settlement_price: utils::scale(1_500, Perpetuals::PRICE_DECIMALS),
+        },
+    )
+    .await
+    .unwrap();
+
+    let sweep_receiving_account =
+        utils::find_associated_token_account(&keypairs[MULTISIG_MEMBER_A].pubkey(), &eth_mint).0;
+
+    // Custody can't be swept while positions are open
+    assert!(instructions::test_sweep_custody(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        &sweep_receiving_account,
+    )
+    .await
+    .is_err());
+
+    // Executioner: Settle Martin ETH position
+    instructions::test_settle_position(
+        &mut program_test_ctx,
+        &keypairs[USER_EXECUTIONER],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        &position_pda,
+    )
+    .await
+    .unwrap();
+
+    // Swept tokens can only go to a pool treasury
+    let alice_eth_account =
+        utils::find_associated_token_account(&keypairs[USER_ALICE].pubkey(), &eth_mint).0;
+    assert!(instructions::test_sweep_custody(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        &alice_eth_account,
+    )
+    .await
+    .is_err());
+
+    instructions::test_set_fee_distribution(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        SetFeeDistributionParams {
+            recipients: vec![FeeRecipient {
+                recipient_type: FeeRecipientType::Treasury,
+                owner: keypairs[MULTISIG_MEMBER_A].pubkey(),
+                weight: 10_000,
+            }],
+        },
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    assert!(instructions::test_sweep_custody(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        &alice_eth_account,
+    )
+    .await
+    .is_err());
+
+    instructions::test_sweep_custody(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        &sweep_receiving_account,
+    )
+    .await
+    .unwrap();
+
+    // Swept custody no longer contributes to the cached pool aum
+    let pool_account = utils::get_account::<Pool>(&mut program_test_ctx, pool_pda).await;
+    assert_eq!(pool_account.custody_aum[0].last_usd, 0);
+    assert_eq!(pool_account.custody_aum[0].ema_usd, 0);
+
+    let swept_balance =
+        utils::get_token_account_balance(&mut program_test_ctx, sweep_receiving_account).await;
+    assert!(swept_balance > 0);
+}
@@ -5,7 +5,7 @@ use {
     },
     bonfida_test_utils::ProgramTestExt,
     perpetuals::{
-        instructions::{OpenPositionParams, SetTestOraclePriceParams},
+        instructions::{OpenPositionParams, SetPermissionsParams, SetTestOraclePriceParams},
         state::{custody::PricingParams, position::Side},
     },
     solana_program_test::ProgramTest,
@@ -189,6 +189,27 @@ pub async fn liquidate_position() {
 
     // Price drop makes the position to go over authorized leverage
 
+    // Pause user closes, liquidations are controlled separately
+    instructions::test_set_permissions(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        SetPermissionsParams {
+            allow_swap: true,
+            allow_add_liquidity: true,
+            allow_remove_liquidity: true,
+            allow_open_position: true,
+            allow_close_position: false,
+            allow_pnl_withdrawal: true,
+            allow_collateral_withdrawal: true,
+            allow_size_change: true,
+            allow_liquidation: true,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
     // Executioner: Liquidate Martin ETH position
     instructions::test_liquidate(
         &mut program_test_ctx,
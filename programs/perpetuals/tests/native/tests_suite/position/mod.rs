@@ -1,5 +1,11 @@
+pub mod cross_margin;
+pub mod delisting;
+pub mod leverage_tiers;
 pub mod liquidate_position;
 pub mod max_user_profit;
 pub mod min_max_leverage;
 
-pub use {liquidate_position::*, max_user_profit::*, min_max_leverage::*};
+pub use {
+    cross_margin::*, delisting::*, leverage_tiers::*, liquidate_position::*, max_user_profit::*,
+    min_max_leverage::*,
+};
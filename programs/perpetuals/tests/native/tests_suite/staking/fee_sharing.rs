@@ -0,0 +1,348 @@
+use {
+    crate::{
+        instructions,
+        utils::{self, fixtures},
+    },
+    bonfida_test_utils::ProgramTestExt,
+    perpetuals::{
+        instructions::{
+            InitStakingParams, SetFeeDistributionParams, SetPoolConfigParams, StakeParams,
+            SwapParams,
+        },
+        state::{
+            fee_distribution::{FeeRecipient, FeeRecipientType},
+            perpetuals::Permissions,
+        },
+    },
+    solana_program_test::ProgramTest,
+    solana_sdk::{pubkey::Pubkey, signer::Signer},
+};
+
+const ROOT_AUTHORITY: usize = 0;
+const PERPETUALS_UPGRADE_AUTHORITY: usize = 1;
+const MULTISIG_MEMBER_A: usize = 2;
+const MULTISIG_MEMBER_B: usize = 3;
+const MULTISIG_MEMBER_C: usize = 4;
+const PAYER: usize = 5;
+const USER_ALICE: usize = 6;
+const USER_MARTIN: usize = 7;
+
+const KEYPAIRS_COUNT: usize = 8;
+
+const USDC_DECIMALS: u8 = 6;
+const ETH_DECIMALS: u8 = 9;
+
+pub async fn fee_sharing() {
+    let mut program_test = ProgramTest::default();
+
+    // Initialize the accounts that will be used during the test suite
+    let keypairs =
+        utils::create_and_fund_multiple_accounts(&mut program_test, KEYPAIRS_COUNT).await;
+
+    // Initialize mints
+    let usdc_mint = program_test
+        .add_mint(None, USDC_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+    let eth_mint = program_test
+        .add_mint(None, ETH_DECIMALS, &keypairs[ROOT_AUTHORITY].pubkey())
+        .0;
+
+    // Deploy the perpetuals program onchain as upgradeable program
+    utils::add_perpetuals_program(&mut program_test, &keypairs[PERPETUALS_UPGRADE_AUTHORITY]).await;
+
+    // Start the client and connect to localnet validator
+    let mut program_test_ctx = program_test.start_with_context().await;
+
+    let upgrade_authority = &keypairs[PERPETUALS_UPGRADE_AUTHORITY];
+
+    let multisig_signers = &[
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[MULTISIG_MEMBER_B],
+        &keypairs[MULTISIG_MEMBER_C],
+    ];
+
+    instructions::test_init(
+        &mut program_test_ctx,
+        upgrade_authority,
+        fixtures::init_params_permissions_full(1),
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    // Initialize and fund associated token accounts
+    {
+        // Alice: mint 7.5k USDC and 5 ETH
+        {
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &usdc_mint,
+                &keypairs[USER_ALICE].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(7_500, USDC_DECIMALS),
+            )
+            .await;
+
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &eth_mint,
+                &keypairs[USER_ALICE].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(5, ETH_DECIMALS),
+            )
+            .await;
+        }
+
+        // Martin: mint 1k USDC, 10 ETH
+        {
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &usdc_mint,
+                &keypairs[USER_MARTIN].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(1_000, USDC_DECIMALS),
+            )
+            .await;
+
+            utils::initialize_and_fund_token_account(
+                &mut program_test_ctx,
+                &eth_mint,
+                &keypairs[USER_MARTIN].pubkey(),
+                &keypairs[ROOT_AUTHORITY],
+                utils::scale(10, ETH_DECIMALS),
+            )
+            .await;
+        }
+    }
+
+    // Set the pool with 50%/50% ETH/USDC liquidity
+    let (pool_pda, _, lp_token_mint_pda, _, _) = utils::setup_pool_with_custodies_and_liquidity(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        "FOO",
+        &keypairs[PAYER],
+        multisig_signers,
+        vec![
+            utils::SetupCustodyWithLiquidityParams {
+                setup_custody_params: utils::SetupCustodyParams {
+                    mint: usdc_mint,
+                    decimals: USDC_DECIMALS,
+                    is_stable: true,
+                    target_ratio: utils::ratio_from_percentage(50.0),
+                    min_ratio: utils::ratio_from_percentage(0.0),
+                    max_ratio: utils::ratio_from_percentage(100.0),
+                    initial_price: utils::scale(1, USDC_DECIMALS),
+                    initial_conf: utils::scale_f64(0.01, USDC_DECIMALS),
+                    pricing_params: None,
+                    permissions: None,
+                    fees: None,
+                    borrow_rate: None,
+                },
+                liquidity_amount: utils::scale(7_500, USDC_DECIMALS),
+                payer: utils::copy_keypair(&keypairs[USER_ALICE]),
+            },
+            utils::SetupCustodyWithLiquidityParams {
+                setup_custody_params: utils::SetupCustodyParams {
+                    mint: eth_mint,
+                    decimals: ETH_DECIMALS,
+                    is_stable: false,
+                    target_ratio: utils::ratio_from_percentage(50.0),
+                    min_ratio: utils::ratio_from_percentage(0.0),
+                    max_ratio: utils::ratio_from_percentage(100.0),
+                    initial_price: utils::scale(1_500, ETH_DECIMALS),
+                    initial_conf: utils::scale(10, ETH_DECIMALS),
+                    pricing_params: None,
+                    permissions: None,
+                    fees: None,
+                    borrow_rate: None,
+                },
+                liquidity_amount: utils::scale(5, ETH_DECIMALS),
+                payer: utils::copy_keypair(&keypairs[USER_ALICE]),
+            },
+        ],
+    )
+    .await;
+
+    // Admin: route 50% of protocol fees to stakers
+    instructions::test_init_staking(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        InitStakingParams { fee_share: 5_000 },
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    let alice_lp_token_account_address =
+        utils::find_associated_token_account(&keypairs[USER_ALICE].pubkey(), &lp_token_mint_pda).0;
+
+    let alice_lp_token_account_balance =
+        utils::get_token_account_balance(&mut program_test_ctx, alice_lp_token_account_address)
+            .await;
+
+    // Staking follows the pool liquidity deposit permission
+    instructions::test_set_pool_config(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        SetPoolConfigParams {
+            permissions: Permissions {
+                allow_add_liquidity: false,
+                ..fixtures::permissions_full()
+            },
+            max_aum_usd: 0,
+            max_open_interest_usd: 0,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(instructions::test_stake(
+        &mut program_test_ctx,
+        &keypairs[USER_ALICE],
+        &keypairs[PAYER],
+        &pool_pda,
+        StakeParams {
+            amount: alice_lp_token_account_balance / 4,
+        },
+    )
+    .await
+    .is_err());
+
+    instructions::test_set_pool_config(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        SetPoolConfigParams {
+            permissions: fixtures::permissions_full(),
+            max_aum_usd: 0,
+            max_open_interest_usd: 0,
+        },
+    )
+    .await
+    .unwrap();
+
+    // Alice: stake half of her LP tokens
+    instructions::test_stake(
+        &mut program_test_ctx,
+        &keypairs[USER_ALICE],
+        &keypairs[PAYER],
+        &pool_pda,
+        StakeParams {
+            amount: alice_lp_token_account_balance / 2,
+        },
+    )
+    .await
+    .unwrap();
+
+    // Martin: Swap 500 USDC for ETH to generate protocol fees
+    instructions::test_swap(
+        &mut program_test_ctx,
+        &keypairs[USER_MARTIN],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        // The program receives USDC
+        &usdc_mint,
+        SwapParams {
+            amount_in: utils::scale(500, USDC_DECIMALS),
+            min_amount_out: 0,
+        },
+    )
+    .await
+    .unwrap();
+
+    let alice_usdc_token_account_address =
+        utils::find_associated_token_account(&keypairs[USER_ALICE].pubkey(), &usdc_mint).0;
+
+    let alice_usdc_balance_before =
+        utils::get_token_account_balance(&mut program_test_ctx, alice_usdc_token_account_address)
+            .await;
+
+    // Alice: claim her share of the fees
+    instructions::test_claim_stake_rewards(
+        &mut program_test_ctx,
+        &keypairs[USER_ALICE],
+        &keypairs[PAYER],
+        &pool_pda,
+    )
+    .await
+    .unwrap();
+
+    let alice_usdc_balance_after =
+        utils::get_token_account_balance(&mut program_test_ctx, alice_usdc_token_account_address)
+            .await;
+
+    assert!(alice_usdc_balance_after > alice_usdc_balance_before);
+
+    // Admin: split remaining protocol fees between the treasury and stakers
+    let treasury_usdc_token_account_address = utils::initialize_token_account(
+        &mut program_test_ctx,
+        &usdc_mint,
+        &keypairs[ROOT_AUTHORITY].pubkey(),
+    )
+    .await;
+
+    instructions::test_set_fee_distribution(
+        &mut program_test_ctx,
+        &keypairs[MULTISIG_MEMBER_A],
+        &keypairs[PAYER],
+        &pool_pda,
+        SetFeeDistributionParams {
+            recipients: vec![
+                FeeRecipient {
+                    recipient_type: FeeRecipientType::Treasury,
+                    owner: keypairs[ROOT_AUTHORITY].pubkey(),
+                    weight: 5_000,
+                },
+                FeeRecipient {
+                    recipient_type: FeeRecipientType::Stakers,
+                    owner: Pubkey::default(),
+                    weight: 5_000,
+                },
+            ],
+        },
+        multisig_signers,
+    )
+    .await
+    .unwrap();
+
+    // Martin: Swap 300 USDC for ETH to generate protocol fees
+    instructions::test_swap(
+        &mut program_test_ctx,
+        &keypairs[USER_MARTIN],
+        &keypairs[PAYER],
+        &pool_pda,
+        &eth_mint,
+        // The program receives USDC
+        &usdc_mint,
+        SwapParams {
+            amount_in: utils::scale(300, USDC_DECIMALS),
+            min_amount_out: 0,
+        },
+    )
+    .await
+    .unwrap();
+
+    // Anyone: distribute USDC protocol fees
+    instructions::test_distribute_fees(
+        &mut program_test_ctx,
+        &keypairs[PAYER],
+        &pool_pda,
+        &usdc_mint,
+    )
+    .await
+    .unwrap();
+
+    let treasury_usdc_balance = utils::get_token_account_balance(
+        &mut program_test_ctx,
+        treasury_usdc_token_account_address,
+    )
+    .await;
+
+    assert!(treasury_usdc_balance > 0);
+}
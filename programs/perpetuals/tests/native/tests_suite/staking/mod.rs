@@ -0,0 +1,3 @@
+pub mod fee_sharing;
+
+pub use fee_sharing::*;
@@ -22,6 +22,7 @@ pub fn permissions_full() -> Permissions {
         allow_pnl_withdrawal: true,
         allow_collateral_withdrawal: true,
         allow_size_change: true,
+        allow_liquidation: true,
     }
 }
 
@@ -89,5 +90,6 @@ pub fn init_params_permissions_full(min_signatures: u8) -> InitParams {
         allow_pnl_withdrawal: true,
         allow_collateral_withdrawal: true,
         allow_size_change: true,
+        allow_liquidation: true,
     }
 }
@@ -83,3 +83,95 @@ pub fn get_test_oracle_account(pool_pda: &Pubkey, custody_mint: &Pubkey) -> (Pub
         &perpetuals::id(),
     )
 }
+
+pub fn get_margin_account_pda(owner: &Pubkey, pool_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &["margin_account".as_ref(), owner.as_ref(), pool_pda.as_ref()],
+        &perpetuals::id(),
+    )
+}
+
+pub fn get_staking_pda(pool_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&["staking".as_ref(), pool_pda.as_ref()], &perpetuals::id())
+}
+
+pub fn get_stake_token_account_pda(pool_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &["stake_token_account".as_ref(), pool_pda.as_ref()],
+        &perpetuals::id(),
+    )
+}
+
+pub fn get_stake_account_pda(owner: &Pubkey, pool_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &["stake_account".as_ref(), owner.as_ref(), pool_pda.as_ref()],
+        &perpetuals::id(),
+    )
+}
+
+pub fn get_wsol_account_pda(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &["wsol_account".as_ref(), owner.as_ref()],
+        &perpetuals::id(),
+    )
+}
+
+pub fn get_pool_day_stats_pda(pool_pda: &Pubkey, day: i64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            "pool_day_stats".as_ref(),
+            pool_pda.as_ref(),
+            &day.to_le_bytes(),
+        ],
+        &perpetuals::id(),
+    )
+}
+
+pub fn get_withdrawal_queue_pda(pool_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &["withdrawal_queue".as_ref(), pool_pda.as_ref()],
+        &perpetuals::id(),
+    )
+}
+
+pub fn get_withdrawal_escrow_account_pda(pool_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &["withdrawal_escrow_account".as_ref(), pool_pda.as_ref()],
+        &perpetuals::id(),
+    )
+}
+
+pub fn get_withdrawal_request_pda(owner: &Pubkey, pool_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            "withdrawal_request".as_ref(),
+            owner.as_ref(),
+            pool_pda.as_ref(),
+        ],
+        &perpetuals::id(),
+    )
+}
+
+pub fn get_fee_distribution_pda(pool_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &["fee_distribution".as_ref(), pool_pda.as_ref()],
+        &perpetuals::id(),
+    )
+}
+
+pub fn get_proposal_pda(proposal_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &["proposal".as_ref(), proposal_id.to_le_bytes().as_ref()],
+        &perpetuals::id(),
+    )
+}
+
+pub fn get_queued_instruction_pda(queue_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            "queued_instruction".as_ref(),
+            queue_id.to_le_bytes().as_ref(),
+        ],
+        &perpetuals::id(),
+    )
+}
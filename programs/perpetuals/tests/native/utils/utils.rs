@@ -1,8 +1,7 @@
 use {
-    super::{fixtures, get_program_data_pda, get_test_oracle_account},
+    super::{fixtures, get_program_data_pda, get_test_oracle_account, pda},
     crate::instructions,
     anchor_lang::{prelude::*, InstructionData},
-    anchor_spl::token::spl_token,
     bonfida_test_utils::ProgramTestContextExt,
     perpetuals::{
         instructions::{
@@ -13,11 +12,13 @@ use {
             custody::{BorrowRateParams, Custody, Fees, PricingParams},
             perpetuals::{Permissions, Perpetuals},
             pool::TokenRatios,
+            pool_day_stats::PoolDayStats,
         },
     },
-    solana_program::{bpf_loader_upgradeable, program_pack::Pack, stake_history::Epoch},
+    solana_program::{bpf_loader_upgradeable, stake_history::Epoch, system_instruction},
     solana_program_test::{read_file, BanksClientError, ProgramTest, ProgramTestContext},
     solana_sdk::{account, signature::Keypair, signer::Signer, signers::Signers},
+    spl_token_2022::extension::{transfer_fee, ExtensionType, StateWithExtensions},
     std::{
         ops::{Div, Mul},
         path::Path,
@@ -37,12 +38,16 @@ pub fn create_and_fund_account(address: &Pubkey, program_test: &mut ProgramTest)
 }
 
 pub fn find_associated_token_account(owner: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    find_associated_token_account_with_program(owner, mint, &anchor_spl::token::ID)
+}
+
+pub fn find_associated_token_account_with_program(
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> (Pubkey, u8) {
     Pubkey::find_program_address(
-        &[
-            owner.as_ref(),
-            anchor_spl::token::ID.as_ref(),
-            mint.as_ref(),
-        ],
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
         &anchor_spl::associated_token::ID,
     )
 }
@@ -51,10 +56,11 @@ pub fn copy_keypair(keypair: &Keypair) -> Keypair {
     Keypair::from_bytes(&keypair.to_bytes()).unwrap()
 }
 
+// Works with both SPL Token and Token-2022 accounts
 pub async fn get_token_account(
     program_test_ctx: &mut ProgramTestContext,
     key: Pubkey,
-) -> spl_token::state::Account {
+) -> spl_token_2022::state::Account {
     let raw_account = program_test_ctx
         .banks_client
         .get_account(key)
@@ -62,7 +68,20 @@ pub async fn get_token_account(
         .unwrap()
         .unwrap();
 
-    spl_token::state::Account::unpack(&raw_account.data).unwrap()
+    StateWithExtensions::<spl_token_2022::state::Account>::unpack(&raw_account.data)
+        .unwrap()
+        .base
+}
+
+// Returns the token program owning the mint
+pub async fn get_token_program(program_test_ctx: &mut ProgramTestContext, mint: &Pubkey) -> Pubkey {
+    program_test_ctx
+        .banks_client
+        .get_account(*mint)
+        .await
+        .unwrap()
+        .unwrap()
+        .owner
 }
 
 pub async fn get_token_account_balance(
@@ -72,6 +91,32 @@ pub async fn get_token_account_balance(
     get_token_account(program_test_ctx, key).await.amount
 }
 
+// Wallet of the owner is used in place of a token account for native SOL
+pub fn find_user_token_account(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    if *mint == anchor_spl::token::spl_token::native_mint::ID {
+        *owner
+    } else {
+        find_associated_token_account_with_program(owner, mint, token_program).0
+    }
+}
+
+// Returns lamports for native SOL and token amount otherwise
+pub async fn get_user_token_balance(
+    program_test_ctx: &mut ProgramTestContext,
+    key: Pubkey,
+    mint: &Pubkey,
+) -> u64 {
+    if *mint == anchor_spl::token::spl_token::native_mint::ID {
+        program_test_ctx
+            .banks_client
+            .get_balance(key)
+            .await
+            .unwrap()
+    } else {
+        get_token_account_balance(program_test_ctx, key).await
+    }
+}
+
 pub async fn get_account<T: anchor_lang::AccountDeserialize>(
     program_test_ctx: &mut ProgramTestContext,
     key: Pubkey,
@@ -95,6 +140,21 @@ pub async fn get_current_unix_timestamp(program_test_ctx: &mut ProgramTestContex
         .unix_timestamp
 }
 
+// Program runs in test mode, the current day derives from the perpetuals time
+pub async fn get_current_pool_day_stats_pda(
+    program_test_ctx: &mut ProgramTestContext,
+    pool_pda: &Pubkey,
+) -> Pubkey {
+    let perpetuals_account =
+        get_account::<Perpetuals>(program_test_ctx, pda::get_perpetuals_pda().0).await;
+
+    pda::get_pool_day_stats_pda(
+        pool_pda,
+        PoolDayStats::get_day(perpetuals_account.inception_time),
+    )
+    .0
+}
+
 pub async fn initialize_token_account(
     program_test_ctx: &mut ProgramTestContext,
     mint: &Pubkey,
@@ -140,6 +200,113 @@ pub async fn mint_tokens(
         .unwrap();
 }
 
+// Create a Token-2022 mint with the transfer fee extension
+pub async fn create_mint_with_transfer_fee(
+    program_test_ctx: &mut ProgramTestContext,
+    mint_authority: &Pubkey,
+    decimals: u8,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+) -> Pubkey {
+    let mint = Keypair::new();
+    let space = ExtensionType::get_account_len::<spl_token_2022::state::Mint>(&[
+        ExtensionType::TransferFeeConfig,
+    ]);
+    let rent = program_test_ctx.banks_client.get_rent().await.unwrap();
+
+    let instructions = [
+        system_instruction::create_account(
+            &program_test_ctx.payer.pubkey(),
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &spl_token_2022::ID,
+        ),
+        transfer_fee::instruction::initialize_transfer_fee_config(
+            &spl_token_2022::ID,
+            &mint.pubkey(),
+            Some(mint_authority),
+            Some(mint_authority),
+            transfer_fee_basis_points,
+            maximum_fee,
+        )
+        .unwrap(),
+        spl_token_2022::instruction::initialize_mint2(
+            &spl_token_2022::ID,
+            &mint.pubkey(),
+            mint_authority,
+            None,
+            decimals,
+        )
+        .unwrap(),
+    ];
+
+    let tx = solana_sdk::transaction::Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&program_test_ctx.payer.pubkey()),
+        &[&program_test_ctx.payer, &mint],
+        program_test_ctx.last_blockhash,
+    );
+    program_test_ctx
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    mint.pubkey()
+}
+
+// Create the associated Token-2022 account of the owner and mint tokens to it
+pub async fn initialize_and_fund_token_2022_account(
+    program_test_ctx: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    mint_authority: &Keypair,
+    amount: u64,
+) -> Pubkey {
+    let token_account_address =
+        find_associated_token_account_with_program(owner, mint, &spl_token_2022::ID).0;
+
+    let instructions = [
+        solana_sdk::instruction::Instruction {
+            program_id: anchor_spl::associated_token::ID,
+            accounts: vec![
+                AccountMeta::new(program_test_ctx.payer.pubkey(), true),
+                AccountMeta::new(token_account_address, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(anchor_lang::system_program::ID, false),
+                AccountMeta::new_readonly(spl_token_2022::ID, false),
+            ],
+            // AssociatedTokenAccountInstruction::Create
+            data: vec![0],
+        },
+        spl_token_2022::instruction::mint_to(
+            &spl_token_2022::ID,
+            mint,
+            &token_account_address,
+            &mint_authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap(),
+    ];
+
+    let tx = solana_sdk::transaction::Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&program_test_ctx.payer.pubkey()),
+        &[&program_test_ctx.payer, mint_authority],
+        program_test_ctx.last_blockhash,
+    );
+    program_test_ctx
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    token_account_address
+}
+
 // Deploy the perpetuals program onchain as upgradeable program
 pub async fn add_perpetuals_program(program_test: &mut ProgramTest, upgrade_authority: &Keypair) {
     // Deploy two accounts, one describing the program
@@ -230,6 +397,47 @@ pub async fn create_and_execute_perpetuals_ix<T: InstructionData, U: Signers>(
     Ok(())
 }
 
+/// Simulates a view instruction and decodes the value it returns
+pub async fn simulate_perpetuals_ix<T: InstructionData, U: Signers, R: AnchorDeserialize>(
+    program_test_ctx: &mut ProgramTestContext,
+    accounts_meta: Vec<AccountMeta>,
+    args: T,
+    payer: Option<&Pubkey>,
+    signing_keypairs: &U,
+) -> std::result::Result<R, BanksClientError> {
+    let ix = solana_sdk::instruction::Instruction {
+        program_id: perpetuals::id(),
+        accounts: accounts_meta,
+        data: args.data(),
+    };
+
+    let tx = solana_sdk::transaction::Transaction::new_signed_with_payer(
+        &[ix],
+        payer,
+        signing_keypairs,
+        program_test_ctx.last_blockhash,
+    );
+
+    let simulation = program_test_ctx
+        .banks_client
+        .simulate_transaction(tx)
+        .await?;
+
+    if let Some(Err(err)) = simulation.result {
+        return Err(BanksClientError::TransactionError(err));
+    }
+
+    // trailing zeroes are stripped from the return data by the runtime
+    let mut return_data = simulation
+        .simulation_details
+        .and_then(|details| details.return_data)
+        .map(|return_data| return_data.data)
+        .unwrap_or_default();
+    return_data.resize(return_data.len() + 1024, 0);
+
+    Ok(R::deserialize(&mut return_data.as_slice()).unwrap())
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn set_custody_ratios(
     program_test_ctx: &mut ProgramTestContext,
@@ -254,6 +462,12 @@ pub async fn set_custody_ratios(
             permissions: custody_account.permissions,
             fees: custody_account.fees,
             borrow_rate: custody_account.borrow_rate,
+            leverage_tiers: custody_account
+                .leverage_tiers
+                .iter()
+                .filter(|tier| tier.min_size_usd > 0)
+                .copied()
+                .collect(),
             ratios,
         },
         multisig_signers,
@@ -439,6 +653,7 @@ pub async fn setup_pool_with_custodies(
                 borrow_rate: custody_param
                     .borrow_rate
                     .unwrap_or_else(fixtures::borrow_rate_regular),
+                leverage_tiers: vec![],
 
                 // in BPS, 10_000 = 100%
                 ratios: ratios.clone(),
@@ -499,10 +714,7 @@ pub fn scale(amount: u64, decimals: u8) -> u64 {
 }
 
 pub fn scale_f64(amount: f64, decimals: u8) -> u64 {
-    math::checked_as_u64(
-        math::checked_float_mul(amount, 10u64.pow(decimals as u32) as f64).unwrap(),
-    )
-    .unwrap()
+    math::checked_as_u64(amount * 10u64.pow(decimals as u32) as f64).unwrap()
 }
 
 pub fn ratio_from_percentage(percentage: f64) -> u64 {
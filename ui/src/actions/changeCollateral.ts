@@ -4,6 +4,7 @@ import { TokenE } from "@/lib/Token";
 import { Tab } from "@/lib/types";
 import {
   getPerpetualProgramAndProvider,
+  getWsolAccount,
   PERPETUALS_ADDRESS,
   TRANSFER_AUTHORITY,
 } from "@/utils/constants";
@@ -23,7 +24,11 @@ import {
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import { WalletContextState } from "@solana/wallet-adapter-react";
-import { Connection, TransactionInstruction } from "@solana/web3.js";
+import {
+  Connection,
+  SystemProgram,
+  TransactionInstruction,
+} from "@solana/web3.js";
 
 export async function changeCollateral(
   walletContextState: WalletContextState,
@@ -83,6 +88,7 @@ export async function changeCollateral(
       .accounts({
         owner: publicKey,
         fundingAccount: userCustodyTokenAccount, // user token account for custody token account
+        wsolAccount: getWsolAccount(publicKey),
         transferAuthority: TRANSFER_AUTHORITY,
         perpetuals: PERPETUALS_ADDRESS,
         pool: pool.address,
@@ -90,6 +96,8 @@ export async function changeCollateral(
         custody: custody.address,
         custodyOracleAccount: custody.oracle.oracleAccount,
         custodyTokenAccount: custody.tokenAccount,
+        custodyTokenMint: custody.mint,
+        systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
       });
   } else {
@@ -112,6 +120,7 @@ export async function changeCollateral(
       .accounts({
         owner: publicKey,
         receivingAccount: userCustodyTokenAccount,
+        wsolAccount: getWsolAccount(publicKey),
         transferAuthority: TRANSFER_AUTHORITY,
         perpetuals: PERPETUALS_ADDRESS,
         pool: pool.address,
@@ -119,6 +128,8 @@ export async function changeCollateral(
         custody: custody.address,
         custodyOracleAccount: custody.oracle.oracleAccount,
         custodyTokenAccount: custody.tokenAccount,
+        custodyTokenMint: custody.mint,
+        systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
       });
   }
@@ -4,6 +4,8 @@ import { TokenE } from "@/lib/Token";
 import { Tab } from "@/lib/types";
 import {
   getPerpetualProgramAndProvider,
+  getPoolDayStatsAccount,
+  getWsolAccount,
   PERPETUALS_ADDRESS,
   TRANSFER_AUTHORITY,
 } from "@/utils/constants";
@@ -22,6 +24,7 @@ import { WalletContextState } from "@solana/wallet-adapter-react";
 import {
   Connection,
   LAMPORTS_PER_SOL,
+  SystemProgram,
   TransactionInstruction,
 } from "@solana/web3.js";
 
@@ -104,14 +107,19 @@ export async function changeLiquidity(
       .accounts({
         owner: publicKey,
         fundingAccount: userCustodyTokenAccount, // user token account for custody token account
+        wsolAccount: getWsolAccount(publicKey),
         lpTokenAccount,
         transferAuthority: TRANSFER_AUTHORITY,
         perpetuals: PERPETUALS_ADDRESS,
         pool: pool.address,
+        poolDayStats: getPoolDayStatsAccount(pool.address),
         custody: custody.address,
         custodyOracleAccount: custody.oracle.oracleAccount,
         custodyTokenAccount: custody.tokenAccount,
+        custodyTokenMint: custody.mint,
         lpTokenMint: pool.getLpTokenMint(),
+        systemProgram: SystemProgram.programId,
+        custodyTokenProgram: TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .remainingAccounts(pool.getCustodyMetas());
@@ -131,14 +139,19 @@ export async function changeLiquidity(
       .accounts({
         owner: publicKey,
         receivingAccount: userCustodyTokenAccount, // user token account for custody token account
+        wsolAccount: getWsolAccount(publicKey),
         lpTokenAccount,
         transferAuthority: TRANSFER_AUTHORITY,
         perpetuals: PERPETUALS_ADDRESS,
         pool: pool.address,
+        poolDayStats: getPoolDayStatsAccount(pool.address),
         custody: custody.address,
         custodyOracleAccount: custody.oracle.oracleAccount,
         custodyTokenAccount: custody.tokenAccount,
+        custodyTokenMint: custody.mint,
         lpTokenMint: pool.getLpTokenMint(),
+        systemProgram: SystemProgram.programId,
+        custodyTokenProgram: TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .remainingAccounts(pool.getCustodyMetas());
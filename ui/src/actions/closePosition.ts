@@ -4,6 +4,8 @@ import { PositionAccount } from "@/lib/PositionAccount";
 import { TokenE } from "@/lib/Token";
 import {
   getPerpetualProgramAndProvider,
+  getPoolDayStatsAccount,
+  getWsolAccount,
   PERPETUALS_ADDRESS,
   TRANSFER_AUTHORITY,
 } from "@/utils/constants";
@@ -18,7 +20,11 @@ import {
 import { BN } from "@project-serum/anchor";
 import { getAssociatedTokenAddress, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { WalletContextState } from "@solana/wallet-adapter-react";
-import { Connection, TransactionInstruction } from "@solana/web3.js";
+import {
+  Connection,
+  SystemProgram,
+  TransactionInstruction,
+} from "@solana/web3.js";
 
 export async function closePosition(
   walletContextState: WalletContextState,
@@ -66,13 +72,17 @@ export async function closePosition(
     .accounts({
       owner: publicKey,
       receivingAccount: userCustodyTokenAccount,
+      wsolAccount: getWsolAccount(publicKey),
       transferAuthority: TRANSFER_AUTHORITY,
       perpetuals: PERPETUALS_ADDRESS,
       pool: pool.address,
+      poolDayStats: getPoolDayStatsAccount(pool.address),
       position: position.address,
       custody: custody.address,
       custodyOracleAccount: custody.oracle.oracleAccount,
       custodyTokenAccount: custody.tokenAccount,
+      custodyTokenMint: custody.mint,
+      systemProgram: SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
     })
     .preInstructions(preInstructions);
@@ -10,6 +10,8 @@ import {
   PERPETUALS_ADDRESS,
   TRANSFER_AUTHORITY,
   getPerpetualProgramAndProvider,
+  getPoolDayStatsAccount,
+  getWsolAccount,
 } from "@/utils/constants";
 import {
   createAtaIfNeeded,
@@ -176,13 +178,16 @@ export async function openPositionBuilder(
   let methodBuilder = perpetual_program.methods.openPosition(params).accounts({
     owner: publicKey,
     fundingAccount: userCustodyTokenAccount,
+    wsolAccount: getWsolAccount(publicKey),
     transferAuthority: TRANSFER_AUTHORITY,
     perpetuals: PERPETUALS_ADDRESS,
     pool: pool.address,
+    poolDayStats: getPoolDayStatsAccount(pool.address),
     position: positionAccount,
     custody: positionCustody.address,
     custodyOracleAccount: positionCustody.oracle.oracleAccount,
     custodyTokenAccount: positionCustody.tokenAccount,
+    custodyTokenMint: positionCustody.mint,
     systemProgram: SystemProgram.programId,
     tokenProgram: TOKEN_PROGRAM_ID,
   });
@@ -2,6 +2,8 @@ import { PoolAccount } from "@/lib/PoolAccount";
 import { TokenE } from "@/lib/Token";
 import {
   getPerpetualProgramAndProvider,
+  getPoolDayStatsAccount,
+  getWsolAccount,
   PERPETUALS_ADDRESS,
   TRANSFER_AUTHORITY,
 } from "@/utils/constants";
@@ -18,7 +20,11 @@ import { BN } from "@project-serum/anchor";
 import { MethodsBuilder } from "@project-serum/anchor/dist/cjs/program/namespace/methods";
 import { getAssociatedTokenAddress, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { WalletContextState } from "@solana/wallet-adapter-react";
-import { Connection, TransactionInstruction } from "@solana/web3.js";
+import {
+  Connection,
+  SystemProgram,
+  TransactionInstruction,
+} from "@solana/web3.js";
 
 export async function swapTransactionBuilder(
   walletContextState: WalletContextState,
@@ -139,19 +145,25 @@ export async function swapTransactionBuilder(
     owner: publicKey,
     fundingAccount: fundingAccount,
     receivingAccount: receivingAccount,
+    wsolAccount: getWsolAccount(publicKey),
     transferAuthority: TRANSFER_AUTHORITY,
     perpetuals: PERPETUALS_ADDRESS,
     pool: pool.address,
+    poolDayStats: getPoolDayStatsAccount(pool.address),
 
     receivingCustody: receivingCustody.address,
     receivingCustodyOracleAccount: receivingCustody.oracle.oracleAccount,
     receivingCustodyTokenAccount: receivingCustody.tokenAccount,
+    receivingCustodyTokenMint: receivingCustody.mint,
 
     dispensingCustody: dispensingCustody.address,
     dispensingCustodyOracleAccount: dispensingCustody.oracle.oracleAccount,
     dispensingCustodyTokenAccount: dispensingCustody.tokenAccount,
+    dispensingCustodyTokenMint: dispensingCustody.mint,
 
-    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+    receivingTokenProgram: TOKEN_PROGRAM_ID,
+    dispensingTokenProgram: TOKEN_PROGRAM_ID,
   });
 
   if (preInstructions) {
@@ -10,6 +10,7 @@ import {
   PositionStats,
   PricingParams,
   Stats,
+  TokenFeesStats,
   TradeStats,
   Permissions,
   PriceStat,
@@ -31,6 +32,7 @@ export class CustodyAccount {
   // dynamic variable;
   public assets: Assets;
   public collectedFees: Stats;
+  public collectedTokenFees: TokenFeesStats;
   public volumeStats: Stats;
   public tradeStats: TradeStats;
   public longPositions: PositionStats;
@@ -58,6 +60,7 @@ export class CustodyAccount {
     console.log("custody assets", custody.assets);
     this.assets = custody.assets;
     this.collectedFees = custody.collectedFees;
+    this.collectedTokenFees = custody.collectedTokenFees;
     this.volumeStats = custody.volumeStats;
     this.tradeStats = custody.tradeStats;
     this.longPositions = custody.longPositions;
@@ -32,6 +32,7 @@ export interface Custody {
 
   assets: Assets;
   collectedFees: Stats;
+  collectedTokenFees: TokenFeesStats;
   volumeStats: Stats;
   tradeStats: TradeStats;
   longPositions: PositionStats;
@@ -82,6 +83,15 @@ export interface Stats {
   liquidationUsd: BN;
 }
 
+export interface TokenFeesStats {
+  swap: BN;
+  addLiquidity: BN;
+  removeLiquidity: BN;
+  openPosition: BN;
+  closePosition: BN;
+  liquidation: BN;
+}
+
 export interface Fees {
   mode: FeesMode;
   maxIncrease: BN;
@@ -122,6 +132,7 @@ export interface Permissions {
   allowPnlWithdrawal: boolean;
   allowCollateralWithdrawal: boolean;
   allowSizeChange: boolean;
+  allowLiquidation: boolean;
 }
 
 export interface PricingParams {
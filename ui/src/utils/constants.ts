@@ -1,7 +1,7 @@
 import * as PerpetualsJson from "@/target/idl/perpetuals.json";
 import { IDL as PERPETUALS_IDL, Perpetuals } from "@/target/types/perpetuals";
 import { getProvider } from "@/utils/provider";
-import { AnchorProvider, Program, Wallet } from "@project-serum/anchor";
+import { AnchorProvider, BN, Program, Wallet } from "@project-serum/anchor";
 import NodeWallet from "@project-serum/anchor/dist/cjs/nodewallet";
 import { findProgramAddressSync } from "@project-serum/anchor/dist/cjs/utils/pubkey";
 import { WalletContextState } from "@solana/wallet-adapter-react";
@@ -75,6 +75,27 @@ export const PERPETUALS_ADDRESS = findProgramAddressSync(
   PERPETUALS_PROGRAM_ID
 )[0];
 
+// temporary wrapped SOL account used by the program for native SOL transfers
+export function getWsolAccount(owner: PublicKey) {
+  return findProgramAddressSync(
+    [Buffer.from("wsol_account"), owner.toBuffer()],
+    PERPETUALS_PROGRAM_ID
+  )[0];
+}
+
+// daily statistics account of the pool for the current UTC day
+export function getPoolDayStatsAccount(pool: PublicKey) {
+  const day = Math.floor(Date.now() / 1000 / 86400);
+  return findProgramAddressSync(
+    [
+      Buffer.from("pool_day_stats"),
+      pool.toBuffer(),
+      new BN(day).toArrayLike(Buffer, "le", 8),
+    ],
+    PERPETUALS_PROGRAM_ID
+  )[0];
+}
+
 // default user to launch show basic pool data, etc
 export const DEFAULT_PERPS_USER = Keypair.fromSecretKey(
   Uint8Array.from([